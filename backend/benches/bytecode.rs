@@ -0,0 +1,54 @@
+//! Compares approximating a tree with the variables substituted to evaluating the compiled
+//! bytecode, over the same inputs.
+//!
+//! Run with `cargo bench --bench bytecode`.
+
+use std::{hint::black_box, time::Instant};
+
+use calculatorium_backend::math::{
+    expr::ExpresssionTree, func::Function, symbol::Number, ExpressionElement, FromRawExpr,
+    ParsingScope,
+};
+
+const POINTS: usize = 100_000;
+
+fn main() {
+    let scope = ParsingScope::new(None).with_symbol("x");
+    for expr in [
+        "x^2+2*x+1",
+        r#"\sin{x}^2+\frac{x^3-2*x}{1+x^2}-\sqrt{\abs{x}}"#,
+        r#"e^{-\frac{x^2}{2}}*\cos{3*x}+\log_{2}{x^2+1}"#,
+    ] {
+        let tree = ExpresssionTree::parse_scoped(expr, &scope).unwrap();
+        let xs = (0..POINTS)
+            .map(|i| i as f64 / POINTS as f64 * 20. - 10.)
+            .collect::<Vec<_>>();
+
+        let start = Instant::now();
+        let expected = xs
+            .iter()
+            .map(|x| {
+                let x = ExpressionElement::from(Number::Decimal(*x)).into();
+                black_box(&tree).substitute("x", &x).approximate()
+            })
+            .collect::<Vec<_>>();
+        let tree_time = start.elapsed();
+
+        let start = Instant::now();
+        let compiled = tree.compile(&["x"]);
+        let actual = black_box(&compiled).eval_batch(&[&xs]);
+        let compiled_time = start.elapsed();
+
+        assert!(expected
+            .iter()
+            .zip(&actual)
+            .all(|(a, b)| (a - b).abs() <= 1e-12 * a.abs().max(1.) || a.is_nan() && b.is_nan()));
+        println!(
+            "{}\n  tree:     {:?}\n  bytecode: {:?} ({:.1}x faster)",
+            expr,
+            tree_time,
+            compiled_time,
+            tree_time.as_secs_f64() / compiled_time.as_secs_f64()
+        );
+    }
+}
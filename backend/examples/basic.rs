@@ -1,6 +1,9 @@
 use calculatorium_backend::{
     calculator::{CalculationError, Calculator},
-    math::{symbol::Number, FromRawExpr, IntoRawExpr},
+    math::{
+        func::solve::Roots, symbol::Number, system::Solutions, ExpressionElement, FromRawExpr,
+        IntoRawExpr,
+    },
 };
 
 fn main() {
@@ -17,26 +20,128 @@ fn main() {
 
         calculator.set_expr(input);
         let now = std::time::SystemTime::now();
-        println!("Start calculating the approximation of {}", input);
+        println!("Start calculating {}", input);
 
-        match calculator.approximate() {
-            Ok(ok) => println!(
-                "Done (after {}s)! Expression ≈ \n{}",
+        match calculator
+            .evaluate()
+            .and_then(|exact| Ok((exact, calculator.approximate_complex()?)))
+        {
+            Ok((exact, approx)) => println!(
+                "Done (after {}s)! Expression = \n{}\n≈ \n{}",
                 now.elapsed().unwrap().as_secs_f32(),
-                ok
+                ExpressionElement::from(exact).assemble(),
+                Number::from_complex(approx).assemble()
             ),
-            Err(err) => {
-                let hint = match err {
-                    CalculationError::Parsing(e) => e.expand(&input),
-                };
-                println!("Calculation failed: \n{}", hint);
-            }
+            Err(err) => print_err(err, input),
         };
 
         println!();
     }
 }
 
+fn print_err(err: CalculationError, input: &str) {
+    let hint = match err {
+        CalculationError::Parsing(e) => e.expand(input),
+        CalculationError::Undefined => "The expression is undefined".to_string(),
+        CalculationError::Unsolvable => "The equation can't be solved".to_string(),
+        CalculationError::Cycle(cycle) => format!("Circular dependency: {}", cycle.join(" -> ")),
+        CalculationError::InvalidArguments => "The arguments don't fit together".to_string(),
+    };
+    println!("Calculation failed: \n{}", hint);
+}
+
+/// Solves commands like `solve x: x^2-5x+6=0` and `solve x: x^2<4`, or `solve x, y: x+y=3, x-y=1`
+/// for systems.
+fn solve(calc: &mut Calculator, cmd: &str) {
+    let Some((vars, expr)) = cmd.split_once(':') else {
+        println!("Usage: solve <var>[, <var>...]: <equation>[, <equation>...]");
+        return;
+    };
+    let vars = vars.split(',').map(str::trim).collect::<Vec<_>>();
+    let expr = expr.trim();
+    calc.set_expr(expr);
+
+    if let [var] = vars.as_slice() {
+        if let Ok(set) = calc.solve_inequality(var) {
+            println!("{}", set.assemble_for(var));
+            return;
+        }
+
+        match calc.solve(var) {
+            Ok(Roots::Any) => println!("Every {} is a solution", var),
+            Ok(Roots::Finite(roots)) if roots.is_empty() => println!("No real solutions"),
+            Ok(Roots::Finite(roots)) => {
+                for root in roots {
+                    println!("{} = {}", var, root.assemble());
+                }
+            }
+            Err(err) => print_err(err, expr),
+        }
+        return;
+    }
+
+    let print = |values: &[ExpressionElement]| {
+        let values = vars
+            .iter()
+            .zip(values)
+            .map(|(var, value)| format!("{} = {}", var, value.assemble()))
+            .collect::<Vec<_>>();
+        println!("{}", values.join(", "));
+    };
+    match calc.solve_system(&vars) {
+        Ok(Solutions::None) => println!("No real solutions"),
+        Ok(Solutions::Unique(values)) => print(&values),
+        Ok(Solutions::Finite(solutions)) => solutions.iter().for_each(|s| print(s)),
+        Ok(Solutions::Infinite(values)) => {
+            println!("Infinitely many solutions:");
+            print(&values);
+        }
+        Err(err) => print_err(err, expr),
+    }
+}
+
+/// Tabulates expressions with commands like `table x=0,1,0.25: x^2` for `x` from 0 to 1 in
+/// steps of 0.25.
+fn table(calc: &mut Calculator, cmd: &str) {
+    let parsed = cmd.split_once(':').and_then(|(range, expr)| {
+        let (var, range) = range.split_once('=')?;
+        let range = range
+            .split(',')
+            .map(|x| x.trim().parse::<f64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        match range.as_slice() {
+            &[start, end, step] if step > 0. => Some((var.trim(), start, end, step, expr.trim())),
+            _ => None,
+        }
+    });
+    let Some((var, start, end, step, expr)) = parsed else {
+        println!("Usage: table <var>=<start>,<end>,<step>: <expression>");
+        return;
+    };
+
+    let xs = (0..)
+        .map(|i| start + i as f64 * step)
+        .take_while(|x| *x <= end + step * 1e-9)
+        .collect::<Vec<_>>();
+    calc.set_expr(expr);
+    match calc.approximate_many(var, &xs) {
+        Ok(ys) => {
+            for (x, y) in xs.iter().zip(ys) {
+                println!("{} = {}\t{}", var, x, y);
+            }
+        }
+        Err(err) => print_err(err, expr),
+    }
+}
+
+/// Lists the bound variables that couldn't be recomputed.
+fn print_stale(calc: &Calculator) {
+    let stale = calc.stale_variables();
+    if !stale.is_empty() {
+        println!("Stale variables: {}", stale.join(", "));
+    }
+}
+
 fn handle_cmd(calc: &mut Calculator, input: &str) -> bool {
     let cmd = input.split(' ').collect::<Vec<_>>();
     if cmd.is_empty() {
@@ -45,29 +150,81 @@ fn handle_cmd(calc: &mut Calculator, input: &str) -> bool {
 
     match cmd[0] {
         "set" => {
-            if cmd.len() != 3 {
+            if cmd.len() < 3 {
                 return false;
             }
-            if let Ok(n) = Number::parse_raw(cmd[2], None) {
-                calc.set_variable(cmd[1].to_string(), n);
-                println!("Set variable {} to {}", cmd[1], cmd[2]);
+            // Anything that isn't a number binds the variable to the expression.
+            let value = input.splitn(3, ' ').nth(2).unwrap().trim();
+            if let Ok(n) = Number::parse_raw(value, None) {
+                let result = calc.set_variable(cmd[1].to_string(), n);
+                println!("Set variable {} to {}", cmd[1], value);
+                if let Err(err) = result {
+                    print_err(err, "");
+                }
+            } else {
+                match calc.bind_variable(cmd[1], value) {
+                    Ok(n) => println!("Bound variable {} to {} = {}", cmd[1], value, n.assemble()),
+                    Err(err) => print_err(err, value),
+                }
             }
+            print_stale(calc);
             true
         }
         "get" => {
             if cmd.len() != 2 {
                 return false;
             }
-            match calc.get_variable(&cmd[1]) {
-                Some(n) => println!("Found variable {} with value {}", cmd[1], n.assemble()),
-                None => println!("Unknown variable {}", cmd[1]),
+            match (calc.get_variable(cmd[1]), calc.get_binding(cmd[1])) {
+                (Some(n), Some(binding)) => println!(
+                    "Found variable {} = {} with value {}",
+                    cmd[1],
+                    binding.expr(),
+                    n.assemble()
+                ),
+                (Some(n), None) => {
+                    println!("Found variable {} with value {}", cmd[1], n.assemble())
+                }
+                (None, _) => println!("Unknown variable {}", cmd[1]),
+            }
+            true
+        }
+        "stale" => {
+            if let Err(err) = calc.recompute() {
+                print_err(err, "");
+            }
+            print_stale(calc);
+            true
+        }
+        "def" => {
+            let definition = input["def".len()..].trim();
+            match calc.define_function(definition) {
+                Ok(name) => println!("Defined function {}", name),
+                Err(err) => print_err(err, definition),
             }
             true
         }
+        "solve" => {
+            solve(calc, &input["solve".len()..]);
+            true
+        }
+        "table" => {
+            table(calc, &input["table".len()..]);
+            true
+        }
+        "symbolic" => {
+            let symbolic = match cmd.get(1) {
+                Some(&"on") => true,
+                Some(&"off") => false,
+                _ => return false,
+            };
+            calc.set_symbolic(symbolic);
+            println!("Turned symbolic mode {}", cmd[1]);
+            true
+        }
         "clearvar" => {
-            calc.variables_mut().clear();
+            calc.clear_variables();
             println!("Successfully cleared all variables");
-            return true;
+            true
         }
         _ => false,
     }
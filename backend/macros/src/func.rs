@@ -11,9 +11,11 @@ pub fn expand_from_expr_derive(input: syn::DeriveInput) -> proc_macro::TokenStre
     let num_fields = data.fields.len();
     let mut field_ctons = Vec::with_capacity(num_fields);
     let mut field_accessors = Vec::with_capacity(num_fields);
+    let mut field_names = Vec::with_capacity(num_fields);
 
     for (field_index, field) in data.fields.iter().enumerate() {
         let field_name = field.ident.as_ref().unwrap();
+        field_names.push(field_name);
 
         field_ctons.push(quote! {
             #field_name: expr[#field_index].take().unwrap(),
@@ -37,6 +39,13 @@ pub fn expand_from_expr_derive(input: syn::DeriveInput) -> proc_macro::TokenStre
         }
 
         impl #ty {
+            #[inline]
+            pub fn new(#(#field_names: ExpressionElement),*) -> Self {
+                Self {
+                    #(#field_names,)*
+                }
+            }
+
             #(#field_accessors)*
         }
     }
@@ -51,7 +60,7 @@ pub fn expand_into_raw_expr_derive(input: syn::DeriveInput) -> proc_macro::Token
     };
 
     let mut field_assemblers = Vec::with_capacity(data.fields.len());
-    let mut field_assembler_template = format!("\\{{}}");
+    let mut field_assembler_template = "\\{}".to_string();
 
     for field in &data.fields {
         let field_name = &field.ident;
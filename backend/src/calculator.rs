@@ -1,42 +1,303 @@
-use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
 
 use crate::{
+    dependency::{Binding, DependencyGraph},
     math::{
-        expr::ExpresssionTree, func::Function, symbol::Number, FromRawExpr, LaTexParsingError,
-        MathElement,
+        bytecode::CompiledExpr,
+        expr::ExpresssionTree,
+        func::{inequality::IntervalSet, solve::Roots, user::UserFunction, Function},
+        parallel,
+        symbol::Number,
+        system::{EquationSystem, Solutions},
+        ExpressionElement, FromRawExpr, LaTexParsingError, LaTexParsingResult, MathElement,
+        ParsingScope,
     },
-    DecimalScalar,
+    ComplexScalar, DecimalScalar,
 };
 
+#[cfg(feature = "bignum")]
+use crate::{math::precise, BigDecimalScalar};
+
 pub type CalculationResult<T> = Result<T, CalculationError>;
 
 pub enum CalculationError {
     Parsing(LaTexParsingError),
+    /// The expression has no real value, like `\ln{-1}`.
+    Undefined,
+    /// The equation, inequality or system can't be solved for the unknowns.
+    Unsolvable,
+    /// Binding the variable would make it depend on itself, through the variables in the
+    /// cycle like `["a", "b", "a"]`.
+    Cycle(Vec<String>),
+    /// The arguments don't fit together, like a different number of variables and axes.
+    InvalidArguments,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Calculator {
     expr: String,
+    /// The parsed expression with the variables kept by name, parsed again only when the
+    /// expression or the names it can mention change.
+    tree: OnceLock<LaTexParsingResult<ExpressionElement>>,
+    /// Like `tree`, but with the unknowns it was last approximated over as symbols.
+    grid_tree: Mutex<Option<(Vec<String>, LaTexParsingResult<ExpressionElement>)>>,
     variables: HashMap<String, Number>,
+    functions: HashMap<String, UserFunction>,
+    bindings: DependencyGraph,
+    symbolic: bool,
+    threads: usize,
+    #[cfg(feature = "bignum")]
+    precision: u64,
+}
+
+impl Default for Calculator {
+    fn default() -> Self {
+        Self {
+            expr: Default::default(),
+            tree: Default::default(),
+            grid_tree: Default::default(),
+            variables: Default::default(),
+            functions: Default::default(),
+            bindings: Default::default(),
+            symbolic: false,
+            threads: parallel::available_threads(),
+            #[cfg(feature = "bignum")]
+            precision: precise::DEFAULT_PRECISION,
+        }
+    }
 }
 
 impl Calculator {
-    pub fn evaluate(&self) -> MathElement {
-        todo!()
+    /// The variables and functions expressions are parsed with.
+    #[inline]
+    fn scope(&self) -> ParsingScope<'_> {
+        let scope = ParsingScope::new(Some(&self.variables)).with_functions(&self.functions);
+        if self.symbolic {
+            scope.with_free_symbols()
+        } else {
+            scope
+        }
+    }
+
+    /// The expression with the current values of the variables. It's only parsed the first
+    /// time, or after something it could depend on has changed.
+    fn tree(&self) -> CalculationResult<Cow<'_, ExpressionElement>> {
+        let tree = self
+            .tree
+            .get_or_init(|| {
+                ExpresssionTree::parse_scoped(&self.expr, &self.scope().with_variable_refs())
+                    .map(ExpressionElement::from)
+            })
+            .as_ref()
+            .map_err(|e| CalculationError::Parsing(e.clone()))?;
+
+        Ok(self.substitute_variables(tree, &[]))
+    }
+
+    /// Replaces the variables in `tree` by their current values, except for the `unknowns`.
+    fn substitute_variables<'a>(
+        &self,
+        tree: &'a ExpressionElement,
+        unknowns: &[&str],
+    ) -> Cow<'a, ExpressionElement> {
+        self.variables
+            .iter()
+            .filter(|(var, _)| !unknowns.contains(&var.as_str()))
+            .fold(Cow::Borrowed(tree), |tree, (var, val)| {
+                if tree.depends_on(var) {
+                    Cow::Owned(tree.substitute(var, &val.clone().into()))
+                } else {
+                    tree
+                }
+            })
+    }
+
+    /// The expression compiled for `unknowns`, which is parsed again only when they differ
+    /// from the last ones or the names it can mention change.
+    fn compile(&self, unknowns: &[&str]) -> CalculationResult<CompiledExpr> {
+        let mut grid_tree = self.grid_tree.lock().unwrap();
+        if grid_tree
+            .as_ref()
+            .is_none_or(|(cached, _)| !cached.iter().eq(unknowns))
+        {
+            let mut scope = self.scope().with_variable_refs();
+            for var in unknowns {
+                scope.add_symbol(*var);
+            }
+            let tree =
+                ExpresssionTree::parse_scoped(&self.expr, &scope).map(ExpressionElement::from);
+            *grid_tree = Some((unknowns.iter().map(|v| v.to_string()).collect(), tree));
+        }
+
+        let (_, tree) = grid_tree.as_ref().unwrap();
+        let tree = tree
+            .as_ref()
+            .map_err(|e| CalculationError::Parsing(e.clone()))?;
+        Ok(CompiledExpr::compile(
+            &self.substitute_variables(tree, unknowns),
+            unknowns,
+        ))
+    }
+
+    /// Runs `f` with the number of threads and, with `bignum`, the precision of the calculator.
+    #[inline]
+    fn run<T>(&self, f: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "bignum")]
+        let f = || precise::with_precision(self.precision, f);
+        parallel::with_threads(self.threads, f)
+    }
+
+    /// Drops the parsed expression, after the names it could mention have changed.
+    #[inline]
+    fn invalidate_tree(&mut self) {
+        self.tree.take();
+        self.grid_tree.get_mut().unwrap().take();
+    }
+
+    #[inline]
+    pub fn evaluate(&self) -> CalculationResult<MathElement> {
+        let tree = self.tree()?;
+        Ok(self.run(|| tree.evaluate()))
     }
 
     #[inline]
     pub fn approximate(&self) -> CalculationResult<DecimalScalar> {
-        Ok(
-            ExpresssionTree::parse_raw(&self.expr, Some(&self.variables))
-                .map_err(|e| CalculationError::Parsing(e))?
-                .approximate(),
-        )
+        let tree = self.tree()?;
+        Ok(self.run(|| tree.approximate()))
+    }
+
+    #[inline]
+    pub fn approximate_complex(&self) -> CalculationResult<ComplexScalar> {
+        let tree = self.tree()?;
+        Ok(self.run(|| tree.approximate_complex()))
+    }
+
+    /// Approximates the expression for every value of `var`, which is an unknown instead of a
+    /// variable here. The expression is only parsed again after something it could depend on
+    /// has changed, and evaluated in parallel. Values where it's undefined are `NaN`.
+    pub fn approximate_many(
+        &self,
+        var: &str,
+        values: &[DecimalScalar],
+    ) -> CalculationResult<Vec<DecimalScalar>> {
+        self.approximate_grid(&[var], &[values])
+    }
+
+    /// Approximates the expression at every point of the grid spanned by `axes`, which are
+    /// the values of `vars`. The results are in row-major order, so the last variable changes
+    /// the fastest. Fails with [`CalculationError::InvalidArguments`] if there aren't as many
+    /// axes as variables.
+    pub fn approximate_grid(
+        &self,
+        vars: &[&str],
+        axes: &[&[DecimalScalar]],
+    ) -> CalculationResult<Vec<DecimalScalar>> {
+        if vars.len() != axes.len() {
+            return Err(CalculationError::InvalidArguments);
+        }
+        let compiled = self.compile(vars)?;
+
+        let len = axes.iter().map(|axis| axis.len()).product::<usize>();
+        let mut stride = len;
+        let inputs = axes
+            .iter()
+            .map(|axis| {
+                stride /= axis.len().max(1);
+                (0..len).map(|i| axis[i / stride % axis.len()]).collect()
+            })
+            .collect::<Vec<Vec<_>>>();
+        let inputs = inputs.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        Ok(self.run(|| compiled.eval_batch(&inputs)))
+    }
+
+    /// Approximates the expression with [`Self::get_precision`] significant digits.
+    #[cfg(feature = "bignum")]
+    pub fn approximate_precise(&self) -> CalculationResult<BigDecimalScalar> {
+        self.tree()?
+            .approximate_precise(self.precision + precise::GUARD_DIGITS)
+            .map(|d| precise::round(&d, self.precision))
+            .ok_or(CalculationError::Undefined)
+    }
+
+    /// Solves the expression for `var`, which is an unknown instead of a variable here.
+    pub fn solve(&self, var: &str) -> CalculationResult<Roots> {
+        let scope = self.scope().with_symbol(var);
+        let tree =
+            ExpresssionTree::parse_scoped(&self.expr, &scope).map_err(CalculationError::Parsing)?;
+        self.run(|| tree.solve(var))
+            .ok_or(CalculationError::Unsolvable)
+    }
+
+    /// Solves the expression as an inequality for `var`, which is an unknown instead of a
+    /// variable here.
+    pub fn solve_inequality(&self, var: &str) -> CalculationResult<IntervalSet> {
+        let scope = self.scope().with_symbol(var);
+        let tree =
+            ExpresssionTree::parse_scoped(&self.expr, &scope).map_err(CalculationError::Parsing)?;
+        self.run(|| tree.solve_inequality(var))
+            .ok_or(CalculationError::Unsolvable)
+    }
+
+    /// Solves the expression as a system of equations for `vars`, which are unknowns instead
+    /// of variables here.
+    pub fn solve_system(&self, vars: &[&str]) -> CalculationResult<Solutions> {
+        let mut scope = self.scope();
+        for var in vars {
+            scope.add_symbol(*var);
+        }
+        let system =
+            EquationSystem::parse_scoped(&self.expr, &scope).map_err(CalculationError::Parsing)?;
+        self.run(|| system.solve(vars))
+            .ok_or(CalculationError::Unsolvable)
+    }
+
+    /// Sets the number of threads large matrix calculations are split across. Results are the
+    /// same no matter how many threads are used.
+    ///
+    /// Defaults to the number of threads the machine can run in parallel.
+    #[inline]
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    #[inline]
+    pub fn get_threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Makes unknown names in expressions symbols instead of errors, so expressions like
+    /// `2x+3y` are evaluated symbolically. Off by default.
+    #[inline]
+    pub fn set_symbolic(&mut self, symbolic: bool) {
+        self.symbolic = symbolic;
+        self.invalidate_tree();
+    }
+
+    #[inline]
+    pub fn is_symbolic(&self) -> bool {
+        self.symbolic
+    }
+
+    #[cfg(feature = "bignum")]
+    #[inline]
+    pub fn set_precision(&mut self, precision: u64) {
+        self.precision = precision;
+    }
+
+    #[cfg(feature = "bignum")]
+    #[inline]
+    pub fn get_precision(&self) -> u64 {
+        self.precision
     }
 
     #[inline]
     pub fn set_expr(&mut self, expr: impl Into<String>) {
-        self.expr = expr.into()
+        self.expr = expr.into();
+        self.invalidate_tree();
     }
 
     #[inline]
@@ -44,9 +305,19 @@ impl Calculator {
         &self.expr
     }
 
-    #[inline]
-    pub fn set_variable(&mut self, var: impl Into<String>, val: Number) {
-        self.variables.insert(var.into(), val);
+    /// Sets `var` to a plain value, replacing its binding if there is one, and recomputes the
+    /// variables that depend on it.
+    ///
+    /// The value is set either way, but if a dependent variable fails to recompute, its error
+    /// is returned and it stays stale like in [`Self::recompute`].
+    pub fn set_variable(&mut self, var: impl Into<String>, val: Number) -> CalculationResult<()> {
+        let var = var.into();
+        self.bindings.unbind(&var);
+        self.bindings.invalidate(&var);
+        if self.variables.insert(var, val).is_none() {
+            self.invalidate_tree();
+        }
+        self.recompute()
     }
 
     #[inline]
@@ -54,18 +325,335 @@ impl Calculator {
         self.variables.get(var).cloned()
     }
 
+    /// Like [`Self::set_variable`], except the variables that depend on `var` are only marked
+    /// stale, as the new value isn't known yet.
     #[inline]
     pub fn get_variable_mut(&mut self, var: &str) -> Option<&mut Number> {
+        self.bindings.unbind(var);
+        self.bindings.invalidate(var);
         self.variables.get_mut(var)
     }
 
+    /// Binds `var` to an expression like `\pi r^2`, so it's recomputed whenever the variables
+    /// it mentions change. Returns the new value.
+    ///
+    /// The expression can only mention variables that already exist, and fails with
+    /// [`CalculationError::Cycle`] if they depend on `var`.
+    pub fn bind_variable(
+        &mut self,
+        var: impl Into<String>,
+        expr: impl Into<String>,
+    ) -> CalculationResult<Number> {
+        let (var, expr) = (var.into(), expr.into());
+
+        // Variables are kept as symbols to find the ones the expression mentions.
+        let mut names = self.variables.keys().cloned().collect::<Vec<_>>();
+        names.extend(self.bindings.bindings().keys().cloned());
+        names.sort_unstable();
+        names.dedup();
+        let mut scope = ParsingScope::default().with_functions(&self.functions);
+        for name in &names {
+            scope.add_symbol(name.clone());
+        }
+        let tree = ExpressionElement::from(
+            ExpresssionTree::parse_scoped(&expr, &scope).map_err(CalculationError::Parsing)?,
+        );
+        let deps = names.into_iter().filter(|n| tree.depends_on(n)).collect();
+
+        let value = self.compute(&expr)?;
+        self.bindings
+            .bind(&var, expr, deps)
+            .map_err(CalculationError::Cycle)?;
+        if self.variables.insert(var.clone(), value.clone()).is_none() {
+            self.invalidate_tree();
+        }
+        self.bindings.mark_fresh(&var);
+        let _ = self.recompute();
+        Ok(value)
+    }
+
+    /// Turns `var` into a plain variable that keeps its current value.
+    #[inline]
+    pub fn unbind_variable(&mut self, var: &str) -> Option<Binding> {
+        self.bindings.unbind(var)
+    }
+
+    #[inline]
+    pub fn get_binding(&self, var: &str) -> Option<&Binding> {
+        self.bindings.get(var)
+    }
+
+    /// The bound variables whose values are outdated, sorted by name. They are stale if they
+    /// failed to recompute, or something they depend on was changed through
+    /// [`Self::get_variable_mut`] or [`Self::variables_mut`].
+    #[inline]
+    pub fn stale_variables(&self) -> Vec<&str> {
+        self.bindings.stale()
+    }
+
+    /// Recomputes the stale variables, each after the ones it depends on. Stops at the first
+    /// one that fails, which stays stale along with everything left.
+    pub fn recompute(&mut self) -> CalculationResult<()> {
+        for var in self.bindings.update_order() {
+            let expr = self.bindings.get(&var).unwrap().expr().to_string();
+            let value = self.compute(&expr)?;
+            self.variables.insert(var.clone(), value);
+            self.bindings.mark_fresh(&var);
+        }
+        Ok(())
+    }
+
+    /// Removes every variable and binding.
+    #[inline]
+    pub fn clear_variables(&mut self) {
+        self.variables.clear();
+        self.bindings.clear();
+        self.invalidate_tree();
+    }
+
+    /// The value of a bound variable, exact if possible.
+    fn compute(&self, expr: &str) -> CalculationResult<Number> {
+        let tree = ExpresssionTree::parse_scoped(expr, &self.scope())
+            .map_err(CalculationError::Parsing)?;
+        // Exact values are always finite, unlike decimals and constants like `\infty`.
+        let is_finite = |n: &Number| match n {
+            Number::Decimal(_) | Number::Complex(_) | Number::Constant(_) => {
+                n.approximate_complex().is_finite()
+            }
+            _ => true,
+        };
+        match ExpressionElement::from(self.run(|| tree.evaluate())) {
+            ExpressionElement::Number(n) => Some(n),
+            _ => Some(Number::Decimal(tree.approximate())),
+        }
+        .filter(is_finite)
+        .ok_or(CalculationError::Undefined)
+    }
+
+    /// Defines a function like `f(x)=x^2+1` or `g(x,y)=\sqrt{x^2+y^2}`, replacing any function
+    /// with the same name. Returns the name.
+    ///
+    /// The body is checked right away, so it can't call the function itself, even through other
+    /// functions.
+    pub fn define_function(&mut self, definition: &str) -> CalculationResult<String> {
+        let (name, f) =
+            UserFunction::parse_definition(definition).map_err(CalculationError::Parsing)?;
+        // The body is what's left after the `=`, without surrounding whitespaces.
+        let body_start = definition.trim_end().len() - f.body().len();
+        ExpresssionTree::parse_scoped_with_base_index(
+            f.body(),
+            &self.scope().enter_function(&name, f.params()),
+            body_start,
+        )
+        .map_err(CalculationError::Parsing)?;
+
+        self.functions.insert(name.clone(), f);
+        self.invalidate_tree();
+        Ok(name)
+    }
+
+    #[inline]
+    pub fn get_function(&self, name: &str) -> Option<&UserFunction> {
+        self.functions.get(name)
+    }
+
+    #[inline]
+    pub fn remove_function(&mut self, name: &str) -> Option<UserFunction> {
+        self.invalidate_tree();
+        self.functions.remove(name)
+    }
+
+    #[inline]
+    pub fn functions(&self) -> &HashMap<String, UserFunction> {
+        &self.functions
+    }
+
     #[inline]
     pub fn variables(&self) -> &HashMap<String, Number> {
         &self.variables
     }
 
+    /// Every bound variable is marked stale, as any of them might have changed.
     #[inline]
     pub fn variables_mut(&mut self) -> &mut HashMap<String, Number> {
+        self.bindings.invalidate_all();
+        self.invalidate_tree();
         &mut self.variables
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        calculator::{CalculationError, Calculator},
+        math::{func::Function, symbol::Number, ExpressionElement, IntoRawExpr},
+    };
+
+    #[test]
+    fn test_cached_tree() {
+        let mut calc = Calculator::default();
+        calc.set_expr("a*x+1");
+        assert!(matches!(
+            calc.approximate(),
+            Err(CalculationError::Parsing(_))
+        ));
+
+        // New names are only known after parsing again.
+        calc.set_variable("a", Number::Integer(2)).ok().unwrap();
+        calc.set_variable("x", Number::Integer(3)).ok().unwrap();
+        assert_eq!(calc.approximate().ok().unwrap(), 7.);
+        calc.set_variable("x", Number::Integer(5)).ok().unwrap();
+        assert_eq!(calc.approximate().ok().unwrap(), 11.);
+        *calc.get_variable_mut("a").unwrap() = Number::Integer(-1);
+        assert_eq!(
+            ExpressionElement::from(calc.evaluate().ok().unwrap()).assemble(),
+            "-4"
+        );
+
+        // Bound variables in the expression shadow the ones of the calculator.
+        calc.set_expr(r#"\sum_{x=1}^{x}{a*x}"#);
+        assert_eq!(calc.approximate().ok().unwrap(), -15.);
+
+        calc.set_expr("f(x)");
+        assert!(calc.approximate().is_err());
+        calc.define_function("f(t)=t^2").ok().unwrap();
+        assert_eq!(calc.approximate().ok().unwrap(), 25.);
+        calc.define_function("f(t)=t^3").ok().unwrap();
+        assert_eq!(calc.approximate().ok().unwrap(), 125.);
+
+        calc.clear_variables();
+        assert!(calc.approximate().is_err());
+    }
+
+    #[test]
+    fn test_bound_variables() {
+        let mut calc = Calculator::default();
+        calc.set_variable("r", Number::Integer(2)).ok().unwrap();
+        calc.bind_variable("area", r#"\pi r^2"#).ok().unwrap();
+        calc.bind_variable("volume", "area*h").err().unwrap();
+        calc.set_variable("h", Number::Integer(3)).ok().unwrap();
+        calc.bind_variable("volume", "area*h").ok().unwrap();
+
+        let approximate = |calc: &Calculator, var| {
+            ExpressionElement::from(calc.get_variable(var).unwrap()).approximate()
+        };
+        let pi = std::f64::consts::PI;
+        assert!((approximate(&calc, "area") - 4. * pi).abs() < 1e-12);
+        assert!((approximate(&calc, "volume") - 12. * pi).abs() < 1e-12);
+
+        calc.set_variable("r", Number::Integer(1)).ok().unwrap();
+        assert!((approximate(&calc, "area") - pi).abs() < 1e-12);
+        assert!((approximate(&calc, "volume") - 3. * pi).abs() < 1e-12);
+        assert!(calc.stale_variables().is_empty());
+
+        *calc.get_variable_mut("h").unwrap() = Number::Integer(2);
+        assert_eq!(calc.stale_variables(), ["volume"]);
+        calc.recompute().ok().unwrap();
+        assert!((approximate(&calc, "volume") - 2. * pi).abs() < 1e-12);
+
+        match calc.bind_variable("r", r#"\sqrt{volume}"#) {
+            Err(CalculationError::Cycle(cycle)) => assert_eq!(cycle, ["r", "volume", "area", "r"]),
+            _ => panic!("expected a cycle"),
+        }
+        assert!(matches!(
+            calc.bind_variable("h", "h+1"),
+            Err(CalculationError::Cycle(_))
+        ));
+
+        calc.set_expr("volume+1");
+        assert!((calc.approximate().ok().unwrap() - 2. * pi - 1.).abs() < 1e-12);
+
+        // Values that aren't finite can't be stored.
+        calc.bind_variable("inverse", r#"\frac{1}{r-10}"#)
+            .ok()
+            .unwrap();
+        assert!(matches!(
+            calc.set_variable("r", Number::Integer(10)),
+            Err(CalculationError::Undefined)
+        ));
+        assert_eq!(calc.stale_variables(), ["inverse", "volume"]);
+        assert_eq!(
+            calc.get_variable("inverse").unwrap().assemble(),
+            r#"-\frac{1}{9}"#
+        );
+        calc.set_variable("r", Number::Integer(1)).ok().unwrap();
+        calc.unbind_variable("inverse");
+
+        // Plain values replace bindings.
+        calc.set_variable("area", Number::Integer(5)).ok().unwrap();
+        assert_eq!(calc.get_binding("area"), None);
+        assert!((approximate(&calc, "volume") - 10.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_batch_approximation() {
+        let mut calc = Calculator::default();
+        calc.set_variable("a", Number::Integer(2)).ok().unwrap();
+        calc.set_variable("x", Number::Integer(100)).ok().unwrap();
+        calc.set_expr("a*x^2+1");
+        assert_eq!(
+            calc.approximate_many("x", &[0., 1., 2., 3.]).ok().unwrap(),
+            [1., 3., 9., 19.]
+        );
+
+        calc.set_expr(r#"x-y+\ln{x}"#);
+        let grid = calc
+            .approximate_grid(&["x", "y"], &[&[1., 2.], &[0., 10., 20.]])
+            .ok()
+            .unwrap();
+        let ln2 = 2f64.ln();
+        assert_eq!(grid, [1., -9., -19., 2. + ln2, -8. + ln2, -18. + ln2]);
+
+        calc.set_expr(r#"\ln{x}"#);
+        let values = calc.approximate_many("x", &[-1., 1.]).ok().unwrap();
+        assert!(values[0].is_nan() && values[1] == 0.);
+        calc.set_expr(r#"\sum_{k=1}^{x}{k}"#);
+        let values = calc.approximate_many("x", &[1., 2., 3., 2.5]).ok().unwrap();
+        assert_eq!(values[..3], [1., 3., 6.]);
+        assert!(values[3].is_nan());
+        calc.set_expr("x+z");
+        assert!(calc.approximate_many("x", &[1.]).is_err());
+    }
+
+    #[test]
+    fn test_grid() {
+        let mut calc = Calculator::default();
+        calc.set_expr("x*y+a");
+        assert!(matches!(
+            calc.approximate_grid(&["x", "y"], &[&[1.]]),
+            Err(CalculationError::InvalidArguments)
+        ));
+        assert!(calc.approximate_grid(&["x", "y"], &[&[1.], &[2.]]).is_err());
+
+        calc.set_variable("a", Number::Integer(1)).ok().unwrap();
+        let grid = |calc: &Calculator| {
+            calc.approximate_grid(&["x", "y"], &[&[1., 2.], &[3.]])
+                .ok()
+                .unwrap()
+        };
+        assert_eq!(grid(&calc), [4., 7.]);
+        calc.set_variable("a", Number::Integer(2)).ok().unwrap();
+        assert_eq!(grid(&calc), [5., 8.]);
+        // The unknowns shadow variables of the calculator.
+        calc.set_variable("x", Number::Integer(10)).ok().unwrap();
+        assert_eq!(grid(&calc), [5., 8.]);
+        assert_eq!(calc.approximate_many("y", &[3.]).ok().unwrap(), [32.]);
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test]
+    fn test_precision() {
+        let mut calc = Calculator::default();
+        calc.set_expr("2^{0.5}");
+        let exact =
+            |calc: &Calculator| ExpressionElement::from(calc.evaluate().ok().unwrap()).assemble();
+        assert_eq!(
+            exact(&calc),
+            "1.4142135623730950488016887242096980785696718753769"
+        );
+        calc.set_precision(10);
+        assert_eq!(exact(&calc), "1.414213562");
+        calc.set_expr("2^{100.5}");
+        assert_eq!(exact(&calc), "1.792728671*10^{30}");
+    }
+}
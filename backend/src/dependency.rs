@@ -0,0 +1,210 @@
+//! Variables bound to expressions, like `area = \pi r^2`, and what they depend on.
+
+use std::collections::{HashMap, HashSet};
+
+/// The expression of a bound variable and the variables it mentions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    expr: String,
+    deps: Vec<String>,
+}
+
+impl Binding {
+    #[inline]
+    pub fn expr(&self) -> &str {
+        &self.expr
+    }
+
+    #[inline]
+    pub fn deps(&self) -> &[String] {
+        &self.deps
+    }
+}
+
+/// The bound variables, and which of them have to be recomputed because something they depend
+/// on has changed.
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    bindings: HashMap<String, Binding>,
+    stale: HashSet<String>,
+}
+
+impl DependencyGraph {
+    #[inline]
+    pub fn get(&self, var: &str) -> Option<&Binding> {
+        self.bindings.get(var)
+    }
+
+    #[inline]
+    pub fn bindings(&self) -> &HashMap<String, Binding> {
+        &self.bindings
+    }
+
+    /// Binds `var` to `expr`, which mentions `deps`, and marks it stale. Returns the cycle like
+    /// `["a", "b", "a"]` instead if one of `deps` depends on `var`.
+    pub fn bind(
+        &mut self,
+        var: &str,
+        expr: impl Into<String>,
+        deps: Vec<String>,
+    ) -> Result<(), Vec<String>> {
+        for dep in &deps {
+            if let Some(mut path) = self.path(dep, var) {
+                path.insert(0, var.to_string());
+                return Err(path);
+            }
+        }
+
+        self.bindings.insert(
+            var.to_string(),
+            Binding {
+                expr: expr.into(),
+                deps,
+            },
+        );
+        self.stale.insert(var.to_string());
+        self.invalidate(var);
+        Ok(())
+    }
+
+    /// Turns `var` back into a plain variable.
+    #[inline]
+    pub fn unbind(&mut self, var: &str) -> Option<Binding> {
+        self.stale.remove(var);
+        self.bindings.remove(var)
+    }
+
+    /// The chain of dependencies from `from` to `to`, including both.
+    fn path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        if from == to {
+            return Some(vec![to.to_string()]);
+        }
+
+        self.bindings.get(from)?.deps.iter().find_map(|dep| {
+            let mut path = self.path(dep, to)?;
+            path.insert(0, from.to_string());
+            Some(path)
+        })
+    }
+
+    /// Marks everything that depends on `var`, directly or not, as stale.
+    pub fn invalidate(&mut self, var: &str) {
+        let mut changed = vec![var.to_string()];
+        while let Some(var) = changed.pop() {
+            for (dependent, binding) in &self.bindings {
+                if binding.deps.contains(&var) && self.stale.insert(dependent.clone()) {
+                    changed.push(dependent.clone());
+                }
+            }
+        }
+    }
+
+    /// Marks every bound variable as stale.
+    #[inline]
+    pub fn invalidate_all(&mut self) {
+        self.stale.extend(self.bindings.keys().cloned());
+    }
+
+    #[inline]
+    pub fn is_stale(&self, var: &str) -> bool {
+        self.stale.contains(var)
+    }
+
+    /// The stale variables, sorted by name.
+    pub fn stale(&self) -> Vec<&str> {
+        let mut stale = self.stale.iter().map(String::as_str).collect::<Vec<_>>();
+        stale.sort_unstable();
+        stale
+    }
+
+    #[inline]
+    pub fn mark_fresh(&mut self, var: &str) {
+        self.stale.remove(var);
+    }
+
+    /// The stale variables in the order they have to be recomputed, where every variable comes
+    /// after the ones it depends on.
+    pub fn update_order(&self) -> Vec<String> {
+        let mut order = Vec::with_capacity(self.stale.len());
+        let mut visited = HashSet::new();
+
+        fn visit(
+            graph: &DependencyGraph,
+            var: &str,
+            visited: &mut HashSet<String>,
+            order: &mut Vec<String>,
+        ) {
+            if !graph.stale.contains(var) || !visited.insert(var.to_string()) {
+                return;
+            }
+            for dep in &graph.bindings[var].deps {
+                visit(graph, dep, visited, order);
+            }
+            order.push(var.to_string());
+        }
+
+        for var in self.stale() {
+            visit(self, var, &mut visited, &mut order);
+        }
+        order
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.bindings.clear();
+        self.stale.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dependency::DependencyGraph;
+
+    #[test]
+    fn test_dependencies() {
+        let deps = |vars: &[&str]| vars.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        let mut graph = DependencyGraph::default();
+        graph.bind("area", r#"\pi r^2"#, deps(&["r"])).unwrap();
+        graph
+            .bind("volume", "area*h", deps(&["area", "h"]))
+            .unwrap();
+        graph
+            .bind("mass", "volume*d", deps(&["volume", "d"]))
+            .unwrap();
+        assert_eq!(graph.get("volume").unwrap().deps(), ["area", "h"]);
+        assert_eq!(graph.stale(), ["area", "mass", "volume"]);
+        assert_eq!(graph.update_order(), ["area", "volume", "mass"]);
+
+        for var in graph.update_order() {
+            graph.mark_fresh(&var);
+        }
+        assert!(graph.stale().is_empty());
+
+        // Only what depends on the change has to be recomputed.
+        graph.invalidate("h");
+        assert_eq!(graph.update_order(), ["volume", "mass"]);
+        graph.mark_fresh("volume");
+        graph.mark_fresh("mass");
+        graph.invalidate("r");
+        assert_eq!(graph.update_order(), ["area", "volume", "mass"]);
+        graph.invalidate_all();
+        graph.mark_fresh("area");
+        assert_eq!(graph.update_order(), ["volume", "mass"]);
+
+        assert_eq!(
+            graph.bind("r", r#"\sqrt{mass}"#, deps(&["mass"])),
+            Err(deps(&["r", "mass", "volume", "area", "r"]))
+        );
+        assert_eq!(graph.bind("h", "h+1", deps(&["h"])), Err(deps(&["h", "h"])));
+        assert_eq!(graph.get("r"), None);
+
+        // Without the binding, nothing depends on `area` anymore.
+        graph.unbind("volume").unwrap();
+        graph.mark_fresh("mass");
+        graph.invalidate("area");
+        assert!(graph.stale().is_empty());
+
+        graph.clear();
+        assert!(graph.bindings().is_empty() && graph.stale().is_empty());
+    }
+}
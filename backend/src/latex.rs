@@ -6,7 +6,11 @@ pub const FUNC_BEGIN: char = '\\';
 pub const WHITESPACE: char = ' ';
 
 pub const PI: &str = "\\pi";
+pub const INFTY: &str = "\\infty";
 pub const E: &str = "e";
+pub const I: &str = "i";
+/// `\mathrm{i}`, an upright imaginary unit.
+pub const MATHRM: &str = "mathrm";
 
 pub const PARENTHESES_L: char = '(';
 pub const PARENTHESES_R: char = ')';
@@ -20,11 +24,69 @@ pub const SUBTRACT: &str = "-";
 pub const MULTIPLY: &str = "*";
 pub const DIVIDE: &str = "/";
 
+pub const LESS: &str = "<";
+pub const GREATER: &str = ">";
+/// `\le`, which can also be written as `\leq`.
+pub const LESS_EQUAL: &str = "le";
+pub const LESS_EQUAL_ALT: &str = "leq";
+/// `\ge`, which can also be written as `\geq`.
+pub const GREATER_EQUAL: &str = "ge";
+pub const GREATER_EQUAL_ALT: &str = "geq";
+/// `\neq`, which can also be written as `\ne`.
+pub const NOT_EQUAL: &str = "neq";
+pub const NOT_EQUAL_ALT: &str = "ne";
+
+/// The `\in` in solution sets, like `x \in [0, 1]`.
+pub const IN: &str = "\\in";
+/// The union of intervals, like `(-\infty, 0) \cup (0, \infty)`.
+pub const CUP: &str = "\\cup";
+pub const EMPTY_SET: &str = "\\emptyset";
+pub const REALS: &str = "\\mathbb{R}";
+
 pub const SUPER_SCRIPT: &str = "^";
+/// The exponent of transposes, like `A^T` or `A^{T}`.
+pub const TRANSPOSE: &str = "T";
 pub const SUB_SCRIPT: char = '_';
 
+pub const BEGIN: &str = "begin";
+pub const END: &str = "end";
+pub const PMATRIX: &str = "pmatrix";
+pub const BMATRIX: &str = "bmatrix";
+pub const VMATRIX: &str = "vmatrix";
+pub const MATRIX_COL_SEP: char = '&';
+pub const MATRIX_ROW_SEP: &str = "\\\\";
+/// Systems of equations, like `\begin{cases} x+y=1 \\ x-y=0 \end{cases}`.
+pub const CASES: &str = "cases";
+/// Separates the equations of systems that aren't written as `cases`, next to `\\`.
+pub const EQUATION_SEP: char = ',';
+
+/// `\operatorname{name}`, for functions that don't have their own commands.
+pub const OPERATORNAME: &str = "operatorname";
+
 pub const FRAC: &str = "frac";
+/// The `d` in `\frac{d}{dx}`.
+pub const DIFFERENTIAL: &str = "d";
+/// The `\partial` in `\frac{\partial}{\partial x}`.
+pub const PARTIAL: &str = "partial";
 pub const ROOT: &str = "sqrt";
+/// `\int_{a}^{b} f \, dx`
+pub const INTEGRAL: &str = "int";
+/// The thin space in front of differentials, like the `\,` in `\int_{0}^{1} x \, dx`.
+pub const THIN_SPACE: &str = "\\,";
+/// `\lim_{x \to a} f`
+pub const LIMIT: &str = "lim";
+/// The arrow in the subscript of limits, like the `\to` in `\lim_{x \to 0}`.
+pub const TO: &str = "\\to";
+/// `\sum_{k=1}^{n} f`
+pub const SUM: &str = "sum";
+/// `\prod_{k=1}^{n} f`
+pub const PRODUCT: &str = "prod";
+/// The `=` in the subscripts of sums and products, like `\sum_{k=1}^{n}`.
+pub const EQUAL: &str = "=";
+/// `\operatorname{series}_{x=a}^{n} f`
+pub const SERIES: &str = "series";
+/// The remainder of series, like the `O` in `O(x^{6})`.
+pub const BIG_O: &str = "O";
 
 pub const LOG: &str = "log_";
 pub const LG: &str = "lg";
@@ -50,3 +112,10 @@ pub const TANH: &str = "tanh";
 pub const COTH: &str = "coth";
 pub const SECH: &str = "sech";
 pub const CSCH: &str = "csch";
+
+pub const ABS: &str = "abs";
+
+pub const DET: &str = "det";
+pub const RANK: &str = "rank";
+pub const TRACE: &str = "tr";
+pub const EIG: &str = "eig";
@@ -1,7 +1,16 @@
 pub mod calculator;
+pub mod dependency;
 pub mod latex;
 pub mod math;
 pub mod utils;
 
 pub type DecimalScalar = f64;
 pub type IntegerScalar = i64;
+pub type ComplexScalar = num_complex::Complex<DecimalScalar>;
+
+#[cfg(feature = "bignum")]
+pub type BigIntegerScalar = num_bigint::BigInt;
+#[cfg(feature = "bignum")]
+pub type BigRationalScalar = num_rational::BigRational;
+#[cfg(feature = "bignum")]
+pub type BigDecimalScalar = bigdecimal::BigDecimal;
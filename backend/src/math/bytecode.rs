@@ -0,0 +1,404 @@
+//! Expressions compiled to a flat list of stack machine instructions, for evaluating the same
+//! expression for many values of its variables, like when plotting.
+//!
+//! Compiled expressions give the same results as [`Function::approximate`] with the variables
+//! substituted, up to rounding.
+
+use crate::{
+    math::{
+        func::{decl::MathFunction, Function},
+        parallel,
+        symbol::Number,
+        ExpressionElement,
+    },
+    DecimalScalar, IntegerScalar,
+};
+
+/// Functions of one value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    Abs,
+    Sin,
+    Cos,
+    Tan,
+    Cot,
+    Sec,
+    Csc,
+    Arcsin,
+    Arccos,
+    Arctan,
+    Arccot,
+    Arcsec,
+    Arccsc,
+    Sinh,
+    Cosh,
+    Tanh,
+    Coth,
+    Sech,
+    Csch,
+}
+
+impl UnaryOp {
+    #[inline]
+    fn apply(self, x: DecimalScalar) -> DecimalScalar {
+        match self {
+            UnaryOp::Abs => x.abs(),
+            UnaryOp::Sin => x.sin(),
+            UnaryOp::Cos => x.cos(),
+            UnaryOp::Tan => x.tan(),
+            UnaryOp::Cot => 1. / x.tan(),
+            UnaryOp::Sec => 1. / x.cos(),
+            UnaryOp::Csc => 1. / x.sin(),
+            UnaryOp::Arcsin => x.asin(),
+            UnaryOp::Arccos => x.acos(),
+            UnaryOp::Arctan => x.atan(),
+            UnaryOp::Arccot => (1. / x).atan(),
+            UnaryOp::Arcsec => (1. / x).acos(),
+            UnaryOp::Arccsc => (1. / x).asin(),
+            UnaryOp::Sinh => x.sinh(),
+            UnaryOp::Cosh => x.cosh(),
+            UnaryOp::Tanh => x.tanh(),
+            UnaryOp::Coth => 1. / x.tanh(),
+            UnaryOp::Sech => 1. / x.cosh(),
+            UnaryOp::Csch => 1. / x.sinh(),
+        }
+    }
+}
+
+/// Functions of two values, which take their parameters in the order they are declared in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+    /// The index, then the radicand.
+    Root,
+    /// The base, then the antilogarithm.
+    Log,
+}
+
+impl BinaryOp {
+    #[inline]
+    fn apply(self, lhs: DecimalScalar, rhs: DecimalScalar) -> DecimalScalar {
+        match self {
+            BinaryOp::Add => lhs + rhs,
+            BinaryOp::Subtract => lhs - rhs,
+            BinaryOp::Multiply => lhs * rhs,
+            BinaryOp::Divide => lhs / rhs,
+            BinaryOp::Power => lhs.powf(rhs),
+            BinaryOp::Root => rhs.powf(1. / lhs),
+            BinaryOp::Log => rhs.log(lhs),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    /// Pushes a value.
+    Const(DecimalScalar),
+    /// Pushes the value of the variable in a slot.
+    Load(usize),
+    /// Replaces the top value with the function of it.
+    Unary(UnaryOp),
+    /// Replaces the top two values with the function of them.
+    Binary(BinaryOp),
+    /// Raises the top value to an integer power.
+    Powi(i32),
+    /// Pushes the approximation of a subtree that has no instructions, like an integral with a
+    /// variable bound, with the variables substituted. This is as slow as approximating the
+    /// tree.
+    Tree(Box<ExpressionElement>),
+}
+
+/// An expression compiled for its variables, which are given by slot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledExpr {
+    code: Vec<Instruction>,
+    vars: Vec<String>,
+    stack: usize,
+}
+
+impl CompiledExpr {
+    /// Compiles `expr` with a slot for each of `vars`. Subtrees without variables are
+    /// approximated right away, and other variables are `NaN`, like in
+    /// [`Function::approximate`].
+    pub fn compile(expr: &ExpressionElement, vars: &[&str]) -> CompiledExpr {
+        let mut compiled = CompiledExpr {
+            code: Vec::new(),
+            vars: vars.iter().map(|v| v.to_string()).collect(),
+            stack: 0,
+        };
+        compiled.emit(expr, 0);
+        compiled
+    }
+
+    #[inline]
+    pub fn code(&self) -> &[Instruction] {
+        &self.code
+    }
+
+    /// The variables in the order of their slots.
+    #[inline]
+    pub fn vars(&self) -> &[String] {
+        &self.vars
+    }
+
+    /// Emits the instructions for `expr` when `depth` values are already on the stack.
+    fn emit(&mut self, expr: &ExpressionElement, depth: usize) {
+        self.stack = self.stack.max(depth + 1);
+
+        if !expr.is_symbolic() {
+            self.code.push(Instruction::Const(expr.approximate()));
+            return;
+        }
+
+        let func = match expr {
+            ExpressionElement::Variable(v) => {
+                self.code
+                    .push(match self.vars.iter().position(|var| var == v) {
+                        Some(slot) => Instruction::Load(slot),
+                        None => Instruction::Const(DecimalScalar::NAN),
+                    });
+                return;
+            }
+            ExpressionElement::Function(func) => func,
+            _ => {
+                self.code.push(Instruction::Tree(Box::new(expr.clone())));
+                return;
+            }
+        };
+
+        let unary = match func.as_ref() {
+            MathFunction::Abs(_) => Some(UnaryOp::Abs),
+            MathFunction::Sin(_) => Some(UnaryOp::Sin),
+            MathFunction::Cos(_) => Some(UnaryOp::Cos),
+            MathFunction::Tan(_) => Some(UnaryOp::Tan),
+            MathFunction::Cot(_) => Some(UnaryOp::Cot),
+            MathFunction::Sec(_) => Some(UnaryOp::Sec),
+            MathFunction::Csc(_) => Some(UnaryOp::Csc),
+            MathFunction::Arcsin(_) => Some(UnaryOp::Arcsin),
+            MathFunction::Arccos(_) => Some(UnaryOp::Arccos),
+            MathFunction::Arctan(_) => Some(UnaryOp::Arctan),
+            MathFunction::Arccot(_) => Some(UnaryOp::Arccot),
+            MathFunction::Arcsec(_) => Some(UnaryOp::Arcsec),
+            MathFunction::Arccsc(_) => Some(UnaryOp::Arccsc),
+            MathFunction::Sinh(_) => Some(UnaryOp::Sinh),
+            MathFunction::Cosh(_) => Some(UnaryOp::Cosh),
+            MathFunction::Tanh(_) => Some(UnaryOp::Tanh),
+            MathFunction::Coth(_) => Some(UnaryOp::Coth),
+            MathFunction::Sech(_) => Some(UnaryOp::Sech),
+            MathFunction::Csch(_) => Some(UnaryOp::Csch),
+            _ => None,
+        };
+        let binary = match func.as_ref() {
+            MathFunction::Add(_) => Some(BinaryOp::Add),
+            MathFunction::Subtract(_) => Some(BinaryOp::Subtract),
+            MathFunction::Multiply(_) => Some(BinaryOp::Multiply),
+            MathFunction::Divide(_) | MathFunction::Fraction(_) => Some(BinaryOp::Divide),
+            MathFunction::Power(_) => Some(BinaryOp::Power),
+            MathFunction::Root(_) => Some(BinaryOp::Root),
+            MathFunction::Log(_) => Some(BinaryOp::Log),
+            _ => None,
+        };
+
+        let params = func.params();
+        // Integer powers are much faster with repeated multiplication.
+        let exp = match (binary, params.get(1)) {
+            (Some(BinaryOp::Power), Some(ExpressionElement::Number(Number::Integer(n)))) => {
+                i32::try_from(*n).ok()
+            }
+            _ => None,
+        };
+        match (unary, binary, exp) {
+            (Some(op), ..) => {
+                self.emit(params[0], depth);
+                self.code.push(Instruction::Unary(op));
+            }
+            (_, _, Some(n)) => {
+                self.emit(params[0], depth);
+                self.code.push(Instruction::Powi(n));
+            }
+            (_, Some(op), _) => {
+                self.emit(params[0], depth);
+                self.emit(params[1], depth + 1);
+                self.code.push(Instruction::Binary(op));
+            }
+            _ => self.code.push(Instruction::Tree(Box::new(expr.clone()))),
+        }
+    }
+
+    /// The value for `values`, which are the variables in the order of their slots.
+    ///
+    /// # Panics
+    ///
+    /// If there aren't as many values as variables.
+    #[inline]
+    pub fn eval(&self, values: &[DecimalScalar]) -> DecimalScalar {
+        assert_eq!(values.len(), self.vars.len());
+        self.run(|slot| values[slot], &mut Vec::with_capacity(self.stack))
+    }
+
+    /// The values for many assignments of the variables, where `inputs` has the values of each
+    /// variable in the order of their slots. Large batches are split across
+    /// [`parallel::threads`].
+    ///
+    /// # Panics
+    ///
+    /// If there isn't one slice per variable, or they have different lengths.
+    pub fn eval_batch(&self, inputs: &[&[DecimalScalar]]) -> Vec<DecimalScalar> {
+        let len = inputs.first().map_or(1, |input| input.len());
+        let chunk = len.div_ceil(parallel::threads()).max(1);
+        parallel::map(len.div_ceil(chunk), len * self.code.len(), |i| {
+            let range = i * chunk..len.min(i * chunk + chunk);
+            let inputs = inputs
+                .iter()
+                .map(|input| &input[range.clone()])
+                .collect::<Vec<_>>();
+            let mut output = vec![0.; range.len()];
+            self.eval_batch_into(&inputs, &mut output);
+            output
+        })
+        .concat()
+    }
+
+    /// Like [`CompiledExpr::eval_batch`], but writes into `output`, which has to be as long as
+    /// the inputs.
+    pub fn eval_batch_into(&self, inputs: &[&[DecimalScalar]], output: &mut [DecimalScalar]) {
+        assert_eq!(inputs.len(), self.vars.len());
+        assert!(inputs.iter().all(|input| input.len() == output.len()));
+
+        let mut stack = Vec::with_capacity(self.stack);
+        for (i, out) in output.iter_mut().enumerate() {
+            *out = self.run(|slot| inputs[slot][i], &mut stack);
+        }
+    }
+
+    /// Runs the code, where `value` gives the value in a slot.
+    #[inline]
+    fn run(
+        &self,
+        value: impl Fn(usize) -> DecimalScalar,
+        stack: &mut Vec<DecimalScalar>,
+    ) -> DecimalScalar {
+        stack.clear();
+        for instruction in &self.code {
+            match instruction {
+                Instruction::Const(c) => stack.push(*c),
+                Instruction::Load(slot) => stack.push(value(*slot)),
+                Instruction::Unary(op) => {
+                    let x = stack.last_mut().unwrap();
+                    *x = op.apply(*x);
+                }
+                Instruction::Powi(n) => {
+                    let x = stack.last_mut().unwrap();
+                    *x = x.powi(*n);
+                }
+                Instruction::Binary(op) => {
+                    let rhs = stack.pop().unwrap();
+                    let lhs = stack.last_mut().unwrap();
+                    *lhs = op.apply(*lhs, rhs);
+                }
+                Instruction::Tree(tree) => stack.push(
+                    self.vars
+                        .iter()
+                        .enumerate()
+                        .fold(tree.as_ref().clone(), |tree, (slot, var)| {
+                            tree.substitute(var, &substituted(value(slot)).into())
+                        })
+                        .approximate(),
+                ),
+            }
+        }
+        stack.pop().unwrap()
+    }
+}
+
+/// The number substituted into the parts that aren't compiled. Whole values are integers, as
+/// things like the bounds of sums only take those.
+fn substituted(x: DecimalScalar) -> Number {
+    if x.fract() == 0. && x.abs() <= IntegerScalar::MAX as DecimalScalar / 2. {
+        Number::Integer(x as IntegerScalar)
+    } else {
+        Number::Decimal(x)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::math::{
+        bytecode::{CompiledExpr, Instruction},
+        expr::ExpresssionTree,
+        parallel,
+        symbol::Number,
+        ExpressionElement, FromRawExpr, Function, ParsingScope,
+    };
+
+    #[test]
+    fn test_bytecode() {
+        let scope = ParsingScope::new(None).with_symbol("x").with_symbol("y");
+        let parse =
+            |expr| ExpressionElement::from(ExpresssionTree::parse_scoped(expr, &scope).unwrap());
+        let approximate = |expr: &ExpressionElement, x: f64, y: f64| {
+            expr.substitute("x", &Number::Decimal(x).into())
+                .substitute("y", &Number::Decimal(y).into())
+                .approximate()
+        };
+
+        for expr in [
+            r#"x^2+2*x*y-\frac{y}{3}"#,
+            r#"\sin{x}^2+\cos{y}^2"#,
+            r#"\sqrt{x}+\sqrt[3]{y}-\log_{2}{x}+\ln{y}"#,
+            r#"\sec{x}*\arctan{y}-\abs{x-y}+\sinh{x}"#,
+            r#"e^{x}*\pi-\frac{d}{dx}{x^3}"#,
+            "y",
+            "2",
+        ] {
+            let tree = parse(expr);
+            let compiled = CompiledExpr::compile(&tree, &["x", "y"]);
+            for (x, y) in [(0.5, 2.), (3., 0.25), (1.5, 1.5)] {
+                let (expected, actual) = (approximate(&tree, x, y), compiled.eval(&[x, y]));
+                assert!(
+                    (expected - actual).abs() < 1e-12,
+                    "{}: {} {}",
+                    expr,
+                    expected,
+                    actual
+                );
+            }
+        }
+
+        // Constants are folded, and derivatives are approximated as trees.
+        let compiled =
+            CompiledExpr::compile(&parse(r#"\frac{\pi}{2}*x+\frac{d}{dx}{x^2}"#), &["x"]);
+        assert_eq!(compiled.code().len(), 5);
+        assert!(matches!(compiled.code()[0], Instruction::Const(_)));
+        assert!(matches!(compiled.code()[3], Instruction::Tree(_)));
+
+        let compiled = CompiledExpr::compile(&parse("x^3"), &["x"]);
+        assert_eq!(
+            compiled.code(),
+            [Instruction::Load(0), Instruction::Powi(3)]
+        );
+
+        let compiled = CompiledExpr::compile(&parse("x*y+1"), &["x", "y"]);
+        assert_eq!(
+            compiled.eval_batch(&[&[1., 2., 3.], &[4., 5., 6.]]),
+            [5., 11., 19.]
+        );
+        let xs = (0..100_000).map(|i| i as f64).collect::<Vec<_>>();
+        let compiled = CompiledExpr::compile(&parse("2*x+1"), &["x"]);
+        let expected = xs.iter().map(|x| 2. * x + 1.).collect::<Vec<_>>();
+        assert_eq!(compiled.eval_batch(&[&xs]), expected);
+        assert_eq!(
+            parallel::with_threads(4, || compiled.eval_batch(&[&xs])),
+            expected
+        );
+        assert!(compiled.eval_batch(&[&[]]).is_empty());
+
+        assert!(CompiledExpr::compile(&parse("x+y"), &["x"])
+            .eval(&[1.])
+            .is_nan());
+    }
+}
@@ -1,15 +1,30 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Range};
 
 use crate::{
     latex::*,
     math::{
-        func::decl::{get_phantom_function, get_phantom_operator},
+        bytecode::CompiledExpr,
+        func::{
+            decl::{
+                get_phantom_function, get_phantom_operator, PhantomDerivative,
+                PhantomIndefiniteIntegral, PhantomIntegral, PhantomLimit, PhantomPartialDerivative,
+                PhantomProduct, PhantomSeries, PhantomSum, PhantomTranspose, Series,
+            },
+            inequality::{self, IntervalSet},
+            limit::Direction,
+            series::MAX_ORDER,
+            solve::{self, Roots},
+            Differentiable, PhantomFunction,
+        },
+        matrix::{self, Matrix},
+        mpoly::MultiPolynomial,
+        simplify::Simplifier,
         symbol::{BracketState, Constant, Number},
         ExpressionElement, FromRawExpr, Function, IntoRawExpr, LaTexParsingError,
-        LaTexParsingErrorType, LaTexParsingResult, MathElement,
+        LaTexParsingErrorType, LaTexParsingResult, MathElement, ParsingScope,
     },
     utils::BracketStack,
-    DecimalScalar,
+    DecimalScalar, IntegerScalar,
 };
 
 use super::MathElementMeta;
@@ -20,7 +35,27 @@ pub struct ExpressionBuffer {
 }
 
 impl FromRawExpr for ExpressionBuffer {
+    #[inline]
     fn parse_raw(expr: &str, vars: Option<&HashMap<String, Number>>) -> LaTexParsingResult<Self> {
+        Self::parse_scoped(expr, &ParsingScope::new(vars))
+    }
+
+    /// Moves the positions of the parsed elements too, so errors that are only found when
+    /// building the tree point into the whole expression.
+    fn parse_scoped_with_base_index(
+        expr: &str,
+        scope: &ParsingScope,
+        base: usize,
+    ) -> LaTexParsingResult<Self> {
+        let mut buffer = Self::parse_scoped(expr, scope).map_err(|mut e| {
+            e.at.start += base;
+            e
+        })?;
+        buffer.shift(base);
+        Ok(buffer)
+    }
+
+    fn parse_scoped(expr: &str, scope: &ParsingScope) -> LaTexParsingResult<Self> {
         if expr.is_empty() {
             return Err(LaTexParsingError::new(
                 MathElementMeta::at(0),
@@ -28,8 +63,8 @@ impl FromRawExpr for ExpressionBuffer {
             ));
         }
 
-        let default = HashMap::default();
-        let vars = vars.unwrap_or(&default);
+        // Symbols bound by derivatives are visible in the rest of the expression.
+        let mut scope = scope.clone();
 
         // The tokenized expression
         let mut expr_buffer = Vec::new();
@@ -50,16 +85,22 @@ impl FromRawExpr for ExpressionBuffer {
         let mut user_subexpr_start_depth = 0;
         // The start index of a function definition, after the backslash
         let mut func_def_start = -1;
+        // Characters before this index are already consumed, like the `{i}` in `\mathrm{i}`
+        let mut skip_until = 0;
 
         let mut curly_brackets = BracketStack::default();
         let mut parentheses = BracketStack::default();
 
-        for (i, c) in expr.chars().enumerate() {
-            match &c {
-                &CURLY_BRACKET_L => curly_brackets.push(BracketState::Open),
-                &CURLY_BRACKET_R => curly_brackets.push(BracketState::Close),
-                &PARENTHESES_L => parentheses.push(BracketState::Open),
-                &PARENTHESES_R => parentheses.push(BracketState::Close),
+        for (i, c) in expr.char_indices() {
+            if i < skip_until {
+                continue;
+            }
+
+            match c {
+                CURLY_BRACKET_L => curly_brackets.push(BracketState::Open),
+                CURLY_BRACKET_R => curly_brackets.push(BracketState::Close),
+                PARENTHESES_L => parentheses.push(BracketState::Open),
+                PARENTHESES_R => parentheses.push(BracketState::Close),
                 _ => {}
             };
 
@@ -72,12 +113,12 @@ impl FromRawExpr for ExpressionBuffer {
 
             // Function Subexpressions
             if func_subexpr_start != -1
-                && ((c == CURLY_BRACKET_R && curly_brackets.depth() == func_subexpr_start_depth)
-                    || c == WHITESPACE)
+                && c == CURLY_BRACKET_R
+                && curly_brackets.depth() == func_subexpr_start_depth
             {
-                let elem = MathElement::Expression(ExpressionBuffer::parse_raw_with_base_index(
+                let elem = MathElement::Expression(ExpressionBuffer::parse_scoped_with_base_index(
                     &expr[func_subexpr_start as usize..i],
-                    Some(vars),
+                    &scope,
                     func_subexpr_start as usize,
                 )?);
                 let meta = Some((func_subexpr_start as usize..i).into());
@@ -92,20 +133,21 @@ impl FromRawExpr for ExpressionBuffer {
                 && c == PARENTHESES_R
                 && parentheses.depth() == user_subexpr_start_depth
             {
+                push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
                 expr_buffer.push(Some((
                     MathElement::Parentheses(BracketState::Open),
                     Some(MathElementMeta::at(user_subexpr_start as usize - 1)),
                 )));
 
                 expr_buffer.extend(
-                    ExpressionBuffer::parse_raw_with_base_index(
+                    ExpressionBuffer::parse_scoped_with_base_index(
                         &expr[user_subexpr_start as usize..i],
-                        Some(vars),
+                        &scope,
                         user_subexpr_start as usize,
                     )?
                     .expr
                     .into_iter()
-                    .map(|e| Some(e)),
+                    .map(Some),
                 );
 
                 expr_buffer.push(Some((
@@ -117,8 +159,388 @@ impl FromRawExpr for ExpressionBuffer {
                 continue;
             }
 
+            // Constants
+            if func_def_start != -1 && !c.is_ascii_alphabetic() {
+                let name_range = func_def_start as usize - 1..i;
+                if let Ok(constant) = Constant::parse_raw(&expr[name_range.clone()], None) {
+                    push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
+                    expr_buffer.push(Some((
+                        MathElement::Number(Number::Constant(constant)),
+                        Some(name_range.into()),
+                    )));
+                    func_def_start = -1;
+                }
+            }
+
+            // Relations written as commands, like `\le`
+            if func_def_start != -1 && !c.is_ascii_alphabetic() {
+                let name = &expr[func_def_start as usize..i];
+                if matches!(
+                    name,
+                    LESS_EQUAL
+                        | LESS_EQUAL_ALT
+                        | GREATER_EQUAL
+                        | GREATER_EQUAL_ALT
+                        | NOT_EQUAL
+                        | NOT_EQUAL_ALT
+                ) {
+                    expr_buffer.push(Some((
+                        MathElement::PhantomOperator(get_phantom_operator(name).unwrap()),
+                        Some((func_def_start as usize - 1..i).into()),
+                    )));
+                    func_def_start = -1;
+                    if c == WHITESPACE {
+                        continue;
+                    }
+                }
+            }
+
+            // Matrices
+            if func_def_start != -1
+                && c == CURLY_BRACKET_L
+                && &expr[func_def_start as usize..i] == BEGIN
+            {
+                let start = func_def_start as usize - 1;
+                let end = matrix::environment_len(&expr[start..])
+                    .map(|len| start + len)
+                    .ok_or_else(|| {
+                        LaTexParsingError::new(
+                            (start..expr.len()).into(),
+                            LaTexParsingErrorType::InvalidMatrix,
+                        )
+                    })?;
+                let m = Matrix::parse_scoped_with_base_index(&expr[start..end], &scope, start)?;
+
+                push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
+                expr_buffer.push(Some((MathElement::Matrix(m), Some((start..end).into()))));
+
+                curly_brackets.pop();
+                func_def_start = -1;
+                skip_until = end;
+                continue;
+            }
+
+            // Upright constants
+            if func_def_start != -1
+                && c == CURLY_BRACKET_L
+                && &expr[func_def_start as usize..i] == MATHRM
+            {
+                let end = expr[i..]
+                    .find(CURLY_BRACKET_R)
+                    .map_or(expr.len(), |end| i + end + 1);
+                let name_range = func_def_start as usize - 1..end;
+                let constant = Constant::parse_raw_with_base_index(
+                    &expr[name_range.clone()],
+                    None,
+                    name_range.start,
+                )?;
+
+                push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
+                expr_buffer.push(Some((
+                    MathElement::Number(Number::Constant(constant)),
+                    Some(name_range.into()),
+                )));
+
+                // The brackets are part of the constant.
+                curly_brackets.pop();
+                func_def_start = -1;
+                skip_until = end;
+                continue;
+            }
+
+            // Sums, products and series
+            let indexed: Option<(Box<dyn PhantomFunction>, usize)> =
+                if func_def_start == -1 || c.is_ascii_alphabetic() {
+                    None
+                } else {
+                    match &expr[func_def_start as usize..i] {
+                        SUM => Some((Box::<PhantomSum>::default(), i)),
+                        PRODUCT => Some((Box::<PhantomProduct>::default(), i)),
+                        OPERATORNAME
+                            if c == CURLY_BRACKET_L
+                                && expr[i + 1..].starts_with(SERIES)
+                                && expr[i + 1 + SERIES.len()..].starts_with(CURLY_BRACKET_R) =>
+                        {
+                            Some((Box::<PhantomSeries>::default(), i + SERIES.len() + 2))
+                        }
+                        _ => None,
+                    }
+                };
+            if let Some((f, i)) = indexed {
+                let sum = match_sum(&expr[i..]).ok_or_else(|| {
+                    LaTexParsingError::new(
+                        (func_def_start as usize..i).into(),
+                        LaTexParsingErrorType::InvalidFunctionCall,
+                    )
+                })?;
+                let end = i + sum.len;
+                let var_range = sum.var.start + i..sum.var.end + i;
+
+                // Series need a positive order, unless it's only known later.
+                if expr[func_def_start as usize..].starts_with(OPERATORNAME) {
+                    let range = sum.upper.start + i..sum.upper.end + i;
+                    let order = ExpressionElement::from(
+                        ExpresssionTree::parse_scoped_with_base_index(
+                            &expr[range.clone()],
+                            &scope,
+                            range.start,
+                        )?
+                        .evaluate(),
+                    );
+                    let valid = order
+                        .as_rational()
+                        .is_some_and(|r| r.is_integer() && (1..=MAX_ORDER).contains(&r.num()));
+                    if !valid && !order.is_symbolic() {
+                        return Err(LaTexParsingError::new(
+                            range.into(),
+                            LaTexParsingErrorType::InvalidFunctionCall,
+                        ));
+                    }
+                }
+                let var = expr[var_range.clone()].to_string();
+                let operand = sum.operand.start + i..sum.operand.end + i;
+
+                push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
+                expr_buffer.push(Some((
+                    MathElement::PhantomFunction(f),
+                    Some((func_def_start as usize..end).into()),
+                )));
+                expr_buffer.push(Some((
+                    MathElement::Variable(var.clone()),
+                    Some(var_range.into()),
+                )));
+
+                for bound in [sum.lower, sum.upper] {
+                    let bound = bound.start + i..bound.end + i;
+                    expr_buffer.push(Some((
+                        MathElement::Expression(ExpressionBuffer::parse_scoped_with_base_index(
+                            &expr[bound.clone()],
+                            &scope,
+                            bound.start,
+                        )?),
+                        Some(bound.into()),
+                    )));
+                }
+
+                // The index is only visible in the operand.
+                expr_buffer.push(Some((
+                    MathElement::Expression(ExpressionBuffer::parse_scoped_with_base_index(
+                        &expr[operand.clone()],
+                        &scope.clone().with_symbol(var),
+                        operand.start,
+                    )?),
+                    Some(operand.into()),
+                )));
+
+                // The brackets of `\operatorname{series}` are part of the name.
+                if c == CURLY_BRACKET_L {
+                    curly_brackets.pop();
+                }
+                func_def_start = -1;
+                skip_until = end;
+                continue;
+            }
+
+            // Operator names
+            if func_def_start != -1
+                && c == CURLY_BRACKET_L
+                && &expr[func_def_start as usize..i] == OPERATORNAME
+            {
+                let end = expr[i..]
+                    .find(CURLY_BRACKET_R)
+                    .map(|end| i + end + 1)
+                    .ok_or_else(|| {
+                        LaTexParsingError::new(
+                            MathElementMeta::at(i),
+                            LaTexParsingErrorType::InvalidBracketStructure,
+                        )
+                    })?;
+                let f = get_phantom_function(&expr[i + 1..end - 1]).ok_or_else(|| {
+                    LaTexParsingError::new(
+                        (func_def_start as usize..end).into(),
+                        LaTexParsingErrorType::UnknownFunctionName,
+                    )
+                })?;
+
+                push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
+                expr_buffer.push(Some((
+                    MathElement::PhantomFunction(f),
+                    Some((func_def_start as usize..end).into()),
+                )));
+
+                if expr[end..].starts_with([WHITESPACE, PARENTHESES_L]) {
+                    univariate_funcs.push(expr_buffer.len() - 1);
+                }
+
+                // The brackets are part of the name.
+                curly_brackets.pop();
+                func_def_start = -1;
+                skip_until = end;
+                continue;
+            }
+
+            // Derivatives
+            if func_def_start != -1
+                && c == CURLY_BRACKET_L
+                && &expr[func_def_start as usize..i] == FRAC
+            {
+                if let Some((len, var_range, partial)) = match_differential(&expr[i..]) {
+                    let end = i + len;
+                    let var_range = var_range.start + i..var_range.end + i;
+                    let var = expr[var_range.clone()].to_string();
+
+                    push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
+                    let f: Box<dyn PhantomFunction> = if partial {
+                        Box::<PhantomPartialDerivative>::default()
+                    } else {
+                        Box::<PhantomDerivative>::default()
+                    };
+                    expr_buffer.push(Some((
+                        MathElement::PhantomFunction(f),
+                        Some((func_def_start as usize..end).into()),
+                    )));
+                    expr_buffer.push(Some((
+                        MathElement::Variable(var.clone()),
+                        Some(var_range.into()),
+                    )));
+
+                    // The variable takes the place of the function, so the parameter comes
+                    // right after it.
+                    if expr[end..].starts_with([WHITESPACE, PARENTHESES_L]) {
+                        univariate_funcs.push(expr_buffer.len() - 1);
+                    }
+
+                    scope.add_symbol(var);
+                    curly_brackets.pop();
+                    func_def_start = -1;
+                    skip_until = end;
+                    continue;
+                }
+            }
+
+            // Integrals
+            if func_def_start != -1
+                && !c.is_ascii_alphabetic()
+                && &expr[func_def_start as usize..i] == INTEGRAL
+            {
+                let int = match_integral(&expr[i..]).ok_or_else(|| {
+                    LaTexParsingError::new(
+                        (func_def_start as usize..i).into(),
+                        LaTexParsingErrorType::InvalidFunctionCall,
+                    )
+                })?;
+                let end = i + int.len;
+                let var_range = int.var.start + i..int.var.end + i;
+                let var = expr[var_range.clone()].to_string();
+
+                push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
+                let f: Box<dyn PhantomFunction> = if int.bounds.is_some() {
+                    Box::<PhantomIntegral>::default()
+                } else {
+                    Box::<PhantomIndefiniteIntegral>::default()
+                };
+                expr_buffer.push(Some((
+                    MathElement::PhantomFunction(f),
+                    Some((func_def_start as usize..end).into()),
+                )));
+                expr_buffer.push(Some((
+                    MathElement::Variable(var.clone()),
+                    Some(var_range.into()),
+                )));
+
+                for bound in int.bounds.into_iter().flat_map(<[_; 2]>::from) {
+                    let bound = bound.start + i..bound.end + i;
+                    expr_buffer.push(Some((
+                        MathElement::Expression(ExpressionBuffer::parse_scoped_with_base_index(
+                            &expr[bound.clone()],
+                            &scope,
+                            bound.start,
+                        )?),
+                        Some(bound.into()),
+                    )));
+                }
+
+                // The variable of integration is only visible in the integrand.
+                let integrand = int.integrand.start + i..int.integrand.end + i;
+                let elem = if integrand.is_empty() {
+                    MathElement::Number(Number::Integer(1))
+                } else {
+                    MathElement::Expression(ExpressionBuffer::parse_scoped_with_base_index(
+                        &expr[integrand.clone()],
+                        &scope.clone().with_symbol(var),
+                        integrand.start,
+                    )?)
+                };
+                expr_buffer.push(Some((elem, Some(integrand.into()))));
+
+                // The bracket right after `\int` is part of the integral.
+                match c {
+                    CURLY_BRACKET_L => curly_brackets.pop(),
+                    PARENTHESES_L => parentheses.pop(),
+                    _ => {}
+                }
+                func_def_start = -1;
+                skip_until = end;
+                continue;
+            }
+
+            // Limits
+            if func_def_start != -1
+                && !c.is_ascii_alphabetic()
+                && &expr[func_def_start as usize..i] == LIMIT
+            {
+                let lim = match_limit(&expr[i..]).ok_or_else(|| {
+                    LaTexParsingError::new(
+                        (func_def_start as usize..i).into(),
+                        LaTexParsingErrorType::InvalidFunctionCall,
+                    )
+                })?;
+                let end = i + lim.len;
+                let var_range = lim.var.start + i..lim.var.end + i;
+                let var = expr[var_range.clone()].to_string();
+                let point = lim.point.start + i..lim.point.end + i;
+                let operand = lim.operand.start + i..lim.operand.end + i;
+
+                push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
+                expr_buffer.push(Some((
+                    MathElement::PhantomFunction(Box::<PhantomLimit>::default()),
+                    Some((func_def_start as usize..end).into()),
+                )));
+                expr_buffer.push(Some((
+                    MathElement::Variable(var.clone()),
+                    Some(var_range.into()),
+                )));
+                expr_buffer.push(Some((
+                    MathElement::Expression(ExpressionBuffer::parse_scoped_with_base_index(
+                        &expr[point.clone()],
+                        &scope,
+                        point.start,
+                    )?),
+                    Some(point.into()),
+                )));
+                expr_buffer.push(Some((
+                    MathElement::Number(Number::Integer(lim.direction.sign())),
+                    None,
+                )));
+                // The variable is only visible in the operand.
+                expr_buffer.push(Some((
+                    MathElement::Expression(ExpressionBuffer::parse_scoped_with_base_index(
+                        &expr[operand.clone()],
+                        &scope.clone().with_symbol(var),
+                        operand.start,
+                    )?),
+                    Some(operand.into()),
+                )));
+
+                func_def_start = -1;
+                skip_until = end;
+                continue;
+            }
+
             // Functions
-            if func_def_start != -1 && (c == CURLY_BRACKET_L || c == WHITESPACE) {
+            if func_def_start != -1
+                && (c == CURLY_BRACKET_L || c == WHITESPACE || c == PARENTHESES_L)
+            {
                 let mut f_name = &expr[func_def_start as usize..i];
                 let mut meta = (func_def_start as usize..i).into();
                 let mut optional_param = handle_optional_params(f_name);
@@ -133,7 +555,7 @@ impl FromRawExpr for ExpressionBuffer {
                     optional_param = Some(Number::parse_raw_with_base_index(
                         &f_name[param_range.clone()],
                         None,
-                        i,
+                        glb_param_range.start,
                     )?);
                     optional_param_meta = Some(glb_param_range.into());
                     meta = (func_def_start as usize..func_def_start as usize + ROOT.len()).into();
@@ -148,6 +570,7 @@ impl FromRawExpr for ExpressionBuffer {
                     )
                 })?;
 
+                push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
                 expr_buffer.push(Some((MathElement::PhantomFunction(f), Some(meta))));
 
                 if let Some(opt) = optional_param {
@@ -161,20 +584,23 @@ impl FromRawExpr for ExpressionBuffer {
 
                 func_def_start = -1;
 
-                if c == WHITESPACE {
+                // Parentheses are handled later as a user subexpression.
+                if c == WHITESPACE || c == PARENTHESES_L {
                     univariate_funcs.push(expr_buffer.len() - 1);
+                }
+                if c == WHITESPACE {
                     continue;
                 }
             }
 
             if number_start != -1 {
-                if !(c.is_digit(10) || c == '.') {
+                if !(c.is_ascii_digit() || c == '.') {
                     // Real Numbers
                     expr_buffer.push(Some((
                         MathElement::Number(Number::parse_raw_with_base_index(
                             &expr[number_start as usize..i],
                             None,
-                            i,
+                            number_start as usize,
                         )?),
                         Some((number_start as usize..i).into()),
                     )));
@@ -205,56 +631,139 @@ impl FromRawExpr for ExpressionBuffer {
                 continue;
             }
 
-            if c.is_digit(10) || c == '.' {
+            if c.is_ascii_digit() || c == '.' {
                 number_start = i as i32;
                 continue;
             }
 
-            // Constants
-
             // Custom Variables
             if c.is_ascii_lowercase() {
                 if custom_var_start == -1 {
                     custom_var_start = i as i32;
                 }
 
-                if let Some(val) = vars.get(&expr[custom_var_start as usize..i + 1]) {
-                    if !expr_buffer.is_empty()
-                        && expr_buffer.last().unwrap().as_ref().is_some_and(|(e, _)| {
-                            !matches!(
-                                e,
-                                MathElement::PhantomOperator(_) | MathElement::Parentheses(_)
+                let name = &expr[custom_var_start as usize..i + 1];
+
+                // Calls of user functions, like `f(3)`
+                if expr[i + 1..].starts_with(PARENTHESES_L)
+                    && !scope.is_symbol(name)
+                    && (scope.is_expanding(name) || scope.get_function(name).is_some())
+                {
+                    let (len, arg_ranges) = match_call(&expr[i + 1..]).ok_or_else(|| {
+                        LaTexParsingError::new(
+                            MathElementMeta::at(i + 1),
+                            LaTexParsingErrorType::InvalidBracketStructure,
+                        )
+                    })?;
+                    let end = i + 1 + len;
+                    let call = custom_var_start as usize..end;
+                    let err = |ty| LaTexParsingError::new(call.clone().into(), ty);
+
+                    let f = match scope.get_function(name) {
+                        Some(f) if !scope.is_expanding(name) => f,
+                        _ => return Err(err(LaTexParsingErrorType::RecursiveFunction)),
+                    };
+                    let args = arg_ranges
+                        .into_iter()
+                        .map(|arg| {
+                            let arg = arg.start + i + 1..arg.end + i + 1;
+                            ExpresssionTree::parse_scoped_with_base_index(
+                                &expr[arg.clone()],
+                                &scope,
+                                arg.start,
                             )
+                            .map(ExpressionElement::from)
                         })
+                        .collect::<LaTexParsingResult<Vec<_>>>()?;
+                    if args.len() != f.params().len() {
+                        return Err(err(LaTexParsingErrorType::ArityMismatch));
+                    }
+                    // Positions in the body mean nothing to the caller.
+                    let value = f.call(name, &args, &scope).map_err(|e| err(e.ty))?;
+
+                    push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
+                    expr_buffer.push(Some((value.into(), Some(call.into()))));
+                    custom_var_start = -1;
+                    skip_until = end;
+                    continue;
+                }
+
+                // Symbols are bound by the expression itself, so they shadow variables.
+                let val = match scope.get_var(name) {
+                    _ if scope.is_symbol(name) => Some(MathElement::Variable(name.to_string())),
+                    Some(_) if scope.keeps_variable_refs() => {
+                        Some(MathElement::Variable(name.to_string()))
+                    }
+                    Some(val) => Some(MathElement::Number(val.clone())),
+                    // Longer names that start with `e` and `i` come first, like `index`.
+                    None if matches!(name, E | I)
+                        && !scope.starts_other_name(&expr[custom_var_start as usize..], name)
+                        && !scope.starts_call(&expr[custom_var_start as usize..]) =>
                     {
-                        expr_buffer.push(Some((
-                            MathElement::PhantomOperator(get_phantom_operator(MULTIPLY).unwrap()),
-                            None,
-                        )));
+                        Constant::parse_raw(name, None)
+                            .ok()
+                            .map(|c| MathElement::Number(Number::Constant(c)))
                     }
+                    None => None,
+                };
 
-                    expr_buffer.push(Some((
-                        MathElement::Number(*val),
-                        Some((custom_var_start as usize..i + 1).into()),
-                    )));
+                if let Some(val) = val {
+                    push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
+                    expr_buffer.push(Some((val, Some((custom_var_start as usize..i + 1).into()))));
                     custom_var_start = -1;
                 }
                 continue;
             }
 
             if custom_var_start != -1 {
-                return Err(LaTexParsingError::new(
-                    (custom_var_start as usize..i).into(),
-                    LaTexParsingErrorType::UnknownVariable,
-                ));
+                let name_range = custom_var_start as usize..i;
+                if !scope.has_free_symbols() {
+                    return Err(LaTexParsingError::new(
+                        name_range.into(),
+                        LaTexParsingErrorType::UnknownVariable,
+                    ));
+                }
+
+                push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
+                expr_buffer.push(Some((
+                    MathElement::Variable(expr[name_range.clone()].to_string()),
+                    Some(name_range.into()),
+                )));
+                custom_var_start = -1;
+            }
+
+            // Transposes
+            if c.to_string() == SUPER_SCRIPT {
+                let transpose = [
+                    format!("{}{}{}", CURLY_BRACKET_L, TRANSPOSE, CURLY_BRACKET_R),
+                    TRANSPOSE.to_string(),
+                ]
+                .into_iter()
+                .find(|t| expr[i + 1..].starts_with(t.as_str()));
+
+                if let Some(t) = transpose {
+                    skip_until = i + 1 + t.len();
+                    expr_buffer.push(Some((
+                        MathElement::PostfixFunction(Box::<PhantomTranspose>::default()),
+                        Some((i..skip_until).into()),
+                    )));
+                    continue;
+                }
             }
 
             // Operators
             if matches!(
                 c.to_string().as_str(),
-                ADD | SUBTRACT | MULTIPLY | DIVIDE | SUPER_SCRIPT
+                ADD | SUBTRACT | MULTIPLY | DIVIDE | SUPER_SCRIPT | EQUAL | LESS | GREATER
             ) {
-                if matches!(c.to_string().as_str(), ADD | SUBTRACT) && expr_buffer.is_empty() {
+                // Signs at the start of either side of an equation or inequality.
+                if matches!(c.to_string().as_str(), ADD | SUBTRACT)
+                    && (expr_buffer.is_empty()
+                        || matches!(
+                            expr_buffer.last(),
+                            Some(Some((MathElement::PhantomOperator(op), _))) if op.priority() == 0
+                        ))
+                {
                     expr_buffer.push(Some((MathElement::Number(Number::Integer(0)), None)));
                 }
 
@@ -288,6 +797,40 @@ impl FromRawExpr for ExpressionBuffer {
             )));
         }
 
+        if func_def_start != -1 {
+            let name_range = func_def_start as usize - 1..expr.len();
+            let constant = Constant::parse_raw(&expr[name_range.clone()], None).map_err(|_| {
+                let ty = if get_phantom_function(&expr[func_def_start as usize..]).is_some() {
+                    LaTexParsingErrorType::InvalidFunctionCall
+                } else {
+                    LaTexParsingErrorType::UnknownFunctionName
+                };
+                LaTexParsingError::new((func_def_start as usize..expr.len()).into(), ty)
+            })?;
+
+            push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
+            expr_buffer.push(Some((
+                MathElement::Number(Number::Constant(constant)),
+                Some(name_range.into()),
+            )));
+        }
+
+        if custom_var_start != -1 {
+            let name_range = custom_var_start as usize..expr.len();
+            if !scope.has_free_symbols() {
+                return Err(LaTexParsingError::new(
+                    name_range.into(),
+                    LaTexParsingErrorType::UnknownVariable,
+                ));
+            }
+
+            push_implicit_multiply(&mut expr_buffer, &univariate_funcs);
+            expr_buffer.push(Some((
+                MathElement::Variable(expr[name_range.clone()].to_string()),
+                Some(name_range.into()),
+            )));
+        }
+
         if curly_brackets.depth() != 0 || parentheses.depth() != 0 {
             return Err(LaTexParsingError::new(
                 MathElementMeta::at(expr.len() - 1),
@@ -349,69 +892,441 @@ impl FromRawExpr for ExpressionBuffer {
         });
 
         Ok(Self {
-            expr: expr_buffer.into_iter().filter_map(|e| e).collect(),
+            expr: expr_buffer.into_iter().flatten().collect(),
         })
     }
 }
 
-fn handle_optional_params(f_name: &str) -> Option<Number> {
-    match f_name {
-        ROOT => Some(Number::Integer(2)),
-        LG => Some(Number::Integer(10)),
-        LN => Some(Number::Constant(Constant::E)),
-        _ => None,
+/// Inserts a multiplication if the previous element is a complete operand, like in `2\pi`.
+fn push_implicit_multiply(
+    expr_buffer: &mut Vec<Option<(MathElement, Option<MathElementMeta>)>>,
+    univariate_funcs: &[usize],
+) {
+    // The parameter of a univariate function is not pushed yet.
+    if univariate_funcs.last() == Some(&(expr_buffer.len().wrapping_sub(1))) {
+        return;
+    }
+
+    if expr_buffer.last().is_some_and(|e| {
+        e.as_ref().is_some_and(|(e, _)| {
+            matches!(
+                e,
+                MathElement::Number(_)
+                    | MathElement::Function(_)
+                    | MathElement::Matrix(_)
+                    | MathElement::Variable(_)
+                    | MathElement::Expression(_)
+                    | MathElement::Parentheses(BracketState::Close)
+            )
+        })
+    }) {
+        expr_buffer.push(Some((
+            MathElement::PhantomOperator(get_phantom_operator(MULTIPLY).unwrap()),
+            None,
+        )));
     }
 }
 
-impl ExpressionBuffer {
-    fn to_postfix(self) -> LaTexParsingResult<Vec<MathElement>> {
-        if self.expr.is_empty() {
-            return Err(LaTexParsingError::new(
-                MathElementMeta::at(0),
-                LaTexParsingErrorType::EmptyInput,
-            ));
+/// Matches `{d}{dx}` or `{\partial}{\partial x}` at the start of `expr`, which follows a `\frac`.
+///
+/// Returns the length, the range of the variable and whether it's a partial derivative.
+fn match_differential(expr: &str) -> Option<(usize, Range<usize>, bool)> {
+    let partial = format!("{}{}", FUNC_BEGIN, PARTIAL);
+    let rest = expr.strip_prefix(CURLY_BRACKET_L)?.trim_start();
+    let (rest, d, is_partial) = match rest.strip_prefix(partial.as_str()) {
+        Some(rest) => (rest, partial.as_str(), true),
+        None => (rest.strip_prefix(DIFFERENTIAL)?, DIFFERENTIAL, false),
+    };
+    let rest = rest
+        .trim_start()
+        .strip_prefix(CURLY_BRACKET_R)?
+        .strip_prefix(CURLY_BRACKET_L)?
+        .trim_start()
+        .strip_prefix(d)?
+        .trim_start();
+
+    let var_len = rest
+        .find(|c: char| !c.is_ascii_lowercase())
+        .unwrap_or(rest.len());
+    if var_len == 0 {
+        return None;
+    }
+    let var_start = expr.len() - rest.len();
+    let len = expr.len()
+        - rest[var_len..]
+            .trim_start()
+            .strip_prefix(CURLY_BRACKET_R)?
+            .len();
+
+    Some((len, var_start..var_start + var_len, is_partial))
+}
+
+/// The parts of `_{a}^{b} f \, dx`, which follows an `\int`.
+struct IntegralMatch {
+    /// The lower and upper bound, which are missing in indefinite integrals.
+    bounds: Option<(Range<usize>, Range<usize>)>,
+    integrand: Range<usize>,
+    var: Range<usize>,
+    len: usize,
+}
+
+/// Matches the bounds, the integrand and the differential of an integral at the start of `expr`.
+fn match_integral(expr: &str) -> Option<IntegralMatch> {
+    let (bounds, integrand_start) = match expr.strip_prefix(SUB_SCRIPT) {
+        Some(lower_expr) => {
+            let lower_start = SUB_SCRIPT.len_utf8();
+            let (lower, lower_len) = match_script(lower_expr)?;
+
+            let upper_start = lower_start + lower_len + SUPER_SCRIPT.len();
+            let (upper, upper_len) =
+                match_script(expr[lower_start + lower_len..].strip_prefix(SUPER_SCRIPT)?)?;
+
+            (
+                Some((
+                    lower.start + lower_start..lower.end + lower_start,
+                    upper.start + upper_start..upper.end + upper_start,
+                )),
+                upper_start + upper_len,
+            )
         }
+        None => (None, 0),
+    };
+
+    let (d, var) = find_differential(&expr[integrand_start..])?;
+    let integrand = expr[integrand_start..integrand_start + d].trim_end();
+    let integrand = integrand
+        .strip_suffix(THIN_SPACE)
+        .unwrap_or(integrand)
+        .trim_end();
+    let leading = integrand.len() - integrand.trim_start().len();
+
+    Some(IntegralMatch {
+        bounds,
+        integrand: integrand_start + leading..integrand_start + integrand.len(),
+        var: var.start + integrand_start..var.end + integrand_start,
+        len: integrand_start + var.end,
+    })
+}
 
-        let mut raw_buffer = self.expr.into_iter().map(|e| Some(e)).collect::<Vec<_>>();
+/// Matches a sub- or superscript like `{a+1}`, `0` or `\infty` at the start of `expr`.
+///
+/// Returns the range of its content and its length.
+fn match_script(expr: &str) -> Option<(Range<usize>, usize)> {
+    match expr.chars().next()? {
+        CURLY_BRACKET_L => {
+            let mut depth = 0;
+            let len = expr.char_indices().find_map(|(i, c)| {
+                match c {
+                    CURLY_BRACKET_L => depth += 1,
+                    CURLY_BRACKET_R => depth -= 1,
+                    _ => {}
+                }
+                (depth == 0).then_some(i + 1)
+            })?;
+            Some((1..len - 1, len))
+        }
+        FUNC_BEGIN => {
+            let len = expr[1..]
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .map_or(expr.len(), |len| len + 1);
+            (len > 1).then_some((0..len, len))
+        }
+        c if c.is_ascii_alphanumeric() => Some((0..1, 1)),
+        _ => None,
+    }
+}
 
-        let mut buffer = Vec::new();
+/// The parts of `_{x \to a} f`, which follows a `\lim`.
+struct LimitMatch {
+    var: Range<usize>,
+    point: Range<usize>,
+    direction: Direction,
+    operand: Range<usize>,
+    len: usize,
+}
 
-        // Solidify all the PhantomFunctions.
-        for i in 0..raw_buffer.len() {
-            if raw_buffer[i].is_none() {
-                continue;
+/// Matches the parenthesized arguments of a call like `(a, b)` at the start of `expr`. Returns
+/// the length and the ranges of the arguments, which are separated by top level `,`s.
+fn match_call(expr: &str) -> Option<(usize, Vec<Range<usize>>)> {
+    let mut depth = 0;
+    let mut start = PARENTHESES_L.len_utf8();
+    let mut args = Vec::new();
+
+    for (i, c) in expr.char_indices() {
+        match c {
+            PARENTHESES_L | CURLY_BRACKET_L => depth += 1,
+            PARENTHESES_R | CURLY_BRACKET_R => {
+                depth -= 1;
+                if depth == 0 {
+                    args.push(start..i);
+                    return Some((i + 1, args));
+                }
+            }
+            EQUATION_SEP if depth == 1 => {
+                args.push(start..i);
+                start = i + 1;
             }
+            _ => {}
+        }
+    }
 
-            let (elem, elem_meta) = raw_buffer[i].take().unwrap();
+    None
+}
 
-            match elem {
-                MathElement::Number(n) => buffer.push((MathElement::Number(n), elem_meta)),
-                MathElement::Parentheses(p) => {
-                    buffer.push((MathElement::Parentheses(p), elem_meta))
-                }
-                MathElement::PhantomFunction(phf) => {
-                    let n = phf.num_params() as usize;
-                    let mut params = Vec::with_capacity(n);
+/// Matches the subscript and the operand of a limit at the start of `expr`.
+fn match_limit(expr: &str) -> Option<LimitMatch> {
+    let (script, script_len) = match_script(expr.strip_prefix(SUB_SCRIPT)?)?;
+    let script = script.start + 1..script.end + 1;
+    let sub = &expr[script.clone()];
+
+    let rest = sub.trim_start();
+    let var_start = script.end - rest.len();
+    let var_len = rest
+        .find(|c: char| !c.is_ascii_lowercase())
+        .unwrap_or(rest.len());
+    if var_len == 0 {
+        return None;
+    }
 
-                    let err_template = LaTexParsingError::new(
-                        elem_meta.unwrap(),
-                        LaTexParsingErrorType::InvalidFunctionCall,
-                    );
+    let rest = rest[var_len..].trim_start().strip_prefix(TO)?;
+    // Longer commands like `\top` aren't arrows.
+    if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let rest = rest.trim();
+    let point_start = script.end - rest.len() - (sub.len() - sub.trim_end().len());
+
+    let (point, direction) = [
+        (format!("{}{{{}}}", SUPER_SCRIPT, ADD), Direction::Right),
+        (format!("{}{}", SUPER_SCRIPT, ADD), Direction::Right),
+        (format!("{}{{{}}}", SUPER_SCRIPT, SUBTRACT), Direction::Left),
+        (format!("{}{}", SUPER_SCRIPT, SUBTRACT), Direction::Left),
+    ]
+    .into_iter()
+    .find_map(|(side, dir)| {
+        rest.strip_suffix(side.as_str())
+            .map(|p| (p.trim_end(), dir))
+    })
+    .unwrap_or((rest, Direction::Both));
+    if point.is_empty() {
+        return None;
+    }
 
-                    if raw_buffer.len() < i + 1 + n {
-                        return Err(err_template);
-                    }
+    let operand_start = SUB_SCRIPT.len_utf8() + script_len;
+    let (operand, operand_len) = match_operand(&expr[operand_start..])?;
 
-                    for param in &mut raw_buffer[i + 1..i + 1 + n] {
-                        let (param, _) = param.take().ok_or_else(|| err_template.clone())?;
+    Some(LimitMatch {
+        var: var_start..var_start + var_len,
+        point: point_start..point_start + point.len(),
+        direction,
+        operand: operand.start + operand_start..operand.end + operand_start,
+        len: operand_start + operand_len,
+    })
+}
 
-                        let converted = match param {
-                            MathElement::Number(n) => ExpressionElement::Number(n),
-                            MathElement::Expression(e) => {
-                                ExpresssionTree::from_postfix(e.to_postfix()?)?.root
-                            }
-                            _ => return Err(err_template),
-                        };
+/// The parts of `_{k=1}^{n} f`, which follows a `\sum`, a `\prod` or an
+/// `\operatorname{series}`.
+struct SumMatch {
+    var: Range<usize>,
+    lower: Range<usize>,
+    upper: Range<usize>,
+    operand: Range<usize>,
+    len: usize,
+}
+
+/// Matches the bounds and the operand of a sum, a product or a series at the start of `expr`.
+fn match_sum(expr: &str) -> Option<SumMatch> {
+    let (script, script_len) = match_script(expr.strip_prefix(SUB_SCRIPT)?)?;
+    let script = script.start + 1..script.end + 1;
+    let sub = &expr[script.clone()];
+
+    let eq = sub.find(EQUAL)?;
+    let (var, lower) = (&sub[..eq], &sub[eq + EQUAL.len()..]);
+    let var_start = script.start + var.len() - var.trim_start().len();
+    let var = var.trim();
+    if var.is_empty() || !var.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    let lower_start = script.start + eq + EQUAL.len() + lower.len() - lower.trim_start().len();
+    let lower = lower.trim();
+    if lower.is_empty() {
+        return None;
+    }
+
+    let upper_start = SUB_SCRIPT.len_utf8() + script_len + SUPER_SCRIPT.len();
+    let (upper, upper_len) =
+        match_script(expr[SUB_SCRIPT.len_utf8() + script_len..].strip_prefix(SUPER_SCRIPT)?)?;
+
+    let operand_start = upper_start + upper_len;
+    let (operand, operand_len) = match_operand(&expr[operand_start..])?;
+
+    Some(SumMatch {
+        var: var_start..var_start + var.len(),
+        lower: lower_start..lower_start + lower.len(),
+        upper: upper.start + upper_start..upper.end + upper_start,
+        operand: operand.start + operand_start..operand.end + operand_start,
+        len: operand_start + operand_len,
+    })
+}
+
+/// Matches the operand of a big operator like `\lim`, which reaches up to the next `+` or `-`
+/// outside of brackets, so `\lim_{x \to 0} \frac{\sin x}{x}+1` only takes the fraction.
+///
+/// Returns the range of the operand without the whitespace around it and its length, `None` if
+/// it's empty.
+fn match_operand(expr: &str) -> Option<(Range<usize>, usize)> {
+    let mut depth = 0;
+    let mut started = false;
+    let mut len = expr.len();
+
+    for (i, c) in expr.char_indices() {
+        match c {
+            CURLY_BRACKET_L | PARENTHESES_L => depth += 1,
+            CURLY_BRACKET_R | PARENTHESES_R => depth -= 1,
+            _ => {}
+        }
+
+        if depth == 0 && started && matches!(c.to_string().as_str(), ADD | SUBTRACT) {
+            len = i;
+            break;
+        }
+        started |= c != WHITESPACE;
+    }
+
+    let operand = expr[..len].trim_end();
+    let leading = operand.len() - operand.trim_start().len();
+    (leading < operand.len()).then_some((leading..operand.len(), len))
+}
+
+/// Finds the differential like the `dx` that closes an integral, skipping the ones of nested
+/// integrals.
+///
+/// Returns the index of the `d` and the range of the variable.
+fn find_differential(expr: &str) -> Option<(usize, Range<usize>)> {
+    let mut depth = 0;
+    let mut nested = 0;
+    let mut prev = None;
+
+    for (i, c) in expr.char_indices() {
+        let prev = prev.replace(c);
+        match c {
+            CURLY_BRACKET_L | PARENTHESES_L => depth += 1,
+            CURLY_BRACKET_R | PARENTHESES_R => depth -= 1,
+            _ => {}
+        }
+        if depth != 0 {
+            continue;
+        }
+
+        if c == FUNC_BEGIN
+            && expr[i + 1..].starts_with(INTEGRAL)
+            && !expr[i + 1 + INTEGRAL.len()..].starts_with(|c: char| c.is_ascii_alphabetic())
+        {
+            nested += 1;
+            continue;
+        }
+
+        // Differentials are never part of names, like the `d` in `\det`.
+        if !expr[i..].starts_with(DIFFERENTIAL)
+            || prev.is_some_and(|p| p.is_ascii_alphabetic() || p == FUNC_BEGIN)
+        {
+            continue;
+        }
+
+        let var_start = i + DIFFERENTIAL.len();
+        let var_len = expr[var_start..]
+            .find(|c: char| !c.is_ascii_lowercase())
+            .unwrap_or(expr.len() - var_start);
+        if var_len == 0 {
+            continue;
+        }
+
+        if nested > 0 {
+            nested -= 1;
+            continue;
+        }
+        return Some((i, var_start..var_start + var_len));
+    }
+
+    None
+}
+
+fn handle_optional_params(f_name: &str) -> Option<Number> {
+    match f_name {
+        ROOT => Some(Number::Integer(2)),
+        LG => Some(Number::Integer(10)),
+        LN => Some(Number::Constant(Constant::E)),
+        _ => None,
+    }
+}
+
+impl ExpressionBuffer {
+    fn shift(&mut self, base: usize) {
+        for (elem, meta) in &mut self.expr {
+            if let Some(meta) = meta {
+                meta.start += base;
+            }
+            if let MathElement::Expression(e) = elem {
+                e.shift(base);
+            }
+        }
+    }
+
+    fn into_postfix(self) -> LaTexParsingResult<Vec<MathElement>> {
+        if self.expr.is_empty() {
+            return Err(LaTexParsingError::new(
+                MathElementMeta::at(0),
+                LaTexParsingErrorType::EmptyInput,
+            ));
+        }
+
+        let mut raw_buffer = self.expr.into_iter().map(Some).collect::<Vec<_>>();
+
+        let mut buffer = Vec::new();
+
+        // Solidify all the PhantomFunctions.
+        for i in 0..raw_buffer.len() {
+            if raw_buffer[i].is_none() {
+                continue;
+            }
+
+            let (elem, elem_meta) = raw_buffer[i].take().unwrap();
+
+            match elem {
+                MathElement::Number(n) => buffer.push((MathElement::Number(n), elem_meta)),
+                MathElement::Matrix(m) => buffer.push((MathElement::Matrix(m), elem_meta)),
+                MathElement::Variable(v) => buffer.push((MathElement::Variable(v), elem_meta)),
+                // Only exists in parameters of postfix functions
+                MathElement::Function(f) => buffer.push((MathElement::Function(f), elem_meta)),
+                MathElement::Parentheses(p) => {
+                    buffer.push((MathElement::Parentheses(p), elem_meta))
+                }
+                MathElement::PhantomFunction(phf) => {
+                    let n = phf.num_params() as usize;
+                    let mut params = Vec::with_capacity(n);
+
+                    let err_template = LaTexParsingError::new(
+                        elem_meta.unwrap(),
+                        LaTexParsingErrorType::InvalidFunctionCall,
+                    );
+
+                    if raw_buffer.len() < i + 1 + n {
+                        return Err(err_template);
+                    }
+
+                    for param in &mut raw_buffer[i + 1..i + 1 + n] {
+                        let (param, _) = param.take().ok_or_else(|| err_template.clone())?;
+
+                        let converted = match param {
+                            MathElement::Number(n) => ExpressionElement::Number(n),
+                            MathElement::Matrix(m) => ExpressionElement::Matrix(m),
+                            MathElement::Variable(v) => ExpressionElement::Variable(v),
+                            MathElement::Function(f) => ExpressionElement::Function(Box::new(f)),
+                            MathElement::Expression(e) => {
+                                ExpresssionTree::from_postfix(e.into_postfix()?)?.root
+                            }
+                            _ => return Err(err_template),
+                        };
 
                         params.push(Some(converted));
                     }
@@ -421,23 +1336,95 @@ impl ExpressionBuffer {
                 MathElement::PhantomOperator(pho) => {
                     buffer.push((MathElement::PhantomOperator(pho), elem_meta))
                 }
-                // Only exists when the prior element is `Power`
-                MathElement::Expression(e) => buffer.push((MathElement::Expression(e), elem_meta)),
-                _ => {
-                    return Err(LaTexParsingError::new(
+                MathElement::PostfixFunction(phf) => {
+                    let err = LaTexParsingError::new(
                         elem_meta.unwrap(),
-                        LaTexParsingErrorType::Unknown,
+                        LaTexParsingErrorType::InvalidFunctionCall,
+                    );
+
+                    let param = match buffer.pop() {
+                        Some((MathElement::Parentheses(BracketState::Close), _)) => {
+                            let mut depth = 0;
+                            let open = (0..buffer.len())
+                                .rev()
+                                .find(|j| {
+                                    match buffer[*j].0 {
+                                        MathElement::Parentheses(BracketState::Open) => depth -= 1,
+                                        MathElement::Parentheses(BracketState::Close) => depth += 1,
+                                        _ => {}
+                                    }
+                                    depth < 0
+                                })
+                                .ok_or_else(|| err.clone())?;
+
+                            let expr = buffer.drain(open + 1..).collect();
+                            buffer.pop();
+                            ExpresssionTree::from_postfix(
+                                ExpressionBuffer { expr }.into_postfix()?,
+                            )?
+                            .root
+                        }
+                        Some((MathElement::Number(n), _)) => n.into(),
+                        Some((MathElement::Matrix(m), _)) => ExpressionElement::Matrix(m),
+                        Some((MathElement::Variable(v), _)) => ExpressionElement::Variable(v),
+                        Some((MathElement::Function(f), _)) => {
+                            ExpressionElement::Function(Box::new(f))
+                        }
+                        Some((MathElement::Expression(e), _)) => {
+                            ExpresssionTree::from_postfix(e.into_postfix()?)?.root
+                        }
+                        _ => return Err(err),
+                    };
+
+                    buffer.push((
+                        MathElement::Function(phf.solidify(vec![Some(param)])),
+                        elem_meta,
                     ));
                 }
+                // Only exists when the prior element is `Power`
+                MathElement::Expression(e) => buffer.push((MathElement::Expression(e), elem_meta)),
             }
         }
 
         let mut fn_stack = Vec::new();
         let mut num_stack = Vec::new();
 
+        // Operators need operands on both sides, unlike the ones in `1+`, `=1` or `(2*)`.
+        let missing_operand = |meta: Option<MathElementMeta>| {
+            LaTexParsingError::new(
+                meta.unwrap_or(MathElementMeta::at(0)),
+                LaTexParsingErrorType::MissingOperand,
+            )
+        };
+        let mut after_operand = false;
+        let mut last_operator = None;
+
         for (elem, elem_meta) in buffer.drain(..) {
+            match &elem {
+                MathElement::PhantomOperator(_) if !after_operand => {
+                    return Err(missing_operand(elem_meta));
+                }
+                MathElement::PhantomOperator(_) => {
+                    after_operand = false;
+                    last_operator = Some(elem_meta);
+                }
+                MathElement::Parentheses(BracketState::Open) => {
+                    after_operand = false;
+                    last_operator = None;
+                }
+                MathElement::Parentheses(BracketState::Close) => {
+                    if let (false, Some(meta)) = (after_operand, last_operator) {
+                        return Err(missing_operand(meta));
+                    }
+                    after_operand = true;
+                }
+                _ => after_operand = true,
+            }
+
             match elem {
                 MathElement::Number(n) => num_stack.push(MathElement::Number(n)),
+                MathElement::Matrix(m) => num_stack.push(MathElement::Matrix(m)),
+                MathElement::Variable(v) => num_stack.push(MathElement::Variable(v)),
                 MathElement::Parentheses(p) => match p {
                     BracketState::Open => fn_stack.push(MathElement::Parentheses(p)),
                     BracketState::Close => loop {
@@ -482,32 +1469,67 @@ impl ExpressionBuffer {
             }
         }
 
+        if let (false, Some(meta)) = (after_operand, last_operator) {
+            return Err(missing_operand(meta));
+        }
+
         num_stack.extend(fn_stack.into_iter().rev());
 
         Ok(num_stack)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ExpresssionTree {
     root: ExpressionElement,
 }
 
 impl FromRawExpr for ExpresssionTree {
+    #[inline]
     fn parse_raw(expr: &str, vars: Option<&HashMap<String, Number>>) -> LaTexParsingResult<Self> {
-        ExpresssionTree::from_postfix(ExpressionBuffer::parse_raw(expr, vars)?.to_postfix()?)
+        Self::parse_scoped(expr, &ParsingScope::new(vars))
+    }
+
+    fn parse_scoped(expr: &str, scope: &ParsingScope) -> LaTexParsingResult<Self> {
+        ExpresssionTree::from_postfix(ExpressionBuffer::parse_scoped(expr, scope)?.into_postfix()?)
     }
 }
 
 impl Function for ExpresssionTree {
+    #[inline]
     fn evaluate(&self) -> MathElement {
-        todo!()
+        self.root.evaluate()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.root.approximate()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> crate::ComplexScalar {
+        self.root.approximate_complex()
+    }
+
+    #[cfg(feature = "bignum")]
+    #[inline]
+    fn approximate_precise(&self, precision: u64) -> Option<crate::BigDecimalScalar> {
+        self.root.approximate_precise(precision)
+    }
+}
+
+impl From<ExpresssionTree> for ExpressionElement {
+    #[inline]
+    fn from(value: ExpresssionTree) -> Self {
+        value.root
+    }
+}
+
+impl From<ExpressionElement> for ExpresssionTree {
+    #[inline]
+    fn from(value: ExpressionElement) -> Self {
+        Self { root: value }
+    }
 }
 
 impl IntoRawExpr for ExpresssionTree {
@@ -517,6 +1539,138 @@ impl IntoRawExpr for ExpresssionTree {
 }
 
 impl ExpresssionTree {
+    /// The derivative with respect to `var`, where other variables are treated as constants.
+    #[inline]
+    pub fn derivative(&self, var: &str) -> ExpresssionTree {
+        Self {
+            root: ExpressionElement::from(self.root.evaluate()).derivative(var),
+        }
+    }
+
+    /// The Taylor or Laurent series around `point` with the terms below `(var-point)^{order}`,
+    /// followed by the remainder, like `1-\frac{x^{2}}{6}+\frac{x^{4}}{120}+O(x^{6})` for
+    /// `\frac{\sin x}{x}` around 0 with order 6. Series that can't be found stay unevaluated.
+    #[inline]
+    pub fn series(
+        &self,
+        var: &str,
+        point: ExpressionElement,
+        order: IntegerScalar,
+    ) -> ExpresssionTree {
+        Self {
+            root: Series::new(
+                ExpressionElement::Variable(var.to_string()),
+                point,
+                Number::Integer(order).into(),
+                self.root.clone(),
+            )
+            .evaluate()
+            .into(),
+        }
+    }
+
+    /// The tree with every free `var` replaced by `replacement`. Nothing is evaluated, so
+    /// `x^2+1` with `x` replaced by `2` becomes `2^2+1`.
+    #[inline]
+    pub fn substitute(&self, var: &str, replacement: &ExpresssionTree) -> ExpresssionTree {
+        Self {
+            root: self.root.substitute(var, &replacement.root),
+        }
+    }
+
+    /// The tree with the variables in `vars` replaced by their values and the subtrees without
+    /// variables evaluated, like `4+y` for `x^2+y` with `x=2`. Variables that aren't in `vars`
+    /// are kept.
+    pub fn partial_eval(&self, vars: &HashMap<String, Number>) -> ExpresssionTree {
+        let root = vars.iter().fold(self.root.clone(), |root, (var, value)| {
+            root.substitute(var, &value.clone().into())
+        });
+        Self {
+            root: root.fold_constants(),
+        }
+    }
+
+    /// The tree compiled with a slot for each of `vars`, to be evaluated many times.
+    #[inline]
+    pub fn compile(&self, vars: &[&str]) -> CompiledExpr {
+        CompiledExpr::compile(&self.root, vars)
+    }
+
+    /// The real roots of the equation in `var`, where expressions that aren't equations are set
+    /// to 0. `None` if it can't be solved.
+    #[inline]
+    pub fn solve(&self, var: &str) -> Option<Roots> {
+        solve::solve(&self.root, var)
+    }
+
+    /// The values of `var` where the inequality holds. `None` if the tree isn't an inequality
+    /// of polynomials or rational functions.
+    #[inline]
+    pub fn solve_inequality(&self, var: &str) -> Option<IntervalSet> {
+        inequality::solve_inequality(&self.root, var)
+    }
+
+    /// The polynomial with its products and powers multiplied out, like `4*x` for
+    /// `(x+1)^2-(x-1)^2`. `None` if the tree isn't a polynomial with rational coefficients.
+    pub fn expand(&self) -> Option<ExpresssionTree> {
+        Some(Self {
+            root: self.polynomial()?.to_expr(),
+        })
+    }
+
+    /// The polynomial with its terms grouped by the powers of `var`.
+    pub fn collect(&self, var: &str) -> Option<ExpresssionTree> {
+        Some(Self {
+            root: self.polynomial()?.collect(var),
+        })
+    }
+
+    /// The polynomial factored over the rationals.
+    pub fn factor(&self) -> Option<ExpresssionTree> {
+        Some(Self {
+            root: self.polynomial()?.factor()?.to_expr(),
+        })
+    }
+
+    /// The greatest common divisor of two polynomials, with coprime integer coefficients.
+    pub fn gcd(&self, rhs: &ExpresssionTree) -> Option<ExpresssionTree> {
+        Some(Self {
+            root: self.polynomial()?.gcd(&rhs.polynomial()?)?.to_expr(),
+        })
+    }
+
+    /// The quotient and the remainder of polynomial division.
+    pub fn div_rem(&self, rhs: &ExpresssionTree) -> Option<(ExpresssionTree, ExpresssionTree)> {
+        let (quot, rem) = self.polynomial()?.div_rem(&rhs.polynomial()?)?;
+        Some((
+            Self {
+                root: quot.to_expr(),
+            },
+            Self {
+                root: rem.to_expr(),
+            },
+        ))
+    }
+
+    /// The tree rewritten with every [`Rule`](crate::math::simplify::Rule) and brought into
+    /// canonical form, like `1` for `\sin^2{x}+\cos^2{x}`.
+    #[inline]
+    pub fn simplify(&self) -> ExpresssionTree {
+        self.simplify_with(&Simplifier::default())
+    }
+
+    #[inline]
+    pub fn simplify_with(&self, simplifier: &Simplifier) -> ExpresssionTree {
+        Self {
+            root: simplifier.simplify(&self.root),
+        }
+    }
+
+    #[inline]
+    fn polynomial(&self) -> Option<MultiPolynomial> {
+        MultiPolynomial::from_expr(&self.root.evaluate().into())
+    }
+
     fn from_postfix(expr: Vec<MathElement>) -> LaTexParsingResult<Self> {
         if expr.is_empty() {
             return Err(LaTexParsingError::new(
@@ -531,6 +1685,8 @@ impl ExpresssionTree {
         while let Some(elem) = expr.pop() {
             match elem {
                 MathElement::Number(n) => tree_buffer.push(ExpressionElement::Number(n)),
+                MathElement::Matrix(m) => tree_buffer.push(ExpressionElement::Matrix(m)),
+                MathElement::Variable(v) => tree_buffer.push(ExpressionElement::Variable(v)),
                 MathElement::Function(f) => {
                     tree_buffer.push(ExpressionElement::Function(Box::new(f)))
                 }
@@ -541,7 +1697,7 @@ impl ExpresssionTree {
                 }
                 // Only exists when the operator is `Power`
                 MathElement::Expression(e) => {
-                    tree_buffer.push(ExpresssionTree::from_postfix(e.to_postfix()?)?.root)
+                    tree_buffer.push(ExpresssionTree::from_postfix(e.into_postfix()?)?.root)
                 }
                 _ => unreachable!(),
             }
@@ -554,9 +1710,106 @@ impl ExpresssionTree {
 }
 
 #[cfg(test)]
+#[allow(clippy::excessive_precision)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_free_symbols() {
+        let vars = HashMap::from([("a".to_string(), Number::Integer(2))]);
+        let scope = ParsingScope::new(Some(&vars)).with_free_symbols();
+        let parse = |expr| ExpresssionTree::parse_scoped(expr, &scope).unwrap();
+
+        assert_eq!(parse("1+random*5").assemble(), "1+random*5");
+        assert_eq!(parse("2x+3y").assemble(), "2*x+3*y");
+        assert_eq!(
+            ExpressionElement::from(parse("ax+x").evaluate()).assemble(),
+            "3*x"
+        );
+        assert_eq!(parse("x^2y").derivative("x").assemble(), "2*x*y");
+        let three = Number::Integer(3).into();
+        assert_eq!(
+            parse("xy+1").root.substitute("xy", &three).assemble(),
+            "3+1"
+        );
+        assert!(parse("x+1").root.is_symbolic());
+    }
+
+    #[test]
+    fn test_substitution() {
+        let scope = ParsingScope::new(None).with_symbol("x").with_symbol("y");
+        let parse = |expr| ExpresssionTree::parse_scoped(expr, &scope).unwrap();
+
+        let f = parse("x^2+2*x*y");
+        assert_eq!(f.substitute("x", &parse("2")).assemble(), "2^{2}+2*2*y");
+        assert_eq!(
+            f.substitute("x", &parse("y+1")).assemble(),
+            "(y+1)^{2}+2*(y+1)*y"
+        );
+
+        let x = HashMap::from([("x".to_string(), Number::Integer(2))]);
+        assert_eq!(f.partial_eval(&x).assemble(), "4+4*y");
+        let xy = HashMap::from([
+            ("x".to_string(), Number::Integer(2)),
+            ("y".to_string(), Number::Integer(3)),
+        ]);
+        assert_eq!(f.partial_eval(&xy).assemble(), "16");
+        // Bound variables are left alone.
+        assert_eq!(
+            parse(r#"\sum_{x=1}^{y}{x}"#).partial_eval(&xy).assemble(),
+            "6"
+        );
+    }
+
+    fn evaluate(expr: &str) -> String {
+        ExpressionElement::from(ExpresssionTree::parse_raw(expr, None).unwrap().evaluate())
+            .assemble()
+    }
+
+    #[test]
+    fn test_expr_evaluation() {
+        assert_eq!(evaluate(r#"\frac{2}{4}+\sin{\frac{\pi}{6}}"#), "1");
+        assert_eq!(evaluate(r#"\frac{1}{3}*3"#), "1");
+        assert_eq!(evaluate(r#"2^{10}-\frac{1}{4}"#), r#"\frac{4095}{4}"#);
+        assert_eq!(evaluate(r#"\frac{1}{3}+\frac{1}{6}-1"#), r#"-\frac{1}{2}"#);
+        assert_eq!(
+            evaluate(r#"(\frac{2}{3})^{-2}*\frac{1}{9}"#),
+            r#"\frac{1}{4}"#
+        );
+        assert_eq!(
+            evaluate(r#"\frac{\sqrt{2}}{3}*\frac{3}{4}"#),
+            r#"\frac{\sqrt{2}}{4}"#
+        );
+        assert_eq!(evaluate(r#"\sqrt{8}"#), r#"2*\sqrt{2}"#);
+        assert_eq!(evaluate(r#"\sqrt{2}*\sqrt{6}"#), r#"2*\sqrt{3}"#);
+        assert_eq!(
+            evaluate(r#"\sqrt[3]{-27}+\sqrt{\frac{1}{4}}"#),
+            r#"-\frac{5}{2}"#
+        );
+        assert_eq!(evaluate(r#"\cos{\frac{\pi}{4}}"#), r#"\frac{\sqrt{2}}{2}"#);
+        assert_eq!(evaluate(r#"\tan{\frac{\pi}{6}}"#), r#"\frac{\sqrt{3}}{3}"#);
+        assert_eq!(evaluate(r#"\arcsin{\frac{1}{2}}"#), r#"\frac{\pi}{6}"#);
+        assert_eq!(evaluate(r#"\log_{2}{\frac{1}{8}}+\ln{e}"#), "-2");
+        assert_eq!(evaluate(r#"1+\sqrt{2}-2\sqrt{2}"#), r#"1-\sqrt{2}"#);
+        #[cfg(not(feature = "bignum"))]
+        assert_eq!(evaluate(r#"\sin{1}+0.5"#), "1.3414709848078965");
+        assert_eq!(evaluate(r#"2\pi"#), r#"2*\pi"#);
+        assert_eq!(evaluate(r#"(1+\sqrt{3})*2"#), r#"2*(1+\sqrt{3})"#);
+        assert_eq!(
+            evaluate(r#"\operatorname{abs}{-\frac{3}{2}}+\operatorname{abs}{2i}"#),
+            r#"\frac{7}{2}"#
+        );
+        assert_eq!(
+            evaluate(r#"\operatorname{abs}{1-\sqrt{2}}"#),
+            r#"\operatorname{abs}{1-\sqrt{2}}"#
+        );
+        assert_eq!(evaluate(r#"\frac{1}{0}"#), r#"\frac{1}{0}"#);
+        assert_eq!(evaluate("1/0"), "1/0");
+        assert_eq!(evaluate(r#"0^{-2}"#), "0^{-2}");
+        #[cfg(not(feature = "bignum"))]
+        assert_eq!(evaluate("99999999999*99999999999"), "9.9999999998*10^{21}");
+    }
+
     #[test]
     fn test_univariate_funcs() {
         assert_eq!(
@@ -601,6 +1854,626 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_complex_numbers() {
+        let approximate = |expr: &str| {
+            let c = ExpresssionTree::parse_raw(expr, None)
+                .unwrap()
+                .approximate_complex();
+            (c.re as f32, c.im as f32)
+        };
+
+        assert_eq!(evaluate(r#"\sqrt{-4}"#), "2i");
+        assert_eq!(evaluate(r#"\ln{-1}"#), r#"i\pi"#);
+        assert_eq!(evaluate(r#"\sqrt{-8}"#), r#"2*\sqrt{2}i"#);
+        assert_eq!(evaluate(r#"i*i+\mathrm{i}^3"#), "-1-i");
+        assert_eq!(evaluate(r#"\frac{3}{i}+2i"#), "-i");
+        assert_eq!(evaluate(r#"(1+2i)*0.5"#), "0.5+i");
+        assert_eq!(evaluate(r#"\pi i"#), r#"\pi i"#);
+        assert_eq!(evaluate(r#"(1+i)^2"#), "2i");
+        assert_eq!(evaluate(r#"(2-3i)^{-1}"#), r#"\frac{2}{13}+\frac{3i}{13}"#);
+        assert_eq!(evaluate(r#"e^{i\pi}"#), "-1");
+        assert_eq!(evaluate(r#"e^{\frac{\pi i}{2}}"#), "i");
+        assert_eq!(evaluate(r#"(-8)^{\frac{1}{3}}"#), "-2");
+
+        // Only variables named exactly like them shadow `e` and `i`.
+        let vars = HashMap::from([
+            ("index".to_string(), Number::Integer(3)),
+            ("exp".to_string(), Number::Integer(2)),
+        ]);
+        let scope = ParsingScope::new(Some(&vars));
+        let evaluate_scoped = |expr| {
+            ExpressionElement::from(
+                ExpresssionTree::parse_scoped(expr, &scope)
+                    .unwrap()
+                    .evaluate(),
+            )
+            .assemble()
+        };
+        assert_eq!(evaluate_scoped("index+2i"), "3+2i");
+        assert_eq!(evaluate_scoped("exp*e"), "2*e");
+
+        assert_eq!(approximate(r#"\sqrt{-4}"#), (0., 2.));
+        assert_eq!(approximate(r#"\ln{-1}"#), (0., std::f32::consts::PI));
+        assert_eq!(approximate(r#"\sqrt[3]{-8}"#), (-2., 0.));
+        assert_eq!(approximate(r#"(-8)^{\frac{1}{3}}"#), (-2., 0.));
+        assert_eq!(approximate(r#"(-8)^{\frac{2}{3}}"#), (4., 0.));
+        assert_eq!(approximate(r#"\arcsin{2}"#), (1.5707964, -1.316958));
+        assert_eq!(approximate(r#"\sin{i}"#), (0., 1.1752012));
+        assert_eq!(approximate(r#"e^{i\pi}"#).0, -1.);
+        assert_eq!(approximate(r#"\frac{1}{0}"#).0, f32::INFINITY);
+    }
+
+    #[test]
+    fn test_matrices() {
+        assert_eq!(
+            evaluate(r#"\begin{pmatrix}1&2\\3&4\end{pmatrix}\begin{pmatrix}5\\6\end{pmatrix}"#),
+            r#"\begin{pmatrix}17\\39\end{pmatrix}"#
+        );
+        assert_eq!(
+            evaluate(
+                r#"2\begin{bmatrix}1 & \frac{1}{2}\end{bmatrix}-\begin{bmatrix}1 & 1\end{bmatrix}"#
+            ),
+            r#"\begin{bmatrix}1&0\end{bmatrix}"#
+        );
+        assert_eq!(
+            evaluate(
+                r#"(\begin{pmatrix}1&2\\3&4\end{pmatrix}+\begin{pmatrix}0&1\\1&0\end{pmatrix})^T"#
+            ),
+            r#"\begin{pmatrix}1&4\\3&4\end{pmatrix}"#
+        );
+        assert_eq!(
+            evaluate(r#"\begin{pmatrix}1&1\\0&1\end{pmatrix}^{3}/2"#),
+            r#"\begin{pmatrix}\frac{1}{2}&\frac{3}{2}\\0&\frac{1}{2}\end{pmatrix}"#
+        );
+        assert_eq!(
+            evaluate(r#"\begin{vmatrix}\sqrt{2}&1\\1&\sqrt{2}\end{vmatrix}+1"#),
+            "2"
+        );
+        assert_eq!(
+            evaluate(r#"\begin{pmatrix}1&2\end{pmatrix}\begin{pmatrix}1&2\end{pmatrix}"#),
+            r#"\begin{pmatrix}1&2\end{pmatrix}*\begin{pmatrix}1&2\end{pmatrix}"#
+        );
+        assert_eq!(
+            ExpresssionTree::parse_raw(r#"3\begin{vmatrix}1&2\\3&4\end{vmatrix}"#, None)
+                .unwrap()
+                .approximate(),
+            -6.
+        );
+        assert_eq!(
+            ExpresssionTree::parse_raw(r#"1+\begin{pmatrix}1&2"#, None).unwrap_err(),
+            LaTexParsingError::new((2..20).into(), LaTexParsingErrorType::InvalidMatrix)
+        );
+    }
+
+    #[test]
+    fn test_linear_algebra() {
+        assert_eq!(
+            evaluate(r#"\det{\begin{pmatrix}1&2\\3&4\end{pmatrix}^T}"#),
+            "-2"
+        );
+        assert_eq!(
+            evaluate(r#"\begin{pmatrix}2&1\\5&3\end{pmatrix}^{-1}"#),
+            r#"\begin{pmatrix}3&-1\\-5&2\end{pmatrix}"#
+        );
+        assert_eq!(
+            evaluate(r#"\begin{pmatrix}1&1\\1&2\end{pmatrix}^{-2}"#),
+            r#"\begin{pmatrix}5&-3\\-3&2\end{pmatrix}"#
+        );
+        assert_eq!(
+            evaluate(r#"\operatorname{rank}{\begin{bmatrix}1&2\\2&4\end{bmatrix}}+1"#),
+            "2"
+        );
+        assert_eq!(
+            evaluate(
+                r#"\operatorname{tr} \begin{pmatrix}\frac{1}{2}&0\\0&\frac{1}{3}\end{pmatrix}"#
+            ),
+            r#"\frac{5}{6}"#
+        );
+        assert_eq!(
+            evaluate(r#"\operatorname{eig}(\begin{pmatrix}1&2\\2&1\end{pmatrix})"#),
+            r#"\begin{pmatrix}3\\-1\end{pmatrix}"#
+        );
+        assert_eq!(
+            evaluate(r#"\operatorname{eig}{\begin{pmatrix}1&1\\1&0\end{pmatrix}}"#),
+            r#"\begin{pmatrix}\frac{1+\sqrt{5}}{2}\\\frac{1-\sqrt{5}}{2}\end{pmatrix}"#
+        );
+        assert_eq!(
+            evaluate(r#"\operatorname{tr}{\begin{pmatrix}1&2\end{pmatrix}}"#),
+            r#"\operatorname{tr}{\begin{pmatrix}1&2\end{pmatrix}}"#
+        );
+        assert_eq!(
+            ExpresssionTree::parse_raw(r#"\det{\begin{pmatrix}0.5&1\\2&3\end{pmatrix}}"#, None)
+                .unwrap()
+                .approximate(),
+            -0.5
+        );
+        assert_eq!(
+            ExpresssionTree::parse_raw(r#"\operatorname{dim}{2}"#, None).unwrap_err(),
+            LaTexParsingError::new((1..18).into(), LaTexParsingErrorType::UnknownFunctionName)
+        );
+    }
+
+    #[test]
+    fn test_derivatives() {
+        assert_eq!(evaluate(r#"\frac{d}{dx}{x^3}"#), "3*x^{2}");
+        assert_eq!(evaluate(r#"1+\frac{d}{dx}(2x+1)"#), "3");
+        assert_eq!(evaluate(r#"\frac{d}{dx}{\sin{x^2}}"#), r#"2*\cos{x^{2}}*x"#);
+        assert_eq!(
+            evaluate(r#"\frac{d}{dx} \tan{x}"#),
+            r#"\frac{1}{\cos{x}^{2}}"#
+        );
+        assert_eq!(evaluate(r#"\frac{d}{dx}{e^{2x}}"#), "2*e^{2*x}");
+        assert_eq!(evaluate(r#"\frac{d}{dx}{x^x}"#), r#"x^{x}*(\ln{x}+1)"#);
+        assert_eq!(
+            evaluate(r#"\frac{d}{dx}{\arcsin{x}}"#),
+            r#"\frac{1}{\sqrt{1-x^{2}}}"#
+        );
+        assert_eq!(
+            evaluate(r#"\frac{d}{dx}{\log_{2}{x}}"#),
+            r#"\frac{1}{x*\ln{2}}"#
+        );
+        assert_eq!(evaluate(r#"\frac{d}{dx}{\cosh{3x}}"#), r#"3*\sinh{3*x}"#);
+        assert_eq!(
+            evaluate(r#"\frac{d}{dx}{\operatorname{abs}{x}}"#),
+            r#"\frac{x}{\operatorname{abs}{x}}"#
+        );
+
+        let scope = ParsingScope::new(None).with_symbol("y");
+        let tree =
+            ExpresssionTree::parse_scoped(r#"\frac{\partial}{\partial x}{x^2y+y^2}"#, &scope)
+                .unwrap();
+        assert_eq!(ExpressionElement::from(tree.evaluate()).assemble(), "2*x*y");
+        assert_eq!(tree.derivative("y").assemble(), "2*x");
+
+        assert_eq!(
+            ExpresssionTree::parse_raw(r#"x^2"#, None).unwrap_err(),
+            LaTexParsingError::new((0..1).into(), LaTexParsingErrorType::UnknownVariable)
+        );
+    }
+
+    #[test]
+    fn test_integrals() {
+        let approximate = |expr| {
+            ExpresssionTree::parse_raw(expr, None)
+                .unwrap()
+                .approximate()
+        };
+
+        assert!((approximate(r#"\int_{0}^{1} x^2 \, dx"#) - 1. / 3.).abs() < 1e-12);
+        assert!((approximate(r#"\int_0^\pi \sin{x} \, dx"#) - 2.).abs() < 1e-12);
+        assert!((approximate(r#"\int_{0}^{\infty} e^{-x} \, dx"#) - 1.).abs() < 1e-10);
+        assert!(
+            (approximate(r#"\int_{-\infty}^{\infty} e^{-x^2} \, dx"#)
+                - std::f64::consts::PI.sqrt())
+            .abs()
+                < 1e-10
+        );
+        assert!((approximate(r#"\int_{0}^{1}\int_{0}^{2} xy \, dy \, dx"#) - 1.).abs() < 1e-12);
+        assert!((approximate(r#"\int_{0}^{1} \frac{1}{\sqrt{x}} \, dx"#) - 2.).abs() < 1e-8);
+        assert!(approximate(r#"\int_{0}^{1} \frac{1}{x} \, dx"#).is_nan());
+        assert!(approximate(r#"\int_{0}^{\pi} \tan{x} \, dx"#).is_nan());
+        assert!(approximate(r#"\int_{0}^{\pi} \frac{1}{\cos{x}} \, dx"#).is_nan());
+        assert_eq!(evaluate(r#"1+\int_{1}^{0} dx"#), "0");
+        assert_eq!(evaluate(r#"\int_{0}^{\pi} \sin x \, dx"#), "2");
+
+        let scope = ParsingScope::new(None).with_symbol("y");
+        let tree = ExpresssionTree::parse_scoped(r#"\int_{0}^{y} xy \, dx"#, &scope).unwrap();
+        assert_eq!(tree.assemble(), r#"\int_{0}^{y}{x*y}\,dx"#);
+        assert_eq!(
+            ExpressionElement::from(tree.evaluate()).assemble(),
+            tree.assemble()
+        );
+        assert_eq!(
+            tree.derivative("y").assemble(),
+            r#"y*y+\int_{0}^{y}{x}\,dx"#
+        );
+
+        assert_eq!(
+            ExpresssionTree::parse_raw(r#"\int_{0}^{1} x"#, None).unwrap_err(),
+            LaTexParsingError::new((1..4).into(), LaTexParsingErrorType::InvalidFunctionCall)
+        );
+    }
+
+    #[test]
+    fn test_antiderivatives() {
+        assert_eq!(evaluate(r#"\int x^2 \, dx"#), r#"\frac{x^{3}}{3}"#);
+        assert_eq!(evaluate(r#"\int 3x^2+2x+1 \, dx"#), r#"x^{3}+x^{2}+x"#);
+        assert_eq!(
+            evaluate(r#"\int \frac{1}{x} \, dx"#),
+            r#"\ln{\operatorname{abs}{x}}"#
+        );
+        assert_eq!(evaluate(r#"\int \frac{1}{x^2+1} \, dx"#), r#"\arctan{x}"#);
+        assert_eq!(
+            evaluate(r#"\int \frac{1}{x^2-1} \, dx"#),
+            r#"\frac{\ln{\operatorname{abs}{x-1}}}{2}-\frac{\ln{\operatorname{abs}{x+1}}}{2}"#
+        );
+        assert_eq!(evaluate(r#"\int e^{2x+1} \, dx"#), r#"\frac{e^{2*x+1}}{2}"#);
+        assert_eq!(evaluate(r#"\int \ln{x} \, dx"#), r#"x*\ln{x}-x"#);
+        assert_eq!(
+            evaluate(r#"\int \sin{3x} \, dx"#),
+            r#"\frac{-\cos{3*x}}{3}"#
+        );
+        assert_eq!(evaluate(r#"\int x e^{x} \, dx"#), r#"x*e^{x}-e^{x}"#);
+        assert_eq!(evaluate(r#"\int 2x e^{x^2} \, dx"#), r#"e^{x^{2}}"#);
+        assert_eq!(evaluate(r#"\int_{0}^{1} x^2 \, dx"#), r#"\frac{1}{3}"#);
+        assert_eq!(evaluate(r#"\int_{1}^{e} \frac{1}{x} \, dx"#), "1");
+        assert_eq!(
+            evaluate(r#"\int_{-2}^{-1} \frac{1}{x} \, dx"#),
+            r#"-\ln{2}"#
+        );
+        let int = evaluate(r#"\int_{-3}^{-2} \frac{1}{x^2-1} \, dx"#);
+        assert!(!int.contains('i'), "{}", int);
+        assert!(
+            (ExpresssionTree::parse_raw(&int, None)
+                .unwrap()
+                .approximate()
+                - 1.5f64.ln() / 2.)
+                .abs()
+                < 1e-12
+        );
+
+        // Nothing elementary, so they stay as they are.
+        assert_eq!(
+            evaluate(r#"\int \sin{x^2} \, dx"#),
+            r#"\int{\sin{x^{2}}}\,dx"#
+        );
+        assert_eq!(
+            evaluate(r#"\int_{-1}^{1} \frac{1}{x^2} \, dx"#),
+            r#"\int_{-1}^{1}{\frac{1}{x^{2}}}\,dx"#
+        );
+        assert_eq!(
+            evaluate(r#"\int_{0}^{\pi} \tan{x} \, dx"#),
+            r#"\int_{0}^{\pi}{\tan{x}}\,dx"#
+        );
+
+        // The rest are checked by differentiating them back.
+        let scope = ParsingScope::new(None).with_symbol("x");
+        let x = ExpressionElement::from(Number::Decimal(0.7));
+        for (int, f) in [
+            (r#"\int \frac{1}{x^2-2} \, dx"#, r#"\frac{1}{x^2-2}"#),
+            (
+                r#"\int \frac{x^3}{x^2+2x+5} \, dx"#,
+                r#"\frac{x^3}{x^2+2x+5}"#,
+            ),
+            (
+                r#"\int \frac{1}{(x-1)^2*(x+2)} \, dx"#,
+                r#"\frac{1}{(x-1)^2*(x+2)}"#,
+            ),
+            (r#"\int 2^{x} \, dx"#, r#"2^{x}"#),
+            (r#"\int \tan{x} \, dx"#, r#"\tan{x}"#),
+            (r#"\int \sin{x}^2 \, dx"#, r#"\sin{x}^2"#),
+            (r#"\int \sqrt{2x+1} \, dx"#, r#"\sqrt{2x+1}"#),
+            (r#"\int \frac{1}{\sqrt{x}} \, dx"#, r#"\frac{1}{\sqrt{x}}"#),
+            (r#"\int x^2 \sin x \, dx"#, r#"x^2 \sin x"#),
+            (r#"\int x \ln x \, dx"#, r#"x \ln x"#),
+            (r#"\int x \cos{x^2} \, dx"#, r#"x \cos{x^2}"#),
+            (r#"\int \sin x \cos x \, dx"#, r#"\sin x \cos x"#),
+            (r#"\int \frac{\ln x}{x} \, dx"#, r#"\frac{\ln x}{x}"#),
+            (r#"\int \arctan x \, dx"#, r#"\arctan x"#),
+        ] {
+            let int =
+                ExpressionElement::from(ExpresssionTree::parse_raw(int, None).unwrap().evaluate());
+            assert!(!int.assemble().contains(r#"\int"#), "{}", int.assemble());
+            let expected = ExpresssionTree::parse_scoped(f, &scope)
+                .unwrap()
+                .root
+                .substitute("x", &x)
+                .approximate();
+            let actual = int.derivative("x").substitute("x", &x).approximate();
+            assert!((actual - expected).abs() < 1e-10, "{}", int.assemble());
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        assert_eq!(evaluate(r#"\lim_{x \to 0} \frac{\sin x}{x}"#), "1");
+        assert_eq!(
+            evaluate(r#"\lim_{x \to 0} \frac{1-\cos x}{x^2}"#),
+            r#"\frac{1}{2}"#
+        );
+        assert_eq!(
+            evaluate(r#"\lim_{x \to 0} \frac{\sin x - x}{x^3}"#),
+            r#"-\frac{1}{6}"#
+        );
+        assert_eq!(evaluate(r#"\lim_{x \to 2} \frac{x^2-4}{x-2}"#), "4");
+        assert_eq!(evaluate(r#"\lim_{x \to 1} \frac{\ln x}{x-1}"#), "1");
+        assert_eq!(evaluate(r#"\lim_{x \to 0} \frac{2^{x}-1}{x}"#), r#"\ln{2}"#);
+        assert_eq!(
+            evaluate(r#"\lim_{x \to \infty} \frac{2x^2+1}{3x^2-x}"#),
+            r#"\frac{2}{3}"#
+        );
+        assert_eq!(evaluate(r#"\lim_{x \to \infty} (1+\frac{1}{x})^{x}"#), "e");
+        assert_eq!(evaluate(r#"\lim_{x \to \infty} \frac{\ln x}{x}"#), "0");
+        assert_eq!(
+            evaluate(r#"\lim_{x \to \infty} \arctan x"#),
+            r#"\frac{\pi}{2}"#
+        );
+        assert_eq!(evaluate(r#"\lim_{x \to -\infty} e^{x}"#), "0");
+        assert_eq!(
+            evaluate(r#"\lim_{x \to \infty} \frac{e^{x}}{x^2}"#),
+            r#"\infty"#
+        );
+        assert_eq!(evaluate(r#"\lim_{x \to 0^+} x \ln x"#), "0");
+        assert_eq!(evaluate(r#"\lim_{x \to 0^{+}} x^{x}"#), "1");
+        assert_eq!(evaluate(r#"1+\lim_{x \to 3} x^2+1"#), "11");
+
+        // One-sided limits
+        assert_eq!(evaluate(r#"\lim_{x \to 0^+} \frac{1}{x}"#), r#"\infty"#);
+        assert_eq!(evaluate(r#"\lim_{x \to 0^-} \frac{1}{x}"#), r#"-\infty"#);
+        assert_eq!(evaluate(r#"\lim_{x \to 0} \frac{1}{x^2}"#), r#"\infty"#);
+        assert_eq!(
+            evaluate(r#"\lim_{x \to 0} \frac{1}{x}"#),
+            r#"\lim_{x \to 0}{\frac{1}{x}}"#
+        );
+        assert_eq!(
+            evaluate(r#"\lim_{x \to 0} \sin{\frac{1}{x}}"#),
+            r#"\lim_{x \to 0}{\sin{\frac{1}{x}}}"#
+        );
+
+        // Conjugates
+        assert_eq!(
+            evaluate(r#"\lim_{x \to \infty} (\sqrt{x^2+x}-x)"#),
+            r#"\frac{1}{2}"#
+        );
+        assert_eq!(
+            evaluate(r#"\lim_{x \to \infty} (\sqrt{x^2+x}-x+\frac{1}{1000000000})"#),
+            r#"\frac{500000001}{1000000000}"#
+        );
+        assert_eq!(
+            evaluate(r#"\lim_{x \to \infty} \frac{e^x}{x^{10}}"#),
+            r#"\infty"#
+        );
+
+        let scope = ParsingScope::new(None).with_symbol("a");
+        let tree = ExpresssionTree::parse_scoped(r#"\lim_{x \to 0} \frac{\sin{ax}}{x}"#, &scope);
+        assert_eq!(
+            ExpressionElement::from(tree.unwrap().evaluate()).assemble(),
+            "a"
+        );
+        let tree = ExpresssionTree::parse_scoped(r#"\lim_{x \to a^-} x^2"#, &scope).unwrap();
+        assert_eq!(tree.assemble(), r#"\lim_{x \to a^{-}}{x^{2}}"#);
+        assert_eq!(tree.derivative("a").assemble(), "2*a");
+        assert_eq!(tree.derivative("x").assemble(), "0");
+
+        assert_eq!(
+            ExpresssionTree::parse_raw(r#"\lim x"#, None).unwrap_err(),
+            LaTexParsingError::new((1..4).into(), LaTexParsingErrorType::InvalidFunctionCall)
+        );
+        assert_eq!(
+            ExpresssionTree::parse_raw(r#"\lim_{x \to 0}"#, None).unwrap_err(),
+            LaTexParsingError::new((1..4).into(), LaTexParsingErrorType::InvalidFunctionCall)
+        );
+    }
+
+    #[test]
+    fn test_sums() {
+        assert_eq!(evaluate(r#"\sum_{k=1}^{10} k"#), "55");
+        assert_eq!(evaluate(r#"\sum_{k=1}^{10} k^2"#), "385");
+        assert_eq!(evaluate(r#"\sum_{k=0}^{5} 2^{k}"#), "63");
+        assert_eq!(evaluate(r#"\sum_{k=1}^{3} \frac{1}{k}"#), r#"\frac{11}{6}"#);
+        assert_eq!(evaluate(r#"\sum_{k=1}^{0} k"#), "0");
+        assert_eq!(evaluate(r#"\sum_{i=1}^{3} \sum_{j=1}^{i} j"#), "10");
+        assert_eq!(evaluate(r#"1+\sum_{k=1}^{4} k+1"#), "12");
+        assert_eq!(evaluate(r#"\prod_{k=1}^{5} k"#), "120");
+        assert_eq!(evaluate(r#"\prod_{k=1}^{0} k"#), "1");
+        assert_eq!(evaluate(r#"\sum_{k=1}^{1.5} k"#), r#"\sum_{k=1}^{1.5}{k}"#);
+
+        // Infinite series
+        assert_eq!(evaluate(r#"\sum_{k=0}^{\infty} \frac{1}{2^k}"#), "2");
+        assert_eq!(evaluate(r#"\sum_{k=1}^{\infty} k"#), r#"\infty"#);
+        assert_eq!(
+            evaluate(r#"\sum_{k=1}^{\infty} \frac{1}{k}"#),
+            r#"\sum_{k=1}^{\infty}{\frac{1}{k}}"#
+        );
+        let tree = ExpresssionTree::parse_raw(r#"\sum_{k=1}^{\infty} \frac{1}{k^2}"#, None);
+        assert!((tree.unwrap().approximate() - std::f64::consts::PI.powi(2) / 6.).abs() < 1e-8);
+        let tree = ExpresssionTree::parse_raw(r#"\sum_{k=1}^{\infty} \frac{(0-1)^{k+1}}{k}"#, None);
+        assert!((tree.unwrap().approximate() - std::f64::consts::LN_2).abs() < 1e-8);
+        let tree =
+            ExpresssionTree::parse_raw(r#"\prod_{k=1}^{\infty} (1-\frac{1}{(k+1)^2})"#, None);
+        assert!((tree.unwrap().approximate() - 0.5).abs() < 1e-8);
+        // Too many terms to work out exactly.
+        let tree = ExpresssionTree::parse_raw(r#"\sum_{k=1}^{1000000} \frac{1}{k}"#, None);
+        assert!((tree.unwrap().approximate() - 14.392726722865723).abs() < 1e-12);
+        let tree = ExpresssionTree::parse_raw(r#"\sum_{k=1}^{1000000} \frac{1}{k^2}"#, None);
+        let sum = tree.unwrap().approximate_complex();
+        assert!((sum.re - 1.6449330668482264).abs() < 1e-12 && sum.im == 0.);
+        let tree = ExpresssionTree::parse_raw(r#"\prod_{k=1}^{1000000} (1+\frac{1}{k^2})"#, None);
+        assert!(!tree.unwrap().approximate_complex().is_nan());
+
+        // Closed forms
+        let scope = ParsingScope::new(None).with_symbol("n").with_symbol("x");
+        let closed = |expr| {
+            let tree = ExpresssionTree::parse_scoped(expr, &scope).unwrap();
+            ExpressionElement::from(tree.evaluate()).assemble()
+        };
+        assert_eq!(
+            closed(r#"\sum_{k=1}^{n} k"#),
+            r#"\frac{n^{2}}{2}+\frac{n}{2}"#
+        );
+        assert_eq!(
+            closed(r#"\sum_{k=1}^{n} k^2"#),
+            r#"\frac{n^{3}}{3}+\frac{n^{2}}{2}+\frac{n}{6}"#
+        );
+        assert_eq!(closed(r#"\sum_{k=0}^{n} 2^{k}"#), "2^{n+1}-1");
+        assert_eq!(
+            closed(r#"\sum_{k=0}^{n} x^{k}"#),
+            r#"\frac{x^{n+1}-1}{x-1}"#
+        );
+        assert_eq!(closed(r#"\sum_{k=1}^{3} x^k"#), "x+x^{2}+x^{3}");
+        assert_eq!(closed(r#"\prod_{k=1}^{n} 2"#), "2^{n}");
+        assert_eq!(
+            closed(r#"\prod_{k=1}^{n} 2^{k}"#),
+            r#"2^{\frac{n^{2}}{2}+\frac{n}{2}}"#
+        );
+        assert_eq!(closed(r#"\prod_{k=1}^{n} k"#), r#"\prod_{k=1}^{n}{k}"#);
+
+        let tree = ExpresssionTree::parse_scoped(r#"\sum_{k=1}^{\infty} x^k"#, &scope).unwrap();
+        assert_eq!(
+            tree.derivative("x").assemble(),
+            r#"\sum_{k=1}^{\infty}{k*x^{k-1}}"#
+        );
+        assert_eq!(tree.derivative("n").assemble(), "0");
+
+        let mut vars = HashMap::new();
+        vars.insert("n".to_string(), Number::Integer(100));
+        let tree = ExpresssionTree::parse_raw(r#"\sum_{k=1}^{n} k"#, Some(&vars)).unwrap();
+        assert_eq!(ExpressionElement::from(tree.evaluate()).assemble(), "5050");
+
+        assert_eq!(
+            ExpresssionTree::parse_raw(r#"\sum k"#, None).unwrap_err(),
+            LaTexParsingError::new((1..4).into(), LaTexParsingErrorType::InvalidFunctionCall)
+        );
+        assert_eq!(
+            ExpresssionTree::parse_raw(r#"\prod_{k}^{3} k"#, None).unwrap_err(),
+            LaTexParsingError::new((1..5).into(), LaTexParsingErrorType::InvalidFunctionCall)
+        );
+    }
+
+    #[test]
+    fn test_series() {
+        let scope = ParsingScope::new(None).with_symbol("x").with_symbol("a");
+        let series = |expr, point: &str, order| {
+            let point = ExpresssionTree::parse_scoped(point, &scope).unwrap().root;
+            let tree = ExpresssionTree::parse_scoped(expr, &scope).unwrap();
+            tree.series("x", point, order).assemble()
+        };
+
+        assert_eq!(
+            series(r#"\frac{\sin x}{x}"#, "0", 6),
+            r#"1-\frac{x^{2}}{6}+\frac{x^{4}}{120}+O(x^{6})"#
+        );
+        assert_eq!(
+            series(r#"e^{x}"#, "0", 4),
+            r#"1+x+\frac{x^{2}}{2}+\frac{x^{3}}{6}+O(x^{4})"#
+        );
+        assert_eq!(
+            series(r#"\ln{(1+x)}"#, "0", 4),
+            r#"x-\frac{x^{2}}{2}+\frac{x^{3}}{3}+O(x^{4})"#
+        );
+        assert_eq!(
+            series(r#"\tan x"#, "0", 8),
+            r#"x+\frac{x^{3}}{3}+\frac{2*x^{5}}{15}+\frac{17*x^{7}}{315}+O(x^{8})"#
+        );
+        assert_eq!(
+            series(r#"\arctan x"#, "0", 6),
+            r#"x-\frac{x^{3}}{3}+\frac{x^{5}}{5}+O(x^{6})"#
+        );
+        assert_eq!(
+            series(r#"\sqrt{1+x}"#, "0", 3),
+            r#"1+\frac{x}{2}-\frac{x^{2}}{8}+O(x^{3})"#
+        );
+        assert_eq!(
+            series(r#"\frac{x}{e^{x}-1}"#, "0", 5),
+            r#"1-\frac{x}{2}+\frac{x^{2}}{12}-\frac{x^{4}}{720}+O(x^{5})"#
+        );
+        assert_eq!(
+            series(r#"(1+x)^{x}"#, "0", 4),
+            r#"1+x^{2}-\frac{x^{3}}{2}+O(x^{4})"#
+        );
+        assert_eq!(
+            series(r#"\sqrt[3]{x^3+x^4}"#, "0", 3),
+            r#"x+\frac{x^{2}}{3}+O(x^{3})"#
+        );
+        assert_eq!(
+            series(r#"\ln x"#, "1", 3),
+            r#"x-1-\frac{(x-1)^{2}}{2}+O((x-1)^{3})"#
+        );
+        assert_eq!(
+            series(r#"e^{x}"#, "a", 2),
+            r#"e^{a}+e^{a}*(x-a)+O((x-a)^{2})"#
+        );
+
+        // Laurent series
+        assert_eq!(
+            series(r#"\frac{1}{\sin x}"#, "0", 4),
+            r#"\frac{1}{x}+\frac{x}{6}+\frac{7*x^{3}}{360}+O(x^{4})"#
+        );
+        assert_eq!(
+            series(r#"\cot x"#, "0", 2),
+            r#"\frac{1}{x}-\frac{x}{3}+O(x^{2})"#
+        );
+        assert_eq!(
+            series(r#"\frac{1}{x^2}+x"#, "0", 3),
+            r#"\frac{1}{x^{2}}+x+O(x^{3})"#
+        );
+
+        // Essential singularities and branch points
+        assert_eq!(
+            series(r#"e^{\frac{1}{x}}"#, "0", 3),
+            r#"\operatorname{series}_{x=0}^{3}{e^{\frac{1}{x}}}"#
+        );
+        assert_eq!(
+            series(r#"\sqrt{x^2+x^4}"#, "0", 3),
+            r#"\operatorname{series}_{x=0}^{3}{\sqrt{x^{2}+x^{4}}}"#
+        );
+
+        assert_eq!(
+            evaluate(r#"\operatorname{series}_{x=0}^{4} e^{x}+1"#),
+            r#"2+x+\frac{x^{2}}{2}+\frac{x^{3}}{6}+O(x^{4})"#
+        );
+        let tree =
+            ExpresssionTree::parse_raw(r#"\operatorname{series}_{x=1}^{3}{\ln x}"#, None).unwrap();
+        assert_eq!(
+            tree.assemble(),
+            r#"\operatorname{series}_{x=1}^{3}{\ln{x}}"#
+        );
+        assert_eq!(tree.derivative("x").assemble(), "2-x+O((x-1)^{2})");
+        assert_eq!(
+            ExpresssionTree::parse_raw(r#"\operatorname{series}_{x=0}^{-1}{e^x}"#, None)
+                .unwrap_err(),
+            LaTexParsingError::new((29..31).into(), LaTexParsingErrorType::InvalidFunctionCall)
+        );
+        assert!(
+            ExpresssionTree::parse_raw(r#"\operatorname{series}_{x=0}^{1000}{e^x}"#, None).is_err()
+        );
+
+        assert_eq!(
+            ExpresssionTree::parse_raw(r#"\operatorname{series}{x}"#, None).unwrap_err(),
+            LaTexParsingError::new((1..21).into(), LaTexParsingErrorType::InvalidFunctionCall)
+        );
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test]
+    fn test_precise_evaluation() {
+        use crate::math::precise;
+
+        assert_eq!(evaluate(r#"2^{100}"#), "1267650600228229401496703205376");
+        assert_eq!(
+            evaluate(r#"123456789012345678901234567890+1"#),
+            "123456789012345678901234567891"
+        );
+        assert_eq!(
+            evaluate(r#"\sin{1}+0.5"#),
+            "1.3414709848078965066525023216302989996225630607984"
+        );
+        assert_eq!(evaluate(r#"0.1+0.2"#), "0.3");
+        assert_eq!(
+            evaluate(r#"\frac{1}{3}+0.1"#),
+            "0.43333333333333333333333333333333333333333333333333"
+        );
+        assert_eq!(
+            precise::with_precision(30, || {
+                let tree = ExpresssionTree::parse_raw(r#"\prod_{k=1}^{25}{k}"#, None).unwrap();
+                crate::math::func::op::decimal(tree.into()).assemble()
+            }),
+            "15511210043330985984000000"
+        );
+        assert_eq!(
+            precise::round(
+                &ExpresssionTree::parse_raw(r#"\sqrt{2}+\pi"#, None)
+                    .unwrap()
+                    .approximate_precise(40)
+                    .unwrap(),
+                30
+            )
+            .to_string(),
+            "4.55580621596288828726433210749"
+        );
+    }
+
     #[test]
     fn test_custom_vars() {
         let map = HashMap::from([
@@ -675,9 +2548,33 @@ mod test {
             ExpresssionTree::parse_raw(r#"5+\log_{8}*7"#, None).unwrap_err(),
             LaTexParsingError::new((3..7).into(), LaTexParsingErrorType::InvalidFunctionCall)
         );
+        assert_eq!(
+            ExpresssionTree::parse_raw(r#"2+\mathrm{x}"#, None).unwrap_err(),
+            LaTexParsingError::new((2..12).into(), LaTexParsingErrorType::InvalidConstant)
+        );
         assert_eq!(
             ExpresssionTree::parse_raw(r#"1+random*5"#, None).unwrap_err(),
             LaTexParsingError::new((2..8).into(), LaTexParsingErrorType::UnknownVariable)
         );
+        let missing_operand = |expr, at| {
+            assert_eq!(
+                ExpresssionTree::parse_raw(expr, None).unwrap_err(),
+                LaTexParsingError::new(
+                    MathElementMeta::at(at),
+                    LaTexParsingErrorType::MissingOperand
+                )
+            );
+        };
+        missing_operand("=", 0);
+        missing_operand("=1", 0);
+        missing_operand("2*3=", 3);
+        missing_operand(r#"1+(2*)"#, 4);
+        missing_operand(r#"\sin{1+}"#, 6);
+        missing_operand("<", 0);
+        missing_operand("2-1>", 3);
+        assert_eq!(
+            ExpresssionTree::parse_raw(r#"\leq 2"#, None).unwrap_err(),
+            LaTexParsingError::new((0..4).into(), LaTexParsingErrorType::MissingOperand)
+        );
     }
 }
@@ -3,12 +3,17 @@ use std::fmt::Debug;
 use crate::{
     latex::*,
     math::{
+        func::{limit::Direction, Differentiable},
+        symbol::{Constant, Number},
         ExpressionElement, FromExpr, Function, IntoRawExpr, MathElement, PhantomFunction,
         PhantomOperator, Prioritizable,
     },
-    DecimalScalar,
+    ComplexScalar, DecimalScalar,
 };
 
+#[cfg(feature = "bignum")]
+use crate::BigDecimalScalar;
+
 use calculatorium_macros::{AsPhantomFunction, AsPhantomOperator, FromExpr, IntoRawExpr};
 
 macro_rules! register_phantom_functions {
@@ -33,9 +38,29 @@ macro_rules! register_phantom_operators {
     };
 }
 
+macro_rules! impl_params {
+    ($ty: ident, $($field: ident),*) => {
+        impl $ty {
+            /// The parameters, in the order they are declared.
+            #[inline]
+            pub fn params(&self) -> Vec<&ExpressionElement> {
+                vec![$(&self.$field),*]
+            }
+
+            /// Rebuilds this function with every parameter mapped by `f`.
+            #[inline]
+            pub fn map_params(&self, mut f: impl FnMut(&ExpressionElement) -> ExpressionElement) -> Self {
+                Self {
+                    $($field: f(&self.$field),)*
+                }
+            }
+        }
+    };
+}
+
 macro_rules! define_operator {
     ($priority: literal, $op_ty: ident, $op_name: expr, $($field: ident),*) => {
-        #[derive(Debug, FromExpr, AsPhantomOperator)]
+        #[derive(Debug, Clone, PartialEq, FromExpr, AsPhantomOperator)]
         #[priority($priority)]
         pub struct $op_ty {
             $($field: ExpressionElement,)*
@@ -45,6 +70,8 @@ macro_rules! define_operator {
             pub const LATEX_SYMBOL: &'static str = $op_name;
         }
 
+        impl_params!($op_ty, $($field),*);
+
         impl Prioritizable for $op_ty {
             fn priority(&self) -> u32 {
                 $priority
@@ -55,7 +82,15 @@ macro_rules! define_operator {
 
 macro_rules! define_function {
     ($fn_ty: ident, $fn_name: expr, $($field: ident),*) => {
-        #[derive(Debug, FromExpr, IntoRawExpr, AsPhantomFunction)]
+        define_function!(@decl [IntoRawExpr] $fn_ty, $fn_name, $($field),*);
+    };
+    // For functions that don't follow the `\name{param}{param}` form and
+    // implement `IntoRawExpr` themselves.
+    (@custom_assemble $fn_ty: ident, $fn_name: expr, $($field: ident),*) => {
+        define_function!(@decl [] $fn_ty, $fn_name, $($field),*);
+    };
+    (@decl [$($derive: ident)*] $fn_ty: ident, $fn_name: expr, $($field: ident),*) => {
+        #[derive(Debug, Clone, PartialEq, FromExpr, AsPhantomFunction $(, $derive)*)]
         pub struct $fn_ty {
             $($field: ExpressionElement,)*
         }
@@ -63,31 +98,299 @@ macro_rules! define_function {
         impl $fn_ty {
             pub const LATEX_SYMBOL: &'static str = $fn_name;
         }
+
+        impl_params!($fn_ty, $($field),*);
+
+        impl Prioritizable for $fn_ty {
+            #[inline]
+            fn priority(&self) -> u32 {
+                10
+            }
+        }
     };
 }
 
+/// Wraps an operand in parentheses if needed. Operands that aren't the leftmost one need them
+/// whenever they start with a minus sign, like in `2*(-3)`.
+fn assemble_operand(elem: &ExpressionElement, parenthesize: bool, leftmost: bool) -> String {
+    let raw = elem.assemble();
+    if parenthesize || (!leftmost && raw.starts_with(SUBTRACT)) {
+        format!("({})", raw)
+    } else {
+        raw
+    }
+}
+
 macro_rules! impl_into_raw_expr_op {
-    ($op_ty: ident, $symbol: expr) => {
+    ($op_ty: ident, $symbol: expr, $commutative: literal) => {
         impl IntoRawExpr for $op_ty {
             fn assemble(&self) -> String {
-                if self.lhs.priority() < self.priority() {
-                    format!(
-                        "{}{}({})",
-                        self.rhs.assemble(),
-                        $symbol,
-                        self.lhs.assemble()
-                    )
-                } else {
+                let lhs = assemble_operand(&self.lhs, self.lhs.priority() < self.priority(), true);
+                let rhs = assemble_operand(
+                    &self.rhs,
+                    self.rhs.priority() < self.priority()
+                        || (!$commutative && self.rhs.priority() == self.priority()),
+                    false,
+                );
+
+                format!("{}{}{}", lhs, $symbol, rhs)
+            }
+        }
+    };
+}
+
+impl IntoRawExpr for Multiply {
+    fn assemble(&self) -> String {
+        let rhs = assemble_operand(&self.rhs, self.rhs.priority() < self.priority(), false);
+        if self.lhs.is_integer(-1) {
+            return format!("{}{}", SUBTRACT, rhs);
+        }
+
+        let lhs = assemble_operand(&self.lhs, self.lhs.priority() < self.priority(), true);
+        // The imaginary unit is written next to its coefficient, like `2i` and `i\pi`.
+        if self.rhs.is_imaginary_unit()
+            || (self.lhs.is_imaginary_unit()
+                && matches!(self.rhs, ExpressionElement::Number(Number::Constant(_))))
+        {
+            // `\pi i`, not `\pii`
+            let sep = if lhs.ends_with(|c: char| c.is_ascii_alphabetic())
+                && rhs.starts_with(|c: char| c.is_ascii_alphabetic())
+            {
+                " "
+            } else {
+                ""
+            };
+            format!("{}{}{}", lhs, sep, rhs)
+        } else {
+            format!("{}{}{}", lhs, MULTIPLY, rhs)
+        }
+    }
+}
+
+/// Nothing binds looser than `=`, so neither side needs parentheses.
+impl IntoRawExpr for Equation {
+    fn assemble(&self) -> String {
+        format!("{}{}{}", self.lhs.assemble(), EQUAL, self.rhs.assemble())
+    }
+}
+
+/// Relations bind as loose as `=`. Commands like `\le` are followed by a space so they don't
+/// run into the right hand side.
+macro_rules! impl_into_raw_expr_relation {
+    ($($op_ty: ident, $symbol: expr),*) => {
+        $(
+            impl IntoRawExpr for $op_ty {
+                fn assemble(&self) -> String {
                     format!("{}{}{}", self.lhs.assemble(), $symbol, self.rhs.assemble())
                 }
             }
-        }
+        )*
     };
 }
 
+#[rustfmt::skip]
+impl_into_raw_expr_relation!(
+    Less, LESS,
+    Greater, GREATER,
+    LessEqual, format!("{}{} ", FUNC_BEGIN, LESS_EQUAL),
+    GreaterEqual, format!("{}{} ", FUNC_BEGIN, GREATER_EQUAL),
+    NotEqual, format!("{}{} ", FUNC_BEGIN, NOT_EQUAL)
+);
+
 impl IntoRawExpr for Power {
     fn assemble(&self) -> String {
-        format!("{}^{{{}}}", self.base.assemble(), self.exp.assemble())
+        if self.base.priority() < self.priority()
+            || self.base.assemble().starts_with(SUBTRACT)
+            || matches!(&self.base, ExpressionElement::Function(f) if matches!(**f, MathFunction::Power(_)))
+        {
+            format!("({})^{{{}}}", self.base.assemble(), self.exp.assemble())
+        } else {
+            format!("{}^{{{}}}", self.base.assemble(), self.exp.assemble())
+        }
+    }
+}
+
+impl IntoRawExpr for Transpose {
+    fn assemble(&self) -> String {
+        if self.x.priority() < self.priority()
+            || matches!(&self.x, ExpressionElement::Function(f) if matches!(**f, MathFunction::Power(_) | MathFunction::Transpose(_)))
+        {
+            format!("({})^{{{}}}", self.x.assemble(), TRANSPOSE)
+        } else {
+            format!("{}^{{{}}}", self.x.assemble(), TRANSPOSE)
+        }
+    }
+}
+
+impl IntoRawExpr for Root {
+    fn assemble(&self) -> String {
+        if self.rad == ExpressionElement::Number(Number::Integer(2)) {
+            format!("\\{}{{{}}}", ROOT, self.deg.assemble())
+        } else {
+            format!(
+                "\\{}[{}]{{{}}}",
+                ROOT,
+                self.rad.assemble(),
+                self.deg.assemble()
+            )
+        }
+    }
+}
+
+impl IntoRawExpr for Log {
+    fn assemble(&self) -> String {
+        match &self.base {
+            ExpressionElement::Number(Number::Constant(Constant::E)) => {
+                format!("\\{}{{{}}}", LN, self.anti.assemble())
+            }
+            ExpressionElement::Number(Number::Integer(10)) => {
+                format!("\\{}{{{}}}", LG, self.anti.assemble())
+            }
+            _ => format!(
+                "\\{}{{{}}}{{{}}}",
+                LOG,
+                self.base.assemble(),
+                self.anti.assemble()
+            ),
+        }
+    }
+}
+
+/// Functions written as `\operatorname{name}{x}`.
+macro_rules! impl_into_raw_expr_operatorname {
+    ($($fn_ty: ty),*) => {
+        $(
+            impl IntoRawExpr for $fn_ty {
+                fn assemble(&self) -> String {
+                    format!(
+                        "\\{}{{{}}}{{{}}}",
+                        OPERATORNAME,
+                        Self::LATEX_SYMBOL,
+                        self.x.assemble()
+                    )
+                }
+            }
+        )*
+    };
+}
+
+impl_into_raw_expr_operatorname!(Abs, Rank, Trace, Eig);
+
+impl IntoRawExpr for Derivative {
+    fn assemble(&self) -> String {
+        format!(
+            "\\{}{{{}}}{{{}{}}}{{{}}}",
+            FRAC,
+            DIFFERENTIAL,
+            DIFFERENTIAL,
+            self.var.assemble(),
+            self.f.assemble()
+        )
+    }
+}
+
+impl IntoRawExpr for PartialDerivative {
+    fn assemble(&self) -> String {
+        format!(
+            "\\{}{{\\{}}}{{\\{} {}}}{{{}}}",
+            FRAC,
+            PARTIAL,
+            PARTIAL,
+            self.var.assemble(),
+            self.f.assemble()
+        )
+    }
+}
+
+impl IntoRawExpr for Integral {
+    fn assemble(&self) -> String {
+        format!(
+            "\\{}_{{{}}}^{{{}}}{{{}}}{}{}{}",
+            INTEGRAL,
+            self.lower.assemble(),
+            self.upper.assemble(),
+            self.f.assemble(),
+            THIN_SPACE,
+            DIFFERENTIAL,
+            self.var.assemble()
+        )
+    }
+}
+
+impl IntoRawExpr for IndefiniteIntegral {
+    fn assemble(&self) -> String {
+        format!(
+            "\\{}{{{}}}{}{}{}",
+            INTEGRAL,
+            self.f.assemble(),
+            THIN_SPACE,
+            DIFFERENTIAL,
+            self.var.assemble()
+        )
+    }
+}
+
+impl IntoRawExpr for Limit {
+    fn assemble(&self) -> String {
+        let side = match self.direction() {
+            Direction::Both => "",
+            Direction::Left => "^{-}",
+            Direction::Right => "^{+}",
+        };
+
+        format!(
+            "\\{}_{{{} {} {}{}}}{{{}}}",
+            LIMIT,
+            self.var.assemble(),
+            TO,
+            self.point.assemble(),
+            side,
+            self.f.assemble()
+        )
+    }
+}
+
+/// Sums and products, written as `\sum_{k=1}^{n}{f}`.
+macro_rules! impl_into_raw_expr_big_operator {
+    ($($fn_ty: ty),*) => {
+        $(
+            impl IntoRawExpr for $fn_ty {
+                fn assemble(&self) -> String {
+                    format!(
+                        "\\{}_{{{}{}{}}}^{{{}}}{{{}}}",
+                        Self::LATEX_SYMBOL,
+                        self.var.assemble(),
+                        EQUAL,
+                        self.lower.assemble(),
+                        self.upper.assemble(),
+                        self.f.assemble()
+                    )
+                }
+            }
+        )*
+    };
+}
+
+impl_into_raw_expr_big_operator!(Sum, Product);
+
+impl IntoRawExpr for Series {
+    fn assemble(&self) -> String {
+        format!(
+            "\\{}{{{}}}_{{{}{}{}}}^{{{}}}{{{}}}",
+            OPERATORNAME,
+            SERIES,
+            self.var.assemble(),
+            EQUAL,
+            self.point.assemble(),
+            self.order.assemble(),
+            self.f.assemble()
+        )
+    }
+}
+
+impl IntoRawExpr for BigO {
+    fn assemble(&self) -> String {
+        format!("{}({})", BIG_O, self.x.assemble())
     }
 }
 
@@ -96,16 +399,21 @@ define_operator!(1, Subtract, SUBTRACT, lhs, rhs);
 define_operator!(5, Multiply, MULTIPLY, lhs, rhs);
 define_operator!(5, Divide, DIVIDE, lhs, rhs);
 define_operator!(10, Power, SUPER_SCRIPT, base, exp);
+define_operator!(0, Equation, EQUAL, lhs, rhs);
+define_operator!(0, Less, LESS, lhs, rhs);
+define_operator!(0, Greater, GREATER, lhs, rhs);
+define_operator!(0, LessEqual, LESS_EQUAL, lhs, rhs);
+define_operator!(0, GreaterEqual, GREATER_EQUAL, lhs, rhs);
+define_operator!(0, NotEqual, NOT_EQUAL, lhs, rhs);
 
-impl_into_raw_expr_op!(Add, ADD);
-impl_into_raw_expr_op!(Subtract, SUBTRACT);
-impl_into_raw_expr_op!(Multiply, MULTIPLY);
-impl_into_raw_expr_op!(Divide, DIVIDE);
+impl_into_raw_expr_op!(Add, ADD, true);
+impl_into_raw_expr_op!(Subtract, SUBTRACT, false);
+impl_into_raw_expr_op!(Divide, DIVIDE, false);
 
 define_function!(Fraction, FRAC, num, den);
-define_function!(Root, ROOT, rad, deg);
+define_function!(@custom_assemble Root, ROOT, rad, deg);
 
-define_function!(Log, LOG, base, anti);
+define_function!(@custom_assemble Log, LOG, base, anti);
 
 define_function!(Sin, SIN, x);
 define_function!(Cos, COS, x);
@@ -121,12 +429,30 @@ define_function!(Arccot, ARCCOT, x);
 define_function!(Arcsec, ARCSEC, x);
 define_function!(Arccsc, ARCCSC, x);
 
-define_function!(Sinh, SIN, x);
-define_function!(Cosh, COS, x);
-define_function!(Tanh, TAN, x);
-define_function!(Coth, COT, x);
-define_function!(Sech, SEC, x);
-define_function!(Csch, CSC, x);
+define_function!(Sinh, SINH, x);
+define_function!(Cosh, COSH, x);
+define_function!(Tanh, TANH, x);
+define_function!(Coth, COTH, x);
+define_function!(Sech, SECH, x);
+define_function!(Csch, CSCH, x);
+
+define_function!(@custom_assemble Abs, ABS, x);
+
+define_function!(@custom_assemble Transpose, TRANSPOSE, x);
+define_function!(Det, DET, x);
+define_function!(@custom_assemble Rank, RANK, x);
+define_function!(@custom_assemble Trace, TRACE, x);
+define_function!(@custom_assemble Eig, EIG, x);
+
+define_function!(@custom_assemble Derivative, DIFFERENTIAL, var, f);
+define_function!(@custom_assemble PartialDerivative, PARTIAL, var, f);
+define_function!(@custom_assemble Integral, INTEGRAL, var, lower, upper, f);
+define_function!(@custom_assemble IndefiniteIntegral, INTEGRAL, var, f);
+define_function!(@custom_assemble Limit, LIMIT, var, point, side, f);
+define_function!(@custom_assemble Sum, SUM, var, lower, upper, f);
+define_function!(@custom_assemble Product, PRODUCT, var, lower, upper, f);
+define_function!(@custom_assemble Series, SERIES, var, point, order, f);
+define_function!(@custom_assemble BigO, BIG_O, x);
 
 #[rustfmt::skip]
 register_phantom_functions!(
@@ -154,7 +480,14 @@ register_phantom_functions!(
     TANH, PhantomTanh,
     COTH, PhantomCoth,
     SECH, PhantomSech,
-    CSCH, PhantomCsch
+    CSCH, PhantomCsch,
+
+    ABS, PhantomAbs,
+
+    DET, PhantomDet,
+    RANK, PhantomRank,
+    TRACE, PhantomTrace,
+    EIG, PhantomEig
 );
 
 #[rustfmt::skip]
@@ -163,16 +496,54 @@ register_phantom_operators!(
     SUBTRACT, PhantomSubtract,
     MULTIPLY, PhantomMultiply,
     DIVIDE, PhantomDivide,
-    SUPER_SCRIPT, PhantomPower
+    SUPER_SCRIPT, PhantomPower,
+    EQUAL, PhantomEquation,
+    LESS, PhantomLess,
+    GREATER, PhantomGreater,
+    LESS_EQUAL | LESS_EQUAL_ALT, PhantomLessEqual,
+    GREATER_EQUAL | GREATER_EQUAL_ALT, PhantomGreaterEqual,
+    NOT_EQUAL | NOT_EQUAL_ALT, PhantomNotEqual
 );
 
 macro_rules! define_math_enum {
     ($enum_ty: ident, $($ident: ident, $ty: ty),*) => {
-        #[derive(Debug)]
+        #[derive(Debug, Clone, PartialEq)]
         pub enum $enum_ty {
             $($ident($ty),)*
         }
 
+        impl Prioritizable for $enum_ty {
+            fn priority(&self) -> u32 {
+                match self {
+                    $($enum_ty::$ident(elem) => elem.priority(),)*
+                }
+            }
+        }
+
+        impl $enum_ty {
+            #[inline]
+            pub fn params(&self) -> Vec<&ExpressionElement> {
+                match self {
+                    $($enum_ty::$ident(elem) => elem.params(),)*
+                }
+            }
+
+            #[inline]
+            pub fn map_params(&self, f: impl FnMut(&ExpressionElement) -> ExpressionElement) -> Self {
+                match self {
+                    $($enum_ty::$ident(elem) => $enum_ty::$ident(elem.map_params(f)),)*
+                }
+            }
+        }
+
+        impl Differentiable for $enum_ty {
+            fn derivative(&self, var: &str) -> ExpressionElement {
+                match self {
+                    $($enum_ty::$ident(elem) => elem.derivative(var),)*
+                }
+            }
+        }
+
         impl Function for $enum_ty {
             fn evaluate(&self) -> MathElement {
                 match self {
@@ -185,8 +556,30 @@ macro_rules! define_math_enum {
                     $($enum_ty::$ident(elem) => elem.approximate(),)*
                 }
             }
+
+            fn approximate_complex(&self) -> ComplexScalar {
+                match self {
+                    $($enum_ty::$ident(elem) => elem.approximate_complex(),)*
+                }
+            }
+
+            #[cfg(feature = "bignum")]
+            fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+                match self {
+                    $($enum_ty::$ident(elem) => elem.approximate_precise(precision),)*
+                }
+            }
         }
 
+        $(
+            impl From<$ty> for ExpressionElement {
+                #[inline]
+                fn from(value: $ty) -> Self {
+                    ExpressionElement::Function(Box::new($enum_ty::$ident(value)))
+                }
+            }
+        )*
+
         impl IntoRawExpr for $enum_ty {
             fn assemble(&self) -> String {
                 match self {
@@ -205,6 +598,12 @@ define_math_enum!(
     Multiply, Multiply,
     Divide, Divide,
     Power, Power,
+    Equation, Equation,
+    Less, Less,
+    Greater, Greater,
+    LessEqual, LessEqual,
+    GreaterEqual, GreaterEqual,
+    NotEqual, NotEqual,
     Fraction, Fraction,
     Root, Root,
     Log, Log,
@@ -225,5 +624,42 @@ define_math_enum!(
     Tanh, Tanh,
     Coth, Coth,
     Sech, Sech,
-    Csch, Csch
+    Csch, Csch,
+    Abs, Abs,
+    Transpose, Transpose,
+    Det, Det,
+    Rank, Rank,
+    Trace, Trace,
+    Eig, Eig,
+    Derivative, Derivative,
+    PartialDerivative, PartialDerivative,
+    Integral, Integral,
+    IndefiniteIntegral, IndefiniteIntegral,
+    Limit, Limit,
+    Sum, Sum,
+    Product, Product,
+    Series, Series,
+    BigO, BigO
 );
+
+impl MathFunction {
+    /// The variable bound by integrals, limits, sums, products and series, which is only visible
+    /// in their operand.
+    ///
+    /// It's always the first parameter, while the operand is always the last one.
+    pub fn bound_var(&self) -> Option<&str> {
+        let var = match self {
+            MathFunction::Integral(int) => int.var(),
+            MathFunction::Limit(lim) => lim.var(),
+            MathFunction::Sum(sum) => sum.var(),
+            MathFunction::Product(prod) => prod.var(),
+            MathFunction::Series(series) => series.var(),
+            _ => return None,
+        };
+
+        match var {
+            ExpressionElement::Variable(v) => Some(v),
+            _ => None,
+        }
+    }
+}
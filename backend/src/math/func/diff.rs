@@ -0,0 +1,507 @@
+use crate::{
+    math::{
+        func::{
+            decl::{
+                Abs, Add, Arccos, Arccot, Arccsc, Arcsec, Arcsin, Arctan, BigO, Cos, Cosh, Cot,
+                Coth, Csc, Csch, Derivative, Det, Divide, Eig, Equation, Fraction, Greater,
+                GreaterEqual, IndefiniteIntegral, Integral, Less, LessEqual, Limit, Log,
+                MathFunction, Multiply, NotEqual, PartialDerivative, Power, Product, Rank, Root,
+                Sec, Sech, Series, Sin, Sinh, Subtract, Sum, Tan, Tanh, Trace, Transpose,
+            },
+            exp_log::{log, power, root},
+            linalg::{det, eig, trace, transpose},
+            op::{abs, add, divide, multiply, negate, subtract},
+            series::big_o,
+            trig::{cos, cosh, cot, coth, csc, csch, sec, sech, sin, sinh, tan, tanh},
+            Differentiable, Function,
+        },
+        matrix::Matrix,
+        symbol::{Constant, Number},
+        ExpressionElement, MathElement,
+    },
+    ComplexScalar, DecimalScalar,
+};
+
+impl Differentiable for ExpressionElement {
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        match self {
+            ExpressionElement::Number(_) => Number::Integer(0).into(),
+            ExpressionElement::Function(f) => f.derivative(var),
+            ExpressionElement::Matrix(m) => m.derivative(var),
+            ExpressionElement::Variable(v) => Number::Integer((v == var) as i64).into(),
+        }
+    }
+}
+
+impl Differentiable for Matrix {
+    #[inline]
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        self.map(|e| e.derivative(var)).into()
+    }
+}
+
+macro_rules! impl_function_for_derivative {
+    ($fn_ty: ident) => {
+        impl Function for $fn_ty {
+            fn evaluate(&self) -> MathElement {
+                let ExpressionElement::Variable(var) = self.var() else {
+                    return MathElement::Function(self.clone().into());
+                };
+
+                ExpressionElement::from(self.f().evaluate())
+                    .derivative(var)
+                    .into()
+            }
+
+            #[inline]
+            fn approximate(&self) -> DecimalScalar {
+                ExpressionElement::from(self.evaluate()).approximate()
+            }
+
+            #[inline]
+            fn approximate_complex(&self) -> ComplexScalar {
+                ExpressionElement::from(self.evaluate()).approximate_complex()
+            }
+        }
+
+        impl Differentiable for $fn_ty {
+            fn derivative(&self, var: &str) -> ExpressionElement {
+                let value = ExpressionElement::from(self.evaluate());
+                // Derivatives that can't be found stay as they are.
+                if is_derivative(&value) {
+                    return Derivative::new(ExpressionElement::Variable(var.to_string()), value)
+                        .into();
+                }
+                value.derivative(var)
+            }
+        }
+
+        impl From<$fn_ty> for MathFunction {
+            #[inline]
+            fn from(value: $fn_ty) -> Self {
+                MathFunction::$fn_ty(value)
+            }
+        }
+    };
+}
+
+impl_function_for_derivative!(Derivative);
+impl_function_for_derivative!(PartialDerivative);
+
+#[inline]
+fn is_derivative(elem: &ExpressionElement) -> bool {
+    matches!(
+        elem,
+        ExpressionElement::Function(f)
+            if matches!(**f, MathFunction::Derivative(_) | MathFunction::PartialDerivative(_))
+    )
+}
+
+#[inline]
+fn one() -> ExpressionElement {
+    Number::Integer(1).into()
+}
+
+#[inline]
+fn two() -> ExpressionElement {
+    Number::Integer(2).into()
+}
+
+#[inline]
+fn ln(x: ExpressionElement) -> ExpressionElement {
+    log(Number::Constant(Constant::E).into(), x)
+}
+
+/// Applies the chain rule `\frac{d}{dx}{f(u)}=f'(u)\frac{du}{dx}`.
+#[inline]
+fn chain(outer: ExpressionElement, u: &ExpressionElement, var: &str) -> ExpressionElement {
+    multiply(outer, u.derivative(var))
+}
+
+impl Differentiable for Add {
+    #[inline]
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        add(self.lhs().derivative(var), self.rhs().derivative(var))
+    }
+}
+
+impl Differentiable for Subtract {
+    #[inline]
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        subtract(self.lhs().derivative(var), self.rhs().derivative(var))
+    }
+}
+
+impl Differentiable for Multiply {
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        add(
+            multiply(self.lhs().derivative(var), self.rhs().clone()),
+            multiply(self.lhs().clone(), self.rhs().derivative(var)),
+        )
+    }
+}
+
+/// `(\frac{u}{v})'=\frac{u'v-uv'}{v^2}`
+fn quotient_rule(num: &ExpressionElement, den: &ExpressionElement, var: &str) -> ExpressionElement {
+    let (num_d, den_d) = (num.derivative(var), den.derivative(var));
+    if den_d.is_integer(0) {
+        return divide(num_d, den.clone());
+    }
+
+    divide(
+        subtract(multiply(num_d, den.clone()), multiply(num.clone(), den_d)),
+        power(den.clone(), two()),
+    )
+}
+
+impl Differentiable for Divide {
+    #[inline]
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        quotient_rule(self.lhs(), self.rhs(), var)
+    }
+}
+
+impl Differentiable for Fraction {
+    #[inline]
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        quotient_rule(self.num(), self.den(), var)
+    }
+}
+
+impl Differentiable for Power {
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        let (base, exp) = (self.base(), self.exp());
+        let exp_d = exp.derivative(var);
+
+        // (u^n)'=nu^{n-1}u'
+        if exp_d.is_integer(0) {
+            return chain(
+                multiply(
+                    exp.clone(),
+                    power(base.clone(), subtract(exp.clone(), one())),
+                ),
+                base,
+                var,
+            );
+        }
+
+        // (a^v)'=a^v\ln{a}v'
+        let base_d = base.derivative(var);
+        if base_d.is_integer(0) {
+            return multiply(
+                multiply(power(base.clone(), exp.clone()), ln(base.clone())),
+                exp_d,
+            );
+        }
+
+        // (u^v)'=u^v(v'\ln{u}+\frac{vu'}{u})
+        multiply(
+            power(base.clone(), exp.clone()),
+            add(
+                multiply(exp_d, ln(base.clone())),
+                divide(multiply(exp.clone(), base_d), base.clone()),
+            ),
+        )
+    }
+}
+
+impl Differentiable for Root {
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        // The fields are named the other way around, `rad` is the degree.
+        let (deg, radicand) = (self.rad(), self.deg());
+
+        // (\sqrt[n]{u})'=\frac{u'}{n\sqrt[n]{u^{n-1}}}
+        if deg.derivative(var).is_integer(0) {
+            return divide(
+                radicand.derivative(var),
+                multiply(
+                    deg.clone(),
+                    root(
+                        deg.clone(),
+                        power(radicand.clone(), subtract(deg.clone(), one())),
+                    ),
+                ),
+            );
+        }
+
+        Power::new(radicand.clone(), divide(one(), deg.clone())).derivative(var)
+    }
+}
+
+impl Differentiable for Log {
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        let (base, anti) = (self.base(), self.anti());
+
+        // (\log_{a}{u})'=\frac{u'}{u\ln{a}}
+        if base.derivative(var).is_integer(0) {
+            return divide(
+                anti.derivative(var),
+                multiply(anti.clone(), ln(base.clone())),
+            );
+        }
+
+        Divide::new(ln(anti.clone()), ln(base.clone())).derivative(var)
+    }
+}
+
+macro_rules! impl_chain_rule {
+    ($fn_ty: ident, |$u: ident| $outer: expr) => {
+        impl Differentiable for $fn_ty {
+            fn derivative(&self, var: &str) -> ExpressionElement {
+                let $u = self.x().clone();
+                chain($outer, self.x(), var)
+            }
+        }
+    };
+}
+
+impl_chain_rule!(Sin, |u| cos(u));
+impl_chain_rule!(Cos, |u| negate(sin(u)));
+impl_chain_rule!(Tan, |u| divide(one(), power(cos(u), two())));
+impl_chain_rule!(Cot, |u| negate(divide(one(), power(sin(u), two()))));
+impl_chain_rule!(Sec, |u| multiply(sec(u.clone()), tan(u)));
+impl_chain_rule!(Csc, |u| negate(multiply(csc(u.clone()), cot(u))));
+
+impl_chain_rule!(Arcsin, |u| divide(
+    one(),
+    root(two(), subtract(one(), power(u, two())))
+));
+impl_chain_rule!(Arccos, |u| negate(divide(
+    one(),
+    root(two(), subtract(one(), power(u, two())))
+)));
+impl_chain_rule!(Arctan, |u| divide(one(), add(one(), power(u, two()))));
+impl_chain_rule!(Arccot, |u| negate(divide(
+    one(),
+    add(one(), power(u, two()))
+)));
+// \frac{1}{u^2\sqrt{1-\frac{1}{u^2}}}=\frac{1}{|u|\sqrt{u^2-1}}
+impl_chain_rule!(Arcsec, |u| divide(
+    one(),
+    multiply(
+        power(u.clone(), two()),
+        root(two(), subtract(one(), divide(one(), power(u, two()))))
+    )
+));
+impl_chain_rule!(Arccsc, |u| negate(divide(
+    one(),
+    multiply(
+        power(u.clone(), two()),
+        root(two(), subtract(one(), divide(one(), power(u, two()))))
+    )
+)));
+
+impl_chain_rule!(Sinh, |u| cosh(u));
+impl_chain_rule!(Cosh, |u| sinh(u));
+impl_chain_rule!(Tanh, |u| divide(one(), power(cosh(u), two())));
+impl_chain_rule!(Coth, |u| negate(divide(one(), power(sinh(u), two()))));
+impl_chain_rule!(Sech, |u| negate(multiply(sech(u.clone()), tanh(u))));
+impl_chain_rule!(Csch, |u| negate(multiply(csch(u.clone()), coth(u))));
+
+// \frac{d}{dx}{|u|}=\frac{u}{|u|}\frac{du}{dx}
+impl_chain_rule!(Abs, |u| divide(u.clone(), abs(u)));
+
+/// Both sides of equations and inequalities are differentiated on their own.
+macro_rules! impl_relation_derivative {
+    ($($ty: ident),*) => {
+        $(
+            impl Differentiable for $ty {
+                fn derivative(&self, var: &str) -> ExpressionElement {
+                    $ty::new(self.lhs().derivative(var), self.rhs().derivative(var)).into()
+                }
+            }
+        )*
+    };
+}
+
+impl_relation_derivative!(Equation, Less, Greater, LessEqual, GreaterEqual, NotEqual);
+
+impl Differentiable for Transpose {
+    #[inline]
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        transpose(self.x().derivative(var))
+    }
+}
+
+impl Differentiable for Trace {
+    #[inline]
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        trace(self.x().derivative(var))
+    }
+}
+
+impl Differentiable for Rank {
+    /// Ranks are integers, so they are constant wherever they are differentiable.
+    #[inline]
+    fn derivative(&self, _var: &str) -> ExpressionElement {
+        Number::Integer(0).into()
+    }
+}
+
+impl Differentiable for Det {
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        let value = det(self.x().clone());
+        if matches!(&value, ExpressionElement::Function(f) if matches!(**f, MathFunction::Det(_))) {
+            return Derivative::new(ExpressionElement::Variable(var.to_string()), value).into();
+        }
+        value.derivative(var)
+    }
+}
+
+impl Differentiable for Eig {
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        let value = eig(self.x().clone());
+        if matches!(&value, ExpressionElement::Function(f) if matches!(**f, MathFunction::Eig(_))) {
+            return Derivative::new(ExpressionElement::Variable(var.to_string()), value).into();
+        }
+        value.derivative(var)
+    }
+}
+
+impl Differentiable for Integral {
+    /// Leibniz's rule, where the variable of integration is bound inside the integrand.
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        let Some(x) = self.var_name() else {
+            return Derivative::new(
+                ExpressionElement::Variable(var.to_string()),
+                self.clone().into(),
+            )
+            .into();
+        };
+
+        let at = |bound: &ExpressionElement| self.f().substitute(x, bound);
+        let bounds = subtract(
+            multiply(at(self.upper()), self.upper().derivative(var)),
+            multiply(at(self.lower()), self.lower().derivative(var)),
+        );
+
+        let f_d = self.f().derivative(var);
+        if x == var || f_d.is_integer(0) {
+            return bounds;
+        }
+
+        add(
+            bounds,
+            Integral::new(
+                self.var().clone(),
+                self.lower().clone(),
+                self.upper().clone(),
+                f_d,
+            )
+            .into(),
+        )
+    }
+}
+
+impl Differentiable for IndefiniteIntegral {
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        if self.var_name() == Some(var) {
+            return self.f().clone();
+        }
+
+        IndefiniteIntegral::new(self.var().clone(), self.f().derivative(var)).into()
+    }
+}
+
+impl Differentiable for Limit {
+    /// Limits are differentiated once they are found, the others are only known to be constant
+    /// if they don't depend on `var`.
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        let lim = ExpressionElement::from(self.evaluate());
+        match &lim {
+            ExpressionElement::Function(f) if matches!(**f, MathFunction::Limit(_)) => {
+                if lim.depends_on(var) {
+                    Derivative::new(ExpressionElement::Variable(var.to_string()), lim).into()
+                } else {
+                    Number::Integer(0).into()
+                }
+            }
+            _ => lim.derivative(var),
+        }
+    }
+}
+
+impl Differentiable for Series {
+    /// Series are differentiated term by term once they are found.
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        let s = ExpressionElement::from(self.evaluate());
+        match &s {
+            ExpressionElement::Function(f) if matches!(**f, MathFunction::Series(_)) => {
+                if s.depends_on(var) {
+                    Derivative::new(ExpressionElement::Variable(var.to_string()), s).into()
+                } else {
+                    Number::Integer(0).into()
+                }
+            }
+            _ => s.derivative(var),
+        }
+    }
+}
+
+impl Differentiable for BigO {
+    /// `O(x^{n})'=O(x^{n-1})`
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        if self.x().depends_on(var) {
+            big_o(self.x().derivative(var))
+        } else {
+            Number::Integer(0).into()
+        }
+    }
+}
+
+impl Differentiable for Sum {
+    /// Sums that can't be found are differentiated term by term, unless their bounds depend on
+    /// `var`.
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        let sum = ExpressionElement::from(self.evaluate());
+        let ExpressionElement::Function(f) = &sum else {
+            return sum.derivative(var);
+        };
+        let MathFunction::Sum(s) = f.as_ref() else {
+            return sum.derivative(var);
+        };
+
+        if !sum.depends_on(var) {
+            Number::Integer(0).into()
+        } else if s.lower().depends_on(var) || s.upper().depends_on(var) {
+            Derivative::new(ExpressionElement::Variable(var.to_string()), sum).into()
+        } else {
+            Sum::new(
+                s.var().clone(),
+                s.lower().clone(),
+                s.upper().clone(),
+                s.f().derivative(var),
+            )
+            .into()
+        }
+    }
+}
+
+impl Differentiable for Product {
+    /// Products that can't be found follow the product rule, unless their bounds depend on
+    /// `var`.
+    fn derivative(&self, var: &str) -> ExpressionElement {
+        let prod = ExpressionElement::from(self.evaluate());
+        let ExpressionElement::Function(f) = &prod else {
+            return prod.derivative(var);
+        };
+        let MathFunction::Product(p) = f.as_ref() else {
+            return prod.derivative(var);
+        };
+
+        if !prod.depends_on(var) {
+            Number::Integer(0).into()
+        } else if p.lower().depends_on(var) || p.upper().depends_on(var) {
+            Derivative::new(ExpressionElement::Variable(var.to_string()), prod).into()
+        } else {
+            // (\prod f)'=\prod f \sum rac{f'}{f}
+            let terms = Sum::new(
+                p.var().clone(),
+                p.lower().clone(),
+                p.upper().clone(),
+                divide(p.f().derivative(var), p.f().clone()),
+            );
+            multiply(prod.clone(), terms.into())
+        }
+    }
+}
@@ -1,45 +1,461 @@
 use crate::{
     math::{
         func::{
-            decl::{Log, Power, Root},
-            Function, Operator,
+            decl::{Log, MathFunction, Multiply, Power, Root},
+            op::{self, as_gaussian, decimal, from_gaussian, split_coefficient},
+            real_or_complex, trig, Function, Operator,
         },
-        MathElement,
+        symbol::{Constant, Number, Rational},
+        ExpressionElement, MathElement,
     },
-    DecimalScalar,
+    ComplexScalar, DecimalScalar, IntegerScalar,
 };
 
+#[cfg(feature = "bignum")]
+use crate::{math::precise, BigDecimalScalar};
+
 impl Function for Root {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        root(self.rad().evaluate().into(), self.deg().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.deg().approximate().powf(1. / self.rad().approximate())
     }
+
+    fn approximate_complex(&self) -> ComplexScalar {
+        let (deg, radicand) = (
+            self.rad().approximate_complex(),
+            self.deg().approximate_complex(),
+        );
+
+        // Odd roots of negative numbers are real.
+        if deg.im == 0. && radicand.im == 0. && radicand.re < 0. && deg.re % 2. == 1. {
+            return (-(-radicand.re).powf(1. / deg.re)).into();
+        }
+
+        real_or_complex(
+            radicand,
+            |r| {
+                if deg.im == 0. {
+                    r.powf(1. / deg.re)
+                } else {
+                    DecimalScalar::NAN
+                }
+            },
+            |r| {
+                if deg == 2.0.into() {
+                    r.sqrt()
+                } else {
+                    r.powc(deg.inv())
+                }
+            },
+        )
+    }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        precise::root(
+            &self.deg().approximate_precise(precision)?,
+            &self.rad().approximate_precise(precision)?,
+            precision,
+        )
+    }
 }
 
 impl Operator for Power {}
 
 impl Function for Power {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        power(self.base().evaluate().into(), self.exp().evaluate().into()).into()
     }
 
-    #[inline]
     fn approximate(&self) -> DecimalScalar {
-        self.base().approximate().powf(self.exp().approximate())
+        let base = self.base().approximate();
+        real_power_of_negative(base, self.exp())
+            .unwrap_or_else(|| base.powf(self.exp().approximate()))
+    }
+
+    fn approximate_complex(&self) -> ComplexScalar {
+        let (base, exp) = (
+            self.base().approximate_complex(),
+            self.exp().approximate_complex(),
+        );
+        if base.im == 0. {
+            if let Some(pow) = real_power_of_negative(base.re, self.exp()) {
+                return pow.into();
+            }
+        }
+        real_or_complex(
+            base,
+            |b| {
+                if exp.im == 0. {
+                    b.powf(exp.re)
+                } else {
+                    DecimalScalar::NAN
+                }
+            },
+            |b| b.powc(exp),
+        )
+    }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        precise::pow(
+            &self.base().approximate_precise(precision)?,
+            &self.exp().approximate_precise(precision)?,
+            precision,
+        )
     }
 }
 
 impl Function for Log {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        log(self.base().evaluate().into(), self.anti().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.anti().approximate().log(self.base().approximate())
     }
+
+    fn approximate_complex(&self) -> ComplexScalar {
+        let (base, anti) = (
+            self.base().approximate_complex(),
+            self.anti().approximate_complex(),
+        );
+        real_or_complex(
+            anti,
+            |a| {
+                if base.im == 0. {
+                    a.log(base.re)
+                } else {
+                    DecimalScalar::NAN
+                }
+            },
+            |a| a.ln() / base.ln(),
+        )
+    }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        let working = precision + precise::GUARD_DIGITS;
+        precise::divide(
+            &precise::ln(&self.anti().approximate_precise(working)?, working)?,
+            &precise::ln(&self.base().approximate_precise(working)?, working)?,
+            precision,
+        )
+    }
+}
+
+/// `a^{\frac{p}{q}}` for negative `a` and odd `q`, which is real. It's the branch exact
+/// evaluation takes too, like `(-8)^{\frac{1}{3}}=-2`.
+fn real_power_of_negative(base: DecimalScalar, exp: &ExpressionElement) -> Option<DecimalScalar> {
+    if base >= 0. || base.is_nan() || exp.is_symbolic() {
+        return None;
+    }
+    let exp = ExpressionElement::from(exp.evaluate()).as_rational()?;
+    if exp.is_integer() || exp.den() % 2 == 0 {
+        return None;
+    }
+
+    let pow = (-base).powf(exp.num() as DecimalScalar / exp.den() as DecimalScalar);
+    Some(if exp.num() % 2 == 0 { pow } else { -pow })
+}
+
+/// `x` if `elem` is `xi`, like `\pi` for `i\pi`.
+fn divide_by_i(elem: &ExpressionElement) -> Option<ExpressionElement> {
+    let (coeff, rest) = split_coefficient(elem);
+    let rest = rest?;
+    if rest.is_imaginary_unit() {
+        return Some(Number::from(coeff).into());
+    }
+
+    let ExpressionElement::Function(f) = rest else {
+        return None;
+    };
+    let MathFunction::Multiply(mul) = f.as_ref() else {
+        return None;
+    };
+    let x = match (mul.lhs().is_imaginary_unit(), mul.rhs().is_imaginary_unit()) {
+        (true, false) => mul.rhs(),
+        (false, true) => mul.lhs(),
+        _ => return None,
+    };
+    Some(op::join_coefficient(coeff, Some(x.clone())))
+}
+
+/// `(a+bi)^n` by squaring, where negative powers are powers of `\frac{a-bi}{a^2+b^2}`.
+fn gaussian_pow(
+    (mut re, mut im): (Rational, Rational),
+    n: IntegerScalar,
+) -> Option<(Rational, Rational)> {
+    if n < 0 {
+        let norm = re.checked_mul(re)?.checked_add(im.checked_mul(im)?)?;
+        (re, im) = (re.checked_div(norm)?, im.checked_neg()?.checked_div(norm)?);
+    }
+
+    let mul = |(a, b): (Rational, Rational), (c, d): (Rational, Rational)| {
+        Some((
+            a.checked_mul(c)?.checked_sub(b.checked_mul(d)?)?,
+            a.checked_mul(d)?.checked_add(b.checked_mul(c)?)?,
+        ))
+    };
+    let (mut pow, mut square, mut n) =
+        ((Rational::ONE, Rational::ZERO), (re, im), n.checked_abs()?);
+    while n > 0 {
+        if n % 2 == 1 {
+            pow = mul(pow, square)?;
+        }
+        n /= 2;
+        if n > 0 {
+            square = mul(square, square)?;
+        }
+    }
+    Some(pow)
+}
+
+/// Splits `n` into `a` and `b`, where `n=a^{deg}b` and `a` is as large as possible.
+fn extract_power(mut n: IntegerScalar, deg: u32) -> (IntegerScalar, IntegerScalar) {
+    // Trial division is way too slow for large primes, just give up after this.
+    const MAX_FACTOR: IntegerScalar = 1 << 16;
+
+    let mut outside = 1;
+    let mut factor = 2 as IntegerScalar;
+
+    while factor <= MAX_FACTOR {
+        let Some(p) = factor.checked_pow(deg) else {
+            break;
+        };
+        if p > n {
+            break;
+        }
+
+        while n % p == 0 {
+            n /= p;
+            outside *= factor;
+        }
+        factor += 1;
+    }
+
+    // Check if the remaining part is a perfect power itself.
+    let guess = (n as DecimalScalar).powf(1. / deg as DecimalScalar).round() as IntegerScalar;
+    if guess > 1 && guess.checked_pow(deg) == Some(n) {
+        return (outside * guess, 1);
+    }
+
+    (outside, n)
+}
+
+pub(crate) fn power(base: ExpressionElement, exp: ExpressionElement) -> ExpressionElement {
+    if let ExpressionElement::Matrix(m) = &base {
+        // A^{-n}=(A^{-1})^n
+        let pow = exp.as_rational().filter(|e| e.is_integer()).and_then(|e| {
+            match u32::try_from(e.num()) {
+                Ok(n) => m.pow(n),
+                Err(_) => m
+                    .inverse()?
+                    .pow(u32::try_from(e.num().checked_neg()?).ok()?),
+            }
+        });
+        return match pow {
+            Some(pow) => pow.into(),
+            None => Power::new(base, exp).into(),
+        };
+    }
+    if base.is_decimal() || exp.is_decimal() {
+        return decimal(Power::new(base, exp).into());
+    }
+
+    // Integer powers of integers and rationals that don't fit into the scalars.
+    #[cfg(feature = "bignum")]
+    if let (ExpressionElement::Number(b), ExpressionElement::Number(e)) = (&base, &exp) {
+        if (precise::is_big(b) || precise::is_big(e))
+            && precise::as_big_rational(b).is_some()
+            && precise::as_big_rational(e).is_some_and(|e| e.is_integer())
+        {
+            return b.clone().pow(e.clone()).into();
+        }
+    }
+
+    if exp.is_integer(0) || base.is_integer(1) {
+        return Number::Integer(1).into();
+    }
+    if exp.is_integer(1) {
+        return base;
+    }
+
+    // i^2=-1
+    if let (true, Some(exp)) = (base.is_imaginary_unit(), exp.as_rational()) {
+        if exp.is_integer() {
+            return match exp.num().rem_euclid(4) {
+                0 => Number::Integer(1).into(),
+                1 => base,
+                2 => Number::Integer(-1).into(),
+                _ => op::negate(base),
+            };
+        }
+    }
+
+    // (1+i)^2=2i
+    if let (Some(base), Some(exp)) = (as_gaussian(&base), exp.as_rational()) {
+        if base.1 != Rational::ZERO && exp.is_integer() {
+            if let Some((re, im)) = gaussian_pow(base, exp.num()) {
+                return from_gaussian(re, im);
+            }
+        }
+    }
+
+    // e^{ix}=\cos{x}+i\sin{x} if both are known, like e^{i\pi}=-1
+    if let (ExpressionElement::Number(Number::Constant(Constant::E)), Some(x)) =
+        (&base, divide_by_i(&exp))
+    {
+        if let Some((cos, sin)) = trig::exact_cos_sin(&x) {
+            return op::add(cos, op::multiply(sin, Number::Constant(Constant::I).into()));
+        }
+    }
+
+    // Division by zero, leave it as is.
+    if base.is_integer(0) && exp.as_rational().is_some_and(|e| e.is_negative()) {
+        return Power::new(base, exp).into();
+    }
+
+    if let (Some(base), Some(exp)) = (base.as_rational(), exp.as_rational()) {
+        if base == Rational::ZERO {
+            return Number::Integer(0).into();
+        }
+
+        // a^{p/q}=\sqrt[q]{a^p}
+        return match base.checked_pow(exp.num()) {
+            Some(pow) if exp.is_integer() => Number::from(pow).into(),
+            Some(pow) => root(Number::Integer(exp.den()).into(), Number::from(pow).into()),
+            // Integers that don't fit are still exact with `bignum`.
+            None if exp.is_integer() => Number::from(base).pow(Number::from(exp)).into(),
+            None => decimal(Power::new(Number::from(base).into(), Number::from(exp).into()).into()),
+        };
+    }
+
+    // (\sqrt[n]{a})^{kn}=a^k
+    if let (ExpressionElement::Function(f), Some(exp)) = (&base, exp.as_rational()) {
+        if let MathFunction::Root(root) = f.as_ref() {
+            if let Some(deg) = root.rad().as_rational() {
+                if exp.is_integer() && deg.is_integer() && exp.num() % deg.num() == 0 {
+                    return power(
+                        root.deg().clone(),
+                        Number::Integer(exp.num() / deg.num()).into(),
+                    );
+                }
+            }
+        }
+    }
+
+    Power::new(base, exp).into()
+}
+
+pub(crate) fn root(deg: ExpressionElement, radicand: ExpressionElement) -> ExpressionElement {
+    if deg.is_decimal() || radicand.is_decimal() {
+        return decimal(Root::new(deg, radicand).into());
+    }
+
+    let (Some(deg_num), Some(radicand_num)) = (deg.as_rational(), radicand.as_rational()) else {
+        return Root::new(deg, radicand).into();
+    };
+
+    let Ok(exp) = u32::try_from(deg_num.num()) else {
+        return Root::new(deg, radicand).into();
+    };
+    // \sqrt{-a}=\sqrt{a}i
+    if exp == 2 && deg_num.is_integer() && radicand_num.is_negative() {
+        if let Some(abs) = radicand_num.checked_neg() {
+            return op::multiply(
+                root(deg, Number::from(abs).into()),
+                Number::Constant(Constant::I).into(),
+            );
+        }
+    }
+    if !deg_num.is_integer() || exp == 0 || (radicand_num.is_negative() && exp % 2 == 0) {
+        return Root::new(deg, radicand).into();
+    }
+
+    // \sqrt[n]{\frac{a}{b}}=\frac{\sqrt[n]{ab^{n-1}}}{b}
+    let Some(inside) = radicand_num
+        .den()
+        .checked_pow(exp - 1)
+        .and_then(|d| d.checked_mul(radicand_num.num().checked_abs()?))
+    else {
+        return Root::new(deg, radicand).into();
+    };
+
+    let (outside, inside) = extract_power(inside, exp);
+    let rest = (inside != 1).then(|| Root::new(deg, Number::Integer(inside).into()).into());
+
+    match Rational::new(outside * radicand_num.num().signum(), radicand_num.den()) {
+        Some(coeff) => op::join_coefficient(coeff, rest),
+        None => decimal(Root::new(Number::Integer(exp as IntegerScalar).into(), radicand).into()),
+    }
+}
+
+pub(crate) fn log(base: ExpressionElement, anti: ExpressionElement) -> ExpressionElement {
+    if base.is_decimal() || anti.is_decimal() {
+        return decimal(Log::new(base, anti).into());
+    }
+
+    if anti.is_integer(1) {
+        return Number::Integer(0).into();
+    }
+    if anti == base {
+        return Number::Integer(1).into();
+    }
+
+    // \ln{-a}=\ln{a}+i\pi
+    if let (ExpressionElement::Number(Number::Constant(Constant::E)), Some(anti)) =
+        (&base, anti.as_rational())
+    {
+        if let (true, Some(abs)) = (anti.is_negative(), anti.checked_neg()) {
+            return op::add(
+                log(base, Number::from(abs).into()),
+                Multiply::new(
+                    Number::Constant(Constant::I).into(),
+                    Number::Constant(Constant::Pi).into(),
+                )
+                .into(),
+            );
+        }
+    }
+
+    if let (Some(base), Some(anti)) = (base.as_rational(), anti.as_rational()) {
+        // \log_{b}{b^k}=k, \log_{b}{\frac{1}{b^k}}=-k
+        let (mut n, sign) = match (anti.num(), anti.den()) {
+            (n, 1) => (n, 1),
+            (1, d) => (d, -1),
+            _ => (0, 0),
+        };
+        let base = if base.is_integer() { base.num() } else { 0 };
+
+        if base > 1 && n > 1 {
+            let mut k = 0;
+            while n % base == 0 {
+                n /= base;
+                k += 1;
+            }
+            if n == 1 {
+                return Number::Integer(sign * k).into();
+            }
+        }
+    }
+
+    if let ExpressionElement::Function(f) = &anti {
+        match f.as_ref() {
+            // \log_{b}{b^x}=x
+            MathFunction::Power(pow) if pow.base() == &base => return pow.exp().clone(),
+            // \log_{b}{\sqrt[n]{b}}=\frac{1}{n}
+            MathFunction::Root(root) if root.deg() == &base => {
+                return op::divide(Number::Integer(1).into(), root.rad().clone())
+            }
+            _ => {}
+        }
+    }
+
+    Log::new(base, anti).into()
 }
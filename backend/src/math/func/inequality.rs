@@ -0,0 +1,286 @@
+//! Inequalities in one unknown, solved to unions of intervals for polynomials and rational
+//! functions.
+
+use crate::{
+    latex::{
+        CUP, EMPTY_SET, IN, INFTY, PARENTHESES_L, PARENTHESES_R, REALS, SQUARE_BRACKET_L,
+        SQUARE_BRACKET_R, SUBTRACT,
+    },
+    math::{
+        func::{
+            decl::{Greater, GreaterEqual, Less, LessEqual, MathFunction, NotEqual},
+            op::subtract,
+            solve::{polynomial_roots, sorted, TOLERANCE},
+            Function,
+        },
+        poly::rational_function,
+        symbol::Number,
+        ExpressionElement, IntoRawExpr, MathElement,
+    },
+    DecimalScalar,
+};
+
+macro_rules! impl_relation {
+    ($($ty: ident),*) => {
+        $(
+            impl Function for $ty {
+                /// Both sides are evaluated on their own.
+                fn evaluate(&self) -> MathElement {
+                    MathElement::Function(MathFunction::$ty($ty::new(
+                        self.lhs().evaluate().into(),
+                        self.rhs().evaluate().into(),
+                    )))
+                }
+
+                /// Inequalities have no value.
+                #[inline]
+                fn approximate(&self) -> DecimalScalar {
+                    DecimalScalar::NAN
+                }
+            }
+        )*
+    };
+}
+
+impl_relation!(Less, Greater, LessEqual, GreaterEqual, NotEqual);
+
+/// One end of an [`Interval`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Endpoint {
+    /// `-\infty` at the start and `\infty` at the end.
+    Unbounded,
+    Open(ExpressionElement),
+    Closed(ExpressionElement),
+}
+
+/// A real interval, which is a single point if both ends are the same closed endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interval {
+    pub start: Endpoint,
+    pub end: Endpoint,
+}
+
+/// A union of disjoint intervals in ascending order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    #[inline]
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Checks if the set is every real number.
+    #[inline]
+    pub fn is_all(&self) -> bool {
+        matches!(
+            self.intervals.as_slice(),
+            [Interval {
+                start: Endpoint::Unbounded,
+                end: Endpoint::Unbounded
+            }]
+        )
+    }
+
+    /// Writes the set as the solutions of `var`, like `x \in (-\infty, 2) \cup [3, \infty)`.
+    pub fn assemble_for(&self, var: &str) -> String {
+        format!("{} {} {}", var, IN, self.assemble())
+    }
+}
+
+impl IntoRawExpr for IntervalSet {
+    fn assemble(&self) -> String {
+        if self.is_empty() {
+            return EMPTY_SET.to_string();
+        }
+        if self.is_all() {
+            return REALS.to_string();
+        }
+
+        self.intervals
+            .iter()
+            .map(|interval| match (&interval.start, &interval.end) {
+                (Endpoint::Closed(a), Endpoint::Closed(b)) if a == b => {
+                    format!("\\{{{}\\}}", a.assemble())
+                }
+                (start, end) => {
+                    let start = match start {
+                        Endpoint::Unbounded => format!("{}{}{}", PARENTHESES_L, SUBTRACT, INFTY),
+                        Endpoint::Open(a) => format!("{}{}", PARENTHESES_L, a.assemble()),
+                        Endpoint::Closed(a) => format!("{}{}", SQUARE_BRACKET_L, a.assemble()),
+                    };
+                    let end = match end {
+                        Endpoint::Unbounded => format!("{}{}", INFTY, PARENTHESES_R),
+                        Endpoint::Open(b) => format!("{}{}", b.assemble(), PARENTHESES_R),
+                        Endpoint::Closed(b) => format!("{}{}", b.assemble(), SQUARE_BRACKET_R),
+                    };
+                    format!("{}, {}", start, end)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(&format!(" {} ", CUP))
+    }
+}
+
+/// How the left hand side of an inequality compares to 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Relation {
+    Negative,
+    NonPositive,
+    Positive,
+    NonNegative,
+    NonZero,
+}
+
+impl Relation {
+    #[inline]
+    fn holds(self, value: DecimalScalar) -> bool {
+        match self {
+            Relation::Negative => value < 0.,
+            Relation::NonPositive => value <= 0.,
+            Relation::Positive => value > 0.,
+            Relation::NonNegative => value >= 0.,
+            Relation::NonZero => value != 0.,
+        }
+    }
+}
+
+/// Solves the inequality `inequality` for `var`.
+///
+/// Both sides have to be polynomials or rational functions of `var` with rational coefficients.
+/// The critical points are found like in [`super::solve::solve`] and are exact as long as the
+/// degrees are at most 4, and the sign in between them is checked numerically. `None` if
+/// `inequality` isn't an inequality or can't be solved.
+pub fn solve_inequality(inequality: &ExpressionElement, var: &str) -> Option<IntervalSet> {
+    let ExpressionElement::Function(f) = inequality else {
+        return None;
+    };
+    let (lhs, rhs, relation) = match f.as_ref() {
+        MathFunction::Less(op) => (op.lhs(), op.rhs(), Relation::Negative),
+        MathFunction::LessEqual(op) => (op.lhs(), op.rhs(), Relation::NonPositive),
+        MathFunction::Greater(op) => (op.lhs(), op.rhs(), Relation::Positive),
+        MathFunction::GreaterEqual(op) => (op.lhs(), op.rhs(), Relation::NonNegative),
+        MathFunction::NotEqual(op) => (op.lhs(), op.rhs(), Relation::NonZero),
+        _ => return None,
+    };
+    let f = subtract(lhs.evaluate().into(), rhs.evaluate().into());
+    let (num, den) = rational_function(&f, var)?;
+
+    // Roots of the numerator can be solutions, but the function is undefined at the ones of
+    // the denominator.
+    let zeros = polynomial_roots(&num, var)?;
+    let poles = polynomial_roots(&den, var)?;
+    let contains = |roots: &[ExpressionElement], x: &ExpressionElement| {
+        let x = x.approximate();
+        roots
+            .iter()
+            .any(|r| (r.approximate() - x).abs() <= TOLERANCE * (1. + x.abs()))
+    };
+    let critical = sorted(zeros.iter().chain(&poles).cloned().collect());
+
+    let at = |x: DecimalScalar| f.substitute(var, &Number::Decimal(x).into()).approximate();
+    let includes_point = |x: &ExpressionElement| {
+        !contains(&poles, x) && (num.is_zero() || contains(&zeros, x)) && relation.holds(0.)
+    };
+    // The open intervals between the critical points.
+    let includes_between = |i: usize| {
+        if num.is_zero() {
+            return relation.holds(0.);
+        }
+
+        let x = match (i.checked_sub(1).map(|i| &critical[i]), critical.get(i)) {
+            (None, None) => 0.,
+            (None, Some(b)) => b.approximate() - 1.,
+            (Some(a), None) => a.approximate() + 1.,
+            (Some(a), Some(b)) => (a.approximate() + b.approximate()) / 2.,
+        };
+        relation.holds(at(x))
+    };
+
+    let mut intervals = Vec::new();
+    let mut start = includes_between(0).then_some(Endpoint::Unbounded);
+    for (i, x) in critical.iter().enumerate() {
+        let point = includes_point(x);
+        let after = includes_between(i + 1);
+
+        start = match start {
+            Some(start) if !point => {
+                intervals.push(Interval {
+                    start,
+                    end: Endpoint::Open(x.clone()),
+                });
+                after.then(|| Endpoint::Open(x.clone()))
+            }
+            Some(start) if !after => {
+                intervals.push(Interval {
+                    start,
+                    end: Endpoint::Closed(x.clone()),
+                });
+                None
+            }
+            Some(start) => Some(start),
+            None if point && !after => {
+                intervals.push(Interval {
+                    start: Endpoint::Closed(x.clone()),
+                    end: Endpoint::Closed(x.clone()),
+                });
+                None
+            }
+            None if point => Some(Endpoint::Closed(x.clone())),
+            None => after.then(|| Endpoint::Open(x.clone())),
+        };
+    }
+    if let Some(start) = start {
+        intervals.push(Interval {
+            start,
+            end: Endpoint::Unbounded,
+        });
+    }
+
+    Some(IntervalSet { intervals })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::math::{expr::ExpresssionTree, FromRawExpr, IntoRawExpr, ParsingScope};
+
+    #[test]
+    fn test_inequalities() {
+        let scope = ParsingScope::new(None).with_symbol("x");
+        let parse = |expr| ExpresssionTree::parse_scoped(expr, &scope).unwrap();
+        let solve = |expr| parse(expr).solve_inequality("x").unwrap().assemble_for("x");
+
+        assert_eq!(parse(r#"x\le -1"#).assemble(), r#"x\le 0-1"#);
+        assert_eq!(parse(r#"2x\neq 1"#).assemble(), r#"2*x\neq 1"#);
+
+        assert_eq!(
+            solve(r#"x^2-5x+6\ge 0"#),
+            r#"x \in (-\infty, 2] \cup [3, \infty)"#
+        );
+        assert_eq!(solve(r#"x^2-5x+6<0"#), r#"x \in (2, 3)"#);
+        assert_eq!(solve(r#"2x+1>x-3"#), r#"x \in (-4, \infty)"#);
+        assert_eq!(solve(r#"-x\geq 2"#), r#"x \in (-\infty, -2]"#);
+        assert_eq!(solve(r#"x^2<2"#), r#"x \in (-\sqrt{2}, \sqrt{2})"#);
+        assert_eq!(solve(r#"\frac{x-3}{x+1}\le 0"#), r#"x \in (-1, 3]"#);
+        assert_eq!(
+            solve(r#"\frac{1}{x}\neq 1"#),
+            r#"x \in (-\infty, 0) \cup (0, 1) \cup (1, \infty)"#
+        );
+        assert_eq!(solve(r#"(x-1)^2\le 0"#), r#"x \in \{1\}"#);
+        assert_eq!(
+            solve(r#"(x-1)^2>0"#),
+            r#"x \in (-\infty, 1) \cup (1, \infty)"#
+        );
+        assert_eq!(solve(r#"x^2+1<0"#), r#"x \in \emptyset"#);
+        assert_eq!(solve(r#"x^2+1>0"#), r#"x \in \mathbb{R}"#);
+        assert_eq!(solve(r#"x+1\le x+1"#), r#"x \in \mathbb{R}"#);
+        assert!(parse(r#"\sin{x}>0"#).solve_inequality("x").is_none());
+    }
+}
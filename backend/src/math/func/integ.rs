@@ -0,0 +1,973 @@
+//! Integrals, found symbolically with a table of rules where possible and approximated with
+//! adaptive Gauss-Kronrod quadrature otherwise.
+
+use crate::{
+    math::{
+        func::{
+            decl::{IndefiniteIntegral, Integral, MathFunction},
+            exp_log::{log, power, root},
+            op::{abs, add, divide, multiply, negate, split_coefficient, subtract},
+            trig::{arctan, cos, cosh, cot, coth, csc, sec, sin, sinh, tan, tanh},
+            Differentiable, Function,
+        },
+        poly::{rational_function, Polynomial},
+        symbol::{Constant, Number, Rational},
+        ExpressionElement, MathElement,
+    },
+    ComplexScalar, DecimalScalar, IntegerScalar,
+};
+
+/// Integrals are done once the estimated error is below this, relative to the value when it's
+/// larger than 1.
+pub const TOLERANCE: DecimalScalar = 1e-10;
+/// Results with a larger relative error are considered divergent.
+pub const MAX_ERROR: DecimalScalar = 1e-6;
+/// The maximum number of subintervals, so integrals that never converge still stop. Running out
+/// of them usually means there's a singularity, like at `\frac{\pi}{2}` in `\tan{x}`.
+pub const MAX_SUBINTERVALS: usize = 2000;
+/// The number of pieces antiderivatives are checked on before they're used for definite
+/// integrals.
+const CONTINUITY_PIECES: usize = 64;
+
+/// The non-negative nodes of the 15-point Kronrod rule on `[-1, 1]`, the odd ones are shared
+/// with the 7-point Gauss rule.
+#[allow(clippy::excessive_precision)]
+const KRONROD_NODES: [DecimalScalar; 8] = [
+    0.991455371120812639206854697526329,
+    0.949107912342758524526189684047851,
+    0.864864423359769072789712788640926,
+    0.741531185599394439863864773280788,
+    0.586087235467691130294144845693013,
+    0.405845151377397166906606412076961,
+    0.207784955007898467600689403773245,
+    0.,
+];
+#[allow(clippy::excessive_precision)]
+const KRONROD_WEIGHTS: [DecimalScalar; 8] = [
+    0.022935322010529224963732008058970,
+    0.063092092629978553290700663189204,
+    0.104790010322250183839876322541518,
+    0.140653259715525918745189590510238,
+    0.169004726639267902826583426598550,
+    0.190350578064785409913256402421014,
+    0.204432940075298892414161999234649,
+    0.209482141084727828012999174891714,
+];
+#[allow(clippy::excessive_precision)]
+const GAUSS_WEIGHTS: [DecimalScalar; 4] = [
+    0.129484966168869693270611432679082,
+    0.279705391489276667901467771423780,
+    0.381830050505118944950369775488975,
+    0.417959183673469387755102040816327,
+];
+
+/// The value of a numeric integral.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quadrature {
+    pub value: DecimalScalar,
+    /// The estimated absolute error.
+    pub error: DecimalScalar,
+}
+
+impl Quadrature {
+    pub const NAN: Self = Self {
+        value: DecimalScalar::NAN,
+        error: DecimalScalar::NAN,
+    };
+
+    /// Checks if the error is small enough for the value to be trusted.
+    #[inline]
+    pub fn converged(&self) -> bool {
+        self.value.is_finite() && self.error <= MAX_ERROR * self.value.abs().max(1.)
+    }
+}
+
+/// Integrates `f` over `[a, b]`, where the bounds can be infinite.
+pub fn integrate(
+    f: impl Fn(DecimalScalar) -> DecimalScalar,
+    a: DecimalScalar,
+    b: DecimalScalar,
+) -> Quadrature {
+    if a.is_nan() || b.is_nan() {
+        return Quadrature::NAN;
+    }
+    if a == b {
+        return Quadrature {
+            value: 0.,
+            error: 0.,
+        };
+    }
+    if a > b {
+        let q = integrate(f, b, a);
+        return Quadrature {
+            value: -q.value,
+            error: q.error,
+        };
+    }
+
+    // Infinite intervals are mapped onto finite ones, the quadrature nodes never touch the
+    // ends, where the substitutions blow up.
+    match (a.is_infinite(), b.is_infinite()) {
+        (false, false) => adaptive(&f, a, b),
+        // x=a+\frac{t}{1-t}
+        (false, true) => adaptive(&|t| f(a + t / (1. - t)) / ((1. - t) * (1. - t)), 0., 1.),
+        // x=b-\frac{1-t}{t}
+        (true, false) => adaptive(&|t| f(b - (1. - t) / t) / (t * t), 0., 1.),
+        // x=\frac{t}{1-t^2}
+        (true, true) => adaptive(
+            &|t| {
+                let d = 1. - t * t;
+                f(t / d) * (1. + t * t) / (d * d)
+            },
+            -1.,
+            1.,
+        ),
+    }
+}
+
+/// Keeps splitting the subinterval with the largest error until the total error is small
+/// enough, `NaN` if it never gets there.
+fn adaptive(
+    f: &dyn Fn(DecimalScalar) -> DecimalScalar,
+    a: DecimalScalar,
+    b: DecimalScalar,
+) -> Quadrature {
+    let mut intervals = vec![(a, b, gauss_kronrod(f, a, b))];
+
+    loop {
+        let (value, error) = intervals
+            .iter()
+            .fold((0., 0.), |(v, e), (_, _, q)| (v + q.value, e + q.error));
+        let total = Quadrature { value, error };
+
+        if !value.is_finite() || error <= TOLERANCE * value.abs().max(1.) {
+            return total;
+        }
+        if intervals.len() >= MAX_SUBINTERVALS {
+            return Quadrature::NAN;
+        }
+
+        let worst = (0..intervals.len())
+            .max_by(|i, j| intervals[*i].2.error.total_cmp(&intervals[*j].2.error))
+            .unwrap();
+        let (a, b, _) = intervals[worst];
+        let mid = (a + b) / 2.;
+        // The interval can't be split any further.
+        if mid <= a || mid >= b {
+            return Quadrature::NAN;
+        }
+
+        intervals[worst] = (a, mid, gauss_kronrod(f, a, mid));
+        intervals.push((mid, b, gauss_kronrod(f, mid, b)));
+    }
+}
+
+/// Checks if `int` grows like the integral of `f` on each piece of `[a, b]`, which fails where
+/// it jumps, like `-\ln{|\cos{x}|}` at `\frac{\pi}{2}`.
+fn is_continuous(
+    int: &ExpressionElement,
+    f: &ExpressionElement,
+    var: &str,
+    a: DecimalScalar,
+    b: DecimalScalar,
+) -> bool {
+    if !a.is_finite() || !b.is_finite() {
+        return false;
+    }
+
+    let at = |g: &ExpressionElement, x| {
+        g.substitute(var, &Number::Decimal(x).into())
+            .approximate_complex()
+    };
+    // The ends are moved inside a bit, `x\ln{x}` is fine at zero but can't be evaluated there.
+    let n = CONTINUITY_PIECES as DecimalScalar;
+    let xs = (0..=CONTINUITY_PIECES)
+        .map(|i| a + (b - a) * (i as DecimalScalar).clamp(1e-6, n - 1e-6) / n)
+        .collect::<Vec<_>>();
+    let values = xs.iter().map(|x| at(int, *x)).collect::<Vec<_>>();
+    if values.iter().any(|v| !v.is_finite()) {
+        return false;
+    }
+
+    xs.windows(2).zip(values.windows(2)).all(|(x, v)| {
+        let re = gauss_kronrod(&|x| at(f, x).re, x[0], x[1]);
+        let im = gauss_kronrod(&|x| at(f, x).im, x[0], x[1]);
+        let piece = ComplexScalar::new(re.value, im.value);
+        (v[1] - v[0] - piece).norm() <= re.error + im.error + MAX_ERROR * piece.norm().max(1.)
+    })
+}
+
+/// The 15-point Kronrod rule, with the difference to the embedded 7-point Gauss rule as the
+/// error.
+fn gauss_kronrod(
+    f: &dyn Fn(DecimalScalar) -> DecimalScalar,
+    a: DecimalScalar,
+    b: DecimalScalar,
+) -> Quadrature {
+    let (center, half) = ((a + b) / 2., (b - a) / 2.);
+
+    let f_center = f(center);
+    let mut kronrod = f_center * KRONROD_WEIGHTS[7];
+    let mut gauss = f_center * GAUSS_WEIGHTS[3];
+
+    for (j, (node, weight)) in KRONROD_NODES[..7].iter().zip(KRONROD_WEIGHTS).enumerate() {
+        let dx = half * node;
+        let sum = f(center - dx) + f(center + dx);
+        kronrod += weight * sum;
+        if j % 2 == 1 {
+            gauss += GAUSS_WEIGHTS[j / 2] * sum;
+        }
+    }
+
+    Quadrature {
+        value: kronrod * half,
+        error: ((kronrod - gauss) * half).abs(),
+    }
+}
+
+impl Integral {
+    /// The name of the variable of integration.
+    #[inline]
+    pub fn var_name(&self) -> Option<&str> {
+        match self.var() {
+            ExpressionElement::Variable(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// `F(b)-F(a)` for bounded intervals, if an antiderivative can be found that's continuous
+    /// on them.
+    fn exact(&self) -> Option<ExpressionElement> {
+        let var = self.var_name()?;
+        let (a, b) = (self.lower(), self.upper());
+        if !a.approximate_complex().is_finite() || !b.approximate_complex().is_finite() {
+            return None;
+        }
+
+        let int = antiderivative(self.f(), var)?;
+        if !is_continuous(&int, self.f(), var, a.approximate(), b.approximate()) {
+            return None;
+        }
+        Some(
+            subtract(int.substitute(var, b), int.substitute(var, a))
+                .evaluate()
+                .into(),
+        )
+    }
+
+    /// Approximates the integral along with its estimated error.
+    pub fn quadrature(&self) -> Quadrature {
+        let Some(var) = self.var_name() else {
+            return Quadrature::NAN;
+        };
+
+        integrate(
+            |x| {
+                self.f()
+                    .substitute(var, &Number::Decimal(x).into())
+                    .approximate()
+            },
+            self.lower().approximate(),
+            self.upper().approximate(),
+        )
+    }
+}
+
+impl Function for Integral {
+    /// Integrals without free variables become decimals, the others stay as they are.
+    fn evaluate(&self) -> MathElement {
+        let int = Integral::new(
+            self.var().clone(),
+            self.lower().evaluate().into(),
+            self.upper().evaluate().into(),
+            self.f().evaluate().into(),
+        );
+
+        if ExpressionElement::from(int.clone()).is_symbolic() {
+            return MathElement::Function(MathFunction::Integral(int));
+        }
+
+        let value = int.approximate_complex();
+        if value.is_nan() {
+            return MathElement::Function(MathFunction::Integral(int));
+        }
+
+        // Antiderivatives can jump inside the interval, so exact values are only trusted if
+        // they agree with the numeric one.
+        if let Some(exact) = int.exact() {
+            if (exact.approximate_complex() - value).norm() <= MAX_ERROR * value.norm().max(1.) {
+                return exact.into();
+            }
+        }
+
+        MathElement::Number(Number::from_complex(value))
+    }
+
+    fn approximate(&self) -> DecimalScalar {
+        let q = self.quadrature();
+        if q.converged() {
+            q.value
+        } else {
+            DecimalScalar::NAN
+        }
+    }
+
+    /// Complex integrands are integrated part by part.
+    fn approximate_complex(&self) -> ComplexScalar {
+        let real = self.approximate();
+        if !real.is_nan() {
+            return real.into();
+        }
+
+        let Some(var) = self.var_name() else {
+            return ComplexScalar::new(DecimalScalar::NAN, 0.);
+        };
+        let (a, b) = (self.lower().approximate(), self.upper().approximate());
+        let at = |x| {
+            self.f()
+                .substitute(var, &Number::Decimal(x).into())
+                .approximate_complex()
+        };
+
+        let (re, im) = (integrate(|x| at(x).re, a, b), integrate(|x| at(x).im, a, b));
+        if re.converged() && im.converged() {
+            ComplexScalar::new(re.value, im.value)
+        } else {
+            ComplexScalar::new(DecimalScalar::NAN, 0.)
+        }
+    }
+}
+
+impl IndefiniteIntegral {
+    /// The name of the variable of integration.
+    #[inline]
+    pub fn var_name(&self) -> Option<&str> {
+        match self.var() {
+            ExpressionElement::Variable(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl Function for IndefiniteIntegral {
+    /// Integrals that can't be found stay as they are.
+    fn evaluate(&self) -> MathElement {
+        let f = ExpressionElement::from(self.f().evaluate());
+        match self.var_name().and_then(|var| antiderivative(&f, var)) {
+            Some(int) => int.into(),
+            None => MathElement::Function(MathFunction::IndefiniteIntegral(
+                IndefiniteIntegral::new(self.var().clone(), f),
+            )),
+        }
+    }
+
+    fn approximate(&self) -> DecimalScalar {
+        match self.evaluate() {
+            MathElement::Function(MathFunction::IndefiniteIntegral(_)) => DecimalScalar::NAN,
+            int => ExpressionElement::from(int).approximate(),
+        }
+    }
+
+    fn approximate_complex(&self) -> ComplexScalar {
+        match self.evaluate() {
+            MathElement::Function(MathFunction::IndefiniteIntegral(_)) => {
+                ComplexScalar::new(DecimalScalar::NAN, 0.)
+            }
+            int => ExpressionElement::from(int).approximate_complex(),
+        }
+    }
+}
+
+/// Substitutions and integrations by parts give up after nesting this deep.
+const MAX_DEPTH: u32 = 6;
+
+/// Finds an antiderivative of `f` with respect to `var`, `None` if no rule applies.
+pub(crate) fn antiderivative(f: &ExpressionElement, var: &str) -> Option<ExpressionElement> {
+    antiderivative_with_depth(f, var, MAX_DEPTH)
+}
+
+fn antiderivative_with_depth(
+    f: &ExpressionElement,
+    var: &str,
+    depth: u32,
+) -> Option<ExpressionElement> {
+    let depth = depth.checked_sub(1)?;
+
+    if !f.depends_on(var) {
+        return Some(multiply(
+            f.clone(),
+            ExpressionElement::Variable(var.to_string()),
+        ));
+    }
+    if let Some(int) =
+        rational_function(f, var).and_then(|(num, den)| integrate_rational(&num, &den, var))
+    {
+        return Some(int);
+    }
+
+    if let ExpressionElement::Function(func) = f {
+        match func.as_ref() {
+            MathFunction::Add(sum) => {
+                return Some(add(
+                    antiderivative_with_depth(sum.lhs(), var, depth)?,
+                    antiderivative_with_depth(sum.rhs(), var, depth)?,
+                ));
+            }
+            MathFunction::Subtract(diff) => {
+                return Some(subtract(
+                    antiderivative_with_depth(diff.lhs(), var, depth)?,
+                    antiderivative_with_depth(diff.rhs(), var, depth)?,
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    let (consts, factors): (Vec<_>, Vec<_>) = factors(f)
+        .into_iter()
+        .filter(|f| !f.is_integer(1))
+        .partition(|f| !f.depends_on(var));
+
+    if !consts.is_empty() {
+        let int = antiderivative_with_depth(&product(factors), var, depth)?;
+        return Some(multiply(product(consts), int));
+    }
+    if let [f] = factors.as_slice() {
+        return integrate_elementary(f, var);
+    }
+
+    integrate_by_substitution(&factors, var, depth)
+        .or_else(|| integrate_by_parts(&factors, var, depth))
+}
+
+/// Splits a product into its factors, where divisors become negative powers.
+fn factors(f: &ExpressionElement) -> Vec<ExpressionElement> {
+    if let ExpressionElement::Function(func) = f {
+        match func.as_ref() {
+            MathFunction::Multiply(mul) => {
+                let mut list = factors(mul.lhs());
+                list.extend(factors(mul.rhs()));
+                return list;
+            }
+            MathFunction::Divide(_) | MathFunction::Fraction(_) => {
+                let params = func.params();
+                let mut list = factors(params[0]);
+                list.extend(factors(params[1]).into_iter().map(reciprocal));
+                return list;
+            }
+            _ => {}
+        }
+    }
+
+    vec![f.clone()]
+}
+
+fn reciprocal(f: ExpressionElement) -> ExpressionElement {
+    if let ExpressionElement::Function(func) = &f {
+        if let MathFunction::Power(pow) = func.as_ref() {
+            if let Some(exp) = pow.exp().as_rational().and_then(|e| e.checked_neg()) {
+                return power(pow.base().clone(), Number::from(exp).into());
+            }
+        }
+    }
+    if f.as_rational().is_some() {
+        return divide(Number::Integer(1).into(), f);
+    }
+
+    power(f, Number::Integer(-1).into())
+}
+
+#[inline]
+fn product(factors: impl IntoIterator<Item = ExpressionElement>) -> ExpressionElement {
+    factors
+        .into_iter()
+        .reduce(multiply)
+        .unwrap_or(Number::Integer(1).into())
+}
+
+#[inline]
+fn ln(x: ExpressionElement) -> ExpressionElement {
+    log(Number::Constant(Constant::E).into(), x)
+}
+
+/// `\ln{|x|}`, which works on both sides of zero, unlike `\ln{x}`.
+#[inline]
+fn ln_abs(x: ExpressionElement) -> ExpressionElement {
+    ln(abs(x))
+}
+
+#[inline]
+fn integer(n: IntegerScalar) -> ExpressionElement {
+    Number::Integer(n).into()
+}
+
+/// Integrals of single functions whose parameter is linear, like `\sin{2x+1}`.
+fn integrate_elementary(f: &ExpressionElement, var: &str) -> Option<ExpressionElement> {
+    let ExpressionElement::Function(func) = f else {
+        return None;
+    };
+
+    // The parameter `u` and `\int f(u) du`.
+    let (u, int) = match func.as_ref() {
+        MathFunction::Power(pow) => {
+            let (base, exp) = (pow.base(), pow.exp());
+            match (base.depends_on(var), exp.depends_on(var)) {
+                // \int a^u du=\frac{a^u}{\ln{a}}
+                (false, true) => (exp, divide(f.clone(), ln(base.clone()))),
+                (true, false) => return integrate_power(base, exp, var),
+                _ => return None,
+            }
+        }
+        MathFunction::Root(rt) => {
+            // The fields are named the other way around, `rad` is the degree.
+            let (n, u) = (rt.rad(), rt.deg());
+            if n.depends_on(var) {
+                return None;
+            }
+            // \int \sqrt[n]{u} du=\frac{n}{n+1}u\sqrt[n]{u}
+            (
+                u,
+                multiply(
+                    divide(n.clone(), add(n.clone(), integer(1))),
+                    multiply(u.clone(), root(n.clone(), u.clone())),
+                ),
+            )
+        }
+        MathFunction::Log(lg) => {
+            let (base, u) = (lg.base(), lg.anti());
+            if base.depends_on(var) {
+                return None;
+            }
+            // \int \log_{a}{u} du=\frac{u\ln{u}-u}{\ln{a}}
+            (
+                u,
+                divide(
+                    subtract(multiply(u.clone(), ln(u.clone())), u.clone()),
+                    ln(base.clone()),
+                ),
+            )
+        }
+        MathFunction::Sin(f) => (f.x(), negate(cos(f.x().clone()))),
+        MathFunction::Cos(f) => (f.x(), sin(f.x().clone())),
+        MathFunction::Tan(f) => (f.x(), negate(ln_abs(cos(f.x().clone())))),
+        MathFunction::Cot(f) => (f.x(), ln_abs(sin(f.x().clone()))),
+        MathFunction::Sec(f) => (f.x(), ln_abs(add(sec(f.x().clone()), tan(f.x().clone())))),
+        MathFunction::Csc(f) => (
+            f.x(),
+            negate(ln_abs(add(csc(f.x().clone()), cot(f.x().clone())))),
+        ),
+        MathFunction::Sinh(f) => (f.x(), cosh(f.x().clone())),
+        MathFunction::Cosh(f) => (f.x(), sinh(f.x().clone())),
+        MathFunction::Tanh(f) => (f.x(), ln(cosh(f.x().clone()))),
+        MathFunction::Coth(f) => (f.x(), ln_abs(sinh(f.x().clone()))),
+        MathFunction::Sech(f) => (f.x(), arctan(sinh(f.x().clone()))),
+        MathFunction::Csch(f) => (f.x(), ln_abs(tanh(divide(f.x().clone(), integer(2))))),
+        // \int \arcsin{u} du=u\arcsin{u}+\sqrt{1-u^2}
+        MathFunction::Arcsin(g) => (
+            g.x(),
+            add(
+                multiply(g.x().clone(), f.clone()),
+                root(
+                    integer(2),
+                    subtract(integer(1), power(g.x().clone(), integer(2))),
+                ),
+            ),
+        ),
+        MathFunction::Arccos(g) => (
+            g.x(),
+            subtract(
+                multiply(g.x().clone(), f.clone()),
+                root(
+                    integer(2),
+                    subtract(integer(1), power(g.x().clone(), integer(2))),
+                ),
+            ),
+        ),
+        // \int \arctan{u} du=u\arctan{u}-\frac{\ln{1+u^2}}{2}
+        MathFunction::Arctan(g) => (
+            g.x(),
+            subtract(
+                multiply(g.x().clone(), f.clone()),
+                divide(
+                    ln(add(integer(1), power(g.x().clone(), integer(2)))),
+                    integer(2),
+                ),
+            ),
+        ),
+        MathFunction::Arccot(g) => (
+            g.x(),
+            add(
+                multiply(g.x().clone(), f.clone()),
+                divide(
+                    ln(add(integer(1), power(g.x().clone(), integer(2)))),
+                    integer(2),
+                ),
+            ),
+        ),
+        _ => return None,
+    };
+
+    linear_substitution(u, int, var)
+}
+
+/// Divides by `u'`, which has to be a constant for substitutions of linear parameters.
+fn linear_substitution(
+    u: &ExpressionElement,
+    int: ExpressionElement,
+    var: &str,
+) -> Option<ExpressionElement> {
+    let du = u.derivative(var);
+    (!du.depends_on(var) && !du.is_integer(0)).then(|| divide(int, du))
+}
+
+/// `\int u^n du`, including the squares of trigonometric functions.
+fn integrate_power(
+    base: &ExpressionElement,
+    exp: &ExpressionElement,
+    var: &str,
+) -> Option<ExpressionElement> {
+    let square = exp.as_rational().map(|e| (e.num(), e.den()));
+    if let (ExpressionElement::Function(f), Some((n @ (2 | -2), 1))) = (base, square) {
+        let u = f.params()[0];
+        let int = match (f.as_ref(), n) {
+            // \int \sin^2{u} du=\frac{u}{2}-\frac{\sin{2u}}{4}
+            (MathFunction::Sin(_), 2) => Some(subtract(
+                divide(u.clone(), integer(2)),
+                divide(sin(multiply(integer(2), u.clone())), integer(4)),
+            )),
+            (MathFunction::Cos(_), 2) => Some(add(
+                divide(u.clone(), integer(2)),
+                divide(sin(multiply(integer(2), u.clone())), integer(4)),
+            )),
+            (MathFunction::Sec(_), 2) | (MathFunction::Cos(_), -2) => Some(tan(u.clone())),
+            (MathFunction::Csc(_), 2) | (MathFunction::Sin(_), -2) => Some(negate(cot(u.clone()))),
+            (MathFunction::Tan(_), 2) => Some(subtract(tan(u.clone()), u.clone())),
+            (MathFunction::Cot(_), 2) => Some(negate(add(cot(u.clone()), u.clone()))),
+            (MathFunction::Sech(_), 2) | (MathFunction::Cosh(_), -2) => Some(tanh(u.clone())),
+            (MathFunction::Csch(_), 2) | (MathFunction::Sinh(_), -2) => {
+                Some(negate(coth(u.clone())))
+            }
+            _ => None,
+        };
+
+        if let Some(int) = int {
+            return linear_substitution(u, int, var);
+        }
+    }
+
+    // \sqrt[n]{u}^{m}=u^{\frac{m}{n}}
+    if let ExpressionElement::Function(f) = base {
+        if let MathFunction::Root(rt) = f.as_ref() {
+            if !rt.rad().depends_on(var) {
+                return integrate_power(rt.deg(), &divide(exp.clone(), rt.rad().clone()), var);
+            }
+        }
+    }
+
+    // \int u^{-1} du=\ln{|u|}
+    if exp.is_integer(-1) {
+        return linear_substitution(base, ln_abs(base.clone()), var);
+    }
+
+    // \int u^n du=\frac{u^{n+1}}{n+1}
+    let n = add(exp.clone(), integer(1));
+    linear_substitution(base, divide(power(base.clone(), n.clone()), n), var)
+}
+
+/// Integrates `f(u)u'` as `\int f(t) dt` at `t=u`, where `u'` may be off by a constant factor.
+fn integrate_by_substitution(
+    factors: &[ExpressionElement],
+    var: &str,
+    depth: u32,
+) -> Option<ExpressionElement> {
+    let x = ExpressionElement::Variable(var.to_string());
+
+    for (i, factor) in factors.iter().enumerate() {
+        let rest = product(
+            factors
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, f)| f.clone()),
+        );
+
+        for (u, outer) in substitutions(factor, &x, var) {
+            let du = u.derivative(var);
+            if du.is_integer(0) {
+                continue;
+            }
+            let Some(c) = constant_ratio(&rest, &du, var) else {
+                continue;
+            };
+            let Some(int) = antiderivative_with_depth(&outer, var, depth) else {
+                continue;
+            };
+
+            return Some(multiply(c, int.substitute(var, &u).evaluate().into()));
+        }
+    }
+
+    None
+}
+
+/// The ways to write `factor` as `f(u)`, along with `f(x)`.
+fn substitutions(
+    factor: &ExpressionElement,
+    x: &ExpressionElement,
+    var: &str,
+) -> Vec<(ExpressionElement, ExpressionElement)> {
+    let mut subs = vec![(factor.clone(), x.clone())];
+
+    if let ExpressionElement::Function(f) = factor {
+        let params = f.params();
+        let mut dependent = params.iter().filter(|p| p.depends_on(var));
+        if let (Some(u), None) = (dependent.next(), dependent.next()) {
+            let u = (*u).clone();
+            let outer = f.map_params(|p| if *p == u { x.clone() } else { p.clone() });
+            subs.push((u, outer.evaluate().into()));
+        }
+    }
+
+    subs
+}
+
+/// `\frac{a}{b}` if it doesn't depend on `var`.
+fn constant_ratio(
+    a: &ExpressionElement,
+    b: &ExpressionElement,
+    var: &str,
+) -> Option<ExpressionElement> {
+    if let (Some((an, ad)), Some((bn, bd))) = (rational_function(a, var), rational_function(b, var))
+    {
+        let (quot, rem) = an.checked_mul(&bd)?.div_rem(&ad.checked_mul(&bn)?)?;
+        return (rem.is_zero() && quot.is_constant()).then(|| Number::from(quot.coeff(0)).into());
+    }
+
+    let ((a_coeff, a_rest), (b_coeff, b_rest)) = (split_coefficient(a), split_coefficient(b));
+    if a_rest == b_rest {
+        return Some(Number::from(a_coeff.checked_div(b_coeff)?).into());
+    }
+
+    let ratio = divide(a.clone(), b.clone());
+    (!ratio.depends_on(var)).then_some(ratio)
+}
+
+/// `\int u dv=uv-\int v du`, where `u` is the first factor in the order of logarithms,
+/// inverse trigonometric functions, polynomials, trigonometric functions and exponentials.
+fn integrate_by_parts(
+    factors: &[ExpressionElement],
+    var: &str,
+    depth: u32,
+) -> Option<ExpressionElement> {
+    let (i, _) = factors
+        .iter()
+        .enumerate()
+        .filter_map(|(i, f)| liate_order(f, var).map(|order| (i, order)))
+        .min_by_key(|(_, order)| *order)?;
+
+    let u = &factors[i];
+    let dv = product(
+        factors
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, f)| f.clone()),
+    );
+
+    let v = antiderivative_with_depth(&dv, var, depth)?;
+    let rest = antiderivative_with_depth(&multiply(v.clone(), u.derivative(var)), var, depth)?;
+    Some(subtract(multiply(u.clone(), v), rest))
+}
+
+fn liate_order(f: &ExpressionElement, var: &str) -> Option<u8> {
+    if Polynomial::from_expr(f, var).is_some() {
+        return Some(2);
+    }
+
+    let ExpressionElement::Function(func) = f else {
+        return None;
+    };
+    match func.as_ref() {
+        MathFunction::Log(_) => Some(0),
+        MathFunction::Arcsin(_)
+        | MathFunction::Arccos(_)
+        | MathFunction::Arctan(_)
+        | MathFunction::Arccot(_)
+        | MathFunction::Arcsec(_)
+        | MathFunction::Arccsc(_) => Some(1),
+        MathFunction::Sin(_)
+        | MathFunction::Cos(_)
+        | MathFunction::Tan(_)
+        | MathFunction::Cot(_)
+        | MathFunction::Sec(_)
+        | MathFunction::Csc(_)
+        | MathFunction::Sinh(_)
+        | MathFunction::Cosh(_)
+        | MathFunction::Tanh(_)
+        | MathFunction::Coth(_)
+        | MathFunction::Sech(_)
+        | MathFunction::Csch(_) => Some(3),
+        MathFunction::Power(pow) if !pow.base().depends_on(var) => Some(4),
+        _ => None,
+    }
+}
+
+/// The terms of a partial fraction decomposition.
+enum PartialFraction {
+    /// `\frac{1}{(x-r)^n}`
+    Linear(Rational, usize),
+    /// `\frac{x}{x^2+px+q}`
+    QuadraticLinear,
+    /// `\frac{1}{x^2+px+q}`
+    QuadraticConstant,
+}
+
+/// Integrates `\frac{num}{den}` with partial fractions, where the denominator has to split into
+/// rational roots and at most one quadratic factor.
+fn integrate_rational(num: &Polynomial, den: &Polynomial, var: &str) -> Option<ExpressionElement> {
+    let (quot, rem) = num.div_rem(den)?;
+    let int = quot.integral()?.to_expr(var);
+    if rem.is_zero() {
+        return Some(int);
+    }
+
+    let lead = den.leading().recip()?;
+    let (rem, den) = (rem.checked_scale(lead)?, den.checked_scale(lead)?);
+    let (roots, rest) = den.rational_roots()?;
+    let quadratic = match rest.degree()? {
+        0 => None,
+        2 => Some(rest.checked_scale(rest.leading().recip()?)?),
+        _ => return None,
+    };
+
+    // Each term times the denominator, where the coefficients are the unknowns.
+    let mut terms = Vec::new();
+    for (r, multiplicity) in &roots {
+        let linear = Polynomial::new(vec![r.checked_neg()?, Rational::ONE]);
+        for n in 1..=*multiplicity {
+            let (basis, _) = den.div_rem(&linear.checked_pow(n as u32)?)?;
+            terms.push((basis, PartialFraction::Linear(*r, n)));
+        }
+    }
+    if let Some(q) = &quadratic {
+        let (basis, _) = den.div_rem(q)?;
+        let x = Polynomial::monomial(Rational::ONE, 1);
+        terms.push((basis.checked_mul(&x)?, PartialFraction::QuadraticLinear));
+        terms.push((basis, PartialFraction::QuadraticConstant));
+    }
+
+    let n = terms.len();
+    let system = (0..n)
+        .map(|deg| terms.iter().map(|(basis, _)| basis.coeff(deg)).collect())
+        .collect();
+    let coeffs = solve_linear(system, (0..n).map(|deg| rem.coeff(deg)).collect())?;
+
+    let x_minus =
+        |r: Rational| Some(Polynomial::new(vec![r.checked_neg()?, Rational::ONE]).to_expr(var));
+    let mut int = int;
+    let (mut b, mut c) = (Rational::ZERO, Rational::ZERO);
+
+    for ((_, term), coeff) in terms.iter().zip(coeffs) {
+        let term = match term {
+            PartialFraction::Linear(r, 1) => {
+                multiply(Number::from(coeff).into(), ln_abs(x_minus(*r)?))
+            }
+            // \int \frac{1}{(x-r)^n} dx=\frac{1}{(1-n)(x-r)^{n-1}}
+            PartialFraction::Linear(r, n) => divide(
+                Number::from(coeff.checked_div(Rational::from(1 - *n as IntegerScalar))?).into(),
+                power(x_minus(*r)?, integer(*n as IntegerScalar - 1)),
+            ),
+            PartialFraction::QuadraticLinear => {
+                b = coeff;
+                continue;
+            }
+            PartialFraction::QuadraticConstant => {
+                c = coeff;
+                continue;
+            }
+        };
+        int = add(int, term);
+    }
+
+    if let Some(q) = quadratic {
+        int = add(int, integrate_quadratic(&q, b, c, var)?);
+    }
+
+    Some(int)
+}
+
+/// `\int \frac{bx+c}{x^2+px+q} dx` for quadratics without rational roots.
+fn integrate_quadratic(
+    q: &Polynomial,
+    b: Rational,
+    c: Rational,
+    var: &str,
+) -> Option<ExpressionElement> {
+    let p = q.coeff(1);
+    // Completing the square, x^2+px+q=(x+h)^2+k
+    let h = p.checked_div(Rational::from(2))?;
+    let k = q.coeff(0).checked_sub(h.checked_mul(h)?)?;
+    let positive = !k.is_negative() && k != Rational::ZERO;
+
+    // \frac{b}{2}\ln{|x^2+px+q|}
+    let log_part = multiply(
+        Number::from(b.checked_div(Rational::from(2))?).into(),
+        if positive {
+            ln(q.to_expr(var))
+        } else {
+            ln_abs(q.to_expr(var))
+        },
+    );
+
+    // The rest is c-\frac{bp}{2} times \int \frac{1}{x^2+px+q} dx
+    let rest = c.checked_sub(b.checked_mul(p)?.checked_div(Rational::from(2))?)?;
+    if rest == Rational::ZERO {
+        return Some(log_part);
+    }
+
+    let shifted = Polynomial::new(vec![h, Rational::from(1)]).to_expr(var);
+    let rest = ExpressionElement::from(Number::from(rest));
+
+    let int = if positive {
+        // \frac{1}{\sqrt{k}}\arctan{\frac{x+h}{\sqrt{k}}}
+        let sqrt = root(integer(2), Number::from(k).into());
+        divide(arctan(divide(shifted, sqrt.clone())), sqrt)
+    } else {
+        // \frac{1}{2\sqrt{-k}}\ln{|\frac{x+h-\sqrt{-k}}{x+h+\sqrt{-k}}|}
+        let sqrt = root(integer(2), Number::from(k.checked_neg()?).into());
+        divide(
+            ln_abs(divide(
+                subtract(shifted.clone(), sqrt.clone()),
+                add(shifted, sqrt.clone()),
+            )),
+            multiply(integer(2), sqrt),
+        )
+    };
+
+    Some(add(log_part, multiply(rest, int)))
+}
+
+/// Solves a square system of linear equations with Gaussian elimination.
+fn solve_linear(mut a: Vec<Vec<Rational>>, mut b: Vec<Rational>) -> Option<Vec<Rational>> {
+    let n = b.len();
+
+    for col in 0..n {
+        let pivot = (col..n).find(|row| a[*row][col] != Rational::ZERO)?;
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let pivot = a[col].clone();
+        for row in 0..n {
+            if row == col || a[row][col] == Rational::ZERO {
+                continue;
+            }
+            let factor = a[row][col].checked_div(pivot[col])?;
+            for (x, p) in a[row].iter_mut().zip(&pivot).skip(col) {
+                *x = x.checked_sub(factor.checked_mul(*p)?)?;
+            }
+            b[row] = b[row].checked_sub(factor.checked_mul(b[col])?)?;
+        }
+    }
+
+    (0..n).map(|i| b[i].checked_div(a[i][i])).collect()
+}
@@ -0,0 +1,638 @@
+//! Limits, found with the leading terms of series and L'Hôpital's rule where possible and
+//! extrapolated numerically otherwise.
+
+use crate::{
+    math::{
+        func::{
+            decl::{Limit, MathFunction},
+            exp_log::{log, power},
+            op::{add, divide, multiply, negate, subtract},
+            Differentiable, Function,
+        },
+        poly::rational_function,
+        symbol::{Constant, Number, Rational},
+        ExpressionElement, MathElement,
+    },
+    ComplexScalar, DecimalScalar, IntegerScalar,
+};
+
+/// How many times limits are rewritten, like with L'Hôpital's rule, before giving up.
+pub const MAX_DEPTH: u32 = 8;
+/// Extrapolated limits are accepted once two estimates agree up to this, relative to the value
+/// when it's larger than 1.
+pub const TOLERANCE: DecimalScalar = 1e-8;
+/// The number of halvings of the step size when extrapolating, starting from `\frac{1}{4}`.
+pub(crate) const MAX_STEPS: i32 = 10;
+
+/// The side a limit is approached from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Both,
+    /// From below, like `x \to a^-`.
+    Left,
+    /// From above, like `x \to a^+`.
+    Right,
+}
+
+impl Direction {
+    /// The sign of the direction, which is how it's stored in [`Limit`].
+    #[inline]
+    pub fn sign(self) -> IntegerScalar {
+        match self {
+            Direction::Both => 0,
+            Direction::Left => -1,
+            Direction::Right => 1,
+        }
+    }
+}
+
+impl Limit {
+    /// The name of the variable that approaches the point.
+    #[inline]
+    pub fn var_name(&self) -> Option<&str> {
+        match self.var() {
+            ExpressionElement::Variable(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn direction(&self) -> Direction {
+        match self.side() {
+            s if s.is_integer(-1) => Direction::Left,
+            s if s.is_integer(1) => Direction::Right,
+            _ => Direction::Both,
+        }
+    }
+}
+
+impl Function for Limit {
+    /// Limits that don't exist or can't be found stay as they are.
+    fn evaluate(&self) -> MathElement {
+        let lim = Limit::new(
+            self.var().clone(),
+            self.point().evaluate().into(),
+            self.side().clone(),
+            self.f().evaluate().into(),
+        );
+
+        match lim
+            .var_name()
+            .and_then(|var| limit(lim.f(), var, lim.point(), lim.direction()))
+        {
+            Some(value) => value.into(),
+            None => MathElement::Function(MathFunction::Limit(lim)),
+        }
+    }
+
+    fn approximate(&self) -> DecimalScalar {
+        match self.evaluate() {
+            MathElement::Function(MathFunction::Limit(_)) => DecimalScalar::NAN,
+            lim => ExpressionElement::from(lim).approximate(),
+        }
+    }
+
+    fn approximate_complex(&self) -> ComplexScalar {
+        match self.evaluate() {
+            MathElement::Function(MathFunction::Limit(_)) => {
+                ComplexScalar::new(DecimalScalar::NAN, 0.)
+            }
+            lim => ExpressionElement::from(lim).approximate_complex(),
+        }
+    }
+}
+
+/// The limit of `f` as `var` approaches `point` from `dir`, `None` if it doesn't exist or can't
+/// be found.
+pub fn limit(
+    f: &ExpressionElement,
+    var: &str,
+    point: &ExpressionElement,
+    dir: Direction,
+) -> Option<ExpressionElement> {
+    let t = ExpressionElement::Variable(var.to_string());
+
+    // Every limit is turned into one at zero, which is only approached from above unless it's
+    // approached from both sides.
+    let (g, both) = match infinite_sign(point) {
+        // x=\frac{1}{t}
+        Some(sign) => (f.substitute(var, &divide(integer(sign), t)), false),
+        None => match dir {
+            Direction::Both => (f.substitute(var, &add(point.clone(), t)), true),
+            Direction::Right => (f.substitute(var, &add(point.clone(), t)), false),
+            Direction::Left => (f.substitute(var, &subtract(point.clone(), t)), false),
+        },
+    };
+
+    limit_at_zero(&g.evaluate().into(), var, both, 0)
+}
+
+/// The limit of `g` as `var` approaches zero, from above unless `both` is set.
+fn limit_at_zero(
+    g: &ExpressionElement,
+    var: &str,
+    both: bool,
+    depth: u32,
+) -> Option<ExpressionElement> {
+    if !g.depends_on(var) {
+        return is_finite(g).then(|| g.clone());
+    }
+    if depth > MAX_DEPTH {
+        return None;
+    }
+
+    direct(g, var, both)
+        .or_else(|| leading_term(g, var)?.limit(both))
+        .or_else(|| rewrite(g, var, both, depth))
+        // Inexact parts would spoil the rest, so only whole limits are extrapolated.
+        .or_else(|| (depth == 0).then(|| extrapolate(g, var, both))?)
+}
+
+/// Plugs in zero, which works for every continuous function, and looks for poles like
+/// `\frac{1}{x^2}`.
+fn direct(g: &ExpressionElement, var: &str, both: bool) -> Option<ExpressionElement> {
+    let at_zero = g.substitute(var, &integer(0));
+    if let Some(value) = evaluate_finite(&at_zero) {
+        return Some(value);
+    }
+
+    // The value at zero doesn't tell the sign, so it's read from the nearby values, which
+    // have to keep growing.
+    infinite_sign(&at_zero.evaluate().into())?;
+    let at = |t| g.substitute(var, &Number::Decimal(t).into()).approximate();
+    let sides: &[DecimalScalar] = if both { &[1., -1.] } else { &[1.] };
+    let signs = sides
+        .iter()
+        .map(|s| {
+            let (near, nearer) = (at(s * 1e-6), at(s * 1e-9));
+            (nearer.abs() >= near.abs() && near != 0. && near.signum() == nearer.signum())
+                .then_some(nearer.signum())
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    if signs.iter().all(|s| *s == signs[0]) {
+        Some(infinity(signs[0] as IntegerScalar))
+    } else {
+        None
+    }
+}
+
+/// Evaluates `elem` from the bottom up, `None` if anything on the way is infinite or undefined.
+///
+/// Evaluating it at once isn't enough, as things like `1^{\frac{1}{0}}` become finite again.
+fn evaluate_finite(elem: &ExpressionElement) -> Option<ExpressionElement> {
+    let value = match elem {
+        ExpressionElement::Function(f) => {
+            let mut finite = true;
+            let f = f.map_params(|p| {
+                evaluate_finite(p).unwrap_or_else(|| {
+                    finite = false;
+                    p.clone()
+                })
+            });
+            if !finite {
+                return None;
+            }
+            f.evaluate().into()
+        }
+        _ => elem.evaluate().into(),
+    };
+
+    is_finite(&value).then_some(value)
+}
+
+/// The leading term `ct^p` of a series around zero.
+#[derive(Debug, Clone)]
+enum Lead {
+    Zero,
+    Term(ExpressionElement, Rational),
+}
+
+impl Lead {
+    fn limit(self, both: bool) -> Option<ExpressionElement> {
+        let (c, p) = match self {
+            Lead::Zero => return Some(integer(0)),
+            Lead::Term(c, p) => (c, p),
+        };
+
+        if p == Rational::ZERO {
+            return Some(c);
+        }
+        if !p.is_negative() {
+            return Some(integer(0));
+        }
+
+        // Odd powers flip their signs below zero and fractional ones aren't real there.
+        if both && (p.den() % 2 == 0 || p.num() % 2 != 0) {
+            return None;
+        }
+        let sign = c.approximate();
+        if sign.is_nan() || sign == 0. {
+            return None;
+        }
+        Some(infinity(sign.signum() as IntegerScalar))
+    }
+
+    fn add(self, rhs: Self) -> Option<Self> {
+        match (self, rhs) {
+            (Lead::Zero, lead) | (lead, Lead::Zero) => Some(lead),
+            (Lead::Term(c, p), Lead::Term(d, q)) => {
+                if p.checked_sub(q)?.is_negative() {
+                    Some(Lead::Term(c, p))
+                } else if p != q {
+                    Some(Lead::Term(d, q))
+                } else {
+                    // The terms cancel, and the next ones aren't known.
+                    let sum = ExpressionElement::from(add(c, d).evaluate());
+                    (!is_zero(&sum)).then_some(Lead::Term(sum, p))
+                }
+            }
+        }
+    }
+
+    fn mul(self, rhs: Self) -> Option<Self> {
+        match (self, rhs) {
+            (Lead::Zero, _) | (_, Lead::Zero) => Some(Lead::Zero),
+            (Lead::Term(c, p), Lead::Term(d, q)) => {
+                Some(Lead::Term(multiply(c, d), p.checked_add(q)?))
+            }
+        }
+    }
+
+    fn pow(self, exp: Rational) -> Option<Self> {
+        match self {
+            Lead::Zero => (!exp.is_negative() && exp != Rational::ZERO).then_some(Lead::Zero),
+            Lead::Term(c, p) => Some(Lead::Term(
+                power(c, Number::from(exp).into()),
+                p.checked_mul(exp)?,
+            )),
+        }
+    }
+}
+
+/// Finds the leading term of `g` around zero, `None` if it's not a power of `var` or the
+/// leading terms cancel out.
+fn leading_term(g: &ExpressionElement, var: &str) -> Option<Lead> {
+    if !g.depends_on(var) {
+        if !is_finite(g) {
+            return None;
+        }
+        return Some(if is_zero(g) {
+            Lead::Zero
+        } else {
+            Lead::Term(g.clone(), Rational::ZERO)
+        });
+    }
+
+    // Rational functions are exact, no matter how much cancels out.
+    if let Some((num, den)) = rational_function(g, var) {
+        let lowest = |coeffs: &[Rational]| coeffs.iter().position(|c| *c != Rational::ZERO);
+        let Some(p) = lowest(num.coeffs()) else {
+            return Some(Lead::Zero);
+        };
+        let q = lowest(den.coeffs())?;
+        return Some(Lead::Term(
+            Number::from(num.coeff(p).checked_div(den.coeff(q))?).into(),
+            Rational::from(p as IntegerScalar - q as IntegerScalar),
+        ));
+    }
+
+    let ExpressionElement::Function(f) = g else {
+        return match g {
+            ExpressionElement::Variable(_) => Some(Lead::Term(integer(1), Rational::ONE)),
+            _ => None,
+        };
+    };
+
+    match f.as_ref() {
+        MathFunction::Add(op) => leading_term(op.lhs(), var)?.add(leading_term(op.rhs(), var)?),
+        MathFunction::Subtract(op) => leading_term(op.lhs(), var)?
+            .add(leading_term(op.rhs(), var)?.mul(Lead::Term(integer(-1), Rational::ZERO))?),
+        MathFunction::Multiply(op) => {
+            leading_term(op.lhs(), var)?.mul(leading_term(op.rhs(), var)?)
+        }
+        MathFunction::Divide(op) => {
+            leading_term(op.lhs(), var)?.mul(leading_term(op.rhs(), var)?.pow(Rational::from(-1))?)
+        }
+        MathFunction::Fraction(frac) => leading_term(frac.num(), var)?
+            .mul(leading_term(frac.den(), var)?.pow(Rational::from(-1))?),
+        MathFunction::Power(pow) if !pow.exp().depends_on(var) => {
+            leading_term(pow.base(), var)?.pow(pow.exp().as_rational()?)
+        }
+        MathFunction::Root(rt) if !rt.rad().depends_on(var) => {
+            leading_term(rt.deg(), var)?.pow(rt.rad().as_rational()?.recip()?)
+        }
+        // a^{u}
+        MathFunction::Power(pow) if !pow.base().depends_on(var) => {
+            let u = leading_term(pow.exp(), var)?.limit(false)?;
+            let value = evaluate_finite(&power(pow.base().clone(), u))?;
+            (!is_zero(&value)).then_some(Lead::Term(value, Rational::ZERO))
+        }
+        MathFunction::Log(lg) if !lg.base().depends_on(var) => {
+            let u = leading_term(lg.anti(), var)?.limit(false)?;
+            if u.is_integer(1) {
+                // \log_{a}{u} \sim \frac{u-1}{\ln{a}}
+                let ln = log(Number::Constant(Constant::E).into(), lg.base().clone());
+                return leading_term(&subtract(lg.anti().clone(), u).evaluate().into(), var)?
+                    .mul(Lead::Term(divide(integer(1), ln), Rational::ZERO));
+            }
+            let value = evaluate_finite(&log(lg.base().clone(), u))?;
+            (!is_zero(&value)).then_some(Lead::Term(value, Rational::ZERO))
+        }
+        _ => {
+            let [u] = f.params()[..] else {
+                return None;
+            };
+            let lead = leading_term(u, var)?;
+            let vanishes = match &lead {
+                Lead::Zero => true,
+                Lead::Term(_, p) => !p.is_negative() && *p != Rational::ZERO,
+            };
+
+            if let Lead::Term(c, p) = &lead {
+                if p.is_negative() {
+                    // \arctan{\infty}=\frac{\pi}{2}
+                    let sign = integer(c.approximate().signum() as IntegerScalar);
+                    let value = match f.as_ref() {
+                        MathFunction::Arctan(_) => divide(
+                            multiply(sign, Number::Constant(Constant::Pi).into()),
+                            integer(2),
+                        ),
+                        MathFunction::Tanh(_) => sign,
+                        _ => return None,
+                    };
+                    return Some(Lead::Term(value, Rational::ZERO));
+                }
+            }
+
+            if !vanishes {
+                // Continuous at a nonzero value of `u`.
+                let u = lead.limit(false)?;
+                let value = evaluate_finite(&f.map_params(|_| u.clone()).evaluate().into())?;
+                return (!is_zero(&value)).then_some(Lead::Term(value, Rational::ZERO));
+            }
+
+            match f.as_ref() {
+                // \sin{u} \sim u
+                MathFunction::Sin(_)
+                | MathFunction::Tan(_)
+                | MathFunction::Arcsin(_)
+                | MathFunction::Arctan(_)
+                | MathFunction::Sinh(_)
+                | MathFunction::Tanh(_) => Some(lead),
+                // \csc{u} \sim \frac{1}{u}
+                MathFunction::Cot(_)
+                | MathFunction::Csc(_)
+                | MathFunction::Coth(_)
+                | MathFunction::Csch(_) => lead.pow(Rational::from(-1)),
+                MathFunction::Cos(_)
+                | MathFunction::Sec(_)
+                | MathFunction::Cosh(_)
+                | MathFunction::Sech(_) => Some(Lead::Term(integer(1), Rational::ZERO)),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Rewrites indeterminate forms into ones that can be found, like L'Hôpital's rule for
+/// `\frac{0}{0}` and `\frac{\infty}{\infty}`.
+fn rewrite(g: &ExpressionElement, var: &str, both: bool, depth: u32) -> Option<ExpressionElement> {
+    let ExpressionElement::Function(f) = g else {
+        return None;
+    };
+    let lim = |g: &ExpressionElement| limit_at_zero(&g.evaluate().into(), var, both, depth + 1);
+
+    let (num, den) = match f.as_ref() {
+        MathFunction::Divide(op) => (op.lhs().clone(), op.rhs().clone()),
+        MathFunction::Fraction(frac) => (frac.num().clone(), frac.den().clone()),
+        MathFunction::Add(op) => {
+            return sum(lim(op.lhs())?, lim(op.rhs())?)
+                .or_else(|| lim(&conjugate(op.lhs(), op.rhs(), false)?))
+        }
+        MathFunction::Subtract(op) => {
+            return sum(lim(op.lhs())?, negate(lim(op.rhs())?))
+                .or_else(|| lim(&conjugate(op.lhs(), op.rhs(), true)?))
+        }
+        MathFunction::Multiply(op) => {
+            let (lhs, rhs) = (lim(op.lhs())?, lim(op.rhs())?);
+            let indeterminate = (is_zero(&lhs) && infinite_sign(&rhs).is_some())
+                || (is_zero(&rhs) && infinite_sign(&lhs).is_some());
+            if !indeterminate {
+                return match (infinite_sign(&lhs), infinite_sign(&rhs)) {
+                    (Some(s), Some(t)) => Some(infinity(s * t)),
+                    _ => quotient(lhs, divide(integer(1), rhs).evaluate().into()),
+                };
+            }
+
+            // uv=\frac{u}{\frac{1}{v}}, where rational functions are the ones turned upside
+            // down since they stay simple.
+            if rational_function(op.lhs(), var).is_some() {
+                (op.rhs().clone(), divide(integer(1), op.lhs().clone()))
+            } else {
+                (op.lhs().clone(), divide(integer(1), op.rhs().clone()))
+            }
+        }
+        // u^v=e^{v\ln{u}}
+        MathFunction::Power(pow) if pow.base().depends_on(var) && pow.exp().depends_on(var) => {
+            let e = ExpressionElement::from(Number::Constant(Constant::E));
+            let exp = lim(&multiply(
+                pow.exp().clone(),
+                log(e.clone(), pow.base().clone()),
+            ))?;
+            return match infinite_sign(&exp) {
+                Some(1) => Some(infinity(1)),
+                Some(_) => Some(integer(0)),
+                None => Some(power(e, exp).evaluate().into()),
+            };
+        }
+        _ => return None,
+    };
+
+    let (n, d) = (lim(&num)?, lim(&den)?);
+    let indeterminate = (is_zero(&n) && is_zero(&d))
+        || (infinite_sign(&n).is_some() && infinite_sign(&d).is_some());
+    if !indeterminate {
+        return quotient(n, d);
+    }
+
+    // \lim \frac{f}{g}=\lim \frac{f'}{g'}
+    lim(&divide(num.derivative(var), den.derivative(var)))
+}
+
+/// Multiplies `u+v` or `u-v` by its conjugate if one of them is a square root, like
+/// `\sqrt{a}-b=\frac{a-b^2}{\sqrt{a}+b}`, which turns `\infty-\infty` into a quotient.
+fn conjugate(
+    u: &ExpressionElement,
+    v: &ExpressionElement,
+    minus: bool,
+) -> Option<ExpressionElement> {
+    let is_sqrt = |elem: &ExpressionElement| match elem {
+        ExpressionElement::Function(f) => {
+            matches!(f.as_ref(), MathFunction::Root(rt) if rt.rad().is_integer(2))
+        }
+        _ => false,
+    };
+    if !is_sqrt(u) && !is_sqrt(v) {
+        return None;
+    }
+
+    let square = |elem: &ExpressionElement| power(elem.clone(), integer(2));
+    let den = if minus {
+        add(u.clone(), v.clone())
+    } else {
+        subtract(u.clone(), v.clone())
+    };
+    Some(divide(subtract(square(u), square(v)), den))
+}
+
+/// The limit of a sum, given the limits of its terms.
+fn sum(lhs: ExpressionElement, rhs: ExpressionElement) -> Option<ExpressionElement> {
+    match (infinite_sign(&lhs), infinite_sign(&rhs)) {
+        (None, None) => Some(add(lhs, rhs).evaluate().into()),
+        (Some(s), None) | (None, Some(s)) => Some(infinity(s)),
+        // \infty-\infty
+        (Some(s), Some(t)) => (s == t).then(|| infinity(s)),
+    }
+}
+
+/// The limit of a quotient, given the limits of its parts, as long as it's not indeterminate.
+fn quotient(num: ExpressionElement, den: ExpressionElement) -> Option<ExpressionElement> {
+    let sign = |elem: &ExpressionElement| {
+        let value = elem.approximate();
+        (!value.is_nan() && value != 0.).then_some(value.signum() as IntegerScalar)
+    };
+
+    match (infinite_sign(&num), infinite_sign(&den)) {
+        (None, None) => (!is_zero(&den)).then(|| divide(num, den).evaluate().into()),
+        (None, Some(_)) => Some(integer(0)),
+        (Some(s), None) => Some(infinity(s * sign(&den)?)),
+        (Some(_), Some(_)) => None,
+    }
+}
+
+/// Extrapolates the value at zero from the values at `t=\frac{1}{4}, \frac{1}{8}, ...`, which
+/// only works if `g` has no other variables.
+fn extrapolate(g: &ExpressionElement, var: &str, both: bool) -> Option<ExpressionElement> {
+    if g.substitute(var, &integer(1)).is_symbolic() {
+        return None;
+    }
+
+    let at = |t| g.substitute(var, &Number::Decimal(t).into()).approximate();
+    let right = extrapolate_side(at)?;
+    if both {
+        let left = extrapolate_side(|t| at(-t))?;
+        let agree = if right.is_infinite() {
+            left == right
+        } else {
+            (left - right).abs() <= TOLERANCE * right.abs().max(1.)
+        };
+        if !agree {
+            return None;
+        }
+    }
+
+    Some(if right.is_infinite() {
+        infinity(right.signum() as IntegerScalar)
+    } else {
+        Number::Decimal(right).into()
+    })
+}
+
+/// Richardson extrapolation of `f` at zero, where `f` is sampled at halving steps.
+///
+/// Returns an infinity if the values keep growing at least linearly.
+pub(crate) fn extrapolate_side(
+    f: impl Fn(DecimalScalar) -> DecimalScalar,
+) -> Option<DecimalScalar> {
+    let steps = (2..2 + MAX_STEPS)
+        .map(|k| DecimalScalar::powi(2., -k))
+        .collect::<Vec<_>>();
+    let values = steps.iter().map(|t| f(*t)).collect::<Vec<_>>();
+
+    // Fast growing ones like `e^{\frac{1}{t}}` overflow on the way.
+    if let Some(overflow) = values.iter().position(|v| !v.is_finite()) {
+        let (inf, finite) = (values[overflow], &values[..overflow]);
+        // Only the last values have to grow, `\frac{e^{x}}{x^{10}}` shrinks at first.
+        let growing = finite.len() >= 2
+            && finite
+                .windows(2)
+                .skip((finite.len() - 1) / 2)
+                .all(|w| w[1].abs() > w[0].abs() && w[1].signum() == inf.signum());
+        return (!inf.is_nan() && growing).then_some(inf);
+    }
+
+    let growing = values
+        .windows(2)
+        .skip(values.len() / 2)
+        .all(|w| w[1].abs() >= 1.9 * w[0].abs() && w[0].signum() == w[1].signum());
+    if growing {
+        return Some(DecimalScalar::INFINITY.copysign(values[values.len() - 1]));
+    }
+
+    // Neville's table, where each column cancels one more power of the step.
+    let mut table = values.clone();
+    let mut best: Option<(DecimalScalar, DecimalScalar)> = None;
+    for col in 1..steps.len() {
+        let prev = table.clone();
+        for i in col..steps.len() {
+            table[i] =
+                (steps[i - col] * prev[i] - steps[i] * prev[i - 1]) / (steps[i - col] - steps[i]);
+            let error = (table[i] - prev[i])
+                .abs()
+                .max((table[i] - table[i - 1]).abs());
+            if best.is_none_or(|(_, e)| error < e) {
+                best = Some((table[i], error));
+            }
+        }
+    }
+
+    let (value, error) = best?;
+    (error <= TOLERANCE * value.abs().max(1.)).then_some(value)
+}
+
+#[inline]
+pub(crate) fn integer(n: IntegerScalar) -> ExpressionElement {
+    Number::Integer(n).into()
+}
+
+/// `\infty` or `-\infty`.
+pub(crate) fn infinity(sign: IntegerScalar) -> ExpressionElement {
+    let inf = ExpressionElement::from(Number::Constant(Constant::Infinity));
+    if sign < 0 {
+        negate(inf)
+    } else {
+        inf
+    }
+}
+
+/// The sign of `elem` if it's an infinity.
+pub(crate) fn infinite_sign(elem: &ExpressionElement) -> Option<IntegerScalar> {
+    if elem.is_symbolic() {
+        return None;
+    }
+    let value = elem.approximate();
+    value
+        .is_infinite()
+        .then_some(value.signum() as IntegerScalar)
+}
+
+pub(crate) fn is_zero(elem: &ExpressionElement) -> bool {
+    !elem.is_symbolic() && elem.approximate_complex() == ComplexScalar::new(0., 0.)
+}
+
+/// Checks if `elem` has a finite value. Symbolic ones only have to avoid dividing by zero.
+pub(crate) fn is_finite(elem: &ExpressionElement) -> bool {
+    if !elem.is_symbolic() {
+        return elem.approximate_complex().is_finite();
+    }
+
+    match elem {
+        ExpressionElement::Function(f) => match f.as_ref() {
+            MathFunction::Divide(op) => !is_zero(op.rhs()),
+            MathFunction::Fraction(frac) => !is_zero(frac.den()),
+            _ => true,
+        },
+        _ => true,
+    }
+}
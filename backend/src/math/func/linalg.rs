@@ -0,0 +1,141 @@
+use crate::{
+    math::{
+        func::{
+            decl::{Det, Eig, Rank, Trace, Transpose},
+            Function,
+        },
+        matrix::{Matrix, MatrixKind},
+        symbol::Number,
+        ExpressionElement, MathElement,
+    },
+    DecimalScalar,
+};
+
+impl Function for Transpose {
+    fn evaluate(&self) -> MathElement {
+        transpose(self.x().evaluate().into()).into()
+    }
+
+    /// Scalars are their own transposes, while matrices don't have a scalar value.
+    #[inline]
+    fn approximate(&self) -> DecimalScalar {
+        self.x().approximate()
+    }
+}
+
+impl Function for Det {
+    fn evaluate(&self) -> MathElement {
+        det(self.x().evaluate().into()).into()
+    }
+
+    fn approximate(&self) -> DecimalScalar {
+        match approximate_matrix(self.x()) {
+            Some(m) => m
+                .determinant()
+                .map_or(DecimalScalar::NAN, |det| det.approximate()),
+            None => self.x().approximate(),
+        }
+    }
+}
+
+impl Function for Rank {
+    fn evaluate(&self) -> MathElement {
+        rank(self.x().evaluate().into()).into()
+    }
+
+    fn approximate(&self) -> DecimalScalar {
+        match approximate_matrix(self.x()) {
+            Some(m) => m.rank() as DecimalScalar,
+            None => (self.x().approximate() != 0.) as u8 as DecimalScalar,
+        }
+    }
+}
+
+impl Function for Trace {
+    fn evaluate(&self) -> MathElement {
+        trace(self.x().evaluate().into()).into()
+    }
+
+    fn approximate(&self) -> DecimalScalar {
+        match approximate_matrix(self.x()) {
+            Some(m) => m.trace().map_or(DecimalScalar::NAN, |tr| tr.approximate()),
+            None => self.x().approximate(),
+        }
+    }
+}
+
+impl Function for Eig {
+    fn evaluate(&self) -> MathElement {
+        eig(self.x().evaluate().into()).into()
+    }
+
+    /// Eigenvalues are vectors, unless the parameter is a scalar.
+    fn approximate(&self) -> DecimalScalar {
+        match approximate_matrix(self.x()) {
+            Some(_) => DecimalScalar::NAN,
+            None => self.x().approximate(),
+        }
+    }
+}
+
+/// Evaluates `x` and turns it into a matrix of decimals, `None` if it's not a matrix.
+fn approximate_matrix(x: &ExpressionElement) -> Option<Matrix> {
+    let m = match x {
+        ExpressionElement::Matrix(m) => m.clone(),
+        _ => match x.evaluate().into() {
+            ExpressionElement::Matrix(m) => m,
+            _ => return None,
+        },
+    };
+
+    Some(m.map(|e| Number::Decimal(e.approximate()).into()))
+}
+
+pub(crate) fn transpose(x: ExpressionElement) -> ExpressionElement {
+    match x {
+        ExpressionElement::Matrix(m) => ExpressionElement::Matrix(m.transpose()),
+        ExpressionElement::Number(_) => x,
+        _ => Transpose::new(x).into(),
+    }
+}
+
+pub(crate) fn det(x: ExpressionElement) -> ExpressionElement {
+    match &x {
+        ExpressionElement::Matrix(m) => m.determinant().unwrap_or_else(|| Det::new(x).into()),
+        ExpressionElement::Number(_) => x,
+        _ => Det::new(x).into(),
+    }
+}
+
+pub(crate) fn rank(x: ExpressionElement) -> ExpressionElement {
+    match &x {
+        ExpressionElement::Matrix(m) => Number::Integer(m.rank() as i64).into(),
+        ExpressionElement::Number(_) => Number::Integer(!x.is_integer(0) as i64).into(),
+        _ => Rank::new(x).into(),
+    }
+}
+
+pub(crate) fn trace(x: ExpressionElement) -> ExpressionElement {
+    match &x {
+        ExpressionElement::Matrix(m) => m.trace().unwrap_or_else(|| Trace::new(x).into()),
+        ExpressionElement::Number(_) => x,
+        _ => Trace::new(x).into(),
+    }
+}
+
+/// The eigenvalues as a column vector.
+pub(crate) fn eig(x: ExpressionElement) -> ExpressionElement {
+    match &x {
+        ExpressionElement::Matrix(m) => m
+            .eigenvalues()
+            .and_then(|eig| {
+                Matrix::new(
+                    MatrixKind::PMatrix,
+                    eig.into_iter().map(|lambda| vec![lambda]).collect(),
+                )
+            })
+            .map_or_else(|| Eig::new(x).into(), ExpressionElement::Matrix),
+        ExpressionElement::Number(_) => x,
+        _ => Eig::new(x).into(),
+    }
+}
@@ -2,17 +2,31 @@ use std::fmt::Debug;
 
 use crate::{
     math::{ExpressionElement, IntoRawExpr, MathElement, Prioritizable},
-    DecimalScalar,
+    ComplexScalar, DecimalScalar,
+};
+
+#[cfg(feature = "bignum")]
+use crate::{
+    math::{precise, symbol::Number},
+    BigDecimalScalar,
 };
 
 use self::decl::MathFunction;
 
 pub mod decl;
+pub mod diff;
 pub mod exp_log;
+pub mod inequality;
 pub mod integ;
+pub mod limit;
+pub mod linalg;
 pub mod op;
+pub mod series;
+pub mod solve;
 pub mod sp;
+pub mod sum;
 pub mod trig;
+pub mod user;
 
 pub trait AsPhantomFunction {}
 
@@ -24,6 +38,54 @@ pub trait PhantomFunction: Debug + Prioritizable {
 pub trait Function: Debug + IntoRawExpr {
     fn evaluate(&self) -> MathElement;
     fn approximate(&self) -> DecimalScalar;
+
+    /// Approximates the value in the complex plane, so things like `\sqrt{-4}` don't end up
+    /// as `NaN`.
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        self.approximate().into()
+    }
+
+    /// Approximates the value with `precision` significant digits, `None` if it's not a finite
+    /// real number.
+    ///
+    /// Functions without an arbitrary precision implementation only have a value here if they
+    /// [`Function::evaluate`] to an exact number, since widening [`Function::approximate`]
+    /// would make up the missing digits.
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        match ExpressionElement::from(self.evaluate()) {
+            ExpressionElement::Number(n)
+                if matches!(n, Number::BigDecimal(_)) || precise::as_big_rational(&n).is_some() =>
+            {
+                n.approximate_precise(precision)
+            }
+            _ => None,
+        }
+    }
+}
+
+pub trait Differentiable {
+    /// The derivative with respect to `var`, where other variables are treated as constants.
+    fn derivative(&self, var: &str) -> ExpressionElement;
+}
+
+/// Applies `real` if `x` is real and so is the result, and `complex` otherwise.
+///
+/// Real results then stay free from the rounding errors of complex arithmetic.
+pub(crate) fn real_or_complex(
+    x: ComplexScalar,
+    real: impl FnOnce(DecimalScalar) -> DecimalScalar,
+    complex: impl FnOnce(ComplexScalar) -> ComplexScalar,
+) -> ComplexScalar {
+    if x.im == 0. {
+        let result = real(x.re);
+        if !result.is_nan() {
+            return result.into();
+        }
+    }
+
+    complex(x)
 }
 
 pub trait PhantomOperator: Debug + Prioritizable {
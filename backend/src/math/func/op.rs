@@ -1,73 +1,633 @@
 use crate::{
     math::{
         func::{
-            decl::{Add, Divide, Fraction, Multiply, Subtract},
-            Function, Operator,
+            decl::{Abs, Add, Divide, Fraction, MathFunction, Multiply, Subtract},
+            exp_log, Function, Operator,
         },
-        MathElement,
+        matrix::Matrix,
+        symbol::{Constant, Number, Rational},
+        ExpressionElement, MathElement,
     },
-    DecimalScalar,
+    ComplexScalar, DecimalScalar,
 };
 
+#[cfg(feature = "bignum")]
+use crate::{math::precise, BigDecimalScalar};
+
 impl Operator for Add {}
 
 impl Function for Add {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        add(self.lhs().evaluate().into(), self.rhs().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.lhs().approximate() + self.rhs().approximate()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        self.lhs().approximate_complex() + self.rhs().approximate_complex()
+    }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        let (lhs, rhs) = (
+            self.lhs().approximate_precise(precision)?,
+            self.rhs().approximate_precise(precision)?,
+        );
+        Some((lhs + rhs).with_prec(precision))
+    }
 }
 
 impl Operator for Subtract {}
 
 impl Function for Subtract {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        subtract(self.lhs().evaluate().into(), self.rhs().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.lhs().approximate() - self.rhs().approximate()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        self.lhs().approximate_complex() - self.rhs().approximate_complex()
+    }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        let (lhs, rhs) = (
+            self.lhs().approximate_precise(precision)?,
+            self.rhs().approximate_precise(precision)?,
+        );
+        Some((lhs - rhs).with_prec(precision))
+    }
 }
 
 impl Operator for Multiply {}
 
 impl Function for Multiply {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        multiply(self.lhs().evaluate().into(), self.rhs().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.lhs().approximate() * self.rhs().approximate()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        self.lhs().approximate_complex() * self.rhs().approximate_complex()
+    }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        let (lhs, rhs) = (
+            self.lhs().approximate_precise(precision)?,
+            self.rhs().approximate_precise(precision)?,
+        );
+        Some((lhs * rhs).with_prec(precision))
+    }
 }
 
 impl Operator for Divide {}
 
 impl Function for Divide {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        divide(self.lhs().evaluate().into(), self.rhs().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.lhs().approximate() / self.rhs().approximate()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        divide_complex(
+            self.lhs().approximate_complex(),
+            self.rhs().approximate_complex(),
+        )
+    }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        precise::divide(
+            &self.lhs().approximate_precise(precision)?,
+            &self.rhs().approximate_precise(precision)?,
+            precision,
+        )
+    }
 }
 
 impl Function for Fraction {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        let (num, den): (ExpressionElement, ExpressionElement) =
+            (self.num().evaluate().into(), self.den().evaluate().into());
+        // Division by zero, leave it as the fraction it was written as.
+        if den.is_integer(0) {
+            return MathElement::Function(MathFunction::Fraction(Fraction::new(num, den)));
+        }
+        divide(num, den).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.num().approximate() / self.den().approximate()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        divide_complex(
+            self.num().approximate_complex(),
+            self.den().approximate_complex(),
+        )
+    }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        precise::divide(
+            &self.num().approximate_precise(precision)?,
+            &self.den().approximate_precise(precision)?,
+            precision,
+        )
+    }
+}
+
+impl Function for Abs {
+    fn evaluate(&self) -> MathElement {
+        abs(self.x().evaluate().into()).into()
+    }
+
+    #[inline]
+    fn approximate(&self) -> DecimalScalar {
+        self.x().approximate().abs()
+    }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        self.x().approximate_complex().norm().into()
+    }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        Some(self.x().approximate_precise(precision)?.abs())
+    }
+}
+
+/// Divides complex numbers, keeping the real division for reals so `\frac{1}{0}` is still
+/// infinity.
+#[inline]
+fn divide_complex(lhs: ComplexScalar, rhs: ComplexScalar) -> ComplexScalar {
+    if lhs.im == 0. && rhs.im == 0. {
+        (lhs.re / rhs.re).into()
+    } else {
+        lhs / rhs
+    }
+}
+
+/// Approximates an element that can't be kept exact, as one of its operands is a decimal.
+pub(crate) fn decimal(elem: ExpressionElement) -> ExpressionElement {
+    // Variables have no value, keep them around.
+    if elem.is_symbolic() {
+        return elem;
+    }
+
+    #[cfg(feature = "bignum")]
+    if let Some(d) = elem.approximate_precise(precise::precision() + precise::GUARD_DIGITS) {
+        return Number::BigDecimal(precise::round(&d, precise::precision())).into();
+    }
+
+    let real = elem.approximate();
+    if real.is_nan() {
+        let complex = elem.approximate_complex();
+        if !complex.is_nan() {
+            return Number::from_complex(complex).into();
+        }
+    }
+
+    Number::Decimal(real).into()
+}
+
+/// Returns the numbers if both elements are numbers and no symbolic constant is involved.
+#[inline]
+fn as_plain_numbers(lhs: &ExpressionElement, rhs: &ExpressionElement) -> Option<(Number, Number)> {
+    match (lhs, rhs) {
+        (ExpressionElement::Number(lhs), ExpressionElement::Number(rhs))
+            if !matches!(lhs, Number::Constant(_)) && !matches!(rhs, Number::Constant(_)) =>
+        {
+            Some((lhs.clone(), rhs.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Splits an element into its rational coefficient and the remaining irrational part.
+///
+/// `\frac{3\sqrt{2}}{4}` is split into `\frac{3}{4}` and `\sqrt{2}`.
+pub(crate) fn split_coefficient(
+    elem: &ExpressionElement,
+) -> (Rational, Option<&ExpressionElement>) {
+    if let Some(r) = elem.as_rational() {
+        return (r, None);
+    }
+
+    if let ExpressionElement::Function(f) = elem {
+        match f.as_ref() {
+            MathFunction::Multiply(mul) => {
+                if let Some(r) = mul.lhs().as_rational() {
+                    return (r, Some(mul.rhs()));
+                }
+                if let Some(r) = mul.rhs().as_rational() {
+                    return (r, Some(mul.lhs()));
+                }
+            }
+            MathFunction::Fraction(_) | MathFunction::Divide(_) => {
+                let (num, den) = match f.as_ref() {
+                    MathFunction::Fraction(frac) => (frac.num(), frac.den()),
+                    MathFunction::Divide(div) => (div.lhs(), div.rhs()),
+                    _ => unreachable!(),
+                };
+
+                if let Some(den) = den.as_rational() {
+                    let (coeff, rest) = split_coefficient(num);
+                    if let Some(coeff) = coeff.checked_div(den) {
+                        return (coeff, rest);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (Rational::ONE, Some(elem))
+}
+
+/// The inverse of [`split_coefficient`].
+pub(crate) fn join_coefficient(
+    coeff: Rational,
+    rest: Option<ExpressionElement>,
+) -> ExpressionElement {
+    let Some(rest) = rest else {
+        return Number::from(coeff).into();
+    };
+
+    match (coeff.num(), coeff.den()) {
+        (0, _) => Number::Integer(0).into(),
+        (1, 1) => rest,
+        (n, 1) => Multiply::new(Number::Integer(n).into(), rest).into(),
+        (1, d) => Fraction::new(rest, Number::Integer(d).into()).into(),
+        (n, d) => Fraction::new(
+            Multiply::new(Number::Integer(n).into(), rest).into(),
+            Number::Integer(d).into(),
+        )
+        .into(),
+    }
+}
+
+/// Splits `a+bi` with rational `a` and `b` into its parts.
+pub(crate) fn as_gaussian(elem: &ExpressionElement) -> Option<(Rational, Rational)> {
+    match split_coefficient(elem) {
+        (coeff, None) => return Some((coeff, Rational::ZERO)),
+        (coeff, Some(rest)) if rest.is_imaginary_unit() => return Some((Rational::ZERO, coeff)),
+        _ => {}
+    }
+
+    let ExpressionElement::Function(f) = elem else {
+        return None;
+    };
+    match f.as_ref() {
+        MathFunction::Add(op) => {
+            let ((a, b), (c, d)) = (as_gaussian(op.lhs())?, as_gaussian(op.rhs())?);
+            Some((a.checked_add(c)?, b.checked_add(d)?))
+        }
+        MathFunction::Subtract(op) => {
+            let ((a, b), (c, d)) = (as_gaussian(op.lhs())?, as_gaussian(op.rhs())?);
+            Some((a.checked_sub(c)?, b.checked_sub(d)?))
+        }
+        _ => None,
+    }
+}
+
+/// `a+bi`, the inverse of [`as_gaussian`].
+pub(crate) fn from_gaussian(re: Rational, im: Rational) -> ExpressionElement {
+    add(
+        Number::from(re).into(),
+        join_coefficient(im, Some(Number::Constant(Constant::I).into())),
+    )
+}
+
+/// Evaluates operations involving matrices, where `scalar` is applied elementwise if only one
+/// operand is a matrix. Operations that are undefined for the operands are kept as they are.
+fn matrix_op<T: Into<ExpressionElement>>(
+    lhs: ExpressionElement,
+    rhs: ExpressionElement,
+    matrices: impl FnOnce(&Matrix, &Matrix) -> Option<Matrix>,
+    scalar: Option<fn(ExpressionElement, ExpressionElement) -> ExpressionElement>,
+    unevaluated: fn(ExpressionElement, ExpressionElement) -> T,
+) -> ExpressionElement {
+    let result = match (&lhs, &rhs) {
+        (ExpressionElement::Matrix(lhs), ExpressionElement::Matrix(rhs)) => matrices(lhs, rhs),
+        (ExpressionElement::Matrix(m), s) => scalar.map(|f| m.map(|e| f(e.clone(), s.clone()))),
+        (s, ExpressionElement::Matrix(m)) => scalar.map(|f| m.map(|e| f(s.clone(), e.clone()))),
+        _ => None,
+    };
+
+    result.map_or_else(|| unevaluated(lhs, rhs).into(), Into::into)
+}
+
+/// Collects the terms of a sum, along with their rational coefficients.
+fn collect_terms(
+    elem: &ExpressionElement,
+    negate: bool,
+    terms: &mut Vec<(Rational, Option<ExpressionElement>)>,
+) -> Option<()> {
+    if let ExpressionElement::Function(f) = elem {
+        match f.as_ref() {
+            MathFunction::Add(add) => {
+                collect_terms(add.lhs(), negate, terms)?;
+                return collect_terms(add.rhs(), negate, terms);
+            }
+            MathFunction::Subtract(sub) => {
+                collect_terms(sub.lhs(), negate, terms)?;
+                return collect_terms(sub.rhs(), !negate, terms);
+            }
+            _ => {}
+        }
+    }
+
+    let (coeff, rest) = split_coefficient(elem);
+    let coeff = if negate { coeff.checked_neg()? } else { coeff };
+
+    match terms.iter_mut().find(|(_, r)| r.as_ref() == rest) {
+        Some((c, _)) => *c = c.checked_add(coeff)?,
+        None => terms.push((coeff, rest.cloned())),
+    }
+
+    Some(())
+}
+
+pub(crate) fn add(lhs: ExpressionElement, rhs: ExpressionElement) -> ExpressionElement {
+    if lhs.is_matrix() || rhs.is_matrix() {
+        return matrix_op(lhs, rhs, |l, r| l.zip_with(r, add), None, Add::new);
+    }
+    if let Some((lhs, rhs)) = as_plain_numbers(&lhs, &rhs) {
+        return (lhs + rhs).into();
+    }
+    if lhs.is_decimal() || rhs.is_decimal() {
+        return decimal(Add::new(lhs, rhs).into());
+    }
+
+    let mut terms = Vec::new();
+    if collect_terms(&lhs, false, &mut terms)
+        .and_then(|_| collect_terms(&rhs, false, &mut terms))
+        .is_none()
+    {
+        return decimal(Add::new(lhs, rhs).into());
+    }
+
+    terms
+        .into_iter()
+        .filter(|(coeff, _)| *coeff != Rational::ZERO)
+        .fold(None, |sum, (coeff, rest)| {
+            let Some(sum) = sum else {
+                return Some(join_coefficient(coeff, rest));
+            };
+
+            Some(match coeff.checked_neg() {
+                Some(neg) if coeff.is_negative() => {
+                    Subtract::new(sum, join_coefficient(neg, rest)).into()
+                }
+                _ => Add::new(sum, join_coefficient(coeff, rest)).into(),
+            })
+        })
+        .unwrap_or(Number::Integer(0).into())
+}
+
+#[inline]
+pub(crate) fn negate(elem: ExpressionElement) -> ExpressionElement {
+    multiply(Number::Integer(-1).into(), elem)
+}
+
+#[inline]
+pub(crate) fn subtract(lhs: ExpressionElement, rhs: ExpressionElement) -> ExpressionElement {
+    if lhs.is_matrix() || rhs.is_matrix() {
+        return matrix_op(
+            lhs,
+            rhs,
+            |l, r| l.zip_with(r, subtract),
+            None,
+            Subtract::new,
+        );
+    }
+    if let Some((lhs, rhs)) = as_plain_numbers(&lhs, &rhs) {
+        return (lhs - rhs).into();
+    }
+    if lhs.is_decimal() || rhs.is_decimal() {
+        return decimal(Subtract::new(lhs, rhs).into());
+    }
+
+    add(lhs, negate(rhs))
+}
+
+pub(crate) fn multiply(lhs: ExpressionElement, rhs: ExpressionElement) -> ExpressionElement {
+    if lhs.is_matrix() || rhs.is_matrix() {
+        return matrix_op(lhs, rhs, Matrix::mul, Some(multiply), Multiply::new);
+    }
+    if let Some((lhs, rhs)) = as_plain_numbers(&lhs, &rhs) {
+        return (lhs * rhs).into();
+    }
+    if lhs.is_decimal() || rhs.is_decimal() {
+        return decimal(Multiply::new(lhs, rhs).into());
+    }
+
+    let (lhs_coeff, lhs_rest) = split_coefficient(&lhs);
+    let (rhs_coeff, rhs_rest) = split_coefficient(&rhs);
+
+    let Some(coeff) = lhs_coeff.checked_mul(rhs_coeff) else {
+        return decimal(Multiply::new(lhs, rhs).into());
+    };
+
+    let rest = match (lhs_rest, rhs_rest) {
+        (None, None) => None,
+        (Some(rest), None) | (None, Some(rest)) => Some(rest.clone()),
+        (Some(lhs_rest), Some(rhs_rest)) => {
+            if let Some(product) = multiply_roots(lhs_rest, rhs_rest) {
+                return multiply(Number::from(coeff).into(), product);
+            }
+
+            if lhs_rest == rhs_rest {
+                // The square might be rational again, like `i^2`.
+                return multiply(
+                    Number::from(coeff).into(),
+                    exp_log::power(lhs_rest.clone(), Number::Integer(2).into()),
+                );
+            } else {
+                Some(Multiply::new(lhs_rest.clone(), rhs_rest.clone()).into())
+            }
+        }
+    };
+
+    join_coefficient(coeff, rest)
+}
+
+/// `\sqrt[n]{a}*\sqrt[n]{b}=\sqrt[n]{ab}` for rational `a` and `b`.
+fn multiply_roots(lhs: &ExpressionElement, rhs: &ExpressionElement) -> Option<ExpressionElement> {
+    let (ExpressionElement::Function(lhs), ExpressionElement::Function(rhs)) = (lhs, rhs) else {
+        return None;
+    };
+    let (MathFunction::Root(lhs), MathFunction::Root(rhs)) = (lhs.as_ref(), rhs.as_ref()) else {
+        return None;
+    };
+
+    if lhs.rad() != rhs.rad() {
+        return None;
+    }
+
+    let radicand = lhs
+        .deg()
+        .as_rational()?
+        .checked_mul(rhs.deg().as_rational()?)?;
+    Some(exp_log::root(
+        lhs.rad().clone(),
+        Number::from(radicand).into(),
+    ))
+}
+
+pub(crate) fn divide(lhs: ExpressionElement, rhs: ExpressionElement) -> ExpressionElement {
+    if lhs.is_matrix() || rhs.is_matrix() {
+        // Only matrices divided by scalars are elementwise.
+        let scalar = (!rhs.is_matrix()).then_some(divide as fn(_, _) -> _);
+        return matrix_op(lhs, rhs, |_, _| None, scalar, Divide::new);
+    }
+    // Division by zero, leave it as is.
+    if rhs.is_integer(0) {
+        return Divide::new(lhs, rhs).into();
+    }
+    if let Some((lhs, rhs)) = as_plain_numbers(&lhs, &rhs) {
+        return (lhs / rhs).into();
+    }
+    if lhs.is_decimal() || rhs.is_decimal() {
+        return decimal(Divide::new(lhs, rhs).into());
+    }
+
+    let (lhs_coeff, lhs_rest) = split_coefficient(&lhs);
+    let (rhs_coeff, rhs_rest) = split_coefficient(&rhs);
+
+    let Some(coeff) = lhs_coeff.checked_div(rhs_coeff) else {
+        return decimal(Divide::new(lhs, rhs).into());
+    };
+
+    let rest = match (lhs_rest, rhs_rest) {
+        (rest, None) => rest.cloned(),
+        (Some(lhs_rest), Some(rhs_rest)) if lhs_rest == rhs_rest => None,
+        (lhs_rest, Some(rhs_rest)) => {
+            // \frac{x}{i}=-xi
+            if rhs_rest.is_imaginary_unit() {
+                let num = lhs_rest.cloned().unwrap_or(Number::Integer(1).into());
+                return multiply(
+                    Number::from(coeff).into(),
+                    negate(multiply(num, rhs_rest.clone())),
+                );
+            }
+
+            // Rationalize the denominator of square roots.
+            if let ExpressionElement::Function(f) = rhs_rest {
+                if let MathFunction::Root(root) = f.as_ref() {
+                    if let (true, Some(n)) = (root.rad().is_integer(2), root.deg().as_rational()) {
+                        if n.is_integer() {
+                            let num = match lhs_rest {
+                                Some(lhs_rest) => multiply(lhs_rest.clone(), rhs_rest.clone()),
+                                None => rhs_rest.clone(),
+                            };
+                            return multiply(
+                                Number::from(coeff).into(),
+                                divide(num, Number::from(n).into()),
+                            );
+                        }
+                    }
+                }
+            }
+
+            let num = lhs_rest.cloned().unwrap_or(Number::Integer(1).into());
+            return multiply(
+                Number::from(coeff).into(),
+                Fraction::new(num, rhs_rest.clone()).into(),
+            );
+        }
+    };
+
+    join_coefficient(coeff, rest)
+}
+
+/// The absolute value, where matrices are taken elementwise.
+pub(crate) fn abs(elem: ExpressionElement) -> ExpressionElement {
+    if let ExpressionElement::Matrix(m) = &elem {
+        return m.map(|e| abs(e.clone())).into();
+    }
+    if elem.is_decimal() {
+        return decimal(Abs::new(elem).into());
+    }
+
+    let (coeff, rest) = split_coefficient(&elem);
+    let Some(coeff) = (if coeff.is_negative() {
+        coeff.checked_neg()
+    } else {
+        Some(coeff)
+    }) else {
+        return decimal(Abs::new(elem).into());
+    };
+
+    let rest = match rest {
+        // |i|=1
+        Some(rest) if rest.is_imaginary_unit() => None,
+        Some(rest) if is_nonnegative(rest, &|_| false) => Some(rest.clone()),
+        Some(rest) => Some(Abs::new(rest.clone()).into()),
+        None => None,
+    };
+    join_coefficient(coeff, rest)
+}
+
+/// Checks if an element is known to be a non-negative real number, where `assumed` tells
+/// which variables are.
+pub(crate) fn is_nonnegative(elem: &ExpressionElement, assumed: &dyn Fn(&str) -> bool) -> bool {
+    match elem {
+        ExpressionElement::Number(Number::Constant(c)) => !matches!(c, Constant::I),
+        ExpressionElement::Number(Number::Complex(_)) => false,
+        ExpressionElement::Number(n) => n.approximate() >= 0.,
+        ExpressionElement::Variable(v) => assumed(v),
+        ExpressionElement::Matrix(_) => false,
+        ExpressionElement::Function(f) => match f.as_ref() {
+            MathFunction::Abs(_) | MathFunction::Cosh(_) => true,
+            // Even powers, or any real power of a non-negative base.
+            MathFunction::Power(pow) => {
+                pow.exp()
+                    .as_rational()
+                    .is_some_and(|e| e.is_integer() && e.num() % 2 == 0)
+                    || is_nonnegative(pow.base(), assumed) && !pow.exp().is_imaginary_unit()
+            }
+            // Principal roots of even degree are only real for non-negative radicands.
+            MathFunction::Root(root) => {
+                root.rad().is_integer(2) || is_nonnegative(root.deg(), assumed)
+            }
+            MathFunction::Add(add) => {
+                is_nonnegative(add.lhs(), assumed) && is_nonnegative(add.rhs(), assumed)
+            }
+            MathFunction::Multiply(mul) => {
+                is_nonnegative(mul.lhs(), assumed) && is_nonnegative(mul.rhs(), assumed)
+            }
+            MathFunction::Divide(div) => {
+                is_nonnegative(div.lhs(), assumed) && is_nonnegative(div.rhs(), assumed)
+            }
+            MathFunction::Fraction(frac) => {
+                is_nonnegative(frac.num(), assumed) && is_nonnegative(frac.den(), assumed)
+            }
+            _ => false,
+        },
+    }
 }
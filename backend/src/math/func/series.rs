@@ -0,0 +1,476 @@
+//! Taylor and Laurent series, found by multiplying and dividing truncated power series and by
+//! expanding functions with their derivatives.
+
+use crate::{
+    math::{
+        func::{
+            decl::{BigO, MathFunction, Series},
+            exp_log::{log, power},
+            limit::{infinite_sign, integer, is_finite, is_zero},
+            op::{add, divide, multiply, negate, split_coefficient, subtract},
+            trig::{cos, cosh, sin, sinh},
+            Differentiable, Function,
+        },
+        symbol::{Constant, Number, Rational},
+        ExpressionElement, MathElement,
+    },
+    ComplexScalar, DecimalScalar, IntegerScalar,
+};
+
+/// Series are only worked out up to this order, as the work grows quickly with it.
+pub const MAX_ORDER: IntegerScalar = 64;
+/// How many more terms than asked for are worked out at most, as dividing by series that start
+/// at higher powers loses some of them.
+const MAX_EXTRA_TERMS: i32 = 8;
+
+impl Series {
+    /// The name of the variable the series is in.
+    #[inline]
+    pub fn var_name(&self) -> Option<&str> {
+        match self.var() {
+            ExpressionElement::Variable(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl Function for Series {
+    /// Series that can't be found stay as they are.
+    fn evaluate(&self) -> MathElement {
+        let s = Series::new(
+            self.var().clone(),
+            self.point().evaluate().into(),
+            self.order().evaluate().into(),
+            self.f().evaluate().into(),
+        );
+        let order = s
+            .order()
+            .as_rational()
+            .filter(|r| r.is_integer())
+            .map(|r| r.num());
+
+        match s
+            .var_name()
+            .zip(order)
+            .and_then(|(var, order)| series(s.f(), var, s.point(), order))
+        {
+            Some(value) => value.into(),
+            None => MathElement::Function(MathFunction::Series(s)),
+        }
+    }
+
+    fn approximate(&self) -> DecimalScalar {
+        match self.evaluate() {
+            MathElement::Function(MathFunction::Series(_)) => DecimalScalar::NAN,
+            s => ExpressionElement::from(s).approximate(),
+        }
+    }
+
+    fn approximate_complex(&self) -> ComplexScalar {
+        match self.evaluate() {
+            MathElement::Function(MathFunction::Series(_)) => {
+                ComplexScalar::new(DecimalScalar::NAN, 0.)
+            }
+            s => ExpressionElement::from(s).approximate_complex(),
+        }
+    }
+}
+
+impl Function for BigO {
+    fn evaluate(&self) -> MathElement {
+        MathElement::Function(MathFunction::BigO(BigO::new(self.x().evaluate().into())))
+    }
+
+    /// The remainder is dropped, so series approximate like their truncated polynomials.
+    #[inline]
+    fn approximate(&self) -> DecimalScalar {
+        0.
+    }
+}
+
+/// The series of `f` in `var` around `point` with the terms below `(var-point)^{order}`,
+/// followed by the remainder like `O(x^{6})`. `None` if it can't be found, like at essential
+/// singularities or branch points.
+pub fn series(
+    f: &ExpressionElement,
+    var: &str,
+    point: &ExpressionElement,
+    order: IntegerScalar,
+) -> Option<ExpressionElement> {
+    if infinite_sign(point).is_some() || !(1..=MAX_ORDER).contains(&order) {
+        return None;
+    }
+    let order = i32::try_from(order).ok()?;
+
+    let s = (0..=MAX_EXTRA_TERMS)
+        .step_by(2)
+        .filter_map(|extra| expand(f, var, point, order.checked_add(extra)?))
+        .find(|s| s.order >= order)?;
+
+    let t = subtract(ExpressionElement::Variable(var.to_string()), point.clone());
+    let t_pow = |n: i32| {
+        let n = n as IntegerScalar;
+        if n < 0 {
+            divide(integer(1), power(t.clone(), integer(-n)))
+        } else {
+            power(t.clone(), integer(n))
+        }
+    };
+
+    Some(
+        (s.start..order)
+            .map(|n| (n, s.coeff(n)))
+            .filter(|(_, c)| !is_zero(c))
+            .map(|(n, c)| multiply(c, t_pow(n)))
+            .chain(Some(BigO::new(t_pow(order)).into()))
+            .reduce(add)
+            .unwrap(),
+    )
+}
+
+/// A power series in `t=x-a`, which is known up to but excluding `t^{order}`.
+#[derive(Debug, Clone)]
+struct PowerSeries {
+    /// The power of the first coefficient, which is negative for Laurent series.
+    start: i32,
+    coeffs: Vec<ExpressionElement>,
+    order: i32,
+}
+
+impl PowerSeries {
+    fn new(start: i32, mut coeffs: Vec<ExpressionElement>, order: i32) -> Self {
+        let start = start.min(order);
+        coeffs.resize((order - start) as usize, integer(0));
+        Self {
+            start,
+            coeffs,
+            order,
+        }
+    }
+
+    #[inline]
+    fn constant(c: ExpressionElement, order: i32) -> Self {
+        Self::new(0, vec![c], order)
+    }
+
+    fn coeff(&self, n: i32) -> ExpressionElement {
+        if n < self.start || n >= self.order {
+            integer(0)
+        } else {
+            self.coeffs[(n - self.start) as usize].clone()
+        }
+    }
+
+    /// The power of the first coefficient that isn't zero, `None` if all of them are.
+    fn valuation(&self) -> Option<i32> {
+        self.coeffs
+            .iter()
+            .position(|c| !is_zero(c))
+            .map(|i| self.start + i as i32)
+    }
+
+    fn add(&self, rhs: &Self, subtracting: bool) -> Self {
+        let (start, order) = (self.start.min(rhs.start), self.order.min(rhs.order));
+        let coeffs = (start..order)
+            .map(|n| {
+                let (lhs, rhs) = (self.coeff(n), rhs.coeff(n));
+                let sum = if subtracting {
+                    subtract(lhs, rhs)
+                } else {
+                    add(lhs, rhs)
+                };
+                sum.evaluate().into()
+            })
+            .collect();
+        Self::new(start, coeffs, order)
+    }
+
+    fn mul(&self, rhs: &Self) -> Self {
+        let va = self.valuation().unwrap_or(self.order);
+        let vb = rhs.valuation().unwrap_or(rhs.order);
+        let order = (self.order + vb).min(rhs.order + va);
+        let coeffs = (va + vb..order)
+            .map(|n| {
+                (va..=n - vb)
+                    .map(|i| (self.coeff(i), rhs.coeff(n - i)))
+                    .filter(|(a, b)| !is_zero(a) && !is_zero(b))
+                    .fold(integer(0), |sum, (a, b)| add(sum, multiply(a, b)))
+                    .evaluate()
+                    .into()
+            })
+            .collect();
+        Self::new(va + vb, coeffs, order)
+    }
+
+    /// `\frac{1}{u}`, `None` if all coefficients are zero.
+    fn recip(&self) -> Option<Self> {
+        let v = self.valuation()?;
+        let lead = self.coeff(v);
+
+        // The coefficients of u\frac{1}{u}=1 besides the first one are all zero.
+        let mut coeffs: Vec<ExpressionElement> = Vec::new();
+        for n in 0..self.order - v {
+            let c = if n == 0 {
+                divide(integer(1), lead.clone())
+            } else {
+                let sum = (1..=n)
+                    .map(|k| multiply(self.coeff(v + k), coeffs[(n - k) as usize].clone()))
+                    .fold(integer(0), add);
+                divide(negate(sum), lead.clone())
+            };
+            coeffs.push(c.evaluate().into());
+        }
+
+        Some(Self::new(-v, coeffs, self.order - 2 * v))
+    }
+
+    #[inline]
+    fn div(&self, rhs: &Self) -> Option<Self> {
+        Some(self.mul(&rhs.recip()?))
+    }
+
+    fn pow(&self, exp: IntegerScalar) -> Option<Self> {
+        if exp < 0 {
+            return self.pow(-exp)?.recip();
+        }
+
+        let mut result = Self::constant(integer(1), self.order);
+        let (mut base, mut exp) = (self.clone(), exp);
+        while exp > 0 {
+            if exp % 2 == 1 {
+                result = result.mul(&base);
+            }
+            exp /= 2;
+            if exp > 0 {
+                base = base.mul(&base);
+            }
+        }
+        Some(result)
+    }
+
+    /// Multiplies the series by `t^{n}`.
+    #[inline]
+    fn shift(mut self, n: i32) -> Self {
+        self.start += n;
+        self.order += n;
+        self
+    }
+
+    /// `F(u)` from the Taylor series of `F` around the constant term of `u`, where `F` is given
+    /// as a function of `var` and this is `u`.
+    fn compose(&self, f: &ExpressionElement, var: &str) -> Option<Self> {
+        // Like e^{\frac{1}{x}}.
+        if self.valuation().is_some_and(|v| v < 0) {
+            return None;
+        }
+
+        let mut w = self.clone();
+        if let Some(c) = w.coeffs.get_mut(-w.start as usize) {
+            *c = integer(0);
+        }
+        let Some(vw) = w.valuation() else {
+            let value = taylor(f, var, &self.coeff(0), 1)?.coeff(0);
+            return Some(Self::constant(value, self.order));
+        };
+
+        // F(u_0+w)=\sum c_n w^n, where the terms below t^{order} come from w^n with nv<order.
+        let taylor = taylor(f, var, &self.coeff(0), (self.order - 1) / vw + 1)?;
+        let mut result = Self::constant(taylor.coeff(0), self.order);
+        let mut w_pow = Self::constant(integer(1), self.order);
+        for n in 1..taylor.order {
+            let c = taylor.coeff(n);
+            w_pow = w_pow.mul(&w);
+            let coeffs = w_pow
+                .coeffs
+                .iter()
+                .map(|a| multiply(c.clone(), a.clone()).evaluate().into())
+                .collect();
+            result = result.add(&Self::new(w_pow.start, coeffs, w_pow.order), false);
+        }
+
+        Some(result.truncate(taylor.order.saturating_mul(vw)))
+    }
+
+    /// Drops the terms from `t^{order}` on.
+    #[inline]
+    fn truncate(self, order: i32) -> Self {
+        let order = order.min(self.order);
+        Self::new(self.start, self.coeffs, order)
+    }
+}
+
+/// The Taylor series of `F` around `u0` up to `s^{order}` where `s=y-u_0`, and `F` is given as a
+/// function of `var`. It's the integral of the series of `F'`, which is simpler to find than
+/// taking `F'` over and over.
+fn taylor(
+    f: &ExpressionElement,
+    var: &str,
+    u0: &ExpressionElement,
+    order: i32,
+) -> Option<PowerSeries> {
+    let value = ExpressionElement::from(f.substitute(var, u0).evaluate());
+    if !is_finite(&value) {
+        return None;
+    }
+    if order <= 1 {
+        return Some(PowerSeries::constant(value, order));
+    }
+
+    let derivative = ExpressionElement::from(f.derivative(var).evaluate());
+    let d = expand(&derivative, var, u0, order - 1)?;
+    // Like \ln{y} at 0.
+    if d.valuation().is_some_and(|v| v < 0) {
+        return None;
+    }
+
+    let coeffs = Some(value)
+        .into_iter()
+        .chain((0..d.order).map(|n| {
+            divide(d.coeff(n), integer(n as IntegerScalar + 1))
+                .evaluate()
+                .into()
+        }))
+        .collect();
+    Some(PowerSeries::new(0, coeffs, d.order + 1))
+}
+
+/// Expands `elem` into a power series in `var` around `point`, truncated at `t^{order}`. The
+/// series may end earlier if some terms got lost on the way.
+fn expand(
+    elem: &ExpressionElement,
+    var: &str,
+    point: &ExpressionElement,
+    order: i32,
+) -> Option<PowerSeries> {
+    let sub = |elem: &ExpressionElement| expand(elem, var, point, order);
+
+    if !elem.depends_on(var) {
+        return Some(PowerSeries::constant(elem.clone(), order));
+    }
+    let func = match elem {
+        ExpressionElement::Variable(_) => {
+            return Some(PowerSeries::new(0, vec![point.clone(), integer(1)], order))
+        }
+        ExpressionElement::Function(func) => func,
+        _ => return None,
+    };
+
+    match func.as_ref() {
+        MathFunction::Add(op) => Some(sub(op.lhs())?.add(&sub(op.rhs())?, false)),
+        MathFunction::Subtract(op) => Some(sub(op.lhs())?.add(&sub(op.rhs())?, true)),
+        MathFunction::Multiply(op) => Some(sub(op.lhs())?.mul(&sub(op.rhs())?)),
+        MathFunction::Divide(op) => sub(op.lhs())?.div(&sub(op.rhs())?),
+        MathFunction::Fraction(frac) => sub(frac.num())?.div(&sub(frac.den())?),
+        // u^v=e^{v\ln u}
+        MathFunction::Power(pow) if pow.base().depends_on(var) && pow.exp().depends_on(var) => {
+            sub(&power(
+                Number::Constant(Constant::E).into(),
+                multiply(pow.exp().clone(), natural_log(pow.base().clone())),
+            ))
+        }
+        MathFunction::Power(pow) if pow.base().depends_on(var) => {
+            power_series(func, sub(pow.base())?, pow.exp(), var)
+        }
+        MathFunction::Root(rt) if !rt.rad().depends_on(var) => power_series(
+            func,
+            sub(rt.deg())?,
+            &divide(integer(1), rt.rad().clone()),
+            var,
+        ),
+        // \log_a b=\frac{\ln b}{\ln a}
+        MathFunction::Log(lg) if lg.base().depends_on(var) => sub(&divide(
+            natural_log(lg.anti().clone()),
+            natural_log(lg.base().clone()),
+        )),
+        MathFunction::Derivative(_)
+        | MathFunction::PartialDerivative(_)
+        | MathFunction::IndefiniteIntegral(_) => None,
+        _ if func.bound_var().is_some() => None,
+        _ => {
+            // Everything else depends on `var` through a single parameter.
+            let mut dependent = func.params().into_iter().filter(|p| p.depends_on(var));
+            let u = dependent.next().filter(|u| !u.is_matrix())?;
+            if dependent.next().is_some() {
+                return None;
+            }
+
+            let f = func.map_params(|p| {
+                if p.depends_on(var) {
+                    ExpressionElement::Variable(var.to_string())
+                } else {
+                    p.clone()
+                }
+            });
+            sub(u)?
+                .compose(&ExpressionElement::Function(Box::new(f)), var)
+                .or_else(|| sub(&as_quotient(func)?))
+        }
+    }
+}
+
+/// Expands `u^p` given the series of `u`, where `func` is either a power or a root.
+fn power_series(
+    func: &MathFunction,
+    u: PowerSeries,
+    exp: &ExpressionElement,
+    var: &str,
+) -> Option<PowerSeries> {
+    let exp = ExpressionElement::from(exp.evaluate());
+    let p = exp.as_rational();
+    // Integer powers keep the poles.
+    if let Some(p) = p.filter(|p| p.is_integer()) {
+        return u.pow(p.num());
+    }
+
+    let f = ExpressionElement::Function(Box::new(func.map_params(|p| {
+        if p.depends_on(var) {
+            ExpressionElement::Variable(var.to_string())
+        } else {
+            p.clone()
+        }
+    })));
+    let v = u.valuation()?;
+    if v == 0 {
+        return u.compose(&f, var);
+    }
+
+    // u^p=t^{pv}(\frac{u}{t^v})^p, as long as the root of t^v is t^{pv} on both sides of the
+    // point.
+    let p = p?;
+    let shift = p.checked_mul(Rational::from(v as IntegerScalar))?;
+    let (den, shift_den) = (p.den(), v as IntegerScalar / p.den());
+    if !shift.is_integer() || (den % 2 == 0 && shift_den % 2 != 0) {
+        return None;
+    }
+    Some(u.shift(-v).compose(&f, var)?.shift(shift.num() as i32))
+}
+
+/// Writes the reciprocal trigonometric and hyperbolic functions, which have poles, as quotients.
+fn as_quotient(func: &MathFunction) -> Option<ExpressionElement> {
+    let x = || func.params()[0].clone();
+    let (num, den) = match func {
+        MathFunction::Tan(_) => (sin(x()), cos(x())),
+        MathFunction::Cot(_) => (cos(x()), sin(x())),
+        MathFunction::Sec(_) => (integer(1), cos(x())),
+        MathFunction::Csc(_) => (integer(1), sin(x())),
+        MathFunction::Tanh(_) => (sinh(x()), cosh(x())),
+        MathFunction::Coth(_) => (cosh(x()), sinh(x())),
+        MathFunction::Sech(_) => (integer(1), cosh(x())),
+        MathFunction::Csch(_) => (integer(1), sinh(x())),
+        _ => return None,
+    };
+    Some(divide(num, den))
+}
+
+#[inline]
+fn natural_log(x: ExpressionElement) -> ExpressionElement {
+    log(Number::Constant(Constant::E).into(), x)
+}
+
+/// Drops the constant factor of the remainder, so `O(6x^{5})` is just `O(x^{5})`.
+pub(crate) fn big_o(x: ExpressionElement) -> ExpressionElement {
+    match split_coefficient(&x) {
+        (_, Some(rest)) => BigO::new(rest.clone()).into(),
+        (_, None) => BigO::new(integer(1)).into(),
+    }
+}
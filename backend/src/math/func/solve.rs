@@ -0,0 +1,696 @@
+//! Equations in one unknown, solved exactly for polynomials up to degree 4 and numerically
+//! otherwise.
+
+use crate::{
+    math::{
+        func::{
+            decl::{Equation, MathFunction},
+            exp_log::root,
+            op::{add, divide, multiply, negate, subtract},
+            trig::{arccos, cos},
+            Differentiable, Function,
+        },
+        mpoly::MultiPolynomial,
+        poly::{rational_function, Polynomial},
+        symbol::{Constant, Number, Rational},
+        ExpressionElement, MathElement,
+    },
+    DecimalScalar, IntegerScalar,
+};
+
+/// Numeric roots are searched for in `[-SEARCH_RANGE, SEARCH_RANGE]` first.
+pub const SEARCH_RANGE: DecimalScalar = 100.;
+/// If there are none, the range is widened up to `[-MAX_SEARCH_RANGE, MAX_SEARCH_RANGE]`.
+pub const MAX_SEARCH_RANGE: DecimalScalar = 1e12;
+/// How much the search range is widened each time.
+const WIDENING: DecimalScalar = 100.;
+/// Numeric roots are accepted once the equation holds up to this.
+pub const TOLERANCE: DecimalScalar = 1e-9;
+/// The number of intervals the search range is split into when looking for sign changes.
+const SAMPLES: usize = 4000;
+const MAX_ITERATIONS: usize = 100;
+/// The largest denominator numeric roots are snapped to, if the rational solves the equation
+/// exactly.
+const MAX_SNAP_DENOMINATOR: IntegerScalar = 12;
+
+impl Function for Equation {
+    /// Both sides are evaluated on their own.
+    fn evaluate(&self) -> MathElement {
+        MathElement::Function(MathFunction::Equation(Equation::new(
+            self.lhs().evaluate().into(),
+            self.rhs().evaluate().into(),
+        )))
+    }
+
+    /// Equations have no value.
+    #[inline]
+    fn approximate(&self) -> DecimalScalar {
+        DecimalScalar::NAN
+    }
+}
+
+/// The solutions of an equation in one unknown.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Roots {
+    /// The real roots in ascending order, which might be none. Roots with other unknowns in them
+    /// are kept in the order they are found.
+    Finite(Vec<ExpressionElement>),
+    /// Every value is a solution, like for `x+1=1+x`.
+    Any,
+}
+
+/// Solves `equation` for `var`, where expressions that aren't equations are set to 0.
+///
+/// Rational functions are solved exactly as long as what's left after dividing out the
+/// rational roots has a degree of at most 4, and equations with other unknowns if they are
+/// linear or quadratic. Everything else is solved numerically, starting in [`SEARCH_RANGE`].
+/// `None` if the equation can't be solved, which includes numeric searches that find nothing.
+/// Periodic equations like `\sin{x}=\frac{1}{2}` have infinitely many roots, of which only the
+/// ones in [`SEARCH_RANGE`] are returned.
+pub fn solve(equation: &ExpressionElement, var: &str) -> Option<Roots> {
+    let f = residual(equation);
+
+    if let Some((num, den)) = rational_function(&f, var) {
+        if num.is_zero() {
+            return Some(Roots::Any);
+        }
+
+        // Roots of the denominator are poles instead.
+        let den = den.to_expr(var);
+        let roots = polynomial_roots(&num, var)?
+            .into_iter()
+            .filter(|r| {
+                let den = den.substitute(var, r);
+                match den.as_rational() {
+                    Some(den) => den != Rational::ZERO,
+                    None => den.approximate().abs() > TOLERANCE,
+                }
+            })
+            .collect();
+        return Some(Roots::Finite(sorted(roots)));
+    }
+
+    if f.any_variable(&|v| v != var) {
+        return symbolic_roots(&f, var).map(Roots::Finite);
+    }
+    if !f.depends_on(var) {
+        return Some(if is_zero(&f) {
+            Roots::Any
+        } else {
+            Roots::Finite(Vec::new())
+        });
+    }
+
+    // Widening the search range only finds more roots of periodic equations.
+    let max_range = if is_periodic(&f, var) {
+        SEARCH_RANGE
+    } else {
+        MAX_SEARCH_RANGE
+    };
+    let roots = numeric_roots(&f, var, max_range);
+    (!roots.is_empty()).then(|| Roots::Finite(sorted(roots)))
+}
+
+/// Checks if `var` only shows up in trigonometric functions of linear arguments, so `f` is
+/// periodic or at least keeps oscillating.
+fn is_periodic(f: &ExpressionElement, var: &str) -> bool {
+    fn only_in_trig(elem: &ExpressionElement, var: &str) -> bool {
+        let ExpressionElement::Function(func) = elem else {
+            return !elem.depends_on(var);
+        };
+        match func.as_ref() {
+            MathFunction::Sin(_)
+            | MathFunction::Cos(_)
+            | MathFunction::Tan(_)
+            | MathFunction::Cot(_)
+            | MathFunction::Sec(_)
+            | MathFunction::Csc(_) => {
+                let slope = func.params()[0].derivative(var);
+                !ExpressionElement::from(slope.evaluate()).depends_on(var)
+            }
+            _ => func.params().into_iter().all(|p| only_in_trig(p, var)),
+        }
+    }
+
+    f.depends_on(var) && only_in_trig(f, var)
+}
+
+/// Moves everything in `equation` to the left hand side, so the roots are where it's 0.
+pub(crate) fn residual(equation: &ExpressionElement) -> ExpressionElement {
+    match equation {
+        ExpressionElement::Function(f) => match f.as_ref() {
+            MathFunction::Equation(eq) => {
+                subtract(eq.lhs().evaluate().into(), eq.rhs().evaluate().into())
+            }
+            _ => equation.evaluate().into(),
+        },
+        _ => equation.evaluate().into(),
+    }
+}
+
+#[inline]
+fn num(r: Rational) -> ExpressionElement {
+    Number::from(r).into()
+}
+
+#[inline]
+fn is_zero(elem: &ExpressionElement) -> bool {
+    elem.as_rational() == Some(Rational::ZERO)
+}
+
+/// Sorts real roots in ascending order and removes duplicates.
+pub(crate) fn sorted(mut roots: Vec<ExpressionElement>) -> Vec<ExpressionElement> {
+    roots.sort_by(|a, b| a.approximate().total_cmp(&b.approximate()));
+    roots.dedup_by(|a, b| {
+        let (a, b) = (a.approximate(), b.approximate());
+        (a - b).abs() <= TOLERANCE * (1. + a.abs())
+    });
+    roots
+}
+
+/// The real roots of a polynomial, exact if what's left after dividing out the rational roots
+/// has a degree of at most 4.
+pub(crate) fn polynomial_roots(p: &Polynomial, var: &str) -> Option<Vec<ExpressionElement>> {
+    let (rational, rest) = p.rational_roots()?;
+    let mut roots = rational
+        .into_iter()
+        .map(|(r, _)| num(r))
+        .collect::<Vec<_>>();
+
+    let c = |n| rest.coeff(n);
+    match rest.degree() {
+        None | Some(0) => {}
+        Some(1) => roots.push(num(c(0).checked_neg()?.checked_div(c(1))?)),
+        Some(2) => roots.extend(quadratic(c(2), c(1), c(0))?),
+        Some(3) => roots.extend(cubic(c(3), c(2), c(1), c(0))?),
+        Some(4) => roots.extend(quartic(c(4), c(3), c(2), c(1), c(0))?),
+        Some(_) => roots.extend(numeric_roots(&rest.to_expr(var), var, MAX_SEARCH_RANGE)),
+    }
+
+    Some(roots)
+}
+
+/// The real roots of `ax^2+bx+c`.
+fn quadratic(a: Rational, b: Rational, c: Rational) -> Option<Vec<ExpressionElement>> {
+    // \Delta=b^2-4ac
+    let disc = b
+        .checked_mul(b)?
+        .checked_sub(Rational::from(4).checked_mul(a)?.checked_mul(c)?)?;
+    let (center, den) = (b.checked_neg()?, a.checked_mul(Rational::from(2))?);
+
+    if disc.is_negative() {
+        return Some(Vec::new());
+    }
+    if disc == Rational::ZERO {
+        return Some(vec![num(center.checked_div(den)?)]);
+    }
+
+    let sqrt = root(num(Rational::from(2)), num(disc));
+    Some(vec![
+        divide(subtract(num(center), sqrt.clone()), num(den)),
+        divide(add(num(center), sqrt), num(den)),
+    ])
+}
+
+/// The real roots of `ax^3+bx^2+cx+d` with Cardano's formula, or the trigonometric one if all
+/// three roots are real.
+fn cubic(a: Rational, b: Rational, c: Rational, d: Rational) -> Option<Vec<ExpressionElement>> {
+    let r = Rational::from;
+    let a2 = a.checked_mul(a)?;
+
+    // t^3+pt+q with x=t-\frac{b}{3a}
+    let p = r(3)
+        .checked_mul(a)?
+        .checked_mul(c)?
+        .checked_sub(b.checked_mul(b)?)?
+        .checked_div(r(3).checked_mul(a2)?)?;
+    let q = r(2)
+        .checked_mul(b.checked_pow(3)?)?
+        .checked_sub(r(9).checked_mul(a)?.checked_mul(b)?.checked_mul(c)?)?
+        .checked_add(r(27).checked_mul(a2)?.checked_mul(d)?)?
+        .checked_div(r(27).checked_mul(a2)?.checked_mul(a)?)?;
+    let shift = num(b.checked_neg()?.checked_div(r(3).checked_mul(a)?)?);
+
+    let half_q = q.checked_div(r(2))?;
+    let third_p = p.checked_div(r(3))?;
+    let disc = half_q
+        .checked_mul(half_q)?
+        .checked_add(third_p.checked_pow(3)?)?;
+
+    if disc == Rational::ZERO {
+        if p == Rational::ZERO {
+            return Some(vec![shift]);
+        }
+        // A single and a double root.
+        let single = r(3).checked_mul(q)?.checked_div(p)?;
+        let double = single.checked_div(r(-2))?;
+        return Some(vec![
+            add(num(single), shift.clone()),
+            add(num(double), shift),
+        ]);
+    }
+
+    if !disc.is_negative() {
+        let sqrt = root(num(r(2)), num(disc));
+        let rational = sqrt.as_rational().is_some();
+        let (u, v) = (
+            add(num(half_q.checked_neg()?), sqrt.clone()),
+            subtract(num(half_q.checked_neg()?), sqrt),
+        );
+        let x = add(add(real_cbrt(u), real_cbrt(v)), shift);
+        if rational {
+            return Some(vec![x]);
+        }
+
+        // Nested radicals like \sqrt[3]{-\frac{1}{2}+\sqrt{\frac{31}{108}}} don't tell much, so
+        // the root is polished with Newton's method and given as a decimal instead.
+        let (a, b, c, d) = (
+            a.to_decimal(),
+            b.to_decimal(),
+            c.to_decimal(),
+            d.to_decimal(),
+        );
+        let f = |x: DecimalScalar| ((a * x + b) * x + c) * x + d;
+        let df = |x: DecimalScalar| (3. * a * x + 2. * b) * x + c;
+        let x = x.approximate();
+        return Some(vec![Number::Decimal(newton(&f, &df, x).unwrap_or(x)).into()]);
+    }
+
+    // t_k=2\sqrt{-\frac{p}{3}}\cos{(\frac{1}{3}\arccos{(\frac{3q}{2p}\sqrt{-\frac{3}{p}})}-\frac{2\pi k}{3})}
+    let amplitude = multiply(num(r(2)), root(num(r(2)), num(third_p.checked_neg()?)));
+    let angle = divide(
+        arccos(multiply(
+            num(r(3).checked_mul(q)?.checked_div(r(2).checked_mul(p)?)?),
+            root(num(r(2)), num(r(-3).checked_div(p)?)),
+        )),
+        num(r(3)),
+    );
+    (0..3)
+        .map(|k| {
+            let offset = multiply(
+                num(Rational::new(2 * k, 3)?),
+                Number::Constant(Constant::Pi).into(),
+            );
+            Some(add(
+                multiply(amplitude.clone(), cos(subtract(angle.clone(), offset))),
+                shift.clone(),
+            ))
+        })
+        .collect()
+}
+
+/// The real cube root, which stays real for negative numbers.
+fn real_cbrt(x: ExpressionElement) -> ExpressionElement {
+    if x.approximate() < 0. {
+        negate(root(num(Rational::from(3)), negate(x)))
+    } else {
+        root(num(Rational::from(3)), x)
+    }
+}
+
+/// The real roots of `ax^4+bx^3+cx^2+dx+e` with Ferrari's method.
+fn quartic(
+    a: Rational,
+    b: Rational,
+    c: Rational,
+    d: Rational,
+    e: Rational,
+) -> Option<Vec<ExpressionElement>> {
+    let r = Rational::from;
+    let (b, c, d, e) = (
+        b.checked_div(a)?,
+        c.checked_div(a)?,
+        d.checked_div(a)?,
+        e.checked_div(a)?,
+    );
+    let b2 = b.checked_mul(b)?;
+
+    // y^4+py^2+qy+r with x=y-\frac{b}{4}
+    let p = c.checked_sub(r(3).checked_mul(b2)?.checked_div(r(8))?)?;
+    let q = d
+        .checked_sub(b.checked_mul(c)?.checked_div(r(2))?)?
+        .checked_add(b2.checked_mul(b)?.checked_div(r(8))?)?;
+    let s = e
+        .checked_sub(b.checked_mul(d)?.checked_div(r(4))?)?
+        .checked_add(b2.checked_mul(c)?.checked_div(r(16))?)?
+        .checked_sub(r(3).checked_mul(b2.checked_mul(b2)?)?.checked_div(r(256))?)?;
+    let shift = num(b.checked_neg()?.checked_div(r(4))?);
+    let sqrt = |x: ExpressionElement| root(num(r(2)), x);
+
+    let mut roots = Vec::new();
+    if q == Rational::ZERO {
+        // Quadratic in y^2.
+        for z in quadratic(Rational::ONE, p, s)? {
+            match z.approximate() {
+                _ if is_zero(&z) => roots.push(shift.clone()),
+                z_approx if z_approx > 0. => {
+                    let y = sqrt(z);
+                    roots.push(add(negate(y.clone()), shift.clone()));
+                    roots.push(add(y, shift.clone()));
+                }
+                _ => {}
+            }
+        }
+        return Some(roots);
+    }
+
+    // (y^2+\frac{p}{2}+m)^2=(\sqrt{2m}y-\frac{q}{2\sqrt{2m}})^2 for a positive root of the
+    // resolvent 8m^3+8pm^2+(2p^2-8r)m-q^2.
+    let resolvent = Polynomial::new(vec![
+        q.checked_mul(q)?.checked_neg()?,
+        r(2).checked_mul(p.checked_mul(p)?)?
+            .checked_sub(r(8).checked_mul(s)?)?,
+        r(8).checked_mul(p)?,
+        r(8),
+    ]);
+    let m = polynomial_roots(&resolvent, "m")?
+        .into_iter()
+        .max_by(|a, b| a.approximate().total_cmp(&b.approximate()))?;
+
+    let w = sqrt(multiply(num(r(2)), m.clone()));
+    let base = negate(add(num(r(2).checked_mul(p)?), multiply(num(r(2)), m)));
+    let offset = divide(num(r(2).checked_mul(q)?), w.clone());
+
+    // y=\frac{\pm\sqrt{2m}\pm\sqrt{-2p-2m\mp\frac{2q}{\sqrt{2m}}}}{2}
+    for (center, disc) in [
+        (w.clone(), subtract(base.clone(), offset.clone())),
+        (negate(w), add(base, offset)),
+    ] {
+        let approx = disc.approximate();
+        if approx.abs() <= TOLERANCE * (1. + center.approximate().abs()) {
+            roots.push(add(divide(center, num(r(2))), shift.clone()));
+        } else if approx > 0. {
+            let sqrt = sqrt(disc);
+            roots.push(add(
+                divide(subtract(center.clone(), sqrt.clone()), num(r(2))),
+                shift.clone(),
+            ));
+            roots.push(add(divide(add(center, sqrt), num(r(2))), shift.clone()));
+        }
+    }
+
+    Some(roots)
+}
+
+/// Roots of linear and quadratic equations with other unknowns in their coefficients.
+fn symbolic_roots(f: &ExpressionElement, var: &str) -> Option<Vec<ExpressionElement>> {
+    let poly = MultiPolynomial::from_expr(f)?;
+    let c = |n| poly.coeff_in(var, n).to_expr();
+
+    match poly.degree_in(var) {
+        1 => Some(vec![negate(divide(c(0), c(1)))]),
+        2 => {
+            let (a, b) = (c(2), c(1));
+            let disc = subtract(
+                multiply(b.clone(), b.clone()),
+                multiply(num(Rational::from(4)), multiply(a.clone(), c(0))),
+            );
+            let sqrt = root(num(Rational::from(2)), disc);
+            let den = multiply(num(Rational::from(2)), a);
+            Some(vec![
+                divide(subtract(negate(b.clone()), sqrt.clone()), den.clone()),
+                divide(add(negate(b), sqrt), den),
+            ])
+        }
+        _ => None,
+    }
+}
+
+/// Finds roots in [`SEARCH_RANGE`] by bracketing sign changes with Brent's method, and roots
+/// that only touch 0 with Newton's method. If there are none, the range is widened up to
+/// `[-max_range, max_range]`.
+fn numeric_roots(
+    f: &ExpressionElement,
+    var: &str,
+    max_range: DecimalScalar,
+) -> Vec<ExpressionElement> {
+    let at = |x: DecimalScalar| f.substitute(var, &Number::Decimal(x).into()).approximate();
+    let df = f.derivative(var);
+    let slope = |x: DecimalScalar| df.substitute(var, &Number::Decimal(x).into()).approximate();
+
+    let step = 2. * SEARCH_RANGE / SAMPLES as DecimalScalar;
+    let xs = (0..=SAMPLES)
+        .map(|i| -SEARCH_RANGE + i as DecimalScalar * step)
+        .collect::<Vec<_>>();
+    let mut roots = scan(&at, &slope, &xs);
+
+    // Far away roots, like the one of `\ln{x}=5`, are looked for with samples that get sparser
+    // towards the ends.
+    let mut range = SEARCH_RANGE;
+    while roots.is_empty() && range < max_range {
+        let ratio = WIDENING.powf(2. / SAMPLES as DecimalScalar);
+        let outer = (0..=SAMPLES / 2)
+            .map(|i| range * ratio.powi(i as i32))
+            .collect::<Vec<_>>();
+        let inner = outer.iter().rev().map(|x| -x).collect::<Vec<_>>();
+        roots.extend(scan(&at, &slope, &inner));
+        roots.extend(scan(&at, &slope, &outer));
+        range *= WIDENING;
+    }
+
+    roots.into_iter().map(|x| snap(f, var, x)).collect()
+}
+
+/// Looks for roots between the increasing samples `xs`, with sign changes and local minima of
+/// the absolute value.
+fn scan(
+    at: &impl Fn(DecimalScalar) -> DecimalScalar,
+    slope: &impl Fn(DecimalScalar) -> DecimalScalar,
+    xs: &[DecimalScalar],
+) -> Vec<DecimalScalar> {
+    let ys = xs.iter().map(|x| at(*x)).collect::<Vec<_>>();
+
+    let mut roots = Vec::new();
+    for i in 0..xs.len() - 1 {
+        let (ya, yb) = (ys[i], ys[i + 1]);
+        if ya == 0. {
+            roots.push(xs[i]);
+        } else if ya.is_finite() && yb.is_finite() && ya.signum() != yb.signum() && yb != 0. {
+            // Sign changes across poles converge to the pole instead.
+            if let Some(x) = brent(at, xs[i], xs[i + 1], ya, yb) {
+                if at(x).abs() <= TOLERANCE.sqrt() {
+                    roots.push(x);
+                }
+            }
+        } else if i > 0
+            && ya.abs() < ys[i - 1].abs()
+            && ya.abs() <= yb.abs()
+            && ya.signum() == ys[i - 1].signum()
+            && ya.signum() == yb.signum()
+        {
+            if let Some(x) = newton(at, slope, xs[i])
+                .filter(|x| (xs[i - 1]..=xs[i + 1]).contains(x) && at(*x).abs() <= TOLERANCE)
+            {
+                roots.push(x);
+            }
+        }
+    }
+
+    roots
+}
+
+/// Brent's method on `[a, b]`, where `f(a)` and `f(b)` have different signs.
+fn brent(
+    f: &impl Fn(DecimalScalar) -> DecimalScalar,
+    mut a: DecimalScalar,
+    mut b: DecimalScalar,
+    mut fa: DecimalScalar,
+    mut fb: DecimalScalar,
+) -> Option<DecimalScalar> {
+    if fa.abs() < fb.abs() {
+        std::mem::swap(&mut a, &mut b);
+        std::mem::swap(&mut fa, &mut fb);
+    }
+    let (mut c, mut fc) = (a, fa);
+    let mut d = b - a;
+    let mut bisected = true;
+
+    for _ in 0..MAX_ITERATIONS {
+        if fb == 0. || (b - a).abs() <= DecimalScalar::EPSILON * (1. + b.abs()) {
+            return Some(b);
+        }
+
+        let mut s = if fa != fc && fb != fc {
+            // Inverse quadratic interpolation
+            a * fb * fc / ((fa - fb) * (fa - fc))
+                + b * fa * fc / ((fb - fa) * (fb - fc))
+                + c * fa * fb / ((fc - fa) * (fc - fb))
+        } else {
+            // Secant
+            b - fb * (b - a) / (fb - fa)
+        };
+
+        let bound = (3. * a + b) / 4.;
+        let tol = DecimalScalar::EPSILON * (1. + b.abs());
+        if !(s > bound.min(b) && s < bound.max(b))
+            || (bisected && (s - b).abs() >= (b - c).abs() / 2.)
+            || (!bisected && (s - b).abs() >= (c - d).abs() / 2.)
+            || (bisected && (b - c).abs() < tol)
+            || (!bisected && (c - d).abs() < tol)
+        {
+            s = (a + b) / 2.;
+            bisected = true;
+        } else {
+            bisected = false;
+        }
+
+        let fs = f(s);
+        if !fs.is_finite() {
+            return None;
+        }
+        d = c;
+        (c, fc) = (b, fb);
+        if fa.signum() != fs.signum() {
+            (b, fb) = (s, fs);
+        } else {
+            (a, fa) = (s, fs);
+        }
+        if fa.abs() < fb.abs() {
+            std::mem::swap(&mut a, &mut b);
+            std::mem::swap(&mut fa, &mut fb);
+        }
+    }
+
+    Some(b)
+}
+
+/// Newton's method starting at `x`, `None` if it doesn't converge.
+fn newton(
+    f: &impl Fn(DecimalScalar) -> DecimalScalar,
+    df: &impl Fn(DecimalScalar) -> DecimalScalar,
+    mut x: DecimalScalar,
+) -> Option<DecimalScalar> {
+    for _ in 0..MAX_ITERATIONS {
+        let (y, dy) = (f(x), df(x));
+        if y == 0. {
+            return Some(x);
+        }
+        if dy == 0. || !dy.is_finite() {
+            return None;
+        }
+
+        let next = x - y / dy;
+        if !next.is_finite() {
+            return None;
+        }
+        if (next - x).abs() <= DecimalScalar::EPSILON * (1. + x.abs()) {
+            return Some(next);
+        }
+        x = next;
+    }
+
+    None
+}
+
+/// Replaces a numeric root by a nearby rational if that solves the equation exactly.
+fn snap(f: &ExpressionElement, var: &str, x: DecimalScalar) -> ExpressionElement {
+    for den in 1..=MAX_SNAP_DENOMINATOR {
+        let n = (x * den as DecimalScalar).round();
+        if (n / den as DecimalScalar - x).abs() > TOLERANCE * (1. + x.abs()) {
+            continue;
+        }
+
+        if let Some(r) = Rational::new(n as IntegerScalar, den) {
+            let value = ExpressionElement::from(f.substitute(var, &num(r)).evaluate());
+            if is_zero(&value) {
+                return num(r);
+            }
+        }
+        break;
+    }
+
+    Number::Decimal(x).into()
+}
+
+#[cfg(test)]
+#[allow(clippy::excessive_precision)]
+mod test {
+    use crate::math::{
+        expr::ExpresssionTree,
+        func::{solve::Roots, Function},
+        ExpressionElement, FromRawExpr, IntoRawExpr, ParsingScope,
+    };
+
+    #[test]
+    fn test_solve() {
+        let scope = ParsingScope::new(None)
+            .with_symbol("x")
+            .with_symbol("a")
+            .with_symbol("b");
+        let solve = |expr| match ExpresssionTree::parse_scoped(expr, &scope)
+            .unwrap()
+            .solve("x")
+            .unwrap()
+        {
+            Roots::Finite(roots) => roots.iter().map(|r| r.assemble()).collect::<Vec<_>>(),
+            Roots::Any => vec!["any".to_string()],
+        };
+        let approximate = |expr| match ExpresssionTree::parse_scoped(expr, &scope)
+            .unwrap()
+            .solve("x")
+            .unwrap()
+        {
+            Roots::Finite(roots) => roots.iter().map(|r| r.approximate() as f32).collect(),
+            Roots::Any => Vec::new(),
+        };
+
+        let equation = ExpresssionTree::parse_scoped("1+1=-2+x", &scope).unwrap();
+        assert_eq!(equation.assemble(), "1+1=0-2+x");
+        assert_eq!(
+            ExpressionElement::from(equation.evaluate()).assemble(),
+            "2=-2+x"
+        );
+
+        assert_eq!(solve("x^2-5x+6=0"), ["2", "3"]);
+        assert_eq!(solve("2x+1=x-3"), ["-4"]);
+        assert_eq!(solve("x^2=2"), [r#"-\sqrt{2}"#, r#"\sqrt{2}"#]);
+        assert_eq!(solve("x^2+1=0"), Vec::<String>::new());
+        assert_eq!(solve("(x-1)^2=0"), ["1"]);
+        assert_eq!(solve("x+1=1+x"), ["any"]);
+        assert_eq!(solve(r#"\frac{x^2-1}{x-1}=0"#), ["-1"]);
+        assert_eq!(solve("x^3-6x^2+11x-6=0"), ["1", "2", "3"]);
+        assert_eq!(solve("x^3=2"), [r#"\sqrt[3]{2}"#]);
+        assert_eq!(
+            solve("x^4-5x^2+6=0"),
+            [r#"-\sqrt{3}"#, r#"-\sqrt{2}"#, r#"\sqrt{2}"#, r#"\sqrt{3}"#]
+        );
+        assert_eq!(solve("a*x+b=0"), [r#"-\frac{b}{a}"#]);
+
+        // Three real roots without rational ones.
+        assert_eq!(
+            approximate("x^3-3x+1=0"),
+            [-1.879385242, 0.3472963553, 1.532088886]
+        );
+        assert_eq!(solve("x^3+x+1=0"), ["-0.6823278038280193"]);
+        assert_eq!(approximate("x^4+x-1=0"), [-1.220744085, 0.7244919590]);
+
+        assert_eq!(solve("e^x=1"), ["0"]);
+        assert_eq!(approximate(r#"\cos x=x"#), [0.7390851332]);
+        assert_eq!(approximate("e^x=3-x"), [0.7920599546]);
+        assert_eq!(
+            approximate(r#"x^2=\sin{x}+1"#),
+            [-0.6367326508, 1.409624004]
+        );
+        // Roots that only touch 0.
+        assert_eq!(approximate(r#"e^x=x+1"#), [0.]);
+        // Roots outside of the initial search range.
+        assert_eq!(approximate(r#"\ln{x}=5"#), [148.41316]);
+        assert_eq!(solve(r#"\sqrt{x}=20"#), ["400"]);
+
+        let unsolvable = |expr| {
+            ExpresssionTree::parse_scoped(expr, &scope)
+                .unwrap()
+                .solve("x")
+                .is_none()
+        };
+        assert!(unsolvable("e^x=-1"));
+        assert!(unsolvable(r#"\sin x=1.5"#));
+
+        // Periodic equations only have their roots in the search range.
+        let roots = approximate(r#"\sin x=0"#);
+        assert_eq!(roots.len(), 63);
+        assert_eq!(roots[31], 0.);
+        assert_eq!(roots[32], std::f32::consts::PI);
+        assert_eq!(approximate(r#"\sin{2x}=\cos{x}+\frac{1}{2}"#).len(), 64);
+    }
+}
@@ -0,0 +1,438 @@
+//! Sums and products over an index, worked out term by term for finite integer bounds and found
+//! with closed forms or extrapolated numerically otherwise.
+
+use crate::{
+    math::{
+        bytecode::CompiledExpr,
+        func::{
+            decl::{MathFunction, Product, Sum},
+            exp_log::power,
+            limit::{extrapolate_side, infinite_sign, infinity, integer, MAX_STEPS},
+            op::{add, divide, multiply, subtract},
+            Function,
+        },
+        poly::Polynomial,
+        symbol::Number,
+        ExpressionElement, MathElement,
+    },
+    ComplexScalar, DecimalScalar, IntegerScalar,
+};
+
+/// Finite sums and products with more terms than this are only found if they have closed forms.
+pub const MAX_TERMS: IntegerScalar = 10000;
+/// Finite sums and products without closed forms are approximated term by term up to this many
+/// terms.
+pub const MAX_APPROXIMATED_TERMS: IntegerScalar = 1 << 24;
+/// The number of terms of infinite series that are worked out before extrapolating, which is
+/// where the smallest step of the extrapolation lands.
+const SERIES_TERMS: usize = 1 << (MAX_STEPS + 1);
+
+macro_rules! impl_var_name {
+    ($($ty:ident),*) => {
+        $(
+            impl $ty {
+                /// The name of the index.
+                #[inline]
+                pub fn var_name(&self) -> Option<&str> {
+                    match self.var() {
+                        ExpressionElement::Variable(v) => Some(v),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_var_name!(Sum, Product);
+
+impl Function for Sum {
+    /// Sums that diverge or can't be found stay as they are.
+    fn evaluate(&self) -> MathElement {
+        let sum = Sum::new(
+            self.var().clone(),
+            self.lower().evaluate().into(),
+            self.upper().evaluate().into(),
+            self.f().evaluate().into(),
+        );
+
+        match sum
+            .var_name()
+            .and_then(|var| sum_of(sum.f(), var, sum.lower(), sum.upper()))
+        {
+            Some(value) => value.into(),
+            None => MathElement::Function(MathFunction::Sum(sum)),
+        }
+    }
+
+    fn approximate(&self) -> DecimalScalar {
+        match self.evaluate() {
+            MathElement::Function(MathFunction::Sum(sum)) => sum
+                .var_name()
+                .and_then(|var| accumulate(sum.f(), var, sum.lower(), sum.upper(), false))
+                .unwrap_or(DecimalScalar::NAN),
+            sum => ExpressionElement::from(sum).approximate(),
+        }
+    }
+
+    fn approximate_complex(&self) -> ComplexScalar {
+        match self.evaluate() {
+            MathElement::Function(MathFunction::Sum(sum)) => sum
+                .var_name()
+                .and_then(|var| accumulate(sum.f(), var, sum.lower(), sum.upper(), false))
+                .unwrap_or(DecimalScalar::NAN)
+                .into(),
+            sum => ExpressionElement::from(sum).approximate_complex(),
+        }
+    }
+}
+
+impl Function for Product {
+    /// Products that diverge or can't be found stay as they are.
+    fn evaluate(&self) -> MathElement {
+        let prod = Product::new(
+            self.var().clone(),
+            self.lower().evaluate().into(),
+            self.upper().evaluate().into(),
+            self.f().evaluate().into(),
+        );
+
+        match prod
+            .var_name()
+            .and_then(|var| product_of(prod.f(), var, prod.lower(), prod.upper()))
+        {
+            Some(value) => value.into(),
+            None => MathElement::Function(MathFunction::Product(prod)),
+        }
+    }
+
+    fn approximate(&self) -> DecimalScalar {
+        match self.evaluate() {
+            MathElement::Function(MathFunction::Product(prod)) => prod
+                .var_name()
+                .and_then(|var| accumulate(prod.f(), var, prod.lower(), prod.upper(), true))
+                .unwrap_or(DecimalScalar::NAN),
+            prod => ExpressionElement::from(prod).approximate(),
+        }
+    }
+
+    fn approximate_complex(&self) -> ComplexScalar {
+        match self.evaluate() {
+            MathElement::Function(MathFunction::Product(prod)) => prod
+                .var_name()
+                .and_then(|var| accumulate(prod.f(), var, prod.lower(), prod.upper(), true))
+                .unwrap_or(DecimalScalar::NAN)
+                .into(),
+            prod => ExpressionElement::from(prod).approximate_complex(),
+        }
+    }
+}
+
+/// The sum of `f` for `var` going from `lower` to `upper`, `None` if it diverges or can't be
+/// found.
+pub fn sum_of(
+    f: &ExpressionElement,
+    var: &str,
+    lower: &ExpressionElement,
+    upper: &ExpressionElement,
+) -> Option<ExpressionElement> {
+    if infinite_sign(lower).is_some() {
+        return None;
+    }
+    if let Some(sign) = infinite_sign(upper) {
+        let lower = integer_bound(lower).filter(|_| sign > 0)?;
+        return closed_series(f, var, lower).or_else(|| extrapolate_series(f, var, lower, false));
+    }
+    if !is_bound(lower) || !is_bound(upper) {
+        return None;
+    }
+
+    match terms(f, var, lower, upper) {
+        Some(terms) => Some(terms.fold(integer(0), add).evaluate().into()),
+        None => closed_sum(f, var, lower, upper).map(|sum| sum.evaluate().into()),
+    }
+}
+
+/// The product of `f` for `var` going from `lower` to `upper`, `None` if it diverges or can't
+/// be found.
+pub fn product_of(
+    f: &ExpressionElement,
+    var: &str,
+    lower: &ExpressionElement,
+    upper: &ExpressionElement,
+) -> Option<ExpressionElement> {
+    if infinite_sign(lower).is_some() {
+        return None;
+    }
+    if let Some(sign) = infinite_sign(upper) {
+        let lower = integer_bound(lower).filter(|_| sign > 0)?;
+        if f.is_integer(1) {
+            return Some(integer(1));
+        }
+        return extrapolate_series(f, var, lower, true);
+    }
+    if !is_bound(lower) || !is_bound(upper) {
+        return None;
+    }
+
+    match terms(f, var, lower, upper) {
+        Some(terms) => Some(terms.fold(integer(1), multiply).evaluate().into()),
+        None => closed_product(f, var, lower, upper).map(|prod| prod.evaluate().into()),
+    }
+}
+
+/// The terms for every index between integer bounds, `None` if there are too many of them.
+fn terms<'a>(
+    f: &'a ExpressionElement,
+    var: &'a str,
+    lower: &ExpressionElement,
+    upper: &ExpressionElement,
+) -> Option<impl Iterator<Item = ExpressionElement> + 'a> {
+    let (lower, upper) = (integer_bound(lower)?, integer_bound(upper)?);
+    (upper.checked_sub(lower)? < MAX_TERMS).then(move || {
+        (lower..=upper).map(move |k| f.substitute(var, &integer(k)).evaluate().into())
+    })
+}
+
+/// Adds up or multiplies the terms between integer bounds as decimals, `None` if there are more
+/// than [`MAX_APPROXIMATED_TERMS`] of them or `f` has other variables.
+fn accumulate(
+    f: &ExpressionElement,
+    var: &str,
+    lower: &ExpressionElement,
+    upper: &ExpressionElement,
+    product: bool,
+) -> Option<DecimalScalar> {
+    let (lower, upper) = (integer_bound(lower)?, integer_bound(upper)?);
+    if upper.checked_sub(lower)? >= MAX_APPROXIMATED_TERMS || f.any_variable(&|v| v != var) {
+        return None;
+    }
+
+    let f = CompiledExpr::compile(f, &[var]);
+    let terms = (lower..=upper).map(|k| f.eval(&[k as DecimalScalar]));
+    Some(if product {
+        terms.product()
+    } else {
+        // Kahan summation, as the small terms at the end of long sums would get lost otherwise.
+        let (sum, _) = terms.fold((0., 0.), |(sum, compensation), term| {
+            let term: DecimalScalar = term - compensation;
+            let next = sum + term;
+            (next, (next - sum) - term)
+        });
+        sum
+    })
+}
+
+/// Closed forms of polynomial and geometric sums, and of sums and constant multiples of them.
+fn closed_sum(
+    f: &ExpressionElement,
+    var: &str,
+    lower: &ExpressionElement,
+    upper: &ExpressionElement,
+) -> Option<ExpressionElement> {
+    let closed = |f| closed_sum(f, var, lower, upper);
+
+    if !f.depends_on(var) {
+        return Some(multiply(count(lower, upper), f.clone()));
+    }
+    if let Some(sum) = Polynomial::from_expr(f, var).and_then(|p| p.partial_sum()) {
+        // \sum_{k=a}^{b} p(k)=S(b)-S(a-1)
+        let sum = sum.to_expr(var);
+        let before = subtract(lower.clone(), integer(1));
+        return Some(subtract(
+            sum.substitute(var, upper),
+            sum.substitute(var, &before),
+        ));
+    }
+    if let Some((ratio, first)) = geometric(f, var, lower) {
+        let count = count(lower, upper);
+        if ratio.is_integer(1) {
+            return Some(multiply(count, first));
+        }
+        // \sum_{k=0}^{n-1} ar^k=\frac{a(r^n-1)}{r-1}
+        return Some(divide(
+            multiply(first, subtract(power(ratio.clone(), count), integer(1))),
+            subtract(ratio, integer(1)),
+        ));
+    }
+
+    let ExpressionElement::Function(func) = f else {
+        return None;
+    };
+    match func.as_ref() {
+        MathFunction::Add(op) => Some(add(closed(op.lhs())?, closed(op.rhs())?)),
+        MathFunction::Subtract(op) => Some(subtract(closed(op.lhs())?, closed(op.rhs())?)),
+        MathFunction::Multiply(op) if !op.lhs().depends_on(var) => {
+            Some(multiply(op.lhs().clone(), closed(op.rhs())?))
+        }
+        MathFunction::Multiply(op) if !op.rhs().depends_on(var) => {
+            Some(multiply(closed(op.lhs())?, op.rhs().clone()))
+        }
+        MathFunction::Divide(op) if !op.rhs().depends_on(var) => {
+            Some(divide(closed(op.lhs())?, op.rhs().clone()))
+        }
+        MathFunction::Fraction(frac) if !frac.den().depends_on(var) => {
+            Some(divide(closed(frac.num())?, frac.den().clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Closed forms of convergent geometric series, and of sums and constant multiples of them.
+fn closed_series(
+    f: &ExpressionElement,
+    var: &str,
+    lower: IntegerScalar,
+) -> Option<ExpressionElement> {
+    let closed = |f| closed_series(f, var, lower);
+
+    if f.is_integer(0) {
+        return Some(integer(0));
+    }
+    if let Some((ratio, first)) = geometric(f, var, &integer(lower)) {
+        // \sum_{k=0}^{\infty} ar^k=\frac{a}{1-r} for |r|<1
+        let converges = !ratio.is_symbolic() && ratio.approximate_complex().norm() < 1.;
+        return converges.then(|| divide(first, subtract(integer(1), ratio)));
+    }
+
+    let ExpressionElement::Function(func) = f else {
+        return None;
+    };
+    match func.as_ref() {
+        MathFunction::Add(op) => Some(add(closed(op.lhs())?, closed(op.rhs())?)),
+        MathFunction::Subtract(op) => Some(subtract(closed(op.lhs())?, closed(op.rhs())?)),
+        MathFunction::Multiply(op) if !op.lhs().depends_on(var) => {
+            Some(multiply(op.lhs().clone(), closed(op.rhs())?))
+        }
+        MathFunction::Multiply(op) if !op.rhs().depends_on(var) => {
+            Some(multiply(closed(op.lhs())?, op.rhs().clone()))
+        }
+        MathFunction::Divide(op) if !op.rhs().depends_on(var) => {
+            Some(divide(closed(op.lhs())?, op.rhs().clone()))
+        }
+        MathFunction::Fraction(frac) if !frac.den().depends_on(var) => {
+            Some(divide(closed(frac.num())?, frac.den().clone()))
+        }
+        _ => None,
+    }
+    .map(|sum| sum.evaluate().into())
+}
+
+/// Closed forms of products of constants and of powers whose exponents have closed form sums,
+/// and of products and quotients of them.
+fn closed_product(
+    f: &ExpressionElement,
+    var: &str,
+    lower: &ExpressionElement,
+    upper: &ExpressionElement,
+) -> Option<ExpressionElement> {
+    let closed = |f| closed_product(f, var, lower, upper);
+
+    if !f.depends_on(var) {
+        return Some(power(f.clone(), count(lower, upper)));
+    }
+
+    let ExpressionElement::Function(func) = f else {
+        return None;
+    };
+    match func.as_ref() {
+        MathFunction::Multiply(op) => Some(multiply(closed(op.lhs())?, closed(op.rhs())?)),
+        MathFunction::Divide(op) => Some(divide(closed(op.lhs())?, closed(op.rhs())?)),
+        MathFunction::Fraction(frac) => Some(divide(closed(frac.num())?, closed(frac.den())?)),
+        // \prod r^{g(k)}=r^{\sum g(k)}
+        MathFunction::Power(pow) if !pow.base().depends_on(var) => Some(power(
+            pow.base().clone(),
+            closed_sum(pow.exp(), var, lower, upper)?,
+        )),
+        // \prod g(k)^c=(\prod g(k))^c
+        MathFunction::Power(pow) if !pow.exp().depends_on(var) => {
+            Some(power(closed(pow.base())?, pow.exp().clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Reads `f` as the terms of a geometric sequence `r^{\alpha k+\beta}` or
+/// `\frac{c}{r^{\alpha k+\beta}}`, giving the common ratio and the term at `lower`.
+fn geometric(
+    f: &ExpressionElement,
+    var: &str,
+    lower: &ExpressionElement,
+) -> Option<(ExpressionElement, ExpressionElement)> {
+    let ExpressionElement::Function(func) = f else {
+        return None;
+    };
+
+    match func.as_ref() {
+        MathFunction::Power(pow) if !pow.base().depends_on(var) => {
+            let exp = Polynomial::from_expr(pow.exp(), var)?;
+            if exp.degree() != Some(1) {
+                return None;
+            }
+            let ratio = power(pow.base().clone(), Number::from(exp.coeff(1)).into());
+            let first = power(pow.base().clone(), pow.exp().substitute(var, lower));
+            Some((ratio.evaluate().into(), first.evaluate().into()))
+        }
+        MathFunction::Divide(_) | MathFunction::Fraction(_) => {
+            let (num, den) = (func.params()[0], func.params()[1]);
+            if num.depends_on(var) {
+                return None;
+            }
+            let (ratio, first) = geometric(den, var, lower)?;
+            Some((
+                divide(integer(1), ratio).evaluate().into(),
+                divide(num.clone(), first).evaluate().into(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Adds up or multiplies the first terms of an infinite series and extrapolates where they're
+/// heading, `None` if they don't settle down or `f` has other variables.
+fn extrapolate_series(
+    f: &ExpressionElement,
+    var: &str,
+    lower: IntegerScalar,
+    product: bool,
+) -> Option<ExpressionElement> {
+    if f.substitute(var, &integer(lower)).is_symbolic() {
+        return None;
+    }
+
+    let mut acc = if product { 1. } else { 0. };
+    let partial = (lower..)
+        .take(SERIES_TERMS)
+        .map(|k| {
+            let term = f.substitute(var, &integer(k)).approximate();
+            acc = if product { acc * term } else { acc + term };
+            acc
+        })
+        .collect::<Vec<_>>();
+
+    // The first n terms are taken at t=\frac{1}{n}.
+    let value = extrapolate_side(|t| partial[(1. / t) as usize - 1])?;
+    Some(if value.is_infinite() {
+        infinity(value.signum() as IntegerScalar)
+    } else {
+        Number::Decimal(value).into()
+    })
+}
+
+/// The number of indices from `lower` to `upper`.
+#[inline]
+fn count(lower: &ExpressionElement, upper: &ExpressionElement) -> ExpressionElement {
+    add(subtract(upper.clone(), lower.clone()), integer(1))
+}
+
+/// Bounds of finite sums and products are integers, or unknowns that stand for them.
+#[inline]
+fn is_bound(elem: &ExpressionElement) -> bool {
+    elem.is_symbolic() || integer_bound(elem).is_some()
+}
+
+fn integer_bound(elem: &ExpressionElement) -> Option<IntegerScalar> {
+    elem.as_rational()
+        .filter(|r| r.is_integer())
+        .map(|r| r.num())
+}
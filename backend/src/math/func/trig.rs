@@ -5,207 +5,571 @@ use crate::{
                 Arccos, Arccot, Arccsc, Arcsec, Arcsin, Arctan, Cos, Cosh, Cot, Coth, Csc, Csch,
                 Sec, Sech, Sin, Sinh, Tan, Tanh,
             },
-            Function,
+            op::{self, decimal},
+            real_or_complex, Function,
         },
-        MathElement,
+        symbol::{Constant, Number, Rational},
+        ExpressionElement, MathElement,
     },
-    DecimalScalar,
+    ComplexScalar, DecimalScalar, IntegerScalar,
 };
 
+#[cfg(feature = "bignum")]
+use crate::{math::precise, BigDecimalScalar};
+
 impl Function for Sin {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        sin(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.x().approximate().sin()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            DecimalScalar::sin,
+            ComplexScalar::sin,
+        )
+    }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        Some(precise::sin(
+            &self.x().approximate_precise(precision)?,
+            precision,
+        ))
+    }
 }
 
 impl Function for Cos {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        cos(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.x().approximate().cos()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            DecimalScalar::cos,
+            ComplexScalar::cos,
+        )
+    }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        Some(precise::cos(
+            &self.x().approximate_precise(precision)?,
+            precision,
+        ))
+    }
 }
 
 impl Function for Tan {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        tan(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.x().approximate().tan()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            DecimalScalar::tan,
+            ComplexScalar::tan,
+        )
+    }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        let x = self
+            .x()
+            .approximate_precise(precision + precise::GUARD_DIGITS)?;
+        precise::divide(
+            &precise::sin(&x, precision + precise::GUARD_DIGITS),
+            &precise::cos(&x, precision + precise::GUARD_DIGITS),
+            precision,
+        )
+    }
 }
 
 impl Function for Cot {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        cot(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         1. / self.x().approximate().tan()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            |x| 1. / x.tan(),
+            |x| x.tan().inv(),
+        )
+    }
 }
 
 impl Function for Sec {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        sec(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
-        1. / self.x().approximate().sin()
+        1. / self.x().approximate().cos()
+    }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            |x| 1. / x.cos(),
+            |x| x.cos().inv(),
+        )
     }
 }
 
 impl Function for Csc {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        csc(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
-        1. / self.x().approximate().cos()
+        1. / self.x().approximate().sin()
+    }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            |x| 1. / x.sin(),
+            |x| x.sin().inv(),
+        )
     }
 }
 
 impl Function for Arcsin {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        arcsin(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.x().approximate().asin()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            DecimalScalar::asin,
+            ComplexScalar::asin,
+        )
+    }
 }
 
 impl Function for Arccos {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        arccos(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.x().approximate().acos()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            DecimalScalar::acos,
+            ComplexScalar::acos,
+        )
+    }
 }
 
 impl Function for Arctan {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        arctan(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
-        self.x().approximate().asin()
+        self.x().approximate().atan()
+    }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            DecimalScalar::atan,
+            ComplexScalar::atan,
+        )
     }
 }
 
 impl Function for Arccot {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        arccot(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         (1. / self.x().approximate()).atan()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            |x| (1. / x).atan(),
+            |x| x.inv().atan(),
+        )
+    }
 }
 
 impl Function for Arcsec {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        arcsec(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
-        (1. / self.x().approximate()).asin()
+        (1. / self.x().approximate()).acos()
+    }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            |x| (1. / x).acos(),
+            |x| x.inv().acos(),
+        )
     }
 }
 
 impl Function for Arccsc {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        arccsc(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
-        (1. / self.x().approximate()).acos()
+        (1. / self.x().approximate()).asin()
+    }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            |x| (1. / x).asin(),
+            |x| x.inv().asin(),
+        )
     }
 }
 
 impl Function for Sinh {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        sinh(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.x().approximate().sinh()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            DecimalScalar::sinh,
+            ComplexScalar::sinh,
+        )
+    }
 }
 
 impl Function for Cosh {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        cosh(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.x().approximate().cosh()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            DecimalScalar::cosh,
+            ComplexScalar::cosh,
+        )
+    }
 }
 
 impl Function for Tanh {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        tanh(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         self.x().approximate().tanh()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            DecimalScalar::tanh,
+            ComplexScalar::tanh,
+        )
+    }
 }
 
 impl Function for Coth {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        coth(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         1. / self.x().approximate().tanh()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            |x| 1. / x.tanh(),
+            |x| x.tanh().inv(),
+        )
+    }
 }
 
 impl Function for Sech {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        sech(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         1. / self.x().approximate().cosh()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            |x| 1. / x.cosh(),
+            |x| x.cosh().inv(),
+        )
+    }
 }
 
 impl Function for Csch {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        csch(self.x().evaluate().into()).into()
     }
 
     #[inline]
     fn approximate(&self) -> DecimalScalar {
         1. / self.x().approximate().sinh()
     }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        real_or_complex(
+            self.x().approximate_complex(),
+            |x| 1. / x.sinh(),
+            |x| x.sinh().inv(),
+        )
+    }
 }
+
+/// Returns `k` if the element equals `\frac{k\pi}{12}`, the only angles with a known exact
+/// trigonometric value.
+fn as_twelfth_of_pi(elem: &ExpressionElement) -> Option<IntegerScalar> {
+    let (coeff, rest) = op::split_coefficient(elem);
+    let (num, den) = (coeff.num(), coeff.den());
+
+    match rest {
+        None if num == 0 => Some(0),
+        Some(ExpressionElement::Number(Number::Constant(Constant::Pi))) if 12 % den == 0 => {
+            Some((num % (2 * den)) * (12 / den))
+        }
+        _ => None,
+    }
+}
+
+/// Exact value of `\sin{\frac{k\pi}{12}}`.
+fn sin_of_twelfth_of_pi(k: IntegerScalar) -> Option<ExpressionElement> {
+    let k = k.rem_euclid(24);
+    let (k, sign) = match k {
+        0..=6 => (k, 1),
+        7..=12 => (12 - k, 1),
+        13..=18 => (k - 12, -1),
+        _ => (24 - k, -1),
+    };
+
+    let value = match k {
+        0 => Number::Integer(0).into(),
+        2 => Number::Rational(Rational::new(1, 2).unwrap()).into(),
+        3 => op::divide(
+            super::exp_log::root(Number::Integer(2).into(), Number::Integer(2).into()),
+            Number::Integer(2).into(),
+        ),
+        4 => op::divide(
+            super::exp_log::root(Number::Integer(2).into(), Number::Integer(3).into()),
+            Number::Integer(2).into(),
+        ),
+        6 => Number::Integer(1).into(),
+        _ => return None,
+    };
+
+    Some(op::multiply(Number::Integer(sign).into(), value))
+}
+
+#[inline]
+fn cos_of_twelfth_of_pi(k: IntegerScalar) -> Option<ExpressionElement> {
+    sin_of_twelfth_of_pi(k + 6)
+}
+
+/// Exact values of `\cos{x}` and `\sin{x}`, if both are known.
+pub(crate) fn exact_cos_sin(
+    x: &ExpressionElement,
+) -> Option<(ExpressionElement, ExpressionElement)> {
+    let k = as_twelfth_of_pi(x)?;
+    Some((cos_of_twelfth_of_pi(k)?, sin_of_twelfth_of_pi(k)?))
+}
+
+fn tan_of_twelfth_of_pi(k: IntegerScalar) -> Option<ExpressionElement> {
+    let cos = cos_of_twelfth_of_pi(k)?;
+    (!cos.is_integer(0)).then(|| op::divide(sin_of_twelfth_of_pi(k).unwrap(), cos))
+}
+
+/// Finds `k` in `range` where `f(k)` equals the element, and returns `\frac{k\pi}{12}`.
+fn inverse_of_twelfth_of_pi(
+    elem: &ExpressionElement,
+    range: std::ops::RangeInclusive<IntegerScalar>,
+    f: impl Fn(IntegerScalar) -> Option<ExpressionElement>,
+) -> Option<ExpressionElement> {
+    range
+        .into_iter()
+        .find(|k| f(*k).as_ref() == Some(elem))
+        .map(|k| {
+            op::join_coefficient(
+                Rational::new(k, 12).unwrap(),
+                Some(Number::Constant(Constant::Pi).into()),
+            )
+        })
+}
+
+macro_rules! define_exact_trig {
+    ($fn_name: ident, $fn_ty: ident, $exact: expr) => {
+        pub(crate) fn $fn_name(x: ExpressionElement) -> ExpressionElement {
+            if x.is_decimal() {
+                return decimal($fn_ty::new(x).into());
+            }
+
+            let exact: fn(&ExpressionElement) -> Option<ExpressionElement> = $exact;
+            exact(&x).unwrap_or_else(|| $fn_ty::new(x).into())
+        }
+    };
+}
+
+define_exact_trig!(sin, Sin, |x| sin_of_twelfth_of_pi(as_twelfth_of_pi(x)?));
+define_exact_trig!(cos, Cos, |x| cos_of_twelfth_of_pi(as_twelfth_of_pi(x)?));
+define_exact_trig!(tan, Tan, |x| tan_of_twelfth_of_pi(as_twelfth_of_pi(x)?));
+define_exact_trig!(cot, Cot, |x| {
+    let k = as_twelfth_of_pi(x)?;
+    let sin = sin_of_twelfth_of_pi(k)?;
+    (!sin.is_integer(0)).then(|| op::divide(cos_of_twelfth_of_pi(k).unwrap(), sin))
+});
+define_exact_trig!(sec, Sec, |x| {
+    let cos = cos_of_twelfth_of_pi(as_twelfth_of_pi(x)?)?;
+    (!cos.is_integer(0)).then(|| op::divide(Number::Integer(1).into(), cos))
+});
+define_exact_trig!(csc, Csc, |x| {
+    let sin = sin_of_twelfth_of_pi(as_twelfth_of_pi(x)?)?;
+    (!sin.is_integer(0)).then(|| op::divide(Number::Integer(1).into(), sin))
+});
+
+define_exact_trig!(arcsin, Arcsin, |x| inverse_of_twelfth_of_pi(
+    x,
+    -6..=6,
+    sin_of_twelfth_of_pi
+));
+define_exact_trig!(arccos, Arccos, |x| inverse_of_twelfth_of_pi(
+    x,
+    0..=12,
+    cos_of_twelfth_of_pi
+));
+define_exact_trig!(arctan, Arctan, |x| inverse_of_twelfth_of_pi(
+    x,
+    -5..=5,
+    tan_of_twelfth_of_pi
+));
+define_exact_trig!(arccot, Arccot, |x| {
+    if x.is_integer(0) {
+        return None;
+    }
+    inverse_of_twelfth_of_pi(
+        &op::divide(Number::Integer(1).into(), x.clone()),
+        -5..=5,
+        tan_of_twelfth_of_pi,
+    )
+});
+define_exact_trig!(arcsec, Arcsec, |x| {
+    if x.is_integer(0) {
+        return None;
+    }
+    inverse_of_twelfth_of_pi(
+        &op::divide(Number::Integer(1).into(), x.clone()),
+        0..=12,
+        cos_of_twelfth_of_pi,
+    )
+});
+define_exact_trig!(arccsc, Arccsc, |x| {
+    if x.is_integer(0) {
+        return None;
+    }
+    inverse_of_twelfth_of_pi(
+        &op::divide(Number::Integer(1).into(), x.clone()),
+        -6..=6,
+        sin_of_twelfth_of_pi,
+    )
+});
+
+define_exact_trig!(sinh, Sinh, |x| x
+    .is_integer(0)
+    .then(|| Number::Integer(0).into()));
+define_exact_trig!(cosh, Cosh, |x| x
+    .is_integer(0)
+    .then(|| Number::Integer(1).into()));
+define_exact_trig!(tanh, Tanh, |x| x
+    .is_integer(0)
+    .then(|| Number::Integer(0).into()));
+define_exact_trig!(coth, Coth, |_| None);
+define_exact_trig!(sech, Sech, |x| x
+    .is_integer(0)
+    .then(|| Number::Integer(1).into()));
+define_exact_trig!(csch, Csch, |_| None);
@@ -0,0 +1,198 @@
+//! Functions defined by users, like `f(x)=x^2+1`, which are expanded wherever they are called.
+
+use crate::{
+    latex::{E, EQUAL, EQUATION_SEP, I, PARENTHESES_L, PARENTHESES_R},
+    math::{
+        expr::ExpresssionTree, ExpressionElement, FromRawExpr, LaTexParsingError,
+        LaTexParsingErrorType, LaTexParsingResult, MathElementMeta, ParsingScope,
+    },
+};
+
+/// A function defined by its parameters and the LaTex of its body.
+///
+/// The body is parsed again on every call, so it sees the variables and functions at the time
+/// of the call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserFunction {
+    params: Vec<String>,
+    body: String,
+}
+
+impl UserFunction {
+    #[inline]
+    pub fn new(params: Vec<String>, body: impl Into<String>) -> Self {
+        Self {
+            params,
+            body: body.into(),
+        }
+    }
+
+    #[inline]
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    #[inline]
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// Reads a definition like `g(x,y)=\sqrt{x^2+y^2}` into the name and the function. Names and
+    /// parameters are lowercase letters other than the constants `e` and `i`, and parameters
+    /// can't repeat.
+    pub fn parse_definition(expr: &str) -> LaTexParsingResult<(String, UserFunction)> {
+        let err = |range: std::ops::Range<usize>| {
+            LaTexParsingError::new(
+                range.into(),
+                LaTexParsingErrorType::InvalidFunctionDefinition,
+            )
+        };
+        let is_name = |s: &str| {
+            !s.is_empty() && !matches!(s, E | I) && s.chars().all(|c| c.is_ascii_lowercase())
+        };
+
+        let eq = expr.find(EQUAL).ok_or_else(|| err(0..expr.len()))?;
+        let (head, body) = (expr[..eq].trim(), &expr[eq + EQUAL.len()..]);
+        let name_end = head.find(PARENTHESES_L).ok_or_else(|| err(0..eq))?;
+        let name = head[..name_end].trim();
+        if !is_name(name) {
+            return Err(err(0..name_end));
+        }
+
+        let params = head[name_end + 1..]
+            .strip_suffix(PARENTHESES_R)
+            .ok_or_else(|| err(name_end..eq))?
+            .split(EQUATION_SEP)
+            .map(|p| p.trim().to_string())
+            .collect::<Vec<_>>();
+        if !params.iter().all(|p| is_name(p))
+            || (1..params.len()).any(|i| params[..i].contains(&params[i]))
+        {
+            return Err(err(name_end..eq));
+        }
+        if body.trim().is_empty() {
+            return Err(LaTexParsingError::new(
+                MathElementMeta::at(eq),
+                LaTexParsingErrorType::EmptyInput,
+            ));
+        }
+
+        Ok((name.to_string(), UserFunction::new(params, body.trim())))
+    }
+
+    /// Parses the body of `name` in `scope` and replaces the parameters with `args`. Errors are
+    /// reported relative to the body.
+    pub(crate) fn call(
+        &self,
+        name: &str,
+        args: &[ExpressionElement],
+        scope: &ParsingScope,
+    ) -> LaTexParsingResult<ExpressionElement> {
+        let body = ExpressionElement::from(ExpresssionTree::parse_scoped(
+            &self.body,
+            &scope.enter_function(name, &self.params),
+        )?);
+
+        // Parameters are renamed first, so arguments that mention other parameters aren't
+        // replaced again.
+        let placeholder = |i: usize| format!("#{}", i);
+        let body = self.params.iter().enumerate().fold(body, |body, (i, p)| {
+            body.substitute(p, &ExpressionElement::Variable(placeholder(i)))
+        });
+        Ok(args
+            .iter()
+            .enumerate()
+            .fold(body, |body, (i, arg)| body.substitute(&placeholder(i), arg)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::math::{
+        expr::ExpresssionTree, func::user::UserFunction, symbol::Number, ExpressionElement,
+        FromRawExpr, Function, IntoRawExpr, LaTexParsingErrorType, ParsingScope,
+    };
+
+    #[test]
+    fn test_user_functions() {
+        let mut functions = HashMap::new();
+        for def in [
+            "f(x)=x^2+1",
+            r#"g(x, y)=\sqrt{x^2+y^2}"#,
+            "h(x,y)=x-y",
+            "fact(n)=n*a",
+        ] {
+            let (name, f) = UserFunction::parse_definition(def).unwrap();
+            functions.insert(name, f);
+        }
+        let vars = HashMap::from([("a".to_string(), Number::Integer(2))]);
+        let scope = ParsingScope::new(Some(&vars)).with_functions(&functions);
+        let parse = |expr| ExpresssionTree::parse_scoped(expr, &scope);
+        let evaluate = |expr| ExpressionElement::from(parse(expr).unwrap().evaluate()).assemble();
+        let error = |expr| parse(expr).unwrap_err().ty;
+
+        assert_eq!(evaluate("f(3)"), "10");
+        assert_eq!(evaluate("2f(1)+1"), "5");
+        assert_eq!(evaluate("g(3, 4)"), "5");
+        assert_eq!(evaluate("g(f(0)+2, 4)"), "5");
+        assert_eq!(evaluate("fact(3)"), "6");
+        assert_eq!(evaluate("f(2)g(3, 4)"), "25");
+        assert_eq!(evaluate("g(3, 4)f(2)"), "25");
+        assert_eq!(evaluate("f(1)a"), "4");
+        assert_eq!(evaluate("f(1)(3)"), "6");
+        assert_eq!(evaluate(r#"\frac{d}{dx}{f(x)}"#), "2*x");
+
+        let scope = ParsingScope::new(None)
+            .with_functions(&functions)
+            .with_symbol("x")
+            .with_symbol("y");
+        let swapped = ExpresssionTree::parse_scoped("h(y, x)", &scope).unwrap();
+        assert_eq!(ExpressionElement::from(swapped).assemble(), "y-x");
+
+        assert_eq!(error("f(1, 2)"), LaTexParsingErrorType::ArityMismatch);
+        assert_eq!(error("g(1)"), LaTexParsingErrorType::ArityMismatch);
+        assert_eq!(error("f(1"), LaTexParsingErrorType::InvalidBracketStructure);
+
+        functions.insert(
+            "p".to_string(),
+            UserFunction::parse_definition("p(x)=q(x)+1").unwrap().1,
+        );
+        functions.insert(
+            "q".to_string(),
+            UserFunction::parse_definition("q(x)=2p(x)").unwrap().1,
+        );
+        let scope = ParsingScope::new(None).with_functions(&functions);
+        assert_eq!(
+            ExpresssionTree::parse_scoped("p(1)", &scope)
+                .unwrap_err()
+                .ty,
+            LaTexParsingErrorType::RecursiveFunction
+        );
+
+        // Functions don't shadow constants, but calls of them come first.
+        functions.insert(
+            "ex".to_string(),
+            UserFunction::parse_definition("ex(t)=t+1").unwrap().1,
+        );
+        let scope = ParsingScope::new(None).with_functions(&functions);
+        let evaluate = |expr| {
+            ExpressionElement::from(
+                ExpresssionTree::parse_scoped(expr, &scope)
+                    .unwrap()
+                    .evaluate(),
+            )
+            .assemble()
+        };
+        assert_eq!(evaluate("ex(1)"), "2");
+        assert_eq!(evaluate("2e"), "2*e");
+        assert_eq!(evaluate("e(2)"), "2*e");
+
+        for def in [
+            "f(x)", "f=x", "F(x)=x", "f(x,x)=x", "f(1)=x", "f(x)=", "e(x)=x", "f(i)=i",
+        ] {
+            assert!(UserFunction::parse_definition(def).is_err());
+        }
+    }
+}
@@ -0,0 +1,1125 @@
+//! Matrices and vectors, written as `pmatrix`, `bmatrix` or `vmatrix` environments.
+
+use std::collections::HashMap;
+
+use crate::{
+    latex::{
+        BEGIN, BMATRIX, CURLY_BRACKET_L, CURLY_BRACKET_R, END, FUNC_BEGIN, MATRIX_COL_SEP,
+        MATRIX_ROW_SEP, PMATRIX, VMATRIX,
+    },
+    math::{
+        expr::ExpresssionTree,
+        func::{
+            exp_log::root,
+            op::{add, divide, multiply, negate, subtract},
+        },
+        parallel,
+        symbol::Number,
+        ExpressionElement, FromRawExpr, Function, IntoRawExpr, LaTexParsingError,
+        LaTexParsingErrorType, LaTexParsingResult, MathElement, ParsingScope,
+    },
+    ComplexScalar, DecimalScalar, IntegerScalar,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixKind {
+    /// `\begin{pmatrix}`, wrapped in parentheses.
+    PMatrix,
+    /// `\begin{bmatrix}`, wrapped in square brackets.
+    BMatrix,
+    /// `\begin{vmatrix}`, which is the determinant of the matrix.
+    VMatrix,
+}
+
+impl MatrixKind {
+    #[inline]
+    pub fn env_name(&self) -> &'static str {
+        match self {
+            MatrixKind::PMatrix => PMATRIX,
+            MatrixKind::BMatrix => BMATRIX,
+            MatrixKind::VMatrix => VMATRIX,
+        }
+    }
+
+    #[inline]
+    pub fn from_env_name(name: &str) -> Option<Self> {
+        match name {
+            PMATRIX => Some(MatrixKind::PMatrix),
+            BMATRIX => Some(MatrixKind::BMatrix),
+            VMATRIX => Some(MatrixKind::VMatrix),
+            _ => None,
+        }
+    }
+}
+
+/// A matrix, whose elements are stored in row-major order.
+///
+/// Vectors are matrices with only one row or column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    kind: MatrixKind,
+    rows: usize,
+    cols: usize,
+    elems: Vec<ExpressionElement>,
+}
+
+impl Matrix {
+    /// Returns `None` if the rows are empty or don't have the same length.
+    pub fn new(kind: MatrixKind, rows: Vec<Vec<ExpressionElement>>) -> Option<Self> {
+        let cols = rows.first()?.len();
+        if cols == 0 || rows.iter().any(|row| row.len() != cols) {
+            return None;
+        }
+
+        Some(Self {
+            kind,
+            rows: rows.len(),
+            cols,
+            elems: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn identity(kind: MatrixKind, n: usize) -> Self {
+        Self {
+            kind,
+            rows: n,
+            cols: n,
+            elems: (0..n * n)
+                .map(|i| Number::Integer((i / n == i % n) as i64).into())
+                .collect(),
+        }
+    }
+
+    #[inline]
+    pub fn kind(&self) -> MatrixKind {
+        self.kind
+    }
+
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    #[inline]
+    pub fn get(&self, row: usize, col: usize) -> &ExpressionElement {
+        &self.elems[row * self.cols + col]
+    }
+
+    #[inline]
+    pub fn elems(&self) -> &[ExpressionElement] {
+        &self.elems
+    }
+
+    #[inline]
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    #[inline]
+    pub fn is_vector(&self) -> bool {
+        self.rows == 1 || self.cols == 1
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self {
+            kind: self.kind,
+            rows: self.cols,
+            cols: self.rows,
+            elems: (0..self.elems.len())
+                .map(|i| self.get(i % self.rows, i / self.rows).clone())
+                .collect(),
+        }
+    }
+
+    /// Applies `f` on every element.
+    pub fn map(&self, f: impl FnMut(&ExpressionElement) -> ExpressionElement) -> Self {
+        Self {
+            kind: self.kind,
+            rows: self.rows,
+            cols: self.cols,
+            elems: self.elems.iter().map(f).collect(),
+        }
+    }
+
+    /// Applies `f` on every pair of elements, `None` if the shapes are different.
+    pub(crate) fn zip_with(
+        &self,
+        rhs: &Matrix,
+        f: impl Fn(ExpressionElement, ExpressionElement) -> ExpressionElement,
+    ) -> Option<Self> {
+        if self.rows != rhs.rows || self.cols != rhs.cols {
+            return None;
+        }
+
+        Some(Self {
+            kind: self.kind,
+            rows: self.rows,
+            cols: self.cols,
+            elems: self
+                .elems
+                .iter()
+                .zip(&rhs.elems)
+                .map(|(lhs, rhs)| f(lhs.clone(), rhs.clone()))
+                .collect(),
+        })
+    }
+
+    /// The matrix product, `None` if the shapes don't match.
+    ///
+    /// Dot products of plain numbers skip the exact engine, and large products are split across
+    /// [`parallel::threads`] threads.
+    pub(crate) fn mul(&self, rhs: &Matrix) -> Option<Self> {
+        if self.cols != rhs.rows {
+            return None;
+        }
+
+        let lhs_rows = (0..self.rows)
+            .map(|row| (0..self.cols).map(|k| as_plain(self.get(row, k))).collect())
+            .collect::<Vec<Option<Vec<_>>>>();
+        let rhs_cols = (0..rhs.cols)
+            .map(|col| (0..rhs.rows).map(|k| as_plain(rhs.get(k, col))).collect())
+            .collect::<Vec<Option<Vec<_>>>>();
+
+        let elems = parallel::map(
+            self.rows * rhs.cols,
+            self.rows * rhs.cols * self.cols,
+            |i| {
+                let (row, col) = (i / rhs.cols, i % rhs.cols);
+                if let (Some(lhs), Some(rhs)) = (&lhs_rows[row], &rhs_cols[col]) {
+                    if let Some(dot) = plain_dot(lhs, rhs) {
+                        return dot;
+                    }
+                }
+
+                (0..self.cols)
+                    .map(|k| multiply(self.get(row, k).clone(), rhs.get(k, col).clone()))
+                    .reduce(add)
+                    .unwrap()
+            },
+        );
+
+        Some(Self {
+            kind: self.kind,
+            rows: self.rows,
+            cols: rhs.cols,
+            elems,
+        })
+    }
+
+    /// Raises a square matrix to a non-negative integer power.
+    pub(crate) fn pow(&self, mut exp: u32) -> Option<Self> {
+        if !self.is_square() {
+            return None;
+        }
+
+        let mut base = self.clone();
+        let mut result = Self::identity(self.kind, self.rows);
+        while exp > 0 {
+            if exp % 2 == 1 {
+                result = result.mul(&base)?;
+            }
+            exp /= 2;
+            if exp > 0 {
+                base = base.mul(&base)?;
+            }
+        }
+
+        Some(result)
+    }
+
+    /// The exact determinant, `None` if the matrix is not square.
+    pub(crate) fn determinant(&self) -> Option<ExpressionElement> {
+        if !self.is_square() {
+            return None;
+        }
+
+        // Cofactor expansion gives nicer results for small matrices.
+        if self.rows <= 3 {
+            return Some(self.cofactor_expansion());
+        }
+
+        Some(self.gaussian_elimination())
+    }
+
+    fn cofactor_expansion(&self) -> ExpressionElement {
+        if self.rows == 1 {
+            return self.elems[0].clone();
+        }
+
+        (0..self.cols)
+            .filter(|col| !is_zero(self.get(0, *col)))
+            .map(|col| {
+                let minor = self.minor(0, col).cofactor_expansion();
+                let term = multiply(self.get(0, col).clone(), minor);
+                (col, term)
+            })
+            .fold(None, |det, (col, term)| match det {
+                None if col % 2 == 0 => Some(term),
+                None => Some(negate(term)),
+                Some(det) if col % 2 == 0 => Some(add(det, term)),
+                Some(det) => Some(subtract(det, term)),
+            })
+            .unwrap_or(Number::Integer(0).into())
+    }
+
+    /// The matrix without the given row and column.
+    fn minor(&self, row: usize, col: usize) -> Self {
+        Self {
+            kind: self.kind,
+            rows: self.rows - 1,
+            cols: self.cols - 1,
+            elems: (0..self.elems.len())
+                .filter(|i| i / self.cols != row && i % self.cols != col)
+                .map(|i| self.elems[i].clone())
+                .collect(),
+        }
+    }
+
+    /// Multiplies the diagonal of `U` in `PA=LU`.
+    fn gaussian_elimination(&self) -> ExpressionElement {
+        let (_, swaps, _, u) = self.lu();
+        let det = (0..self.rows)
+            .map(|k| u[k][k].clone())
+            .fold(Number::Integer(1).into(), multiply);
+
+        if swaps % 2 == 0 {
+            det
+        } else {
+            negate(det)
+        }
+    }
+
+    /// Doolittle decomposition with row swaps, where `PA=LU`.
+    ///
+    /// Returns the rows of `A` in the order they end up in, the number of swaps, `L` and `U`.
+    #[allow(clippy::type_complexity)]
+    fn lu(
+        &self,
+    ) -> (
+        Vec<usize>,
+        usize,
+        Vec<Vec<ExpressionElement>>,
+        Vec<Vec<ExpressionElement>>,
+    ) {
+        let n = self.rows;
+        if let Some(decimals) = self.to_plain_decimals() {
+            let (perm, swaps, l, u) = lu_decimal(decimals);
+            let into_elems = |m: Vec<Vec<DecimalScalar>>| {
+                m.into_iter()
+                    .map(|row| row.into_iter().map(|e| Number::Decimal(e).into()).collect())
+                    .collect()
+            };
+            return (perm, swaps, into_elems(l), into_elems(u));
+        }
+
+        let mut perm = (0..n).collect::<Vec<_>>();
+        let mut swaps = 0;
+        let mut l = Self::identity(self.kind, n).into_rows();
+        let mut u = self.clone().into_rows();
+
+        for k in 0..n {
+            let Some(pivot) = (k..n).find(|row| !is_zero(&u[*row][k])) else {
+                continue;
+            };
+            if pivot != k {
+                u.swap(pivot, k);
+                perm.swap(pivot, k);
+                let (top, bottom) = l.split_at_mut(pivot);
+                top[k][..k].swap_with_slice(&mut bottom[0][..k]);
+                swaps += 1;
+            }
+
+            let (top, below) = u.split_at_mut(k + 1);
+            let pivot_row = &top[k];
+            let factors = parallel::map_mut(below, (n - k) * (n - k), |row| {
+                if is_zero(&row[k]) {
+                    return None;
+                }
+
+                let factor = divide(row[k].clone(), pivot_row[k].clone());
+                for (elem, pivot_elem) in row.iter_mut().zip(pivot_row).skip(k) {
+                    *elem = subtract(elem.clone(), multiply(factor.clone(), pivot_elem.clone()));
+                }
+                // Decimals might not cancel out exactly.
+                row[k] = Number::Integer(0).into();
+                Some(factor)
+            });
+
+            for (row, factor) in l[k + 1..].iter_mut().zip(factors) {
+                if let Some(factor) = factor {
+                    row[k] = factor;
+                }
+            }
+        }
+
+        (perm, swaps, l, u)
+    }
+
+    /// The exact decomposition `PA=LU`, returned as `(P, L, U)`. `None` if the matrix is not
+    /// square.
+    pub fn lu_decomposition(&self) -> Option<(Matrix, Matrix, Matrix)> {
+        if !self.is_square() {
+            return None;
+        }
+
+        let n = self.rows;
+        let (perm, _, l, u) = self.lu();
+        let p = (0..n)
+            .map(|row| {
+                (0..n)
+                    .map(|col| Number::Integer((perm[row] == col) as i64).into())
+                    .collect()
+            })
+            .collect();
+
+        Some((
+            Self::new(self.kind, p)?,
+            Self::new(self.kind, l)?,
+            Self::new(self.kind, u)?,
+        ))
+    }
+
+    /// The decomposition `A=QR` using Householder reflections, where `Q` is orthogonal and `R`
+    /// is upper triangular. `None` if an element doesn't have a real value.
+    ///
+    /// Unlike the other decompositions, this one is always numeric.
+    pub fn qr_decomposition(&self) -> Option<(Matrix, Matrix)> {
+        let (q, r) = householder_qr(&self.to_decimals()?);
+        let into_matrix = |m: Vec<Vec<DecimalScalar>>| {
+            Self::new(
+                self.kind,
+                m.into_iter()
+                    .map(|row| row.into_iter().map(|e| Number::Decimal(e).into()).collect())
+                    .collect(),
+            )
+        };
+
+        Some((into_matrix(q)?, into_matrix(r)?))
+    }
+
+    /// Brings the rows into the reduced row echelon form, and returns the columns of pivots.
+    ///
+    /// Only the first `cols` columns are used as pivots.
+    fn row_reduce(rows: &mut [Vec<ExpressionElement>], cols: usize) -> Vec<usize> {
+        let mut pivots = Vec::new();
+
+        for col in 0..cols {
+            let k = pivots.len();
+            let Some(pivot) = (k..rows.len()).find(|row| !is_zero(&rows[*row][col])) else {
+                continue;
+            };
+            rows.swap(pivot, k);
+
+            let pivot_elem = rows[k][col].clone();
+            for elem in &mut rows[k] {
+                *elem = divide(elem.clone(), pivot_elem.clone());
+            }
+
+            let pivot_row = rows[k].clone();
+            for (row, elems) in rows.iter_mut().enumerate() {
+                if row == k || is_zero(&elems[col]) {
+                    continue;
+                }
+
+                let factor = elems[col].clone();
+                for (elem, pivot_elem) in elems.iter_mut().zip(&pivot_row) {
+                    *elem = subtract(elem.clone(), multiply(factor.clone(), pivot_elem.clone()));
+                }
+                elems[col] = Number::Integer(0).into();
+            }
+
+            pivots.push(col);
+        }
+
+        pivots
+    }
+
+    /// The inverse using Gauss-Jordan elimination, `None` if the matrix is singular.
+    pub(crate) fn inverse(&self) -> Option<Self> {
+        if !self.is_square() {
+            return None;
+        }
+
+        let n = self.rows;
+        let mut rows = self
+            .clone()
+            .into_rows()
+            .into_iter()
+            .zip(Self::identity(self.kind, n).into_rows())
+            .map(|(mut row, identity)| {
+                row.extend(identity);
+                row
+            })
+            .collect::<Vec<_>>();
+
+        if Self::row_reduce(&mut rows, n).len() != n {
+            return None;
+        }
+
+        Self::new(
+            self.kind,
+            rows.into_iter().map(|row| row[n..].to_vec()).collect(),
+        )
+    }
+
+    pub(crate) fn rank(&self) -> usize {
+        Self::row_reduce(&mut self.clone().into_rows(), self.cols).len()
+    }
+
+    /// The sum of the diagonal, `None` if the matrix is not square.
+    pub(crate) fn trace(&self) -> Option<ExpressionElement> {
+        if !self.is_square() {
+            return None;
+        }
+
+        (0..self.rows).map(|k| self.get(k, k).clone()).reduce(add)
+    }
+
+    /// The eigenvalues, `None` if the matrix is not square or has elements without real values.
+    ///
+    /// Triangular and `2\times2` matrices have exact eigenvalues. Others are found numerically
+    /// with the QR algorithm, except for integer eigenvalues of integer matrices.
+    pub(crate) fn eigenvalues(&self) -> Option<Vec<ExpressionElement>> {
+        if !self.is_square() {
+            return None;
+        }
+
+        let n = self.rows;
+        let upper = (0..n).all(|row| (0..row).all(|col| is_zero(self.get(row, col))));
+        let lower = (0..n).all(|row| (row + 1..n).all(|col| is_zero(self.get(row, col))));
+        if upper || lower {
+            return Some((0..n).map(|k| self.get(k, k).clone()).collect());
+        }
+
+        if n == 2 {
+            // \lambda=\frac{t\pm\sqrt{t^2-4d}}{2}
+            let (t, d) = (self.trace()?, self.determinant()?);
+            let disc = subtract(
+                multiply(t.clone(), t.clone()),
+                multiply(Number::Integer(4).into(), d),
+            );
+            let sqrt = root(Number::Integer(2).into(), disc);
+            let two = ExpressionElement::from(Number::Integer(2));
+
+            return Some(vec![
+                divide(add(t.clone(), sqrt.clone()), two.clone()),
+                divide(subtract(t, sqrt), two),
+            ]);
+        }
+
+        let integers = self
+            .elems
+            .iter()
+            .all(|e| e.as_rational().is_some_and(|r| r.is_integer()));
+
+        Some(
+            qr_algorithm(self.to_decimals()?)
+                .into_iter()
+                .map(|lambda| {
+                    // Rational eigenvalues of integer matrices are always integers.
+                    let k = lambda.re.round() as IntegerScalar;
+                    if integers
+                        && lambda.im.abs() < EPSILON
+                        && self
+                            .zip_with(&Self::identity(self.kind, n), |a, i| {
+                                subtract(a, multiply(Number::Integer(k).into(), i))
+                            })
+                            .and_then(|m| m.determinant())
+                            .is_some_and(|det| det.is_integer(0))
+                    {
+                        Number::Integer(k).into()
+                    } else {
+                        Number::from_complex(lambda).into()
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    fn into_rows(self) -> Vec<Vec<ExpressionElement>> {
+        self.elems
+            .chunks(self.cols)
+            .map(|row| row.to_vec())
+            .collect()
+    }
+
+    /// Returns the elements if all of them are [`Number::Decimal`]s.
+    fn to_plain_decimals(&self) -> Option<Vec<Vec<DecimalScalar>>> {
+        self.elems
+            .chunks(self.cols)
+            .map(|row| {
+                row.iter()
+                    .map(|e| match e {
+                        ExpressionElement::Number(Number::Decimal(d)) => Some(*d),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn to_decimals(&self) -> Option<Vec<Vec<DecimalScalar>>> {
+        self.elems
+            .chunks(self.cols)
+            .map(|row| {
+                row.iter()
+                    .map(|e| Some(e.approximate()).filter(|e| !e.is_nan()))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Decimals closer to zero than this are treated as zeros, as they are most likely rounding
+/// errors.
+const EPSILON: DecimalScalar = 1e-10;
+/// The QR algorithm gives up after this many iterations.
+const MAX_QR_ITERATIONS: usize = 1000;
+
+/// Numbers that don't need the exact engine.
+#[derive(Debug, Clone, Copy)]
+enum Plain {
+    Integer(IntegerScalar),
+    Decimal(DecimalScalar),
+}
+
+impl Plain {
+    #[inline]
+    fn to_decimal(self) -> DecimalScalar {
+        match self {
+            Plain::Integer(i) => i as DecimalScalar,
+            Plain::Decimal(d) => d,
+        }
+    }
+}
+
+#[inline]
+fn as_plain(elem: &ExpressionElement) -> Option<Plain> {
+    match elem {
+        ExpressionElement::Number(Number::Integer(i)) => Some(Plain::Integer(*i)),
+        ExpressionElement::Number(Number::Decimal(d)) => Some(Plain::Decimal(*d)),
+        _ => None,
+    }
+}
+
+/// The dot product, `None` if integers overflow.
+///
+/// Like in the exact engine, the result is only a decimal if a decimal is involved.
+fn plain_dot(lhs: &[Plain], rhs: &[Plain]) -> Option<ExpressionElement> {
+    let integers = lhs
+        .iter()
+        .chain(rhs)
+        .all(|e| matches!(e, Plain::Integer(_)));
+
+    if integers {
+        let dot = lhs.iter().zip(rhs).try_fold(0i128, |dot, (l, r)| {
+            let (Plain::Integer(l), Plain::Integer(r)) = (l, r) else {
+                unreachable!()
+            };
+            dot.checked_add(*l as i128 * *r as i128)
+        })?;
+        return IntegerScalar::try_from(dot)
+            .ok()
+            .map(|dot| Number::Integer(dot).into());
+    }
+
+    Some(
+        Number::Decimal(
+            lhs.iter()
+                .zip(rhs)
+                .map(|(l, r)| l.to_decimal() * r.to_decimal())
+                .sum(),
+        )
+        .into(),
+    )
+}
+
+/// [`Matrix::lu`] for matrices of decimals, picking the largest pivot of each column to keep
+/// rounding errors small.
+#[allow(clippy::type_complexity)]
+fn lu_decimal(
+    mut u: Vec<Vec<DecimalScalar>>,
+) -> (
+    Vec<usize>,
+    usize,
+    Vec<Vec<DecimalScalar>>,
+    Vec<Vec<DecimalScalar>>,
+) {
+    let n = u.len();
+    let mut perm = (0..n).collect::<Vec<_>>();
+    let mut swaps = 0;
+    let mut l = (0..n)
+        .map(|row| {
+            (0..n)
+                .map(|col| (row == col) as u8 as DecimalScalar)
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+
+    for k in 0..n {
+        let pivot = (k..n)
+            .max_by(|a, b| u[*a][k].abs().total_cmp(&u[*b][k].abs()))
+            .unwrap();
+        if u[pivot][k].abs() < EPSILON {
+            continue;
+        }
+        if pivot != k {
+            u.swap(pivot, k);
+            perm.swap(pivot, k);
+            let (top, bottom) = l.split_at_mut(pivot);
+            top[k][..k].swap_with_slice(&mut bottom[0][..k]);
+            swaps += 1;
+        }
+
+        let (top, below) = u.split_at_mut(k + 1);
+        let pivot_row = &top[k];
+        let factors = parallel::map_mut(below, (n - k) * (n - k), |row| {
+            let factor = row[k] / pivot_row[k];
+            for (elem, pivot_elem) in row.iter_mut().zip(pivot_row).skip(k) {
+                *elem -= factor * pivot_elem;
+            }
+            row[k] = 0.;
+            factor
+        });
+
+        for (row, factor) in l[k + 1..].iter_mut().zip(factors) {
+            row[k] = factor;
+        }
+    }
+
+    (perm, swaps, l, u)
+}
+
+/// Returns `(Q, R)` of a `m\times n` matrix.
+#[allow(clippy::type_complexity)]
+fn householder_qr(a: &[Vec<DecimalScalar>]) -> (Vec<Vec<DecimalScalar>>, Vec<Vec<DecimalScalar>>) {
+    let (m, n) = (a.len(), a[0].len());
+    let mut q = (0..m)
+        .map(|row| {
+            (0..m)
+                .map(|col| (row == col) as u8 as DecimalScalar)
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+    let mut r = a.to_vec();
+
+    for k in 0..n.min(m.saturating_sub(1)) {
+        let norm = (k..m)
+            .map(|row| r[row][k].powi(2))
+            .sum::<DecimalScalar>()
+            .sqrt();
+        if norm == 0. {
+            continue;
+        }
+
+        // v=x-\alpha e_1, with the sign of \alpha chosen to avoid cancellation.
+        let mut v = (k..m).map(|row| r[row][k]).collect::<Vec<_>>();
+        v[0] += norm.copysign(v[0]);
+        let v_norm = v.iter().map(|e| e * e).sum::<DecimalScalar>().sqrt();
+        v.iter_mut().for_each(|e| *e /= v_norm);
+
+        // R=HR, Q=QH, where H=I-2vv^T
+        for col in 0..n {
+            let dot = (k..m)
+                .map(|row| v[row - k] * r[row][col])
+                .sum::<DecimalScalar>();
+            for (row, v) in r[k..].iter_mut().zip(&v) {
+                row[col] -= 2. * v * dot;
+            }
+        }
+        for row in q.iter_mut() {
+            let dot = (k..m)
+                .map(|col| row[col] * v[col - k])
+                .sum::<DecimalScalar>();
+            for col in k..m {
+                row[col] -= 2. * dot * v[col - k];
+            }
+        }
+
+        r[k + 1..].iter_mut().for_each(|row| row[k] = 0.);
+    }
+
+    (q, r)
+}
+
+/// Finds the eigenvalues by repeating `A=RQ` until `A` is upper triangular, except for `2\times2`
+/// blocks of complex eigenvalues.
+fn qr_algorithm(mut a: Vec<Vec<DecimalScalar>>) -> Vec<ComplexScalar> {
+    let n = a.len();
+    let negligible = |a: &[Vec<DecimalScalar>], k: usize| {
+        a[k + 1][k].abs() < EPSILON * (a[k][k].abs() + a[k + 1][k + 1].abs()).max(1.)
+    };
+
+    for _ in 0..MAX_QR_ITERATIONS {
+        if (0..n - 1).all(|k| negligible(&a, k)) {
+            break;
+        }
+
+        let (q, r) = householder_qr(&a);
+        a = (0..n)
+            .map(|row| {
+                (0..n)
+                    .map(|col| (0..n).map(|k| r[row][k] * q[k][col]).sum())
+                    .collect()
+            })
+            .collect();
+    }
+
+    let mut eigenvalues = Vec::with_capacity(n);
+    let mut k = 0;
+    while k < n {
+        if k + 1 < n && !negligible(&a, k) {
+            let t = a[k][k] + a[k + 1][k + 1];
+            let d = a[k][k] * a[k + 1][k + 1] - a[k][k + 1] * a[k + 1][k];
+            let sqrt = ComplexScalar::from(t * t - 4. * d).sqrt();
+            eigenvalues.push((sqrt + t) / 2.);
+            eigenvalues.push((-sqrt + t) / 2.);
+            k += 2;
+        } else {
+            eigenvalues.push(a[k][k].into());
+            k += 1;
+        }
+    }
+
+    eigenvalues
+}
+
+fn is_zero(elem: &ExpressionElement) -> bool {
+    if elem.is_decimal() {
+        elem.approximate_complex().norm() < EPSILON
+    } else {
+        elem.is_integer(0) || elem.approximate() == 0.
+    }
+}
+
+/// Finds the length of the environment at the start of `expr`, including the `\end{...}`.
+pub(crate) fn environment_len(expr: &str) -> Option<usize> {
+    let begin = format!("{}{}", FUNC_BEGIN, BEGIN);
+    let end = format!("{}{}", FUNC_BEGIN, END);
+    let mut depth = 0;
+
+    for (i, _) in expr.char_indices() {
+        if expr[i..].starts_with(&begin) {
+            depth += 1;
+        } else if expr[i..].starts_with(&end) {
+            depth -= 1;
+            if depth == 0 {
+                return expr[i..].find(CURLY_BRACKET_R).map(|len| i + len + 1);
+            }
+        }
+    }
+
+    None
+}
+
+/// Splits `expr` at `sep`s that are not inside curly brackets or nested environments.
+pub(crate) fn split_top_level<'a>(expr: &'a str, sep: &str) -> Vec<(usize, &'a str)> {
+    let begin = format!("{}{}", FUNC_BEGIN, BEGIN);
+    let end = format!("{}{}", FUNC_BEGIN, END);
+    let mut depth = 0;
+    let mut start = 0;
+    let mut pieces = Vec::new();
+
+    for (i, c) in expr.char_indices() {
+        if i < start {
+            continue;
+        }
+
+        match c {
+            CURLY_BRACKET_L => depth += 1,
+            CURLY_BRACKET_R => depth -= 1,
+            _ if expr[i..].starts_with(&begin) => depth += 1,
+            _ if expr[i..].starts_with(&end) => depth -= 1,
+            _ if depth == 0 && expr[i..].starts_with(sep) => {
+                pieces.push((start, &expr[start..i]));
+                start = i + sep.len();
+            }
+            _ => {}
+        }
+    }
+
+    pieces.push((start, &expr[start..]));
+    pieces
+}
+
+impl FromRawExpr for Matrix {
+    #[inline]
+    fn parse_raw(expr: &str, vars: Option<&HashMap<String, Number>>) -> LaTexParsingResult<Self> {
+        Self::parse_scoped(expr, &ParsingScope::new(vars))
+    }
+
+    fn parse_scoped(expr: &str, scope: &ParsingScope) -> LaTexParsingResult<Self> {
+        let err = |range: std::ops::Range<usize>| {
+            LaTexParsingError::new(range.into(), LaTexParsingErrorType::InvalidMatrix)
+        };
+
+        let name = expr
+            .strip_prefix(FUNC_BEGIN)
+            .and_then(|e| e.strip_prefix(BEGIN))
+            .and_then(|e| e.strip_prefix(CURLY_BRACKET_L))
+            .and_then(|e| e.split(CURLY_BRACKET_R).next())
+            .ok_or_else(|| err(0..expr.len()))?;
+        let kind = MatrixKind::from_env_name(name).ok_or_else(|| err(0..expr.len()))?;
+
+        let body_start = BEGIN.len() + name.len() + 3;
+        let end = format!(
+            "{}{}{}{}{}",
+            FUNC_BEGIN, END, CURLY_BRACKET_L, name, CURLY_BRACKET_R
+        );
+        let body_end = expr
+            .strip_suffix(&end)
+            .map(str::len)
+            .ok_or_else(|| err(0..expr.len()))?;
+        let body = &expr[body_start..body_end];
+
+        let mut row_exprs = split_top_level(body, MATRIX_ROW_SEP);
+        // A trailing `\\` doesn't start a new row.
+        if row_exprs.len() > 1 && row_exprs.last().unwrap().1.trim().is_empty() {
+            row_exprs.pop();
+        }
+
+        let mut rows = Vec::with_capacity(row_exprs.len());
+        for (row_start, row) in row_exprs {
+            let mut elems = Vec::new();
+            for (elem_start, elem) in split_top_level(row, &MATRIX_COL_SEP.to_string()) {
+                elems.push(
+                    ExpresssionTree::parse_scoped_with_base_index(
+                        elem,
+                        scope,
+                        body_start + row_start + elem_start,
+                    )?
+                    .into(),
+                );
+            }
+
+            if rows
+                .first()
+                .is_some_and(|r: &Vec<_>| r.len() != elems.len())
+            {
+                let row_start = body_start + row_start;
+                return Err(err(row_start..row_start + row.len()));
+            }
+            rows.push(elems);
+        }
+
+        // Determinants only exist for square matrices.
+        Matrix::new(kind, rows)
+            .filter(|m| kind != MatrixKind::VMatrix || m.rows == m.cols)
+            .ok_or_else(|| err(0..expr.len()))
+    }
+}
+
+impl IntoRawExpr for Matrix {
+    fn assemble(&self) -> String {
+        let body = self
+            .elems
+            .chunks(self.cols)
+            .map(|row| {
+                row.iter()
+                    .map(|e| e.assemble())
+                    .collect::<Vec<_>>()
+                    .join(&MATRIX_COL_SEP.to_string())
+            })
+            .collect::<Vec<_>>()
+            .join(MATRIX_ROW_SEP);
+
+        format!(
+            "{}{}{{{}}}{}{}{}{{{}}}",
+            FUNC_BEGIN,
+            BEGIN,
+            self.kind.env_name(),
+            body,
+            FUNC_BEGIN,
+            END,
+            self.kind.env_name()
+        )
+    }
+}
+
+impl Function for Matrix {
+    fn evaluate(&self) -> MathElement {
+        let evaluated = self.map(|e| e.evaluate().into());
+        match self.kind {
+            MatrixKind::VMatrix => match evaluated.determinant() {
+                Some(det) => det.into(),
+                None => MathElement::Matrix(evaluated),
+            },
+            _ => MathElement::Matrix(evaluated),
+        }
+    }
+
+    /// Only determinants have a scalar value.
+    fn approximate(&self) -> DecimalScalar {
+        match self.kind {
+            MatrixKind::VMatrix => self
+                .map(|e| Number::Decimal(e.approximate()).into())
+                .determinant()
+                .map_or(DecimalScalar::NAN, |det| det.approximate()),
+            _ => DecimalScalar::NAN,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn matrix(expr: &str) -> Matrix {
+        Matrix::parse_raw(expr, None)
+            .unwrap()
+            .map(|e| e.evaluate().into())
+    }
+
+    #[test]
+    fn test_matrix_parser() {
+        let m = matrix(r#"\begin{bmatrix}1 & 2 & 3\\ 4 & 5 & \frac{1}{2}\\\end{bmatrix}"#);
+        assert_eq!((m.rows(), m.cols(), m.kind()), (2, 3, MatrixKind::BMatrix));
+        assert_eq!(
+            m.transpose().assemble(),
+            r#"\begin{bmatrix}1&4\\2&5\\3&\frac{1}{2}\end{bmatrix}"#
+        );
+        assert_eq!(
+            Matrix::parse_raw(r#"\begin{pmatrix}1&2\\3\end{pmatrix}"#, None).unwrap_err(),
+            LaTexParsingError::new((20..21).into(), LaTexParsingErrorType::InvalidMatrix)
+        );
+        assert_eq!(
+            Matrix::parse_raw(r#"\begin{matrix}1\end{matrix}"#, None).unwrap_err(),
+            LaTexParsingError::new((0..27).into(), LaTexParsingErrorType::InvalidMatrix)
+        );
+        assert_eq!(
+            Matrix::parse_raw(r#"\begin{vmatrix}1&2\end{vmatrix}"#, None).unwrap_err(),
+            LaTexParsingError::new((0..31).into(), LaTexParsingErrorType::InvalidMatrix)
+        );
+    }
+
+    #[test]
+    fn test_determinant() {
+        assert_eq!(
+            matrix(r#"\begin{vmatrix}2&0&1\\1&3&2\\1&1&2\end{vmatrix}"#)
+                .determinant()
+                .unwrap(),
+            Number::Integer(6).into()
+        );
+        // Large enough for Gaussian elimination.
+        assert_eq!(
+            matrix(r#"\begin{vmatrix}0&2&0&0\\1&0&0&0\\0&0&\frac{1}{2}&1\\0&0&3&4\end{vmatrix}"#)
+                .determinant()
+                .unwrap(),
+            Number::Integer(2).into()
+        );
+    }
+
+    #[test]
+    fn test_decompositions() {
+        let m = matrix(r#"\begin{pmatrix}0&2&1\\2&2&0\\4&1&3\end{pmatrix}"#);
+        let (p, l, u) = m.lu_decomposition().unwrap();
+        assert_eq!(
+            p.assemble(),
+            r#"\begin{pmatrix}0&1&0\\1&0&0\\0&0&1\end{pmatrix}"#
+        );
+        assert_eq!(
+            l.assemble(),
+            r#"\begin{pmatrix}1&0&0\\0&1&0\\2&-\frac{3}{2}&1\end{pmatrix}"#
+        );
+        assert_eq!(
+            u.assemble(),
+            r#"\begin{pmatrix}2&2&0\\0&2&1\\0&0&\frac{9}{2}\end{pmatrix}"#
+        );
+        assert_eq!(p.mul(&m), l.mul(&u));
+
+        let (q, r) = m.qr_decomposition().unwrap();
+        let qr = q.mul(&r).unwrap();
+        for (a, b) in qr.elems().iter().zip(m.elems()) {
+            assert!((a.approximate() - b.approximate()).abs() < 1e-12);
+        }
+        assert!((0..3).all(|row| (0..row).all(|col| r.get(row, col).approximate() == 0.)));
+    }
+
+    #[test]
+    fn test_linear_algebra() {
+        let m = matrix(r#"\begin{pmatrix}2&1\\5&3\end{pmatrix}"#);
+        assert_eq!(
+            m.inverse().unwrap().assemble(),
+            r#"\begin{pmatrix}3&-1\\-5&2\end{pmatrix}"#
+        );
+        assert_eq!(
+            matrix(r#"\begin{pmatrix}1&2\\2&4\end{pmatrix}"#).inverse(),
+            None
+        );
+        assert_eq!(m.trace().unwrap(), Number::Integer(5).into());
+
+        assert_eq!(
+            matrix(r#"\begin{pmatrix}1&2&3\\2&4&6\\1&0&1\end{pmatrix}"#).rank(),
+            2
+        );
+        assert_eq!(
+            matrix(r#"\begin{pmatrix}0.1&0.2\\0.3&0.6\end{pmatrix}"#).rank(),
+            1
+        );
+
+        let eigenvalues = |expr: &str| {
+            matrix(expr)
+                .eigenvalues()
+                .unwrap()
+                .iter()
+                .map(|e| e.assemble())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            eigenvalues(r#"\begin{pmatrix}2&1\\1&2\end{pmatrix}"#),
+            ["3", "1"]
+        );
+        assert_eq!(
+            eigenvalues(r#"\begin{pmatrix}0&-1\\1&0\end{pmatrix}"#),
+            ["i", "-i"]
+        );
+        assert_eq!(
+            eigenvalues(r#"\begin{pmatrix}1&5&7\\0&2&1\\0&0&3\end{pmatrix}"#),
+            ["1", "2", "3"]
+        );
+        assert_eq!(
+            eigenvalues(r#"\begin{pmatrix}2&0&0\\1&2&1\\1&1&2\end{pmatrix}"#),
+            ["3", "2", "1"]
+        );
+    }
+
+    #[test]
+    fn test_parallel_determinism() {
+        use crate::math::{parallel, symbol::Rational};
+
+        // A simple LCG, so the matrices are the same every time.
+        let mut seed = 42u64;
+        let mut next = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 33) % 100
+        };
+        let mut random_matrix = |n: usize, f: &dyn Fn(u64) -> Number| {
+            Matrix::new(
+                MatrixKind::PMatrix,
+                (0..n)
+                    .map(|_| (0..n).map(|_| f(next()).into()).collect())
+                    .collect(),
+            )
+            .unwrap()
+        };
+
+        let mut a = random_matrix(80, &|x| Number::Integer(x as i64 - 50));
+        a.elems[0] = Number::Rational(Rational::new(1, 2).unwrap()).into();
+        let b = random_matrix(80, &|x| Number::Decimal(x as f64 / 7.));
+        let c = random_matrix(300, &|x| Number::Decimal(x as f64 / 10. - 5.));
+
+        let single = parallel::with_threads(1, || (a.mul(&b), a.mul(&a), c.determinant()));
+        for threads in [2, 3, 8] {
+            assert_eq!(
+                parallel::with_threads(threads, || (a.mul(&b), a.mul(&a), c.determinant())),
+                single
+            );
+        }
+        assert_eq!(parallel::threads(), 1);
+    }
+}
@@ -1,19 +1,30 @@
 use std::{collections::HashMap, fmt::Display};
 
 use crate::{
+    latex::PARENTHESES_L,
     math::func::{Function, PhantomFunction, PhantomOperator},
-    DecimalScalar,
+    ComplexScalar, DecimalScalar, IntegerScalar,
 };
 
 use self::{
     expr::ExpressionBuffer,
-    func::decl::MathFunction,
-    symbol::{BracketState, Number},
+    func::{decl::MathFunction, user::UserFunction},
+    matrix::Matrix,
+    symbol::{BracketState, Constant, Number, Rational},
 };
 
+pub mod bytecode;
 pub mod expr;
 pub mod func;
+pub mod matrix;
+pub mod mpoly;
+pub mod parallel;
+pub mod poly;
+#[cfg(feature = "bignum")]
+pub mod precise;
+pub mod simplify;
 pub mod symbol;
+pub mod system;
 
 pub trait FromExpr {
     fn convert(expr: Vec<Option<ExpressionElement>>) -> Self
@@ -39,6 +50,154 @@ pub trait FromRawExpr {
             e
         })
     }
+
+    /// Parses `expr` with the symbols in `scope`. Types that can't contain symbols only look
+    /// at the variables.
+    fn parse_scoped(expr: &str, scope: &ParsingScope) -> LaTexParsingResult<Self>
+    where
+        Self: Sized,
+    {
+        Self::parse_raw(expr, scope.vars())
+    }
+
+    fn parse_scoped_with_base_index(
+        expr: &str,
+        scope: &ParsingScope,
+        base: usize,
+    ) -> LaTexParsingResult<Self>
+    where
+        Self: Sized,
+    {
+        Self::parse_scoped(expr, scope).map_err(|mut e| {
+            e.at.start += base;
+            e
+        })
+    }
+}
+
+/// The names that are known while parsing, besides constants and functions.
+#[derive(Debug, Clone, Default)]
+pub struct ParsingScope<'a> {
+    vars: Option<&'a HashMap<String, Number>>,
+    /// Names that stay symbolic, like the `x` in `\frac{d}{dx}{x^2}`.
+    symbols: Vec<String>,
+    functions: Option<&'a HashMap<String, UserFunction>>,
+    /// The user functions whose bodies are being parsed, which can't be called again.
+    expanding: Vec<String>,
+    /// Whether names that aren't known become symbols instead of errors.
+    free_symbols: bool,
+    /// Whether variables are kept by name instead of replaced by their values.
+    variable_refs: bool,
+}
+
+impl<'a> ParsingScope<'a> {
+    #[inline]
+    pub fn new(vars: Option<&'a HashMap<String, Number>>) -> Self {
+        Self {
+            vars,
+            ..Default::default()
+        }
+    }
+
+    /// Makes every name that isn't a variable, symbol or function a symbol too, like the
+    /// `random` in `1+random*5`. Runs of letters that don't start with a known name are a single
+    /// symbol.
+    #[inline]
+    pub fn with_free_symbols(mut self) -> Self {
+        self.free_symbols = true;
+        self
+    }
+
+    #[inline]
+    pub fn has_free_symbols(&self) -> bool {
+        self.free_symbols
+    }
+
+    /// Keeps variables by name, so the tree can be parsed once and evaluated with different
+    /// values of them. They are still matched like variables, not symbols.
+    #[inline]
+    pub fn with_variable_refs(mut self) -> Self {
+        self.variable_refs = true;
+        self
+    }
+
+    #[inline]
+    pub fn keeps_variable_refs(&self) -> bool {
+        self.variable_refs
+    }
+
+    #[inline]
+    pub fn with_functions(mut self, functions: &'a HashMap<String, UserFunction>) -> Self {
+        self.functions = Some(functions);
+        self
+    }
+
+    #[inline]
+    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.add_symbol(symbol);
+        self
+    }
+
+    #[inline]
+    pub fn add_symbol(&mut self, symbol: impl Into<String>) {
+        self.symbols.push(symbol.into());
+    }
+
+    #[inline]
+    pub fn vars(&self) -> Option<&'a HashMap<String, Number>> {
+        self.vars
+    }
+
+    #[inline]
+    pub fn symbols(&self) -> &[String] {
+        &self.symbols
+    }
+
+    #[inline]
+    pub fn get_var(&self, name: &str) -> Option<&Number> {
+        self.vars.and_then(|vars| vars.get(name))
+    }
+
+    #[inline]
+    pub fn is_symbol(&self, name: &str) -> bool {
+        self.symbols.iter().any(|s| s == name)
+    }
+
+    #[inline]
+    pub fn get_function(&self, name: &str) -> Option<&'a UserFunction> {
+        self.functions.and_then(|functions| functions.get(name))
+    }
+
+    #[inline]
+    pub fn is_expanding(&self, name: &str) -> bool {
+        self.expanding.iter().any(|f| f == name)
+    }
+
+    /// The scope of the body of the user function `name`, where the parameters are symbols too.
+    pub fn enter_function(&self, name: &str, params: &[String]) -> Self {
+        let mut scope = self.clone();
+        scope.symbols.extend_from_slice(params);
+        scope.expanding.push(name.to_string());
+        scope
+    }
+
+    /// Checks if `expr` starts with a variable or symbol other than `name`, like `index` for `i`.
+    pub fn starts_other_name(&self, expr: &str, name: &str) -> bool {
+        let matches = |k: &str| k != name && expr.starts_with(k);
+        self.vars
+            .is_some_and(|vars| vars.keys().any(|k| matches(k)))
+            || self.symbols.iter().any(|s| matches(s))
+    }
+
+    /// Checks if `expr` starts with a call of a user function, like `ex(2)`.
+    pub fn starts_call(&self, expr: &str) -> bool {
+        self.functions.is_some_and(|functions| {
+            functions.keys().any(|k| {
+                expr.strip_prefix(k.as_str())
+                    .is_some_and(|rest| rest.starts_with(PARENTHESES_L))
+            })
+        })
+    }
 }
 
 pub trait IntoRawExpr {
@@ -91,6 +250,15 @@ pub enum LaTexParsingErrorType {
     InvalidFunctionCall,
     UnknownCharacter,
     UnknownVariable,
+    InvalidMatrix,
+    /// A user function definition that isn't like `f(x)=x^2+1`.
+    InvalidFunctionDefinition,
+    /// A user function that calls itself, directly or through other functions.
+    RecursiveFunction,
+    /// A user function called with the wrong number of arguments.
+    ArityMismatch,
+    /// An operator without an operand on one of its sides, like in `1+` or `=1`.
+    MissingOperand,
     Unknown,
 }
 
@@ -100,10 +268,184 @@ impl Display for LaTexParsingErrorType {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ExpressionElement {
     Number(Number),
     Function(Box<MathFunction>),
+    Matrix(Matrix),
+    /// A symbol without a value, like the `x` in `\frac{d}{dx}{x^2}`.
+    Variable(String),
+}
+
+impl ExpressionElement {
+    /// Returns the exact value if this element is an integer or a rational.
+    #[inline]
+    pub(crate) fn as_rational(&self) -> Option<Rational> {
+        match self {
+            ExpressionElement::Number(n) => n.as_rational(),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn is_decimal(&self) -> bool {
+        match self {
+            ExpressionElement::Number(Number::Decimal(_) | Number::Complex(_)) => true,
+            #[cfg(feature = "bignum")]
+            ExpressionElement::Number(Number::BigDecimal(_)) => true,
+            _ => false,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn is_matrix(&self) -> bool {
+        matches!(self, ExpressionElement::Matrix(_))
+    }
+
+    #[inline]
+    pub(crate) fn is_imaginary_unit(&self) -> bool {
+        matches!(
+            self,
+            ExpressionElement::Number(Number::Constant(Constant::I))
+        )
+    }
+
+    #[inline]
+    pub(crate) fn is_integer(&self, i: IntegerScalar) -> bool {
+        matches!(self, ExpressionElement::Number(Number::Integer(n)) if *n == i)
+    }
+
+    /// Checks if `var` shows up in this element.
+    #[inline]
+    pub fn depends_on(&self, var: &str) -> bool {
+        self.any_variable(&|v| v == var)
+    }
+
+    /// Checks if any variable shows up in this element, so it has no value by itself.
+    #[inline]
+    pub fn is_symbolic(&self) -> bool {
+        self.any_variable(&|_| true)
+    }
+
+    pub(crate) fn any_variable(&self, f: &dyn Fn(&str) -> bool) -> bool {
+        match self {
+            ExpressionElement::Number(_) => false,
+            ExpressionElement::Function(func) => {
+                let params = func.params();
+                match func.bound_var() {
+                    Some(x) => {
+                        let (operand, rest) = params.split_last().unwrap();
+                        rest[1..].iter().any(|p| p.any_variable(f))
+                            || operand.any_variable(&|v| v != x && f(v))
+                    }
+                    None => params.iter().any(|p| p.any_variable(f)),
+                }
+            }
+            ExpressionElement::Matrix(m) => m.elems().iter().any(|e| e.any_variable(f)),
+            ExpressionElement::Variable(v) => f(v),
+        }
+    }
+
+    /// Replaces every free occurrence of `var` with `value`.
+    pub(crate) fn substitute(&self, var: &str, value: &ExpressionElement) -> ExpressionElement {
+        match self {
+            ExpressionElement::Number(_) => self.clone(),
+            ExpressionElement::Function(func) => match &**func {
+                // Only the bounds can be substituted.
+                _ if func.bound_var() == Some(var) => {
+                    let last = func.params().len() - 1;
+                    let mut i = 0;
+                    ExpressionElement::Function(Box::new(func.map_params(|p| {
+                        i += 1;
+                        if i == 1 || i > last {
+                            p.clone()
+                        } else {
+                            p.substitute(var, value)
+                        }
+                    })))
+                }
+                // Derivatives and antiderivatives have to be found before their variable is gone.
+                MathFunction::Derivative(_)
+                | MathFunction::PartialDerivative(_)
+                | MathFunction::IndefiniteIntegral(_)
+                    if func.params()[0] == &ExpressionElement::Variable(var.to_string()) =>
+                {
+                    match ExpressionElement::from(func.evaluate()) {
+                        ExpressionElement::Function(f)
+                            if matches!(
+                                *f,
+                                MathFunction::Derivative(_)
+                                    | MathFunction::PartialDerivative(_)
+                                    | MathFunction::IndefiniteIntegral(_)
+                            ) =>
+                        {
+                            ExpressionElement::Function(f)
+                        }
+                        found => found.substitute(var, value),
+                    }
+                }
+                _ => ExpressionElement::Function(Box::new(
+                    func.map_params(|p| p.substitute(var, value)),
+                )),
+            },
+            ExpressionElement::Matrix(m) => m.map(|e| e.substitute(var, value)).into(),
+            ExpressionElement::Variable(v) if v == var => value.clone(),
+            ExpressionElement::Variable(_) => self.clone(),
+        }
+    }
+
+    /// Evaluates every subtree without variables, keeping the shape of the rest, like `4+y`
+    /// for `2^2+y`.
+    pub(crate) fn fold_constants(&self) -> ExpressionElement {
+        if !self.is_symbolic() {
+            return self.evaluate().into();
+        }
+
+        match self {
+            ExpressionElement::Function(func) => {
+                ExpressionElement::Function(Box::new(func.map_params(|p| p.fold_constants())))
+            }
+            ExpressionElement::Matrix(m) => m.map(|e| e.fold_constants()).into(),
+            _ => self.clone(),
+        }
+    }
+}
+
+impl From<Number> for ExpressionElement {
+    #[inline]
+    fn from(value: Number) -> Self {
+        ExpressionElement::Number(value)
+    }
+}
+
+impl From<Matrix> for ExpressionElement {
+    #[inline]
+    fn from(value: Matrix) -> Self {
+        ExpressionElement::Matrix(value)
+    }
+}
+
+impl From<MathElement> for ExpressionElement {
+    fn from(value: MathElement) -> Self {
+        match value {
+            MathElement::Number(n) => ExpressionElement::Number(n),
+            MathElement::Function(f) => ExpressionElement::Function(Box::new(f)),
+            MathElement::Matrix(m) => ExpressionElement::Matrix(m),
+            MathElement::Variable(v) => ExpressionElement::Variable(v),
+            _ => unreachable!("Only values and functions can be converted into expressions."),
+        }
+    }
+}
+
+impl From<ExpressionElement> for MathElement {
+    fn from(value: ExpressionElement) -> Self {
+        match value {
+            ExpressionElement::Number(n) => MathElement::Number(n),
+            ExpressionElement::Function(f) => MathElement::Function(*f),
+            ExpressionElement::Matrix(m) => MathElement::Matrix(m),
+            ExpressionElement::Variable(v) => MathElement::Variable(v),
+        }
+    }
 }
 
 impl IntoRawExpr for ExpressionElement {
@@ -111,6 +453,8 @@ impl IntoRawExpr for ExpressionElement {
         match self {
             ExpressionElement::Number(n) => n.assemble(),
             ExpressionElement::Function(n) => n.assemble(),
+            ExpressionElement::Matrix(m) => m.assemble(),
+            ExpressionElement::Variable(v) => v.clone(),
         }
     }
 }
@@ -118,21 +462,51 @@ impl IntoRawExpr for ExpressionElement {
 impl Prioritizable for ExpressionElement {
     fn priority(&self) -> u32 {
         match self {
-            ExpressionElement::Number(_) => 1,
-            ExpressionElement::Function(_) => 10,
+            // Complex numbers like `1+2i` are sums.
+            ExpressionElement::Number(Number::Complex(c)) if c.re != 0. => 1,
+            ExpressionElement::Number(_)
+            | ExpressionElement::Matrix(_)
+            | ExpressionElement::Variable(_) => 10,
+            ExpressionElement::Function(f) => f.priority(),
         }
     }
 }
 
 impl Function for ExpressionElement {
     fn evaluate(&self) -> MathElement {
-        todo!()
+        match self {
+            ExpressionElement::Number(n) => n.evaluate(),
+            ExpressionElement::Function(f) => f.evaluate(),
+            ExpressionElement::Matrix(m) => m.evaluate(),
+            ExpressionElement::Variable(v) => MathElement::Variable(v.clone()),
+        }
     }
 
     fn approximate(&self) -> DecimalScalar {
         match self {
             ExpressionElement::Number(n) => n.approximate(),
             ExpressionElement::Function(f) => f.approximate(),
+            ExpressionElement::Matrix(m) => m.approximate(),
+            ExpressionElement::Variable(_) => DecimalScalar::NAN,
+        }
+    }
+
+    fn approximate_complex(&self) -> ComplexScalar {
+        match self {
+            ExpressionElement::Number(n) => n.approximate_complex(),
+            ExpressionElement::Function(f) => f.approximate_complex(),
+            ExpressionElement::Matrix(m) => m.approximate_complex(),
+            ExpressionElement::Variable(_) => ComplexScalar::new(DecimalScalar::NAN, 0.),
+        }
+    }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<crate::BigDecimalScalar> {
+        match self {
+            ExpressionElement::Number(n) => n.approximate_precise(precision),
+            ExpressionElement::Function(f) => f.approximate_precise(precision),
+            ExpressionElement::Matrix(m) => m.approximate_precise(precision),
+            ExpressionElement::Variable(_) => None,
         }
     }
 }
@@ -142,7 +516,11 @@ pub enum MathElement {
     Number(Number),
     Parentheses(BracketState),
     Function(MathFunction),
+    Matrix(Matrix),
+    Variable(String),
     PhantomFunction(Box<dyn PhantomFunction>),
+    /// Functions that come after their parameter, like the transpose in `A^T`.
+    PostfixFunction(Box<dyn PhantomFunction>),
     PhantomOperator(Box<dyn PhantomOperator>),
     Expression(ExpressionBuffer),
 }
@@ -0,0 +1,684 @@
+//! Multivariate polynomials with exact rational coefficients, which can be expanded, collected,
+//! divided and factored.
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+};
+
+use crate::{
+    math::{
+        func::{
+            decl::MathFunction,
+            exp_log::power,
+            op::{add, multiply},
+        },
+        poly::Polynomial,
+        symbol::{Number, Rational},
+        ExpressionElement,
+    },
+    utils::gcd,
+    IntegerScalar,
+};
+
+/// Powers above this aren't multiplied out.
+const MAX_EXPONENT: IntegerScalar = 64;
+
+/// The powers of the variables in a term, sorted by name and without zero powers.
+pub type Monomial = Vec<(String, u32)>;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultiPolynomial {
+    /// Without zero coefficients.
+    terms: BTreeMap<Monomial, Rational>,
+}
+
+/// A polynomial split into a constant and irreducible factors along with their multiplicities.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Factorization {
+    pub coeff: Rational,
+    pub factors: Vec<(MultiPolynomial, u32)>,
+}
+
+impl MultiPolynomial {
+    #[inline]
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn constant(c: Rational) -> Self {
+        Self::term(Vec::new(), c)
+    }
+
+    /// `var^n`
+    pub fn var(var: &str, n: u32) -> Self {
+        let mono = if n == 0 {
+            Vec::new()
+        } else {
+            vec![(var.to_string(), n)]
+        };
+        Self::term(mono, Rational::ONE)
+    }
+
+    fn term(mono: Monomial, c: Rational) -> Self {
+        let mut terms = BTreeMap::new();
+        if c != Rational::ZERO {
+            terms.insert(mono, c);
+        }
+        Self { terms }
+    }
+
+    /// The terms in the order they are written down, highest degree first.
+    pub fn terms(&self) -> impl Iterator<Item = (&Monomial, &Rational)> {
+        let mut terms = self.terms.iter().collect::<Vec<_>>();
+        terms.sort_by(|(a, _), (b, _)| cmp_monomials(a, b));
+        terms.into_iter()
+    }
+
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Checks if the polynomial has no variables.
+    #[inline]
+    pub fn is_constant(&self) -> bool {
+        self.terms.keys().all(|m| m.is_empty())
+    }
+
+    /// The constant value, `None` if there are variables.
+    pub fn as_constant(&self) -> Option<Rational> {
+        match self.terms.iter().next() {
+            None => Some(Rational::ZERO),
+            Some((m, c)) if m.is_empty() && self.terms.len() == 1 => Some(*c),
+            _ => None,
+        }
+    }
+
+    /// The variables, sorted by name.
+    pub fn vars(&self) -> BTreeSet<&str> {
+        self.terms
+            .keys()
+            .flatten()
+            .map(|(v, _)| v.as_str())
+            .collect()
+    }
+
+    /// The highest power of `var`.
+    pub fn degree_in(&self, var: &str) -> u32 {
+        self.terms
+            .keys()
+            .map(|m| power_of(m, var))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The highest sum of the powers in a term, `None` for the zero polynomial.
+    pub fn total_degree(&self) -> Option<u32> {
+        self.terms.keys().map(degree).max()
+    }
+
+    /// The coefficient of `var^n`, which is a polynomial of the other variables.
+    pub fn coeff_in(&self, var: &str, n: u32) -> Self {
+        Self {
+            terms: self
+                .terms
+                .iter()
+                .filter(|(m, _)| power_of(m, var) == n)
+                .map(|(m, c)| (m.iter().filter(|(v, _)| v != var).cloned().collect(), *c))
+                .collect(),
+        }
+    }
+
+    /// The coefficient of the first term.
+    #[inline]
+    pub fn leading(&self) -> Rational {
+        self.first_term().map_or(Rational::ZERO, |(_, c)| c)
+    }
+
+    fn first_term(&self) -> Option<(Monomial, Rational)> {
+        self.terms
+            .iter()
+            .min_by(|(a, _), (b, _)| cmp_monomials(a, b))
+            .map(|(m, c)| (m.clone(), *c))
+    }
+
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        let mut terms = self.terms.clone();
+        for (m, c) in &rhs.terms {
+            let sum = terms
+                .get(m)
+                .copied()
+                .unwrap_or(Rational::ZERO)
+                .checked_add(*c)?;
+            if sum == Rational::ZERO {
+                terms.remove(m);
+            } else {
+                terms.insert(m.clone(), sum);
+            }
+        }
+        Some(Self { terms })
+    }
+
+    pub fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        self.checked_add(&rhs.checked_scale(Rational::from(-1))?)
+    }
+
+    pub fn checked_scale(&self, c: Rational) -> Option<Self> {
+        if c == Rational::ZERO {
+            return Some(Self::zero());
+        }
+        self.terms
+            .iter()
+            .map(|(m, a)| Some((m.clone(), a.checked_mul(c)?)))
+            .collect::<Option<_>>()
+            .map(|terms| Self { terms })
+    }
+
+    pub fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        let mut product = Self::zero();
+        for (ma, a) in &self.terms {
+            for (mb, b) in &rhs.terms {
+                product =
+                    product.checked_add(&Self::term(mul_monomials(ma, mb), a.checked_mul(*b)?))?;
+            }
+        }
+        Some(product)
+    }
+
+    pub fn checked_pow(&self, exp: u32) -> Option<Self> {
+        (0..exp).try_fold(Self::constant(Rational::ONE), |pow, _| {
+            pow.checked_mul(self)
+        })
+    }
+
+    pub fn derivative(&self, var: &str) -> Option<Self> {
+        self.terms
+            .iter()
+            .filter(|(m, _)| power_of(m, var) > 0)
+            .try_fold(Self::zero(), |sum, (m, c)| {
+                let n = power_of(m, var);
+                let mono = div_monomials(m, &vec![(var.to_string(), 1)])?;
+                sum.checked_add(&Self::term(
+                    mono,
+                    c.checked_mul(Rational::from(n as IntegerScalar))?,
+                ))
+            })
+    }
+
+    /// Division where the first term of the remainder can't be divided by the first term of
+    /// `rhs` anymore, returning the quotient and the remainder. `None` if `rhs` is zero.
+    ///
+    /// It's the long division for polynomials of a single variable.
+    pub fn div_rem(&self, rhs: &Self) -> Option<(Self, Self)> {
+        let (lead_mono, lead) = rhs.first_term()?;
+        let (mut quot, mut rem, mut rest) = (Self::zero(), Self::zero(), self.clone());
+
+        while let Some((mono, c)) = rest.first_term() {
+            match div_monomials(&mono, &lead_mono) {
+                Some(q) => {
+                    let q = Self::term(q, c.checked_div(lead)?);
+                    rest = rest.checked_sub(&q.checked_mul(rhs)?)?;
+                    quot = quot.checked_add(&q)?;
+                }
+                None => {
+                    let t = Self::term(mono, c);
+                    rest = rest.checked_sub(&t)?;
+                    rem = rem.checked_add(&t)?;
+                }
+            }
+        }
+
+        Some((quot, rem))
+    }
+
+    /// The quotient if `rhs` divides the polynomial.
+    pub fn exact_div(&self, rhs: &Self) -> Option<Self> {
+        let (quot, rem) = self.div_rem(rhs)?;
+        rem.is_zero().then_some(quot)
+    }
+
+    /// The greatest common divisor, normalized to coprime integer coefficients and a positive
+    /// first term. It's zero only if both polynomials are.
+    pub fn gcd(&self, rhs: &Self) -> Option<Self> {
+        if self.is_zero() {
+            return rhs.normalized();
+        }
+        if rhs.is_zero() {
+            return self.normalized();
+        }
+        let Some(var) = self.vars().union(&rhs.vars()).next().map(|v| v.to_string()) else {
+            return Some(Self::constant(Rational::ONE));
+        };
+
+        // Contents and primitive parts have their own divisors.
+        let (ca, pa) = self.split_content(&var)?;
+        let (cb, pb) = rhs.split_content(&var)?;
+        let content = ca.gcd(&cb)?;
+
+        // Primitive remainder sequence
+        let (mut a, mut b) = if pa.degree_in(&var) >= pb.degree_in(&var) {
+            (pa, pb)
+        } else {
+            (pb, pa)
+        };
+        while !b.is_zero() {
+            let rem = a.pseudo_rem(&b, &var)?;
+            a = b;
+            b = if rem.is_zero() {
+                rem
+            } else {
+                rem.split_content(&var)?.1
+            };
+        }
+        let primitive = if a.degree_in(&var) == 0 {
+            Self::constant(Rational::ONE)
+        } else {
+            a.split_content(&var)?.1
+        };
+
+        content.checked_mul(&primitive)?.normalized()
+    }
+
+    /// Splits the polynomial into its content with respect to `var`, which is the divisor of all
+    /// coefficients of the powers of `var`, and the normalized rest.
+    fn split_content(&self, var: &str) -> Option<(Self, Self)> {
+        let content = (0..=self.degree_in(var))
+            .map(|n| self.coeff_in(var, n))
+            .filter(|c| !c.is_zero())
+            .try_fold(Self::zero(), |content, c| content.gcd(&c))?;
+        let primitive = self.exact_div(&content)?.normalized()?;
+        let content = self.exact_div(&primitive)?;
+        Some((content, primitive))
+    }
+
+    /// The remainder of `lc^{k}p` divided by `rhs` as polynomials of `var`, where `lc` is the
+    /// leading coefficient of `rhs`, so no fractions of the other variables show up.
+    fn pseudo_rem(&self, rhs: &Self, var: &str) -> Option<Self> {
+        let deg = rhs.degree_in(var);
+        let lead = rhs.coeff_in(var, deg);
+        let mut rem = self.clone();
+
+        while !rem.is_zero() && rem.degree_in(var) >= deg {
+            let n = rem.degree_in(var);
+            let shifted = rem
+                .coeff_in(var, n)
+                .checked_mul(&Self::var(var, n - deg))?
+                .checked_mul(rhs)?;
+            rem = rem.checked_mul(&lead)?.checked_sub(&shifted)?;
+        }
+
+        Some(rem)
+    }
+
+    /// Scales the polynomial to coprime integer coefficients and a positive first term.
+    pub fn normalized(&self) -> Option<Self> {
+        if self.is_zero() {
+            return Some(Self::zero());
+        }
+        let content = self.content()?;
+        let content = if self.leading().is_negative() {
+            content.checked_neg()?
+        } else {
+            content
+        };
+        self.checked_scale(content.recip()?)
+    }
+
+    /// The positive rational that scales the coefficients to coprime integers.
+    fn content(&self) -> Option<Rational> {
+        let (num, den) =
+            self.terms
+                .values()
+                .try_fold((0, 1 as IntegerScalar), |(num, den), c| {
+                    Some((
+                        gcd(num, c.num()),
+                        den.checked_mul(c.den() / gcd(den, c.den()))?,
+                    ))
+                })?;
+        Rational::new(num, den)
+    }
+
+    /// Factors the polynomial over the rationals.
+    ///
+    /// The square-free factors are irreducible if they have one variable, or two where they are
+    /// homogeneous. Square-free factors of more variables aren't split any further.
+    pub fn factor(&self) -> Option<Factorization> {
+        if self.is_zero() {
+            return Some(Factorization {
+                coeff: Rational::ZERO,
+                factors: Vec::new(),
+            });
+        }
+
+        // Common powers of variables
+        let mut factors = Vec::new();
+        let mut rest = self.clone();
+        for var in self.vars() {
+            let n = self
+                .terms
+                .keys()
+                .map(|m| power_of(m, var))
+                .min()
+                .unwrap_or(0);
+            if n > 0 {
+                rest = rest.exact_div(&Self::var(var, n))?;
+                factors.push((Self::var(var, 1), n));
+            }
+        }
+        rest.split_factors(&mut factors)?;
+
+        let product = factors
+            .iter()
+            .try_fold(Self::constant(Rational::ONE), |p, (f, n)| {
+                p.checked_mul(&f.checked_pow(*n)?)
+            })?;
+        let coeff = self.exact_div(&product)?.as_constant()?;
+
+        Some(Factorization { coeff, factors })
+    }
+
+    fn split_factors(&self, factors: &mut Vec<(Self, u32)>) -> Option<()> {
+        let Some(var) = self.vars().first().map(|v| v.to_string()) else {
+            return Some(());
+        };
+
+        let (content, primitive) = self.split_content(&var)?;
+        content.split_factors(factors)?;
+        for (f, n) in primitive.square_free(&var)? {
+            factors.extend(f.split_square_free(&var)?.into_iter().map(|f| (f, n)));
+        }
+        Some(())
+    }
+
+    /// Yun's square-free decomposition of a primitive polynomial, giving the factors that
+    /// show up once, twice and so on.
+    fn square_free(&self, var: &str) -> Option<Vec<(Self, u32)>> {
+        let d = self.derivative(var)?;
+        let a = self.gcd(&d)?;
+        let (mut b, c) = (self.exact_div(&a)?, d.exact_div(&a)?);
+        let mut d = c.checked_sub(&b.derivative(var)?)?;
+
+        let mut factors = Vec::new();
+        let mut n = 1;
+        while b.degree_in(var) > 0 {
+            let a = b.gcd(&d)?;
+            b = b.exact_div(&a)?;
+            let c = d.exact_div(&a)?;
+            d = c.checked_sub(&b.derivative(var)?)?;
+            if a.degree_in(var) > 0 {
+                factors.push((a, n));
+            }
+            n += 1;
+        }
+        Some(factors)
+    }
+
+    /// Splits a square-free polynomial into irreducible factors, as long as it has one variable
+    /// or is homogeneous in two. Other polynomials are left as they are.
+    fn split_square_free(&self, var: &str) -> Option<Vec<Self>> {
+        let vars = self.vars();
+        let other = vars.iter().find(|v| **v != var).map(|v| v.to_string());
+        let homogeneous = {
+            let mut degrees = self.terms.keys().map(degree);
+            let first = degrees.next();
+            degrees.all(|d| Some(d) == first)
+        };
+
+        // Homogeneous polynomials of two variables factor like p(x, 1).
+        let (univariate, other) = match other {
+            None => (self.clone(), None),
+            Some(y) if vars.len() == 2 && homogeneous => {
+                let terms = self.terms.iter().map(|(m, c)| {
+                    let mono = m.iter().filter(|(v, _)| *v != y).cloned().collect();
+                    (mono, *c)
+                });
+                (
+                    Self {
+                        terms: terms.collect(),
+                    },
+                    Some(y),
+                )
+            }
+            _ => return Some(vec![self.clone()]),
+        };
+        let p = Polynomial::new(
+            (0..=univariate.degree_in(var))
+                .map(|n| univariate.coeff_in(var, n).as_constant())
+                .collect::<Option<_>>()?,
+        );
+        // Makes p(x, 1) homogeneous again.
+        let homogenize = |p: &Polynomial| -> Option<Self> {
+            let deg = p.degree()? as u32;
+            p.coeffs()
+                .iter()
+                .enumerate()
+                .try_fold(Self::zero(), |sum, (n, c)| {
+                    let mut term = Self::var(var, n as u32).checked_scale(*c)?;
+                    if let Some(y) = &other {
+                        term = term.checked_mul(&Self::var(y, deg - n as u32))?;
+                    }
+                    sum.checked_add(&term)
+                })?
+                .normalized()
+        };
+
+        let (roots, rest) = p.rational_roots()?;
+        let mut factors = roots
+            .into_iter()
+            .map(|(r, _)| {
+                let linear = Polynomial::new(vec![r.checked_neg()?, Rational::ONE]);
+                homogenize(&linear)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if !rest.is_constant() {
+            for f in rest.irreducible_factors()? {
+                factors.push(homogenize(&f)?);
+            }
+        }
+        Some(factors)
+    }
+
+    /// Groups the terms by the powers of `var`, highest first, like `(a+b)x^{2}+cx`.
+    pub fn collect(&self, var: &str) -> ExpressionElement {
+        let x = ExpressionElement::Variable(var.to_string());
+        (0..=self.degree_in(var))
+            .rev()
+            .map(|n| (n, self.coeff_in(var, n)))
+            .filter(|(_, c)| !c.is_zero())
+            .map(|(n, c)| {
+                let c = c.to_expr();
+                match n {
+                    0 => c,
+                    1 => multiply(c, x.clone()),
+                    _ => multiply(
+                        c,
+                        power(x.clone(), Number::Integer(n as IntegerScalar).into()),
+                    ),
+                }
+            })
+            .reduce(add)
+            .unwrap_or(Number::Integer(0).into())
+    }
+
+    /// Reads `elem` as a polynomial of all its variables, `None` if it isn't one.
+    pub fn from_expr(elem: &ExpressionElement) -> Option<Self> {
+        match elem {
+            ExpressionElement::Number(n) => Some(Self::constant(n.as_rational()?)),
+            ExpressionElement::Variable(v) => Some(Self::var(v, 1)),
+            ExpressionElement::Function(f) => match f.as_ref() {
+                MathFunction::Add(op) => {
+                    Self::from_expr(op.lhs())?.checked_add(&Self::from_expr(op.rhs())?)
+                }
+                MathFunction::Subtract(op) => {
+                    Self::from_expr(op.lhs())?.checked_sub(&Self::from_expr(op.rhs())?)
+                }
+                MathFunction::Multiply(op) => {
+                    Self::from_expr(op.lhs())?.checked_mul(&Self::from_expr(op.rhs())?)
+                }
+                MathFunction::Divide(_) | MathFunction::Fraction(_) => {
+                    let params = f.params();
+                    let den = Self::from_expr(params[1])?.as_constant()?;
+                    Self::from_expr(params[0])?.checked_scale(den.recip()?)
+                }
+                MathFunction::Power(pow) => {
+                    let exp = pow.exp().as_rational().filter(Rational::is_integer)?.num();
+                    if !(0..=MAX_EXPONENT).contains(&exp) {
+                        return None;
+                    }
+                    Self::from_expr(pow.base())?.checked_pow(exp as u32)
+                }
+                _ => None,
+            },
+            ExpressionElement::Matrix(_) => None,
+        }
+    }
+
+    /// Writes the polynomial down, highest degree first.
+    pub fn to_expr(&self) -> ExpressionElement {
+        self.terms()
+            .map(|(m, c)| {
+                let c = ExpressionElement::from(Number::from(*c));
+                match monomial_to_expr(m) {
+                    Some(m) => multiply(c, m),
+                    None => c,
+                }
+            })
+            .reduce(add)
+            .unwrap_or(Number::Integer(0).into())
+    }
+}
+
+impl Factorization {
+    /// Writes the factorization down as a product, like `2x(x-1)^{2}`.
+    pub fn to_expr(&self) -> ExpressionElement {
+        let product = self
+            .factors
+            .iter()
+            .map(|(f, n)| match n {
+                1 => f.to_expr(),
+                _ => power(f.to_expr(), Number::Integer(*n as IntegerScalar).into()),
+            })
+            .reduce(multiply);
+
+        match product {
+            Some(p) => multiply(Number::from(self.coeff).into(), p),
+            None => Number::from(self.coeff).into(),
+        }
+    }
+}
+
+fn power_of(mono: &Monomial, var: &str) -> u32 {
+    mono.iter().find(|(v, _)| v == var).map_or(0, |(_, n)| *n)
+}
+
+fn degree(mono: &Monomial) -> u32 {
+    mono.iter().map(|(_, n)| n).sum()
+}
+
+fn mul_monomials(a: &Monomial, b: &Monomial) -> Monomial {
+    let mut powers = a.iter().cloned().collect::<BTreeMap<_, _>>();
+    for (v, n) in b {
+        *powers.entry(v.clone()).or_insert(0) += n;
+    }
+    powers.into_iter().collect()
+}
+
+/// `\frac{a}{b}`, `None` if it isn't a monomial.
+fn div_monomials(a: &Monomial, b: &Monomial) -> Option<Monomial> {
+    let mut powers = a.iter().cloned().collect::<BTreeMap<_, _>>();
+    for (v, n) in b {
+        let power = powers.get_mut(v).filter(|p| **p >= *n)?;
+        *power -= n;
+    }
+    Some(powers.into_iter().filter(|(_, n)| *n > 0).collect())
+}
+
+/// Higher degrees first, then higher powers of the variables that come first by name.
+fn cmp_monomials(a: &Monomial, b: &Monomial) -> Ordering {
+    degree(b).cmp(&degree(a)).then_with(|| {
+        let vars = a.iter().chain(b).map(|(v, _)| v).collect::<BTreeSet<_>>();
+        vars.into_iter()
+            .map(|v| power_of(b, v).cmp(&power_of(a, v)))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+    })
+}
+
+fn monomial_to_expr(mono: &Monomial) -> Option<ExpressionElement> {
+    mono.iter()
+        .map(|(v, n)| {
+            let v = ExpressionElement::Variable(v.clone());
+            match n {
+                1 => v,
+                _ => power(v, Number::Integer(*n as IntegerScalar).into()),
+            }
+        })
+        .reduce(multiply)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::math::{expr::ExpresssionTree, FromRawExpr, IntoRawExpr, ParsingScope};
+
+    #[test]
+    fn test_multi_polynomials() {
+        let scope = ParsingScope::new(None)
+            .with_symbol("x")
+            .with_symbol("y")
+            .with_symbol("a")
+            .with_symbol("b");
+        let tree = |expr| ExpresssionTree::parse_scoped(expr, &scope).unwrap();
+        let expand = |expr| tree(expr).expand().unwrap().assemble();
+        let factor = |expr| tree(expr).factor().unwrap().assemble();
+
+        assert_eq!(expand("(x+1)^2-(x-1)^2"), "4*x");
+        assert_eq!(expand("(x+y)^3"), "x^{3}+3*x^{2}*y+3*x*y^{2}+y^{3}");
+        assert_eq!(expand("(a+b)*(a-b)"), "a^{2}-b^{2}");
+        assert_eq!(expand(r#"\frac{(2x+1)^2}{4}"#), r#"x^{2}+x+\frac{1}{4}"#);
+        assert!(tree(r#"\sin x+1"#).expand().is_none());
+
+        assert_eq!(
+            tree("a*x^2+b*x^2+a*x+3").collect("x").unwrap().assemble(),
+            "(a+b)*x^{2}+a*x+3"
+        );
+
+        assert_eq!(factor("x^3-x"), "x*(x-1)*(x+1)");
+        assert_eq!(factor("2x^2+4x+2"), "2*(x+1)^{2}");
+        assert_eq!(factor("6x^2+5x+1"), "(2*x+1)*(3*x+1)");
+        assert_eq!(factor("x^4-1"), "(x-1)*(x+1)*(x^{2}+1)");
+        assert_eq!(factor("(x-1)^3*(x+2)"), "(x+2)*(x-1)^{3}");
+        assert_eq!(factor("x^3*y-x*y^3"), "x*y*(x-y)*(x+y)");
+        assert_eq!(factor("x^4+2x^2*y+y^2"), "(x^{2}+y)^{2}");
+        assert_eq!(
+            factor(r#"\frac{x^2}{2}-\frac{1}{2}"#),
+            r#"\frac{(x-1)*(x+1)}{2}"#
+        );
+        assert_eq!(factor("0-x^2+1"), "-(x-1)*(x+1)");
+        assert_eq!(factor("x^2+1"), "x^{2}+1");
+        assert_eq!(factor("x^6-1"), "(x-1)*(x+1)*(x^{2}+x+1)*(x^{2}-x+1)");
+        assert_eq!(factor("4x^4-1"), "(2*x^{2}-1)*(2*x^{2}+1)");
+        assert_eq!(
+            factor("x^4+4y^4"),
+            "(x^{2}+2*x*y+2*y^{2})*(x^{2}-2*x*y+2*y^{2})"
+        );
+        assert_eq!(factor("x^4+x+1"), "x^{4}+x+1");
+
+        let gcd = |a, b| tree(a).gcd(&tree(b)).unwrap().assemble();
+        assert_eq!(gcd("x^2-1", "x^2+2x+1"), "x+1");
+        assert_eq!(gcd("x^2-y^2", "x^2-2x*y+y^2"), "x-y");
+        assert_eq!(gcd("6x+6", "4x+4"), "x+1");
+        assert_eq!(gcd("x^2+1", "x+1"), "1");
+
+        let (quot, rem) = tree("x^3+2x+1").div_rem(&tree("x-1")).unwrap();
+        assert_eq!(
+            (quot.assemble(), rem.assemble()),
+            ("x^{2}+x+3".into(), "4".into())
+        );
+        let (quot, rem) = tree("x^2-y^2").div_rem(&tree("x-y")).unwrap();
+        assert_eq!(
+            (quot.assemble(), rem.assemble()),
+            ("x+y".into(), "0".into())
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Splits heavy work, like products of large matrices, across threads.
+//!
+//! Work is always split into contiguous chunks and joined in order, so results never depend
+//! on the number of threads.
+
+use std::{cell::Cell, num::NonZeroUsize, thread};
+
+/// Work smaller than this, roughly the number of scalar operations, stays on the current thread.
+pub const PARALLEL_THRESHOLD: usize = 1 << 16;
+
+thread_local! {
+    static THREADS: Cell<usize> = const { Cell::new(1) };
+}
+
+/// The number of threads the machine can run in parallel.
+#[inline]
+pub fn available_threads() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// The number of threads work on the current thread can be split across.
+#[inline]
+pub fn threads() -> usize {
+    THREADS.get()
+}
+
+/// Runs `f` with work split across `threads` threads.
+pub fn with_threads<T>(threads: usize, f: impl FnOnce() -> T) -> T {
+    struct Restore(usize);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            THREADS.set(self.0);
+        }
+    }
+
+    let _restore = Restore(THREADS.replace(threads.max(1)));
+    f()
+}
+
+/// The thread-local settings that workers inherit from the thread splitting the work. The
+/// number of threads isn't one of them, so workers don't split their work any further.
+#[derive(Clone, Copy)]
+struct Context {
+    #[cfg(feature = "bignum")]
+    precision: u64,
+}
+
+impl Context {
+    #[inline]
+    fn current() -> Self {
+        Self {
+            #[cfg(feature = "bignum")]
+            precision: crate::math::precise::precision(),
+        }
+    }
+
+    #[inline]
+    fn enter<T>(self, f: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "bignum")]
+        return crate::math::precise::with_precision(self.precision, f);
+        #[cfg(not(feature = "bignum"))]
+        f()
+    }
+}
+
+/// Computes `f(i)` for every `i` in `0..len`.
+pub(crate) fn map<T: Send>(len: usize, work: usize, f: impl Fn(usize) -> T + Sync) -> Vec<T> {
+    let threads = threads().min(len);
+    if threads <= 1 || work < PARALLEL_THRESHOLD {
+        return (0..len).map(f).collect();
+    }
+
+    let chunk = len.div_ceil(threads);
+    let (f, context) = (&f, Context::current());
+    thread::scope(|s| {
+        let handles = (0..len)
+            .step_by(chunk)
+            .map(|start| {
+                s.spawn(move || {
+                    context.enter(|| (start..len.min(start + chunk)).map(f).collect::<Vec<_>>())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    })
+}
+
+/// Computes `f(item)` for every item, which can be modified in place.
+pub(crate) fn map_mut<T: Send, R: Send>(
+    items: &mut [T],
+    work: usize,
+    f: impl Fn(&mut T) -> R + Sync,
+) -> Vec<R> {
+    let threads = threads().min(items.len());
+    if threads <= 1 || work < PARALLEL_THRESHOLD {
+        return items.iter_mut().map(f).collect();
+    }
+
+    let chunk = items.len().div_ceil(threads);
+    let (f, context) = (&f, Context::current());
+    thread::scope(|s| {
+        let handles = items
+            .chunks_mut(chunk)
+            .map(|items| {
+                s.spawn(move || context.enter(|| items.iter_mut().map(f).collect::<Vec<_>>()))
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    })
+}
@@ -0,0 +1,494 @@
+//! Univariate polynomials with exact rational coefficients.
+
+use crate::{
+    math::{
+        func::{
+            decl::MathFunction,
+            exp_log::power,
+            op::{add, multiply},
+        },
+        symbol::{Number, Rational},
+        ExpressionElement,
+    },
+    utils::gcd,
+    IntegerScalar,
+};
+
+/// Rational root candidates are only searched if the coefficients are at most this large.
+const MAX_ROOT_SEARCH: IntegerScalar = 1 << 20;
+
+/// Candidate factors tried for each degree before a polynomial is left as it is.
+const MAX_FACTOR_CANDIDATES: usize = 1 << 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    /// Lowest degree first, without trailing zeros.
+    coeffs: Vec<Rational>,
+}
+
+impl Polynomial {
+    pub fn new(mut coeffs: Vec<Rational>) -> Self {
+        while coeffs.last() == Some(&Rational::ZERO) {
+            coeffs.pop();
+        }
+        Self { coeffs }
+    }
+
+    #[inline]
+    pub fn zero() -> Self {
+        Self { coeffs: Vec::new() }
+    }
+
+    #[inline]
+    pub fn constant(c: Rational) -> Self {
+        Self::new(vec![c])
+    }
+
+    /// `c*x^n`
+    pub fn monomial(c: Rational, n: usize) -> Self {
+        let mut coeffs = vec![Rational::ZERO; n + 1];
+        coeffs[n] = c;
+        Self::new(coeffs)
+    }
+
+    #[inline]
+    pub fn coeffs(&self) -> &[Rational] {
+        &self.coeffs
+    }
+
+    /// The degree, `None` for the zero polynomial.
+    #[inline]
+    pub fn degree(&self) -> Option<usize> {
+        self.coeffs.len().checked_sub(1)
+    }
+
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    #[inline]
+    pub fn is_constant(&self) -> bool {
+        self.coeffs.len() <= 1
+    }
+
+    #[inline]
+    pub fn leading(&self) -> Rational {
+        self.coeffs.last().copied().unwrap_or(Rational::ZERO)
+    }
+
+    #[inline]
+    pub fn coeff(&self, n: usize) -> Rational {
+        self.coeffs.get(n).copied().unwrap_or(Rational::ZERO)
+    }
+
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        let len = self.coeffs.len().max(rhs.coeffs.len());
+        (0..len)
+            .map(|i| self.coeff(i).checked_add(rhs.coeff(i)))
+            .collect::<Option<_>>()
+            .map(Self::new)
+    }
+
+    pub fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        self.checked_add(&rhs.checked_scale(Rational::from(-1))?)
+    }
+
+    pub fn checked_scale(&self, c: Rational) -> Option<Self> {
+        self.coeffs
+            .iter()
+            .map(|a| a.checked_mul(c))
+            .collect::<Option<_>>()
+            .map(Self::new)
+    }
+
+    pub fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        if self.is_zero() || rhs.is_zero() {
+            return Some(Self::zero());
+        }
+
+        let mut coeffs = vec![Rational::ZERO; self.coeffs.len() + rhs.coeffs.len() - 1];
+        for (i, a) in self.coeffs.iter().enumerate() {
+            for (j, b) in rhs.coeffs.iter().enumerate() {
+                coeffs[i + j] = coeffs[i + j].checked_add(a.checked_mul(*b)?)?;
+            }
+        }
+        Some(Self::new(coeffs))
+    }
+
+    pub fn checked_pow(&self, exp: u32) -> Option<Self> {
+        (0..exp).try_fold(Self::constant(Rational::ONE), |pow, _| {
+            pow.checked_mul(self)
+        })
+    }
+
+    /// Long division, returning the quotient and the remainder. `None` if `rhs` is zero.
+    pub fn div_rem(&self, rhs: &Self) -> Option<(Self, Self)> {
+        let rhs_deg = rhs.degree()?;
+        let mut quot = vec![Rational::ZERO; self.coeffs.len().saturating_sub(rhs_deg)];
+        let mut rem = self.clone();
+
+        while let Some(deg) = rem.degree().filter(|deg| *deg >= rhs_deg) {
+            let c = rem.leading().checked_div(rhs.leading())?;
+            quot[deg - rhs_deg] = c;
+            rem = rem.checked_sub(&Self::monomial(c, deg - rhs_deg).checked_mul(rhs)?)?;
+        }
+
+        Some((Self::new(quot), rem))
+    }
+
+    pub fn eval(&self, x: Rational) -> Option<Rational> {
+        self.coeffs
+            .iter()
+            .rev()
+            .try_fold(Rational::ZERO, |acc, c| acc.checked_mul(x)?.checked_add(*c))
+    }
+
+    pub fn derivative(&self) -> Option<Self> {
+        self.coeffs
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(n, c)| c.checked_mul(Rational::from(n as IntegerScalar)))
+            .collect::<Option<_>>()
+            .map(Self::new)
+    }
+
+    /// The antiderivative that is zero at zero.
+    pub fn integral(&self) -> Option<Self> {
+        std::iter::once(Some(Rational::ZERO))
+            .chain(
+                self.coeffs
+                    .iter()
+                    .enumerate()
+                    .map(|(n, c)| c.checked_div(Rational::from(n as IntegerScalar + 1))),
+            )
+            .collect::<Option<_>>()
+            .map(Self::new)
+    }
+
+    /// The polynomial `S` where `S(n)` is the sum of this polynomial at `0, 1, ..., n`, like
+    /// `\frac{n^2+n}{2}` for `x`.
+    pub fn partial_sum(&self) -> Option<Self> {
+        // S has one degree more, so that many points pin it down.
+        let mut sum = Rational::ZERO;
+        let points = (0..self.coeffs.len() as IntegerScalar + 1)
+            .map(|n| {
+                let n = Rational::from(n);
+                sum = sum.checked_add(self.eval(n)?)?;
+                Some((n, sum))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Self::interpolate(&points)
+    }
+
+    /// The polynomial of the lowest degree that goes through all `points`, found with Lagrange
+    /// interpolation.
+    pub fn interpolate(points: &[(Rational, Rational)]) -> Option<Self> {
+        points
+            .iter()
+            .enumerate()
+            .try_fold(Self::zero(), |sum, (i, (xi, yi))| {
+                let basis = points
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .try_fold(Self::constant(*yi), |basis, (_, (xj, _))| {
+                        let factor = Self::new(vec![xj.checked_neg()?, Rational::ONE])
+                            .checked_scale(xi.checked_sub(*xj)?.recip()?)?;
+                        basis.checked_mul(&factor)
+                    })?;
+                sum.checked_add(&basis)
+            })
+    }
+
+    /// The rational roots along with their multiplicities, and the rest of the polynomial
+    /// that has no rational roots.
+    pub fn rational_roots(&self) -> Option<(Vec<(Rational, usize)>, Self)> {
+        let mut rest = self.clone();
+        let mut roots = Vec::new();
+
+        let mut push_root = |rest: &mut Self, r: Rational| -> Option<()> {
+            let multiplicity = rest.deflate(r)?;
+            if multiplicity > 0 {
+                roots.push((r, multiplicity));
+            }
+            Some(())
+        };
+
+        push_root(&mut rest, Rational::ZERO)?;
+
+        // Rational root theorem on the polynomial with integer coefficients.
+        let integral = rest.to_integer_coeffs()?;
+        let (Some(&last), Some(&first)) = (integral.last(), integral.first()) else {
+            return Some((roots, rest));
+        };
+        if last.abs() > MAX_ROOT_SEARCH || first.abs() > MAX_ROOT_SEARCH {
+            return Some((roots, rest));
+        }
+
+        // Candidates are distinct, so roots are never found twice.
+        let mut candidates = Vec::new();
+        for p in divisors(first) {
+            for q in divisors(last) {
+                let r = Rational::new(p, q)?;
+                if r.den() == q {
+                    candidates.extend([r, r.checked_neg()?]);
+                }
+            }
+        }
+
+        for r in candidates {
+            if rest.is_constant() {
+                break;
+            }
+            push_root(&mut rest, r)?;
+        }
+
+        Some((roots, rest))
+    }
+
+    /// Splits a polynomial without rational roots into factors that are irreducible over the
+    /// rationals, using Kronecker's method.
+    ///
+    /// A factor of degree `d` is interpolated from divisors of the values at `d+1` points, so
+    /// polynomials with large values there are left as they are.
+    pub fn irreducible_factors(&self) -> Option<Vec<Self>> {
+        let integral = Self::new(
+            self.to_integer_coeffs()?
+                .into_iter()
+                .map(Rational::from)
+                .collect(),
+        );
+
+        // Without rational roots there are no linear factors, so degrees up to 3 are irreducible.
+        for d in 2..=self.degree().unwrap_or(0) / 2 {
+            // 0, 1, -1, 2, -2 and so on
+            let xs = (0..=d as IntegerScalar)
+                .map(|i| Rational::from(if i % 2 == 1 { (i + 1) / 2 } else { -i / 2 }))
+                .collect::<Vec<_>>();
+            let mut choices = Vec::new();
+            for (i, x) in xs.iter().enumerate() {
+                let value = integral.eval(*x)?.num();
+                if value == 0 || value.abs() > MAX_ROOT_SEARCH {
+                    return Some(vec![self.clone()]);
+                }
+                // Factors are only found up to their sign.
+                let divisors = divisors(value);
+                choices.push(if i == 0 {
+                    divisors.iter().map(|&p| Rational::from(p)).collect()
+                } else {
+                    divisors
+                        .iter()
+                        .flat_map(|&p| [Rational::from(p), Rational::from(-p)])
+                        .collect::<Vec<_>>()
+                });
+            }
+            let candidates = choices
+                .iter()
+                .try_fold(1usize, |n, c| n.checked_mul(c.len()))
+                .filter(|n| *n <= MAX_FACTOR_CANDIDATES);
+            let Some(candidates) = candidates else {
+                return Some(vec![self.clone()]);
+            };
+
+            for mut index in 0..candidates {
+                let points = xs
+                    .iter()
+                    .zip(&choices)
+                    .map(|(x, c)| {
+                        let y = c[index % c.len()];
+                        index /= c.len();
+                        (*x, y)
+                    })
+                    .collect::<Vec<_>>();
+                let Some(factor) = Self::interpolate(&points) else {
+                    continue;
+                };
+                if factor.degree() != Some(d) {
+                    continue;
+                }
+                if let Some((quot, rem)) = self.div_rem(&factor) {
+                    if rem.is_zero() {
+                        let mut factors = factor.irreducible_factors()?;
+                        factors.extend(quot.irreducible_factors()?);
+                        return Some(factors);
+                    }
+                }
+            }
+        }
+
+        Some(vec![self.clone()])
+    }
+
+    /// Divides out `x-r` as often as possible, returning how often it was divided.
+    fn deflate(&mut self, r: Rational) -> Option<usize> {
+        let divisor = Self::new(vec![r.checked_neg()?, Rational::ONE]);
+        let mut multiplicity = 0;
+        while !self.is_constant() && self.eval(r)? == Rational::ZERO {
+            *self = self.div_rem(&divisor)?.0;
+            multiplicity += 1;
+        }
+        Some(multiplicity)
+    }
+
+    /// Scales the coefficients to coprime integers.
+    fn to_integer_coeffs(&self) -> Option<Vec<IntegerScalar>> {
+        let lcm = self.coeffs.iter().try_fold(1 as IntegerScalar, |lcm, c| {
+            lcm.checked_mul(c.den() / gcd(lcm, c.den()))
+        })?;
+        let coeffs = self
+            .coeffs
+            .iter()
+            .map(|c| c.checked_mul(Rational::from(lcm)).map(|c| c.num()))
+            .collect::<Option<Vec<_>>>()?;
+        let divisor = coeffs.iter().fold(0, |d, c| gcd(d, *c));
+
+        Some(coeffs.into_iter().map(|c| c / divisor.max(1)).collect())
+    }
+
+    /// Reads `elem` as a polynomial of `var`, `None` if it isn't one.
+    pub fn from_expr(elem: &ExpressionElement, var: &str) -> Option<Self> {
+        match rational_function(elem, var)? {
+            (num, den) if den.is_constant() => num.checked_scale(den.leading().recip()?),
+            _ => None,
+        }
+    }
+
+    /// Writes the polynomial down, highest degree first.
+    pub fn to_expr(&self, var: &str) -> ExpressionElement {
+        let x = ExpressionElement::Variable(var.to_string());
+        self.coeffs
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, c)| **c != Rational::ZERO)
+            .map(|(n, c)| {
+                let c = ExpressionElement::from(Number::from(*c));
+                match n {
+                    0 => c,
+                    1 => multiply(c, x.clone()),
+                    _ => multiply(
+                        c,
+                        power(x.clone(), Number::Integer(n as IntegerScalar).into()),
+                    ),
+                }
+            })
+            .reduce(add)
+            .unwrap_or(Number::Integer(0).into())
+    }
+}
+
+/// Reads `elem` as a fraction of two polynomials of `var`, `None` if it isn't one.
+pub fn rational_function(elem: &ExpressionElement, var: &str) -> Option<(Polynomial, Polynomial)> {
+    let one = || Polynomial::constant(Rational::ONE);
+
+    match elem {
+        ExpressionElement::Number(n) => Some((Polynomial::constant(n.as_rational()?), one())),
+        ExpressionElement::Variable(v) if v == var => {
+            Some((Polynomial::monomial(Rational::ONE, 1), one()))
+        }
+        ExpressionElement::Function(f) => match f.as_ref() {
+            MathFunction::Add(_) | MathFunction::Subtract(_) => {
+                let params = f.params();
+                let (ln, ld) = rational_function(params[0], var)?;
+                let (rn, rd) = rational_function(params[1], var)?;
+                let (ln, rn) = (ln.checked_mul(&rd)?, rn.checked_mul(&ld)?);
+                let num = match f.as_ref() {
+                    MathFunction::Add(_) => ln.checked_add(&rn)?,
+                    _ => ln.checked_sub(&rn)?,
+                };
+                Some((num, ld.checked_mul(&rd)?))
+            }
+            MathFunction::Multiply(mul) => {
+                let (ln, ld) = rational_function(mul.lhs(), var)?;
+                let (rn, rd) = rational_function(mul.rhs(), var)?;
+                Some((ln.checked_mul(&rn)?, ld.checked_mul(&rd)?))
+            }
+            MathFunction::Divide(_) | MathFunction::Fraction(_) => {
+                let params = f.params();
+                let (ln, ld) = rational_function(params[0], var)?;
+                let (rn, rd) = rational_function(params[1], var)?;
+                if rn.is_zero() {
+                    return None;
+                }
+                Some((ln.checked_mul(&rd)?, ld.checked_mul(&rn)?))
+            }
+            MathFunction::Power(pow) => {
+                let exp = pow.exp().as_rational().filter(Rational::is_integer)?;
+                let (num, den) = rational_function(pow.base(), var)?;
+                let n = u32::try_from(exp.num().unsigned_abs()).ok()?;
+                let (num, den) = (num.checked_pow(n)?, den.checked_pow(n)?);
+                if exp.is_negative() {
+                    (!num.is_zero()).then_some((den, num))
+                } else {
+                    Some((num, den))
+                }
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The positive divisors of `n`.
+fn divisors(n: IntegerScalar) -> Vec<IntegerScalar> {
+    let n = n.abs();
+    let mut small = Vec::new();
+    let mut large = Vec::new();
+
+    let mut i = 1;
+    while i * i <= n {
+        if n % i == 0 {
+            small.push(i);
+            if i * i != n {
+                large.push(n / i);
+            }
+        }
+        i += 1;
+    }
+
+    small.extend(large.into_iter().rev());
+    small
+}
+
+#[cfg(test)]
+mod test {
+    use crate::math::IntoRawExpr;
+
+    use super::*;
+
+    fn poly(coeffs: &[IntegerScalar]) -> Polynomial {
+        Polynomial::new(coeffs.iter().map(|c| Rational::from(*c)).collect())
+    }
+
+    #[test]
+    fn test_polynomials() {
+        // (x^2-1)/(x+1)=x-1
+        let (quot, rem) = poly(&[-1, 0, 1]).div_rem(&poly(&[1, 1])).unwrap();
+        assert_eq!(quot, poly(&[-1, 1]));
+        assert!(rem.is_zero());
+
+        // x^3-x^2-x+1=(x-1)^2(x+1)
+        let (roots, rest) = poly(&[1, -1, -1, 1]).rational_roots().unwrap();
+        assert_eq!(roots, vec![(Rational::ONE, 2), (Rational::from(-1), 1)]);
+        assert!(rest.is_constant());
+
+        // 2x^3+x with the irreducible 2x^2+1 left over
+        let (roots, rest) = poly(&[0, 1, 0, 2]).rational_roots().unwrap();
+        assert_eq!(roots, vec![(Rational::ZERO, 1)]);
+        assert_eq!(rest, poly(&[1, 0, 2]));
+
+        assert_eq!(
+            poly(&[1, 2, 3]).integral().unwrap().derivative().unwrap(),
+            poly(&[1, 2, 3])
+        );
+        assert_eq!(poly(&[1, 0, 3]).to_expr("x").assemble(), "3*x^{2}+1");
+
+        // 0^2+1^2+...+10^2
+        let squares = poly(&[0, 0, 1]).partial_sum().unwrap();
+        assert_eq!(squares.degree(), Some(3));
+        assert_eq!(squares.eval(Rational::from(10)), Some(Rational::from(385)));
+    }
+}
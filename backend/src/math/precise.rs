@@ -0,0 +1,467 @@
+//! Arbitrary precision arithmetic, only available with the `bignum` feature.
+//!
+//! All the precisions here are the number of significant decimal digits.
+
+use std::{cell::Cell, num::NonZeroU64};
+
+use bigdecimal::{Context, RoundingMode, Signed, Zero};
+use num_traits::{FromPrimitive, One, ToPrimitive};
+
+use crate::{
+    math::symbol::{Number, Rational},
+    BigDecimalScalar, BigIntegerScalar, BigRationalScalar, DecimalScalar,
+};
+
+/// The precision of decimals that show up during exact evaluation, unless it's changed with
+/// [`with_precision`].
+pub const DEFAULT_PRECISION: u64 = 50;
+/// Extra digits used in intermediate results to absorb rounding errors.
+pub const GUARD_DIGITS: u64 = 10;
+/// Exact powers with more bits than this are approximated instead.
+pub const MAX_EXACT_BITS: u64 = 1 << 20;
+/// Decimals can't be further than this many orders of magnitude away from 1.
+pub const MAX_EXPONENT: i64 = 1 << 40;
+
+thread_local! {
+    static PRECISION: Cell<u64> = const { Cell::new(DEFAULT_PRECISION) };
+}
+
+/// The precision of decimals that show up during exact evaluation on the current thread.
+#[inline]
+pub fn precision() -> u64 {
+    PRECISION.get()
+}
+
+/// Runs `f` with decimals that show up during exact evaluation kept to `precision` digits.
+pub fn with_precision<T>(precision: u64, f: impl FnOnce() -> T) -> T {
+    struct Restore(u64);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            PRECISION.set(self.0);
+        }
+    }
+
+    let _restore = Restore(PRECISION.replace(precision.max(1)));
+    f()
+}
+
+#[inline]
+fn context(precision: u64) -> Context {
+    Context::default().with_precision(NonZeroU64::new(precision.max(1)).unwrap())
+}
+
+/// Converts a decimal through its shortest representation, so `0.1` stays `0.1`.
+pub fn from_decimal(d: DecimalScalar) -> Option<BigDecimalScalar> {
+    if !d.is_finite() {
+        return None;
+    }
+
+    d.to_string()
+        .parse()
+        .ok()
+        .or_else(|| BigDecimalScalar::from_f64(d))
+}
+
+/// Rounds `d` half to even, unlike [`BigDecimalScalar::with_prec`] which truncates. Numbers
+/// that already fit aren't padded with zeros.
+#[inline]
+pub fn round(d: &BigDecimalScalar, precision: u64) -> BigDecimalScalar {
+    if d.digits() <= precision {
+        return d.clone();
+    }
+
+    d.with_precision_round(
+        NonZeroU64::new(precision.max(1)).unwrap(),
+        RoundingMode::HalfEven,
+    )
+}
+
+#[inline]
+pub fn to_decimal(d: &BigDecimalScalar) -> DecimalScalar {
+    d.to_f64().unwrap_or(DecimalScalar::NAN)
+}
+
+/// Shrinks integers back to [`Number::Integer`] if possible.
+#[inline]
+pub fn integer(i: BigIntegerScalar) -> Number {
+    match i.to_i64() {
+        Some(i) => Number::Integer(i),
+        None => Number::BigInteger(i),
+    }
+}
+
+/// Shrinks rationals back to [`Number::Rational`] or [`Number::Integer`] if possible.
+pub fn rational(r: BigRationalScalar) -> Number {
+    if r.is_integer() {
+        return integer(r.to_integer());
+    }
+
+    match (r.numer().to_i64(), r.denom().to_i64()) {
+        (Some(num), Some(den)) => {
+            Rational::new(num, den).map_or(Number::BigRational(r), Number::Rational)
+        }
+        _ => Number::BigRational(r),
+    }
+}
+
+/// The exact value of integers and rationals of any size.
+pub(crate) fn as_big_rational(n: &Number) -> Option<BigRationalScalar> {
+    match n {
+        Number::Integer(i) => Some(BigIntegerScalar::from(*i).into()),
+        Number::Rational(r) => Some(BigRationalScalar::new(r.num().into(), r.den().into())),
+        Number::BigInteger(i) => Some(i.clone().into()),
+        Number::BigRational(r) => Some(r.clone()),
+        _ => None,
+    }
+}
+
+/// Checks if the number is an integer or rational that doesn't fit into the scalars.
+#[inline]
+pub(crate) fn is_big(n: &Number) -> bool {
+    matches!(n, Number::BigInteger(_) | Number::BigRational(_))
+}
+
+/// Applies a binary operation on numbers that can't be done with [`IntegerScalar`] and
+/// [`DecimalScalar`] without losing precision.
+///
+/// Integers and rationals stay exact with `exact_op`, and everything else is approximated with
+/// `dec_op`. Returns `None` if an operand is already a [`DecimalScalar`] or the result is
+/// undefined.
+///
+/// [`IntegerScalar`]: crate::IntegerScalar
+pub(crate) fn binary_op(
+    lhs: &Number,
+    rhs: &Number,
+    exact_op: impl FnOnce(&BigRationalScalar, &BigRationalScalar) -> Option<BigRationalScalar>,
+    dec_op: impl FnOnce(&BigDecimalScalar, &BigDecimalScalar, u64) -> Option<BigDecimalScalar>,
+) -> Option<Number> {
+    use crate::math::func::Function;
+
+    if matches!(lhs, Number::Decimal(_)) || matches!(rhs, Number::Decimal(_)) {
+        return None;
+    }
+
+    if let (Some(lhs), Some(rhs)) = (as_big_rational(lhs), as_big_rational(rhs)) {
+        if let Some(result) = exact_op(&lhs, &rhs) {
+            return Some(rational(result));
+        }
+    }
+
+    // Decimals are used as they are, so exact results like `0.1+0.2` aren't padded with zeros.
+    let precision = precision();
+    let operand = |n: &Number| match n {
+        Number::BigDecimal(d) => Some(d.clone()),
+        _ => n.approximate_precise(precision + GUARD_DIGITS),
+    };
+    dec_op(&operand(lhs)?, &operand(rhs)?, precision + GUARD_DIGITS)
+        .map(|d| Number::BigDecimal(round(&d, precision)))
+}
+
+#[inline]
+pub(crate) fn divide_rational(
+    lhs: &BigRationalScalar,
+    rhs: &BigRationalScalar,
+) -> Option<BigRationalScalar> {
+    (!rhs.is_zero()).then(|| lhs / rhs)
+}
+
+/// Raises rationals to integer powers, unless the result would be too large to keep exact.
+pub(crate) fn pow_rational(
+    base: &BigRationalScalar,
+    exp: &BigRationalScalar,
+) -> Option<BigRationalScalar> {
+    let exp = exp.is_integer().then(|| exp.to_integer().to_i32())??;
+    if base.is_zero() && exp < 0 {
+        return None;
+    }
+
+    let bits = base.numer().bits().max(base.denom().bits());
+    if bits.saturating_mul(exp.unsigned_abs() as u64) > MAX_EXACT_BITS {
+        return None;
+    }
+    Some(num_traits::Pow::pow(base, exp))
+}
+
+pub fn divide(
+    lhs: &BigDecimalScalar,
+    rhs: &BigDecimalScalar,
+    precision: u64,
+) -> Option<BigDecimalScalar> {
+    if rhs.is_zero() {
+        return None;
+    }
+
+    let ctx = context(precision);
+    Some(ctx.multiply(lhs, &rhs.inverse_with_context(&ctx)))
+}
+
+/// Raises `base` to an arbitrary power.
+pub fn pow(
+    base: &BigDecimalScalar,
+    exp: &BigDecimalScalar,
+    precision: u64,
+) -> Option<BigDecimalScalar> {
+    // The result would be too large or too small to represent.
+    if let (Some(base), Some(exp)) = (base.abs().to_f64(), exp.to_f64()) {
+        if base != 0. && (base.log10() * exp).abs() > MAX_EXPONENT as DecimalScalar {
+            return None;
+        }
+    }
+
+    if exp.is_integer() {
+        if let Some(exp) = exp.to_i64() {
+            if base.is_zero() && exp < 0 {
+                return None;
+            }
+            return Some(base.powi_with_context(exp, &context(precision)));
+        }
+    }
+
+    if base.is_zero() && exp.is_positive() {
+        return Some(BigDecimalScalar::zero());
+    }
+
+    // Non integer powers of negative numbers are complex.
+    let ln = ln(base, precision + GUARD_DIGITS)?;
+    Some(exp_of(&(ln * exp), precision))
+}
+
+pub fn root(
+    radicand: &BigDecimalScalar,
+    deg: &BigDecimalScalar,
+    precision: u64,
+) -> Option<BigDecimalScalar> {
+    let odd = deg.is_integer() && deg.to_i64().is_some_and(|d| d % 2 != 0);
+    if radicand.is_negative() && odd {
+        return root(&-radicand, deg, precision).map(|r| -r);
+    }
+
+    if deg.to_i64() == Some(2) {
+        return radicand.sqrt_with_context(&context(precision));
+    }
+
+    pow(
+        radicand,
+        &divide(&BigDecimalScalar::one(), deg, precision + GUARD_DIGITS)?,
+        precision,
+    )
+}
+
+/// Calculates `\pi` using Machin's formula `\pi=16\arctan{\frac{1}{5}}-4\arctan{\frac{1}{239}}`.
+pub fn pi(precision: u64) -> BigDecimalScalar {
+    let scale = precision + GUARD_DIGITS;
+    let unit = BigIntegerScalar::from(10).pow(scale as u32);
+
+    // \arctan{\frac{1}{x}} in fixed point.
+    let arctan_inv = |x: u32| {
+        let x2 = BigIntegerScalar::from(x * x);
+        let mut power = &unit / x;
+        let mut sum = power.clone();
+        let mut k = 1u32;
+
+        loop {
+            power /= &x2;
+            let term = &power / (2 * k + 1);
+            if term.is_zero() {
+                break sum;
+            }
+
+            if k.is_multiple_of(2) {
+                sum += term;
+            } else {
+                sum -= term;
+            }
+            k += 1;
+        }
+    };
+
+    let pi = arctan_inv(5) * 16 - arctan_inv(239) * 4;
+    BigDecimalScalar::new(pi, scale as i64).with_prec(precision)
+}
+
+#[inline]
+pub fn e(precision: u64) -> BigDecimalScalar {
+    exp_of(&BigDecimalScalar::one(), precision)
+}
+
+/// Calculates `e^x` using the Taylor series, after halving `x` until it's small enough.
+pub fn exp_of(x: &BigDecimalScalar, precision: u64) -> BigDecimalScalar {
+    let working = precision + GUARD_DIGITS;
+    let ctx = context(working);
+
+    let mut x = x.clone();
+    let mut halvings = 0;
+    while x.abs() > BigDecimalScalar::from_f64(0.5).unwrap() {
+        x = x.half();
+        halvings += 1;
+    }
+
+    let mut sum = BigDecimalScalar::one();
+    let mut term = BigDecimalScalar::one();
+    let epsilon = BigDecimalScalar::new(1.into(), working as i64);
+    let mut k = 1u64;
+
+    while term.abs() > epsilon {
+        term = ctx.multiply(&term, &x) / BigDecimalScalar::from(k);
+        term = term.with_prec(working);
+        sum += &term;
+        k += 1;
+    }
+
+    for _ in 0..halvings {
+        sum = ctx.multiply(&sum, &sum);
+    }
+
+    sum.with_prec(precision)
+}
+
+/// Calculates the natural logarithm using `\ln{x}=2\operatorname{artanh}{\frac{x-1}{x+1}}`,
+/// after taking square roots of `x` until it's close to `1`.
+pub fn ln(x: &BigDecimalScalar, precision: u64) -> Option<BigDecimalScalar> {
+    if !x.is_positive() {
+        return None;
+    }
+
+    const SQRTS: u32 = 8;
+
+    let working = precision + GUARD_DIGITS + 3;
+    let ctx = context(working);
+
+    let artanh_ln = |mut x: BigDecimalScalar| {
+        for _ in 0..SQRTS {
+            x = x.sqrt_with_context(&ctx).unwrap();
+        }
+
+        let one = BigDecimalScalar::one();
+        let z = divide(&(&x - &one), &(&x + &one), working).unwrap();
+        let z2 = ctx.multiply(&z, &z);
+        let epsilon = BigDecimalScalar::new(1.into(), working as i64);
+
+        let mut power = z.clone();
+        let mut sum = z;
+        let mut k = 1u64;
+        loop {
+            power = ctx.multiply(&power, &z2);
+            let term = (&power / BigDecimalScalar::from(2 * k + 1)).with_prec(working);
+            if term.abs() < epsilon {
+                break;
+            }
+            sum += term;
+            k += 1;
+        }
+
+        sum * BigDecimalScalar::from(2u64 << SQRTS)
+    };
+
+    // x=m*10^n, where m is in [1, 10)
+    let n = x.order_of_magnitude();
+    let m = x * BigDecimalScalar::new(1.into(), n);
+
+    let mut result = artanh_ln(m);
+    if n != 0 {
+        result += artanh_ln(BigDecimalScalar::from(10)) * BigDecimalScalar::from(n);
+    }
+
+    Some(result.with_prec(precision))
+}
+
+/// Reduces `x` into `[-\pi, \pi]`.
+fn reduce_angle(x: &BigDecimalScalar, precision: u64) -> BigDecimalScalar {
+    let magnitude = x.order_of_magnitude().max(0) as u64;
+    let pi = pi(precision + magnitude + GUARD_DIGITS);
+    let tau = pi.double();
+
+    let turns = (x / &tau).round(0);
+    let reduced = x - turns * &tau;
+
+    if reduced > pi {
+        reduced - tau
+    } else if reduced < -&pi {
+        reduced + tau
+    } else {
+        reduced
+    }
+}
+
+/// Sums the Taylor series `\sum_{k}{(-1)^k\frac{x^{2k+offset}}{(2k+offset)!}}`.
+fn alternating_series(x: &BigDecimalScalar, offset: u64, precision: u64) -> BigDecimalScalar {
+    let working = precision + GUARD_DIGITS;
+    let ctx = context(working);
+    let x = reduce_angle(x, working);
+    let x2 = ctx.multiply(&x, &x);
+    let epsilon = BigDecimalScalar::new(1.into(), working as i64);
+
+    let mut term = if offset == 0 {
+        BigDecimalScalar::one()
+    } else {
+        x.clone()
+    };
+    let mut sum = term.clone();
+    let mut k = offset;
+
+    loop {
+        term = -ctx.multiply(&term, &x2) / BigDecimalScalar::from((k + 1) * (k + 2));
+        term = term.with_prec(working);
+        if term.abs() < epsilon {
+            break;
+        }
+        sum += &term;
+        k += 2;
+    }
+
+    sum.with_prec(precision)
+}
+
+#[inline]
+pub fn sin(x: &BigDecimalScalar, precision: u64) -> BigDecimalScalar {
+    alternating_series(x, 1, precision)
+}
+
+#[inline]
+pub fn cos(x: &BigDecimalScalar, precision: u64) -> BigDecimalScalar {
+    alternating_series(x, 0, precision)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PI_50: &str = "3.1415926535897932384626433832795028841971693993751";
+    const E_50: &str = "2.7182818284590452353602874713526624977572470937000";
+
+    fn digits(d: BigDecimalScalar, precision: u64) -> String {
+        round(&d, precision).to_string()
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(digits(pi(50), 50), PI_50);
+        assert_eq!(digits(e(50), 50), E_50);
+    }
+
+    #[test]
+    fn test_elementary_functions() {
+        let two = BigDecimalScalar::from(2);
+        assert_eq!(
+            digits(ln(&two, 40).unwrap(), 30),
+            "0.693147180559945309417232121458"
+        );
+        assert_eq!(
+            digits(root(&two, &two, 40).unwrap(), 30),
+            "1.41421356237309504880168872421"
+        );
+        assert_eq!(
+            digits(sin(&BigDecimalScalar::from(1), 40), 30),
+            "0.841470984807896506652502321630"
+        );
+        assert_eq!(
+            digits(cos(&pi(40), 40), 30),
+            "-1.000000000000000000000000000000"
+        );
+        assert_eq!(
+            digits(pow(&two, &"0.5".parse().unwrap(), 40).unwrap(), 30),
+            "1.41421356237309504880168872421"
+        );
+    }
+}
@@ -0,0 +1,634 @@
+//! Rewrites expressions with identities until they stop changing, then brings them into a
+//! canonical form, so equivalent answers look the same.
+
+use std::cmp::Reverse;
+
+use crate::{
+    math::{
+        func::{
+            decl::{Fraction, MathFunction},
+            exp_log::{log, power, root},
+            op::{abs, add, divide, is_nonnegative, multiply, negate, split_coefficient},
+            Function,
+        },
+        mpoly::MultiPolynomial,
+        symbol::{Number, Rational},
+        ExpressionElement, IntoRawExpr,
+    },
+    IntegerScalar,
+};
+
+/// The default limit on the number of rewrites.
+pub const DEFAULT_MAX_STEPS: usize = 256;
+
+/// The identities a [`Simplifier`] can apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// `\sqrt[n]{x^n}=|x|` for even `n`, which is `x` if `x` is assumed to be non-negative.
+    Roots,
+    /// `x^a*x^b=x^{a+b}`, `(x^a)^b=x^{ab}` for integer `b` and `|x|^2=x^2`.
+    PowerLaws,
+    /// `\log_b{x}+\log_b{y}=\log_b{xy}`, `\log_b{x^n}=n\log_b{x}` and `b^{\log_b{x}}=x`.
+    LogLaws,
+    /// `\sin^2{x}+\cos^2{x}=1` and `\cosh^2{x}-\sinh^2{x}=1`.
+    Pythagorean,
+    /// Cancels the common factors of polynomial fractions, like `\frac{x^2-1}{x-1}=x+1`.
+    Fractions,
+    /// Combines terms that only differ in their coefficients and the order of their factors,
+    /// like `x*y+2*y*x=3*x*y`, and expands polynomial terms so they cancel, like
+    /// `(x+1)^2-x^2=2x+1`.
+    LikeTerms,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 6] = [
+        Rule::Roots,
+        Rule::PowerLaws,
+        Rule::LogLaws,
+        Rule::Pythagorean,
+        Rule::Fractions,
+        Rule::LikeTerms,
+    ];
+}
+
+/// A term-rewriting simplifier with a configurable set of rules.
+///
+/// Rules are applied bottom-up until nothing changes or the step limit is hit, so it always
+/// terminates even if some rules undo each other.
+#[derive(Debug, Clone)]
+pub struct Simplifier {
+    rules: Vec<Rule>,
+    max_steps: usize,
+    /// Variables that are assumed to be non-negative reals.
+    nonnegative: Vec<String>,
+}
+
+impl Default for Simplifier {
+    fn default() -> Self {
+        Self::new(&Rule::ALL)
+    }
+}
+
+impl Simplifier {
+    pub fn new(rules: &[Rule]) -> Self {
+        Self {
+            rules: rules.to_vec(),
+            max_steps: DEFAULT_MAX_STEPS,
+            nonnegative: Vec::new(),
+        }
+    }
+
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        if !self.rules.contains(&rule) {
+            self.rules.push(rule);
+        }
+        self
+    }
+
+    pub fn without_rule(mut self, rule: Rule) -> Self {
+        self.rules.retain(|r| *r != rule);
+        self
+    }
+
+    /// Limits the number of rewrites, where 0 only brings the expression into canonical form.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Assumes `var` to be a non-negative real, so `\sqrt{x^2}` becomes `x` instead of `|x|`.
+    pub fn assume_nonnegative(mut self, var: impl Into<String>) -> Self {
+        self.nonnegative.push(var.into());
+        self
+    }
+
+    pub fn simplify(&self, elem: &ExpressionElement) -> ExpressionElement {
+        let mut steps = 0;
+        let mut current = ExpressionElement::from(elem.evaluate());
+
+        // Passes that only re-evaluate are counted too, just in case evaluation isn't stable.
+        for _ in 0..self.max_steps {
+            let next = self.rewrite(&current, &mut steps);
+            if next == current || steps >= self.max_steps {
+                current = next;
+                break;
+            }
+            current = next;
+        }
+
+        canonical(&current)
+    }
+
+    #[inline]
+    fn is_nonnegative(&self, elem: &ExpressionElement) -> bool {
+        is_nonnegative(elem, &|v| self.nonnegative.iter().any(|n| n == v))
+    }
+
+    /// Rewrites the operands first, then applies the first rule that changes the result.
+    fn rewrite(&self, elem: &ExpressionElement, steps: &mut usize) -> ExpressionElement {
+        let f = match elem {
+            ExpressionElement::Function(f) if !is_opaque(f) => f,
+            ExpressionElement::Matrix(m) => return m.map(|e| self.rewrite(e, steps)).into(),
+            _ => return elem.clone(),
+        };
+
+        let node = ExpressionElement::from(f.map_params(|p| self.rewrite(p, steps)).evaluate());
+        if *steps >= self.max_steps {
+            return node;
+        }
+
+        for rule in &self.rules {
+            if let Some(next) = self.apply(*rule, &node).filter(|next| *next != node) {
+                *steps += 1;
+                return next;
+            }
+        }
+
+        node
+    }
+
+    fn apply(&self, rule: Rule, elem: &ExpressionElement) -> Option<ExpressionElement> {
+        let ExpressionElement::Function(f) = elem else {
+            return None;
+        };
+
+        match (rule, f.as_ref()) {
+            (Rule::Roots, MathFunction::Root(root)) => {
+                let n = integer(root.rad())?;
+                let MathFunction::Power(pow) = as_function(root.deg())? else {
+                    return None;
+                };
+                let m = integer(pow.exp())?;
+                if n == 0 || m % n != 0 {
+                    return None;
+                }
+
+                let (base, k) = (pow.base().clone(), m / n);
+                Some(if n % 2 != 0 || k % 2 == 0 || self.is_nonnegative(&base) {
+                    power(base, Number::Integer(k).into())
+                } else {
+                    power(abs(base), Number::Integer(k).into())
+                })
+            }
+            (Rule::Roots, MathFunction::Abs(x)) => {
+                self.is_nonnegative(x.x()).then(|| x.x().clone())
+            }
+
+            (Rule::PowerLaws, MathFunction::Power(pow)) => match as_function(pow.base())? {
+                // (x^a)^b=x^{ab}
+                MathFunction::Power(inner)
+                    if integer(pow.exp()).is_some() || self.is_nonnegative(inner.base()) =>
+                {
+                    Some(power(
+                        inner.base().clone(),
+                        multiply(inner.exp().clone(), pow.exp().clone()),
+                    ))
+                }
+                // |x|^{2k}=x^{2k}
+                MathFunction::Abs(x) if integer(pow.exp())? % 2 == 0 => {
+                    Some(power(x.x().clone(), pow.exp().clone()))
+                }
+                _ => None,
+            },
+            (
+                Rule::PowerLaws,
+                MathFunction::Multiply(_) | MathFunction::Divide(_) | MathFunction::Fraction(_),
+            ) => {
+                let mut merged: Vec<Factor> = Vec::new();
+                for (base, exp) in factors(elem)? {
+                    match merged.iter_mut().find(|(b, _)| *b == base) {
+                        Some((_, e)) => *e = add(e.clone(), exp),
+                        None => merged.push((base, exp)),
+                    }
+                }
+                Some(product(merged))
+            }
+
+            (Rule::LogLaws, MathFunction::Log(l)) => {
+                let (u, exp) = match as_function(l.anti())? {
+                    MathFunction::Power(pow) => (pow.base(), pow.exp().clone()),
+                    MathFunction::Root(root) => (
+                        root.deg(),
+                        divide(Number::Integer(1).into(), root.rad().clone()),
+                    ),
+                    _ => return None,
+                };
+
+                // \ln{x^2}=2\ln{|x|}, as x^2 is defined for negative x while \ln{x} isn't.
+                let u = match exp.as_rational() {
+                    _ if self.is_nonnegative(u) => u.clone(),
+                    Some(r) if r.num() % 2 == 0 && r.den() % 2 != 0 => abs(u.clone()),
+                    Some(_) => u.clone(),
+                    None => return None,
+                };
+                Some(multiply(exp, log(l.base().clone(), u)))
+            }
+            (Rule::LogLaws, MathFunction::Power(pow)) => {
+                // b^{c\log_b{x}}=x^c
+                let (coeff, rest) = split_coefficient(pow.exp());
+                match as_function(rest?)? {
+                    MathFunction::Log(l) if l.base() == pow.base() => {
+                        Some(power(l.anti().clone(), Number::from(coeff).into()))
+                    }
+                    _ => None,
+                }
+            }
+            (Rule::LogLaws, MathFunction::Add(_) | MathFunction::Subtract(_)) => {
+                combine_logs(terms(elem))
+            }
+
+            (Rule::Pythagorean, MathFunction::Add(_) | MathFunction::Subtract(_)) => {
+                pythagorean(terms(elem))
+            }
+
+            (Rule::Fractions, MathFunction::Fraction(_) | MathFunction::Divide(_)) => {
+                let (num, den) = match f.as_ref() {
+                    MathFunction::Fraction(frac) => (frac.num(), frac.den()),
+                    MathFunction::Divide(div) => (div.lhs(), div.rhs()),
+                    _ => unreachable!(),
+                };
+                let (num, den) = (
+                    MultiPolynomial::from_expr(num)?,
+                    MultiPolynomial::from_expr(den)?,
+                );
+                let gcd = num.gcd(&den)?;
+                if gcd.is_constant() {
+                    return None;
+                }
+
+                Some(divide(
+                    num.exact_div(&gcd)?.to_expr(),
+                    den.exact_div(&gcd)?.to_expr(),
+                ))
+            }
+
+            (Rule::LikeTerms, MathFunction::Add(_) | MathFunction::Subtract(_)) => {
+                Some(canonical(elem))
+            }
+
+            _ => None,
+        }
+    }
+}
+
+/// A base and its exponent.
+type Factor = (ExpressionElement, ExpressionElement);
+
+/// Nodes whose operands can't be rewritten on their own, as they bind a variable or still have to
+/// be found.
+fn is_opaque(f: &MathFunction) -> bool {
+    f.bound_var().is_some()
+        || matches!(
+            f,
+            MathFunction::Derivative(_)
+                | MathFunction::PartialDerivative(_)
+                | MathFunction::IndefiniteIntegral(_)
+        )
+}
+
+#[inline]
+fn as_function(elem: &ExpressionElement) -> Option<&MathFunction> {
+    match elem {
+        ExpressionElement::Function(f) => Some(f),
+        _ => None,
+    }
+}
+
+#[inline]
+fn integer(elem: &ExpressionElement) -> Option<IntegerScalar> {
+    elem.as_rational()
+        .filter(|r| r.is_integer())
+        .map(|r| r.num())
+}
+
+/// Splits a sum into its terms, negating the subtracted ones.
+fn terms(elem: &ExpressionElement) -> Vec<ExpressionElement> {
+    fn collect(elem: &ExpressionElement, negated: bool, terms: &mut Vec<ExpressionElement>) {
+        match as_function(elem) {
+            Some(MathFunction::Add(a)) => {
+                collect(a.lhs(), negated, terms);
+                collect(a.rhs(), negated, terms);
+            }
+            Some(MathFunction::Subtract(s)) => {
+                collect(s.lhs(), negated, terms);
+                collect(s.rhs(), !negated, terms);
+            }
+            _ if negated => terms.push(negate(elem.clone())),
+            _ => terms.push(elem.clone()),
+        }
+    }
+
+    let mut terms = Vec::new();
+    collect(elem, false, &mut terms);
+    terms
+}
+
+#[inline]
+fn sum(terms: impl IntoIterator<Item = ExpressionElement>) -> ExpressionElement {
+    terms.into_iter().fold(Number::Integer(0).into(), add)
+}
+
+/// Splits a product into its bases and exponents, where divisors get negative exponents. `None`
+/// if a factor is a matrix, as those can't be reordered.
+fn factors(elem: &ExpressionElement) -> Option<Vec<Factor>> {
+    fn collect(elem: &ExpressionElement, inverse: bool, factors: &mut Vec<Factor>) -> Option<()> {
+        let sign = |exp: ExpressionElement| if inverse { negate(exp) } else { exp };
+
+        match as_function(elem) {
+            Some(MathFunction::Multiply(mul)) => {
+                collect(mul.lhs(), inverse, factors)?;
+                return collect(mul.rhs(), inverse, factors);
+            }
+            Some(MathFunction::Divide(div)) => {
+                collect(div.lhs(), inverse, factors)?;
+                return collect(div.rhs(), !inverse, factors);
+            }
+            Some(MathFunction::Fraction(frac)) => {
+                collect(frac.num(), inverse, factors)?;
+                return collect(frac.den(), !inverse, factors);
+            }
+            Some(MathFunction::Power(pow)) if !pow.base().is_matrix() => {
+                factors.push((pow.base().clone(), sign(pow.exp().clone())));
+                return Some(());
+            }
+            Some(MathFunction::Root(root)) => {
+                if let Some(exp) = root.rad().as_rational().and_then(Rational::recip) {
+                    factors.push((root.deg().clone(), sign(Number::from(exp).into())));
+                    return Some(());
+                }
+            }
+            _ => {}
+        }
+
+        if elem.is_matrix() {
+            return None;
+        }
+        factors.push((elem.clone(), sign(Number::Integer(1).into())));
+        Some(())
+    }
+
+    let mut factors = Vec::new();
+    collect(elem, false, &mut factors)?;
+    Some(factors)
+}
+
+/// The inverse of [`factors`], which puts the factors with negative exponents into the
+/// denominator.
+fn product(factors: Vec<Factor>) -> ExpressionElement {
+    let raise = |base: ExpressionElement, exp: ExpressionElement| match exp.as_rational() {
+        Some(r) if r.num() == 1 && r.den() > 1 => root(Number::Integer(r.den()).into(), base),
+        _ => power(base, exp),
+    };
+
+    let (mut num, mut den) = (Number::Integer(1).into(), Number::Integer(1).into());
+    for (base, exp) in factors {
+        if split_coefficient(&exp).0.is_negative() {
+            den = multiply(den, raise(base, negate(exp)));
+        } else {
+            num = multiply(num, raise(base, exp));
+        }
+    }
+
+    // \frac{2}{x} rather than 2*\frac{1}{x}
+    let quotient = divide(num, den);
+    let (coeff, rest) = split_coefficient(&quotient);
+    match rest.and_then(as_function) {
+        Some(MathFunction::Fraction(frac)) if coeff != Rational::ONE => {
+            let (num, den) = (coeff.num().abs(), coeff.den());
+            let frac = Fraction::new(
+                multiply(Number::Integer(num).into(), frac.num().clone()),
+                multiply(Number::Integer(den).into(), frac.den().clone()),
+            );
+            if coeff.is_negative() {
+                negate(frac.into())
+            } else {
+                frac.into()
+            }
+        }
+        _ => quotient,
+    }
+}
+
+/// `a\log_b{x}+c\log_b{y}=\log_b{x^a y^c}` for integer coefficients.
+fn combine_logs(terms: Vec<ExpressionElement>) -> Option<ExpressionElement> {
+    let log_of = |term: &ExpressionElement| {
+        let (coeff, rest) = split_coefficient(term);
+        match as_function(rest?)? {
+            MathFunction::Log(l) if coeff.is_integer() => {
+                Some((l.base().clone(), l.anti().clone(), coeff.num()))
+            }
+            _ => None,
+        }
+    };
+
+    let base = terms.iter().filter_map(log_of).find_map(|(base, ..)| {
+        let count = terms
+            .iter()
+            .filter_map(log_of)
+            .filter(|(b, ..)| *b == base)
+            .count();
+        (count > 1).then_some(base)
+    })?;
+
+    let mut anti = Vec::new();
+    let mut rest = Vec::new();
+    for term in terms {
+        match log_of(&term) {
+            Some((b, x, coeff)) if b == base => {
+                for (x, exp) in factors(&x)? {
+                    anti.push((x, multiply(exp, Number::Integer(coeff).into())));
+                }
+            }
+            _ => rest.push(term),
+        }
+    }
+
+    rest.push(log(base, product(anti)));
+    Some(sum(rest))
+}
+
+/// `a\sin^2{x}+b\cos^2{x}=b+(a-b)\sin^2{x}` and `a\cosh^2{x}+b\sinh^2{x}=a+(a+b)\sinh^2{x}`.
+fn pythagorean(terms: Vec<ExpressionElement>) -> Option<ExpressionElement> {
+    #[derive(PartialEq)]
+    enum Squared {
+        Sin,
+        Cos,
+        Sinh,
+        Cosh,
+    }
+
+    let squared = |term: &ExpressionElement| {
+        let (coeff, rest) = split_coefficient(term);
+        let MathFunction::Power(pow) = as_function(rest?)? else {
+            return None;
+        };
+        if !pow.exp().is_integer(2) {
+            return None;
+        }
+
+        let (kind, x) = match as_function(pow.base())? {
+            MathFunction::Sin(f) => (Squared::Sin, f.x()),
+            MathFunction::Cos(f) => (Squared::Cos, f.x()),
+            MathFunction::Sinh(f) => (Squared::Sinh, f.x()),
+            MathFunction::Cosh(f) => (Squared::Cosh, f.x()),
+            _ => return None,
+        };
+        Some((kind, x.clone(), coeff))
+    };
+
+    for (i, lhs) in terms.iter().enumerate() {
+        let Some((kind, x, a)) = squared(lhs) else {
+            continue;
+        };
+        let pair = match kind {
+            Squared::Sin => Squared::Cos,
+            Squared::Cosh => Squared::Sinh,
+            _ => continue,
+        };
+
+        let Some((j, b)) = terms.iter().enumerate().find_map(|(j, rhs)| {
+            let (kind, y, b) = squared(rhs)?;
+            (kind == pair && y == x).then_some((j, b))
+        }) else {
+            continue;
+        };
+
+        let (constant, coeff, squared) = match pair {
+            Squared::Cos => (b, a.checked_sub(b)?, terms[i].clone()),
+            _ => (a, a.checked_add(b)?, terms[j].clone()),
+        };
+        let (_, squared) = split_coefficient(&squared);
+        let squared = multiply(Number::from(coeff).into(), squared?.clone());
+
+        let rest = terms
+            .iter()
+            .enumerate()
+            .filter(|(k, _)| *k != i && *k != j)
+            .map(|(_, t)| t.clone());
+        return Some(sum(rest.chain([squared, Number::from(constant).into()])));
+    }
+
+    None
+}
+
+/// Expands the polynomial terms of sums and orders the terms by descending degree with constants
+/// last, and the factors of products by numbers, variables and then everything else, with ties
+/// broken by their LaTeX.
+fn canonical(elem: &ExpressionElement) -> ExpressionElement {
+    let f = match elem {
+        ExpressionElement::Function(f) if !is_opaque(f) => f,
+        ExpressionElement::Matrix(m) => return m.map(canonical).into(),
+        _ => return elem.clone(),
+    };
+    let elem = ExpressionElement::from(f.map_params(canonical).evaluate());
+
+    match as_function(&elem) {
+        Some(MathFunction::Add(_) | MathFunction::Subtract(_)) => {
+            // Polynomial terms are expanded together, so they cancel wherever they can.
+            let (polynomials, mut terms): (Vec<_>, Vec<_>) = terms(&elem)
+                .into_iter()
+                .partition(|t| MultiPolynomial::from_expr(t).is_some());
+            let expanded = polynomials
+                .iter()
+                .try_fold(MultiPolynomial::zero(), |sum, t| {
+                    sum.checked_add(&MultiPolynomial::from_expr(t)?)
+                });
+            match expanded {
+                Some(p) => terms.extend(self::terms(&p.to_expr())),
+                None => terms.extend(polynomials),
+            }
+
+            let mut terms = terms
+                .iter()
+                .map(|t| ordered_product(t).unwrap_or_else(|| t.clone()))
+                .collect::<Vec<_>>();
+            terms.sort_by_cached_key(|t| (!t.is_symbolic(), Reverse(degree(t)), t.assemble()));
+            sum(terms)
+        }
+        Some(MathFunction::Multiply(_) | MathFunction::Divide(_) | MathFunction::Fraction(_)) => {
+            ordered_product(&elem).unwrap_or(elem)
+        }
+        _ => elem,
+    }
+}
+
+fn ordered_product(elem: &ExpressionElement) -> Option<ExpressionElement> {
+    let mut factors = factors(elem)?;
+    factors.sort_by_cached_key(|(base, _)| {
+        let rank = match base {
+            ExpressionElement::Number(_) => 0,
+            ExpressionElement::Variable(_) => 1,
+            _ => 2,
+        };
+        (rank, base.assemble())
+    });
+    Some(product(factors))
+}
+
+/// The sum of the integer powers of the variables in a term.
+fn degree(term: &ExpressionElement) -> IntegerScalar {
+    factors(term)
+        .unwrap_or_default()
+        .iter()
+        .filter(|(base, _)| matches!(base, ExpressionElement::Variable(_)))
+        .filter_map(|(_, exp)| integer(exp))
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::math::{
+        expr::ExpresssionTree,
+        simplify::{Rule, Simplifier},
+        FromRawExpr, IntoRawExpr, ParsingScope,
+    };
+
+    #[test]
+    fn test_simplify() {
+        let scope = ParsingScope::new(None)
+            .with_symbol("x")
+            .with_symbol("y")
+            .with_symbol("a")
+            .with_symbol("b");
+        let tree = |expr| ExpresssionTree::parse_scoped(expr, &scope).unwrap();
+        let simplify = |expr| tree(expr).simplify().assemble();
+
+        assert_eq!(simplify(r#"(\sin x)^2+(\cos x)^2"#), "1");
+        assert_eq!(simplify(r#"x+3*(\sin y)^2+3*(\cos y)^2"#), "x+3");
+        assert_eq!(simplify(r#"(\cosh x)^2-(\sinh x)^2"#), "1");
+        assert_eq!(simplify(r#"\ln x+\ln y"#), r#"\ln{x*y}"#);
+        assert_eq!(simplify(r#"\ln x-\ln y"#), r#"\ln{\frac{x}{y}}"#);
+        assert_eq!(simplify(r#"\ln{x^2}"#), r#"2*\ln{\operatorname{abs}{x}}"#);
+        assert_eq!(simplify(r#"e^{\ln x}"#), "x");
+        assert_eq!(simplify(r#"e^{2\ln x}"#), "x^{2}");
+        assert_eq!(simplify(r#"\sqrt{x^2}"#), r#"\operatorname{abs}{x}"#);
+        assert_eq!(simplify(r#"\sqrt[3]{x^3}"#), "x");
+        assert_eq!(simplify("x^2*x^3"), "x^{5}");
+        assert_eq!(simplify("(x^2)^3"), "x^{6}");
+        assert_eq!(simplify("x^{a}*x^{b}"), "x^{a+b}");
+        assert_eq!(simplify(r#"\frac{x^3*y}{x*y^2}"#), r#"\frac{x^{2}}{y}"#);
+        assert_eq!(simplify(r#"\frac{x^2-1}{x-1}"#), "x+1");
+        assert_eq!(simplify("x*y+2*y*x"), "3*x*y");
+        assert_eq!(simplify("1+y+x^2"), "x^{2}+y+1");
+        assert_eq!(simplify("(x+1)^2-(x^2+2x+1)"), "0");
+        assert_eq!(simplify(r#"-\frac{1}{x}-\frac{1}{x}"#), r#"-\frac{2}{x}"#);
+        assert_eq!(simplify(r#"\frac{1}{x}+\frac{1}{x}"#), r#"\frac{2}{x}"#);
+
+        let positive = Simplifier::default().assume_nonnegative("x");
+        let simplify_with =
+            |expr, simplifier: &Simplifier| tree(expr).simplify_with(simplifier).assemble();
+        assert_eq!(simplify_with(r#"\sqrt{x^2}"#, &positive), "x");
+        assert_eq!(simplify_with(r#"\ln{x^2}"#, &positive), r#"2*\ln{x}"#);
+        assert_eq!(simplify_with("((x)^{a})^{b}", &positive), "x^{a*b}");
+
+        // Turning rules off or running out of steps still gives a canonical form.
+        let pythagorean = r#"(\sin x)^2+(\cos x)^2"#;
+        let canonical = r#"\cos{x}^{2}+\sin{x}^{2}"#;
+        let without = Simplifier::default().without_rule(Rule::Pythagorean);
+        assert_eq!(simplify_with(pythagorean, &without), canonical);
+        let limited = Simplifier::default().with_max_steps(0);
+        assert_eq!(simplify_with(pythagorean, &limited), canonical);
+        let only = Simplifier::new(&[Rule::Pythagorean]);
+        assert_eq!(simplify_with(pythagorean, &only), "1");
+    }
+}
@@ -1,14 +1,21 @@
 use std::{collections::HashMap, fmt::Display};
 
 use crate::{
-    latex::{E, PI},
+    latex::{
+        ADD, CURLY_BRACKET_L, CURLY_BRACKET_R, E, FRAC, FUNC_BEGIN, I, INFTY, MATHRM, MULTIPLY,
+        PARENTHESES_L, PARENTHESES_R, PI, SUBTRACT, SUPER_SCRIPT,
+    },
     math::{
         func::Function, FromRawExpr, IntoRawExpr, LaTexParsingError, LaTexParsingErrorType,
         LaTexParsingResult, MathElement, MathElementMeta,
     },
-    DecimalScalar, IntegerScalar,
+    utils::gcd,
+    ComplexScalar, DecimalScalar, IntegerScalar,
 };
 
+#[cfg(feature = "bignum")]
+use crate::{math::precise, BigDecimalScalar, BigIntegerScalar, BigRationalScalar};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BracketState {
     Open,
@@ -18,8 +25,8 @@ pub enum BracketState {
 impl IntoRawExpr for BracketState {
     fn assemble(&self) -> String {
         match self {
-            BracketState::Open => format!("("),
-            BracketState::Close => format!(")"),
+            BracketState::Open => PARENTHESES_L.to_string(),
+            BracketState::Close => PARENTHESES_R.to_string(),
         }
     }
 }
@@ -28,6 +35,9 @@ impl IntoRawExpr for BracketState {
 pub enum Constant {
     Pi,
     E,
+    /// The imaginary unit.
+    I,
+    Infinity,
 }
 
 impl Display for Constant {
@@ -35,27 +45,49 @@ impl Display for Constant {
         f.write_str(match self {
             Constant::Pi => "\\pi",
             Constant::E => "e",
+            Constant::I => "i",
+            Constant::Infinity => "\\infty",
         })
     }
 }
 
 impl Function for Constant {
+    #[inline]
     fn evaluate(&self) -> MathElement {
-        todo!()
+        MathElement::Number(Number::Constant(*self))
     }
 
     fn approximate(&self) -> DecimalScalar {
         match *self {
-            // TODO automatically adapt to the target accuracy
             Constant::Pi => std::f64::consts::PI,
             Constant::E => std::f64::consts::E,
+            Constant::I => DecimalScalar::NAN,
+            Constant::Infinity => DecimalScalar::INFINITY,
+        }
+    }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        match *self {
+            Constant::I => ComplexScalar::i(),
+            _ => self.approximate().into(),
         }
     }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        Some(match *self {
+            Constant::Pi => precise::pi(precision),
+            Constant::E => precise::e(precision),
+            Constant::I | Constant::Infinity => return None,
+        })
+    }
 }
 
 impl IntoRawExpr for Constant {
+    #[inline]
     fn assemble(&self) -> String {
-        todo!()
+        self.to_string()
     }
 }
 
@@ -64,6 +96,17 @@ impl FromRawExpr for Constant {
         match expr {
             PI => Ok(Self::Pi),
             E => Ok(Self::E),
+            I => Ok(Self::I),
+            INFTY => Ok(Self::Infinity),
+            _ if expr
+                .strip_prefix(FUNC_BEGIN)
+                .and_then(|e| e.strip_prefix(MATHRM))
+                .and_then(|e| e.strip_prefix(CURLY_BRACKET_L))
+                .and_then(|e| e.strip_suffix(CURLY_BRACKET_R))
+                .is_some_and(|e| e == I || e == E) =>
+            {
+                Self::parse_raw(&expr[expr.len() - 2..expr.len() - 1], None)
+            }
             _ => Err(LaTexParsingError::new(
                 (0..expr.len()).into(),
                 LaTexParsingErrorType::InvalidConstant,
@@ -72,24 +115,333 @@ impl FromRawExpr for Constant {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// An exact rational number, which is always reduced and has a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: IntegerScalar,
+    den: IntegerScalar,
+}
+
+impl Rational {
+    pub const ZERO: Self = Self { num: 0, den: 1 };
+    pub const ONE: Self = Self { num: 1, den: 1 };
+
+    /// Returns `None` if the denominator is zero or the normalized fraction overflows.
+    pub fn new(num: IntegerScalar, den: IntegerScalar) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+
+        let divisor = gcd(num, den);
+        let (num, den) = (num / divisor, den / divisor);
+
+        if den < 0 {
+            Some(Self {
+                num: num.checked_neg()?,
+                den: den.checked_neg()?,
+            })
+        } else {
+            Some(Self { num, den })
+        }
+    }
+
+    #[inline]
+    pub fn num(&self) -> IntegerScalar {
+        self.num
+    }
+
+    #[inline]
+    pub fn den(&self) -> IntegerScalar {
+        self.den
+    }
+
+    #[inline]
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    #[inline]
+    pub fn is_negative(&self) -> bool {
+        self.num < 0
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let divisor = gcd(self.den, rhs.den);
+        let den = (self.den / divisor).checked_mul(rhs.den)?;
+        let num = self
+            .num
+            .checked_mul(den / self.den)?
+            .checked_add(rhs.num.checked_mul(den / rhs.den)?)?;
+        Self::new(num, den)
+    }
+
+    #[inline]
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_add(rhs.checked_neg()?)
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        // Cross reduce first to keep the intermediate values small.
+        let (d0, d1) = (gcd(self.num, rhs.den), gcd(rhs.num, self.den));
+        Self::new(
+            (self.num / d0).checked_mul(rhs.num / d1)?,
+            (self.den / d1).checked_mul(rhs.den / d0)?,
+        )
+    }
+
+    #[inline]
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.checked_mul(rhs.recip()?)
+    }
+
+    #[inline]
+    pub fn checked_neg(self) -> Option<Self> {
+        Some(Self {
+            num: self.num.checked_neg()?,
+            den: self.den,
+        })
+    }
+
+    #[inline]
+    pub fn recip(self) -> Option<Self> {
+        Self::new(self.den, self.num)
+    }
+
+    pub fn checked_pow(self, exp: IntegerScalar) -> Option<Self> {
+        let abs_exp = u32::try_from(exp.checked_abs()?).ok()?;
+        let pow = Self {
+            num: self.num.checked_pow(abs_exp)?,
+            den: self.den.checked_pow(abs_exp)?,
+        };
+
+        if exp < 0 {
+            pow.recip()
+        } else {
+            Some(pow)
+        }
+    }
+
+    #[inline]
+    pub fn to_decimal(self) -> DecimalScalar {
+        self.num as DecimalScalar / self.den as DecimalScalar
+    }
+}
+
+impl From<IntegerScalar> for Rational {
+    #[inline]
+    fn from(value: IntegerScalar) -> Self {
+        Self { num: value, den: 1 }
+    }
+}
+
+impl IntoRawExpr for Rational {
+    fn assemble(&self) -> String {
+        if self.is_integer() {
+            return self.num.to_string();
+        }
+
+        let frac = format!("\\{}{{{}}}{{{}}}", FRAC, self.num.abs(), self.den);
+        if self.is_negative() {
+            format!("{}{}", SUBTRACT, frac)
+        } else {
+            frac
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Number {
     Integer(IntegerScalar),
+    Rational(Rational),
     Decimal(DecimalScalar),
     Constant(Constant),
+    /// Complex numbers that can't be kept exact.
+    Complex(ComplexScalar),
+    /// Integers that don't fit into [`IntegerScalar`].
+    #[cfg(feature = "bignum")]
+    BigInteger(BigIntegerScalar),
+    /// Rationals whose numerator or denominator doesn't fit into [`IntegerScalar`].
+    #[cfg(feature = "bignum")]
+    BigRational(BigRationalScalar),
+    /// Decimals with more significant digits than [`DecimalScalar`] can hold.
+    #[cfg(feature = "bignum")]
+    BigDecimal(BigDecimalScalar),
     // Virtual()
 }
 
+impl Number {
+    /// Returns the exact value of integers and rationals.
+    #[inline]
+    pub fn as_rational(&self) -> Option<Rational> {
+        match *self {
+            Number::Integer(i) => Some(i.into()),
+            Number::Rational(r) => Some(r),
+            _ => None,
+        }
+    }
+
+    /// Converts back to a [`Number::Decimal`] if there's no imaginary part.
+    #[inline]
+    pub fn from_complex(c: ComplexScalar) -> Number {
+        if c.im == 0. {
+            Number::Decimal(c.re)
+        } else {
+            Number::Complex(c)
+        }
+    }
+
+    #[inline]
+    fn is_complex(&self) -> bool {
+        matches!(self, Number::Complex(_) | Number::Constant(Constant::I))
+    }
+
+    /// Raises the number to a power, which stays exact for rationals with integer exponents.
+    pub fn pow(self, exp: Number) -> Number {
+        if let (Some(base), Some(exp)) = (self.as_rational(), exp.as_rational()) {
+            if let Some(pow) = exp
+                .is_integer()
+                .then(|| base.checked_pow(exp.num()))
+                .flatten()
+            {
+                return pow.into();
+            }
+        }
+
+        if self.is_complex() || exp.is_complex() {
+            return Number::from_complex(
+                self.approximate_complex().powc(exp.approximate_complex()),
+            );
+        }
+
+        #[cfg(feature = "bignum")]
+        if let Some(pow) = precise::binary_op(&self, &exp, precise::pow_rational, precise::pow) {
+            return pow;
+        }
+
+        Number::Decimal(self.approximate().powf(exp.approximate()))
+    }
+}
+
+impl From<Rational> for Number {
+    #[inline]
+    fn from(value: Rational) -> Self {
+        if value.is_integer() {
+            Number::Integer(value.num)
+        } else {
+            Number::Rational(value)
+        }
+    }
+}
+
+macro_rules! impl_number_op {
+    (
+        $op_trait: ident,
+        $op_fn: ident,
+        $checked_fn: ident,
+        $op: tt,
+        $big_int_op: expr,
+        $big_dec_op: expr
+    ) => {
+        impl std::ops::$op_trait for Number {
+            type Output = Number;
+
+            fn $op_fn(self, rhs: Self) -> Self::Output {
+                if let (Some(lhs), Some(rhs)) = (self.as_rational(), rhs.as_rational()) {
+                    if let Some(result) = lhs.$checked_fn(rhs) {
+                        return result.into();
+                    }
+                }
+
+                if self.is_complex() || rhs.is_complex() {
+                    return Number::from_complex(
+                        self.approximate_complex() $op rhs.approximate_complex(),
+                    );
+                }
+
+                #[cfg(feature = "bignum")]
+                if let Some(result) = precise::binary_op(&self, &rhs, $big_int_op, $big_dec_op) {
+                    return result;
+                }
+
+                Number::Decimal(self.approximate() $op rhs.approximate())
+            }
+        }
+    };
+}
+
+impl_number_op!(Add, add, checked_add, +, |a, b| Some(a + b), |a, b, _| Some(a + b));
+impl_number_op!(Sub, sub, checked_sub, -, |a, b| Some(a - b), |a, b, _| Some(a - b));
+impl_number_op!(Mul, mul, checked_mul, *, |a, b| Some(a * b), |a, b, _| Some(a * b));
+impl_number_op!(
+    Div,
+    div,
+    checked_div,
+    /,
+    precise::divide_rational,
+    precise::divide
+);
+
+impl std::ops::Neg for Number {
+    type Output = Number;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Number::Integer(-1) * self
+    }
+}
+
 impl Function for Number {
-    fn evaluate(&self) -> super::MathElement {
-        todo!()
+    #[inline]
+    fn evaluate(&self) -> MathElement {
+        MathElement::Number(self.clone())
     }
 
     fn approximate(&self) -> DecimalScalar {
-        match *self {
-            Number::Integer(i) => i as DecimalScalar,
-            Number::Decimal(d) => d,
+        match self {
+            Number::Integer(i) => *i as DecimalScalar,
+            Number::Rational(r) => r.to_decimal(),
+            Number::Decimal(d) => *d,
             Number::Constant(c) => c.approximate(),
+            Number::Complex(c) if c.im == 0. => c.re,
+            Number::Complex(_) => DecimalScalar::NAN,
+            #[cfg(feature = "bignum")]
+            Number::BigInteger(i) => {
+                num_traits::ToPrimitive::to_f64(i).unwrap_or(DecimalScalar::NAN)
+            }
+            #[cfg(feature = "bignum")]
+            Number::BigRational(r) => {
+                num_traits::ToPrimitive::to_f64(r).unwrap_or(DecimalScalar::NAN)
+            }
+            #[cfg(feature = "bignum")]
+            Number::BigDecimal(d) => precise::to_decimal(d),
+        }
+    }
+
+    #[inline]
+    fn approximate_complex(&self) -> ComplexScalar {
+        match self {
+            Number::Constant(c) => c.approximate_complex(),
+            Number::Complex(c) => *c,
+            _ => self.approximate().into(),
+        }
+    }
+
+    #[cfg(feature = "bignum")]
+    fn approximate_precise(&self, precision: u64) -> Option<BigDecimalScalar> {
+        match self {
+            Number::Integer(i) => Some((*i).into()),
+            Number::Rational(r) => precise::divide(&r.num().into(), &r.den().into(), precision),
+            Number::Decimal(d) => precise::from_decimal(*d),
+            Number::Constant(c) => c.approximate_precise(precision),
+            Number::Complex(c) if c.im == 0. => precise::from_decimal(c.re),
+            Number::Complex(_) => None,
+            Number::BigInteger(i) => Some(i.clone().into()),
+            Number::BigRational(r) => precise::divide(
+                &r.numer().clone().into(),
+                &r.denom().clone().into(),
+                precision,
+            ),
+            Number::BigDecimal(d) => Some(d.with_prec(precision)),
         }
     }
 }
@@ -98,12 +450,78 @@ impl IntoRawExpr for Number {
     fn assemble(&self) -> String {
         match self {
             Number::Integer(i) => format!("{}", i),
-            Number::Decimal(d) => format!("{}", d),
+            Number::Rational(r) => r.assemble(),
+            Number::Decimal(d) => assemble_decimal(*d),
             Number::Constant(c) => format!("{}", c),
+            Number::Complex(c) => assemble_complex(c),
+            #[cfg(feature = "bignum")]
+            Number::BigInteger(i) => format!("{}", i),
+            #[cfg(feature = "bignum")]
+            Number::BigRational(r) => {
+                let frac = format!("\\{}{{{}}}{{{}}}", FRAC, r.numer().magnitude(), r.denom());
+                if num_traits::Signed::is_negative(r) {
+                    format!("{}{}", SUBTRACT, frac)
+                } else {
+                    frac
+                }
+            }
+            #[cfg(feature = "bignum")]
+            Number::BigDecimal(d) => assemble_big_decimal(d),
         }
     }
 }
 
+/// Decimals with more leading zeros than this are assembled in scientific notation.
+const MAX_LEADING_ZEROS: i64 = 10;
+/// Decimals this large can't be exact integers, so they're assembled in scientific notation.
+const MIN_APPROXIMATE_INTEGER: DecimalScalar = 1e15;
+
+/// Assembles `mantissa*10^{exponent}`.
+fn assemble_scientific(mantissa: &str, exponent: i64) -> String {
+    format!("{}{}10{}{{{}}}", mantissa, MULTIPLY, SUPER_SCRIPT, exponent)
+}
+
+/// Writes decimals out unless they're so large or small that they would look exact, like
+/// `9.9999999998*10^{21}` for an overflowing product of integers.
+fn assemble_decimal(d: DecimalScalar) -> String {
+    if d.is_infinite() {
+        return if d > 0. {
+            INFTY.to_string()
+        } else {
+            format!("{}{}", SUBTRACT, INFTY)
+        };
+    }
+    if d.is_nan()
+        || d == 0.
+        || d.abs() < MIN_APPROXIMATE_INTEGER && d.abs() >= 10f64.powi(-MAX_LEADING_ZEROS as i32)
+    {
+        return d.to_string();
+    }
+
+    let scientific = format!("{:e}", d);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    assemble_scientific(mantissa, exponent.parse().unwrap())
+}
+
+/// Writes decimals out as long as all of their digits are significant, and uses scientific
+/// notation for the ones that would need padding zeros, like `1.792728671*10^{30}`.
+#[cfg(feature = "bignum")]
+fn assemble_big_decimal(d: &BigDecimalScalar) -> String {
+    use bigdecimal::Zero;
+
+    // The position of the leading digit.
+    let exponent = d.digits() as i64 - 1 - d.fractional_digit_count();
+    if d.is_zero() || d.fractional_digit_count() >= 0 && exponent >= -MAX_LEADING_ZEROS {
+        return d.to_plain_string();
+    }
+
+    let normalized = d.normalized().to_scientific_notation();
+    let mantissa = normalized
+        .split_once('e')
+        .map_or(normalized.as_str(), |(m, _)| m);
+    assemble_scientific(mantissa, exponent)
+}
+
 impl FromRawExpr for Number {
     fn parse_raw(expr: &str, _: Option<&HashMap<String, Number>>) -> LaTexParsingResult<Self> {
         if expr.is_empty() {
@@ -116,8 +534,13 @@ impl FromRawExpr for Number {
         if let Ok(i) = expr.parse::<IntegerScalar>() {
             Ok(Self::Integer(i))
         } else {
+            #[cfg(feature = "bignum")]
+            if let Some(big) = parse_big_number(expr) {
+                return Ok(big);
+            }
+
             expr.parse::<DecimalScalar>()
-                .map(|i| Self::Decimal(i))
+                .map(Self::Decimal)
                 .map_err(|_| {
                     LaTexParsingError::new(
                         (0..expr.len()).into(),
@@ -128,6 +551,34 @@ impl FromRawExpr for Number {
     }
 }
 
+/// Assembles complex numbers like `1+2i`, `-i` or `0.5i`.
+fn assemble_complex(c: &ComplexScalar) -> String {
+    let im = match c.im {
+        1. => I.to_string(),
+        -1. => format!("{}{}", SUBTRACT, I),
+        im => format!("{}{}", im, I),
+    };
+
+    if c.re == 0. {
+        im
+    } else if c.im < 0. {
+        format!("{}{}", c.re, im)
+    } else {
+        format!("{}{}{}", c.re, ADD, im)
+    }
+}
+
+/// Parses numbers that can't be represented by the scalars without losing precision, which are
+/// large integers and all decimals, since even `0.1` has no exact [`DecimalScalar`].
+#[cfg(feature = "bignum")]
+fn parse_big_number(expr: &str) -> Option<Number> {
+    if expr.bytes().all(|b| b.is_ascii_digit()) {
+        return expr.parse().ok().map(Number::BigInteger);
+    }
+
+    expr.parse().ok().map(Number::BigDecimal)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -138,13 +589,97 @@ mod test {
             Number::parse_raw("123", None).unwrap(),
             Number::Integer(123)
         );
+        #[cfg(not(feature = "bignum"))]
         assert_eq!(
             Number::parse_raw("1.024", None).unwrap(),
             Number::Decimal(1.024)
         );
+        #[cfg(feature = "bignum")]
+        assert_eq!(
+            Number::parse_raw("1.024", None).unwrap(),
+            Number::BigDecimal("1.024".parse().unwrap())
+        );
         assert_eq!(
             Number::parse_raw("abc", None).unwrap_err(),
             LaTexParsingError::new((0..3).into(), LaTexParsingErrorType::InvalidNumber)
         );
     }
+
+    #[test]
+    fn test_rational_arithmetic() {
+        let third = || Number::from(Rational::new(1, 3).unwrap());
+        assert_eq!(third() * Number::Integer(3), Number::Integer(1));
+        assert_eq!(
+            third() - Number::Rational(Rational::new(1, 2).unwrap()),
+            Number::Rational(Rational::new(-1, 6).unwrap())
+        );
+        assert_eq!(
+            third() / Number::Integer(-4),
+            Number::Rational(Rational::new(1, -12).unwrap())
+        );
+        assert_eq!(third().pow(Number::Integer(-2)), Number::Integer(9));
+        #[cfg(not(feature = "bignum"))]
+        assert_eq!(
+            Number::Integer(IntegerScalar::MAX) + third(),
+            Number::Decimal(IntegerScalar::MAX as DecimalScalar + 1. / 3.)
+        );
+        assert_eq!(
+            third() + Number::Decimal(0.5),
+            Number::Decimal(1. / 3. + 0.5)
+        );
+        #[cfg(not(feature = "bignum"))]
+        assert_eq!(
+            Number::Integer(1) / Number::Integer(0),
+            Number::Decimal(DecimalScalar::INFINITY)
+        );
+        assert_eq!(Rational::new(-3, 6).unwrap().assemble(), r#"-\frac{1}{2}"#);
+    }
+
+    #[test]
+    fn test_decimal_assembly() {
+        assert_eq!(Number::Decimal(0.25).assemble(), "0.25");
+        assert_eq!(Number::Decimal(-1e14).assemble(), "-100000000000000");
+        assert_eq!(Number::Decimal(-1.5e15).assemble(), "-1.5*10^{15}");
+        assert_eq!(Number::Decimal(2e-11).assemble(), "2*10^{-11}");
+        assert_eq!(
+            Number::Decimal(DecimalScalar::NEG_INFINITY).assemble(),
+            r#"-\infty"#
+        );
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test]
+    fn test_big_arithmetic() {
+        let big = |e: u32| Number::BigInteger(BigIntegerScalar::from(2).pow(e));
+        let two_100 = Number::Integer(2).pow(Number::Integer(100));
+        assert_eq!(two_100, big(100));
+
+        assert_eq!(
+            two_100.clone() - two_100.clone() + Number::Integer(1),
+            Number::Integer(1)
+        );
+        assert_eq!(big(80) / big(79), Number::Integer(2));
+        assert_eq!(big(64) / Number::Integer(3) * Number::Integer(3), big(64));
+        assert_eq!(
+            (big(64) + Number::Integer(1)) / big(64),
+            Number::BigRational(BigRationalScalar::new(
+                BigIntegerScalar::from(2).pow(64u32) + 1,
+                BigIntegerScalar::from(2).pow(64u32)
+            ))
+        );
+        assert_eq!(
+            Number::Integer(2).pow(Number::Integer(-100)).assemble(),
+            format!(r#"\frac{{1}}{{{}}}"#, big(100).assemble())
+        );
+        assert_eq!(
+            Number::Rational(Rational::new(1, 3).unwrap()).pow(Number::Integer(-70)),
+            Number::BigInteger(BigIntegerScalar::from(3).pow(70u32))
+        );
+
+        let decimal = |d: &str| Number::BigDecimal(d.parse().unwrap()).assemble();
+        assert_eq!(decimal("0.00000000125"), "0.00000000125");
+        assert_eq!(decimal("1.25e-12"), "1.25*10^{-12}");
+        assert_eq!(decimal("1.250e30"), "1.25*10^{30}");
+        assert_eq!(decimal("-1.5e-4000000000"), "-1.5*10^{-4000000000}");
+    }
 }
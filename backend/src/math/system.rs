@@ -0,0 +1,589 @@
+//! Systems of equations in several unknowns, solved exactly if they are linear and with
+//! Newton's method otherwise.
+
+use std::collections::HashMap;
+
+use crate::{
+    latex::{
+        BEGIN, CASES, CURLY_BRACKET_L, CURLY_BRACKET_R, END, EQUATION_SEP, FUNC_BEGIN,
+        MATRIX_COL_SEP, MATRIX_ROW_SEP,
+    },
+    math::{
+        expr::ExpresssionTree,
+        func::{
+            op::{add, multiply},
+            solve::{self, residual, Roots, TOLERANCE},
+            Differentiable, Function,
+        },
+        matrix::split_top_level,
+        mpoly::MultiPolynomial,
+        symbol::{Number, Rational},
+        ExpressionElement, FromRawExpr, IntoRawExpr, LaTexParsingError, LaTexParsingErrorType,
+        LaTexParsingResult, ParsingScope,
+    },
+    DecimalScalar, IntegerScalar,
+};
+
+/// Starting points of Newton's method are spread over `[-SEED_RANGE, SEED_RANGE]` in every
+/// unknown.
+pub const SEED_RANGE: DecimalScalar = 10.;
+/// The number of starting points of Newton's method.
+const SEEDS: usize = 64;
+const MAX_ITERATIONS: usize = 100;
+/// How many times a Newton step is halved when it doesn't get closer to a solution.
+const MAX_DAMPING: usize = 30;
+/// The largest denominator numeric solutions are snapped to, if the rationals solve the system
+/// exactly.
+const MAX_SNAP_DENOMINATOR: IntegerScalar = 12;
+
+/// Equations that have to hold at the same time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquationSystem {
+    equations: Vec<ExpressionElement>,
+}
+
+/// The solutions of a system, with the values of the unknowns in the order they were given.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Solutions {
+    /// The equations contradict each other, or no solution was found for nonlinear systems.
+    None,
+    /// Exactly one solution.
+    Unique(Vec<ExpressionElement>),
+    /// Several solutions of a nonlinear system, sorted by the values of the unknowns.
+    Finite(Vec<Vec<ExpressionElement>>),
+    /// Infinitely many solutions. The unknowns that can be chosen freely are kept as variables,
+    /// and the others are written in terms of them.
+    Infinite(Vec<ExpressionElement>),
+}
+
+impl EquationSystem {
+    #[inline]
+    pub fn new(equations: Vec<ExpressionElement>) -> Self {
+        Self { equations }
+    }
+
+    #[inline]
+    pub fn equations(&self) -> &[ExpressionElement] {
+        &self.equations
+    }
+
+    /// Solves the system for `vars`.
+    ///
+    /// Systems where every equation is linear in the unknowns with rational coefficients are
+    /// solved exactly with Gaussian elimination, and a single equation in one unknown like
+    /// [`solve::solve`]. Other systems need at least as many equations as unknowns and are
+    /// solved numerically with Newton's method, starting from points in [`SEED_RANGE`]. `None`
+    /// if the system can't be solved.
+    pub fn solve(&self, vars: &[&str]) -> Option<Solutions> {
+        if vars.is_empty() {
+            return None;
+        }
+
+        let residuals = self.equations.iter().map(residual).collect::<Vec<_>>();
+
+        if let Some(solutions) = linear(&residuals, vars) {
+            return solutions;
+        }
+
+        if let ([f], [var]) = (residuals.as_slice(), vars) {
+            return Some(match solve::solve(f, var)? {
+                Roots::Any => {
+                    Solutions::Infinite(vec![ExpressionElement::Variable(var.to_string())])
+                }
+                Roots::Finite(roots) => match roots.len() {
+                    0 => Solutions::None,
+                    1 => Solutions::Unique(roots),
+                    _ => Solutions::Finite(roots.into_iter().map(|r| vec![r]).collect()),
+                },
+            });
+        }
+
+        if residuals.len() < vars.len()
+            || residuals
+                .iter()
+                .any(|f| f.any_variable(&|v| !vars.contains(&v)))
+        {
+            return None;
+        }
+
+        Some(newton(&residuals, vars))
+    }
+}
+
+impl FromRawExpr for EquationSystem {
+    #[inline]
+    fn parse_raw(expr: &str, vars: Option<&HashMap<String, Number>>) -> LaTexParsingResult<Self> {
+        Self::parse_scoped(expr, &ParsingScope::new(vars))
+    }
+
+    /// Parses a `cases` environment with one equation per row, or equations separated by `,` or
+    /// `\\`. Alignment `&`s are ignored.
+    fn parse_scoped(expr: &str, scope: &ParsingScope) -> LaTexParsingResult<Self> {
+        let begin = format!(
+            "{}{}{}{}{}",
+            FUNC_BEGIN, BEGIN, CURLY_BRACKET_L, CASES, CURLY_BRACKET_R
+        );
+        let end = format!(
+            "{}{}{}{}{}",
+            FUNC_BEGIN, END, CURLY_BRACKET_L, CASES, CURLY_BRACKET_R
+        );
+        let (body_start, body) = match expr.strip_prefix(&begin) {
+            Some(body) => (
+                begin.len(),
+                body.strip_suffix(&end).ok_or_else(|| {
+                    LaTexParsingError::new(
+                        (0..expr.len()).into(),
+                        LaTexParsingErrorType::InvalidBracketStructure,
+                    )
+                })?,
+            ),
+            None => (0, expr),
+        };
+
+        let mut equations = Vec::new();
+        for (row_start, row) in split_top_level(body, MATRIX_ROW_SEP) {
+            for (eq_start, eq) in split_top_level(row, &EQUATION_SEP.to_string()) {
+                if eq.trim().is_empty() {
+                    continue;
+                }
+
+                let eq = split_top_level(eq, &MATRIX_COL_SEP.to_string())
+                    .into_iter()
+                    .map(|(_, piece)| piece)
+                    .collect::<String>();
+                equations.push(
+                    ExpresssionTree::parse_scoped_with_base_index(
+                        &eq,
+                        scope,
+                        body_start + row_start + eq_start,
+                    )?
+                    .into(),
+                );
+            }
+        }
+
+        if equations.is_empty() {
+            return Err(LaTexParsingError::new(
+                (0..expr.len()).into(),
+                LaTexParsingErrorType::EmptyInput,
+            ));
+        }
+
+        Ok(Self { equations })
+    }
+}
+
+impl IntoRawExpr for EquationSystem {
+    fn assemble(&self) -> String {
+        let body = self
+            .equations
+            .iter()
+            .map(|eq| eq.assemble())
+            .collect::<Vec<_>>()
+            .join(MATRIX_ROW_SEP);
+        format!(
+            "{}{}{}{}{}{}{}{}{}{}{}",
+            FUNC_BEGIN,
+            BEGIN,
+            CURLY_BRACKET_L,
+            CASES,
+            CURLY_BRACKET_R,
+            body,
+            FUNC_BEGIN,
+            END,
+            CURLY_BRACKET_L,
+            CASES,
+            CURLY_BRACKET_R
+        )
+    }
+}
+
+#[inline]
+fn num(r: Rational) -> ExpressionElement {
+    Number::from(r).into()
+}
+
+/// Solves systems that are linear in `vars` with rational coefficients by reducing them to
+/// reduced row echelon form. `None` if the system isn't linear, or the coefficients overflow.
+fn linear(residuals: &[ExpressionElement], vars: &[&str]) -> Option<Option<Solutions>> {
+    let mut rows = Vec::with_capacity(residuals.len());
+    for f in residuals {
+        let poly = MultiPolynomial::from_expr(f)?;
+        if poly.total_degree().is_some_and(|d| d > 1)
+            || poly.vars().iter().any(|v| !vars.contains(v))
+        {
+            return None;
+        }
+
+        let mut row = vars
+            .iter()
+            .map(|v| poly.coeff_in(v, 1).as_constant())
+            .collect::<Option<Vec<_>>>()?;
+        let constant = vars
+            .iter()
+            .fold(poly.clone(), |p, v| p.coeff_in(v, 0))
+            .as_constant()?;
+        row.push(constant.checked_neg()?);
+        rows.push(row);
+    }
+
+    let n = vars.len();
+    let Some(pivots) = row_reduce(&mut rows, n) else {
+        return Some(None);
+    };
+
+    if rows[pivots.len()..]
+        .iter()
+        .any(|row| row[n] != Rational::ZERO)
+    {
+        return Some(Some(Solutions::None));
+    }
+
+    let mut values = vars
+        .iter()
+        .map(|v| ExpressionElement::Variable(v.to_string()))
+        .collect::<Vec<_>>();
+    for (row, &col) in pivots.iter().enumerate() {
+        // The free unknowns are moved to the right hand side.
+        values[col] = (0..n)
+            .filter(|c| !pivots.contains(c) && rows[row][*c] != Rational::ZERO)
+            .fold(num(rows[row][n]), |value, c| {
+                add(
+                    value,
+                    multiply(
+                        num(rows[row][c].checked_neg().unwrap()),
+                        ExpressionElement::Variable(vars[c].to_string()),
+                    ),
+                )
+            });
+    }
+
+    Some(Some(if pivots.len() == n {
+        Solutions::Unique(values)
+    } else {
+        Solutions::Infinite(values)
+    }))
+}
+
+/// Brings the augmented matrix `rows` with `n` unknowns to reduced row echelon form with
+/// Gauss-Jordan elimination. Returns the columns of the pivots, where the pivot of the `i`th
+/// column is in the `i`th row. `None` if the coefficients overflow.
+fn row_reduce(rows: &mut [Vec<Rational>], n: usize) -> Option<Vec<usize>> {
+    let mut pivots = Vec::new();
+
+    for col in 0..n {
+        let row = pivots.len();
+        let Some(pivot) = (row..rows.len()).find(|r| rows[*r][col] != Rational::ZERO) else {
+            continue;
+        };
+        rows.swap(row, pivot);
+
+        let scale = rows[row][col];
+        for x in rows[row].iter_mut() {
+            *x = x.checked_div(scale)?;
+        }
+
+        let pivot = rows[row].clone();
+        for (r, other) in rows.iter_mut().enumerate() {
+            if r == row || other[col] == Rational::ZERO {
+                continue;
+            }
+            let factor = other[col];
+            for (x, p) in other.iter_mut().zip(&pivot) {
+                *x = x.checked_sub(factor.checked_mul(*p)?)?;
+            }
+        }
+
+        pivots.push(col);
+    }
+
+    Some(pivots)
+}
+
+/// Solves nonlinear systems with a damped Newton's method starting from points spread over
+/// [`SEED_RANGE`], using the Jacobian from symbolic derivatives. Overdetermined systems are
+/// solved in the least squares sense, and only kept if every equation holds.
+fn newton(residuals: &[ExpressionElement], vars: &[&str]) -> Solutions {
+    let jacobian = residuals
+        .iter()
+        .map(|f| vars.iter().map(|v| f.derivative(v)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let at = |elem: &ExpressionElement, x: &[DecimalScalar]| {
+        vars.iter()
+            .zip(x)
+            .fold(elem.clone(), |elem, (v, x)| {
+                elem.substitute(v, &Number::Decimal(*x).into())
+            })
+            .approximate()
+    };
+    let values = |x: &[DecimalScalar]| residuals.iter().map(|f| at(f, x)).collect::<Vec<_>>();
+    let norm = |y: &[DecimalScalar]| y.iter().map(|y| y * y).sum::<DecimalScalar>();
+
+    let mut solutions = Vec::<Vec<DecimalScalar>>::new();
+    for seed in 0..SEEDS {
+        let mut x = halton(seed + 1, vars.len());
+        let mut y = values(&x);
+
+        // Small residuals aren't enough, as they can be well within the tolerance while x is
+        // still off in the tenth digit, so Newton's method goes on until the steps vanish.
+        for _ in 0..MAX_ITERATIONS {
+            if !norm(&y).is_finite() {
+                break;
+            }
+
+            let j = jacobian
+                .iter()
+                .map(|row| row.iter().map(|d| at(d, &x)).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            let Some(step) = least_squares(j, &y) else {
+                break;
+            };
+            if step
+                .iter()
+                .zip(&x)
+                .all(|(s, x)| s.abs() <= DecimalScalar::EPSILON * (1. + x.abs()))
+            {
+                break;
+            }
+
+            let mut t = 1.;
+            let mut next = None;
+            for _ in 0..MAX_DAMPING {
+                let candidate = x
+                    .iter()
+                    .zip(&step)
+                    .map(|(x, s)| x - t * s)
+                    .collect::<Vec<_>>();
+                let y_next = values(&candidate);
+                if norm(&y_next) < norm(&y) {
+                    next = Some((candidate, y_next));
+                    break;
+                }
+                t /= 2.;
+            }
+
+            let Some((x_next, y_next)) = next else {
+                break;
+            };
+            x = x_next;
+            y = y_next;
+        }
+
+        if y.iter().all(|y| y.abs() <= TOLERANCE)
+            && !solutions.iter().any(|s| {
+                s.iter()
+                    .zip(&x)
+                    .all(|(a, b)| (a - b).abs() <= TOLERANCE.sqrt() * (1. + a.abs()))
+            })
+        {
+            solutions.push(x);
+        }
+    }
+
+    solutions.sort_by(|a, b| {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| a.total_cmp(b))
+            .find(|o| o.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let mut solutions = solutions
+        .into_iter()
+        .map(|x| snap(residuals, vars, &x))
+        .collect::<Vec<_>>();
+
+    match solutions.len() {
+        0 => Solutions::None,
+        1 => Solutions::Unique(solutions.pop().unwrap()),
+        _ => Solutions::Finite(solutions),
+    }
+}
+
+/// The `index`th point of the Halton sequence in `dim` dimensions, scaled to [`SEED_RANGE`].
+fn halton(index: usize, dim: usize) -> Vec<DecimalScalar> {
+    let mut primes = Vec::with_capacity(dim);
+    let mut candidate = 2;
+    while primes.len() < dim {
+        if primes.iter().all(|p| candidate % p != 0) {
+            primes.push(candidate);
+        }
+        candidate += 1;
+    }
+
+    primes
+        .into_iter()
+        .map(|base| {
+            let (mut i, mut f, mut x) = (index, 1., 0.);
+            while i > 0 {
+                f /= base as DecimalScalar;
+                x += f * (i % base) as DecimalScalar;
+                i /= base;
+            }
+            SEED_RANGE * (2. * x - 1.)
+        })
+        .collect()
+}
+
+/// Solves `j * x = y` for `x`, in the least squares sense if there are more rows than columns.
+/// `None` if `j` is singular.
+fn least_squares(j: Vec<Vec<DecimalScalar>>, y: &[DecimalScalar]) -> Option<Vec<DecimalScalar>> {
+    let n = j.first()?.len();
+    if j.len() == n {
+        return gaussian(j, y.to_vec());
+    }
+
+    let jtj = (0..n)
+        .map(|a| {
+            (0..n)
+                .map(|b| j.iter().map(|row| row[a] * row[b]).sum())
+                .collect()
+        })
+        .collect();
+    let jty = (0..n)
+        .map(|a| j.iter().zip(y).map(|(row, y)| row[a] * y).sum())
+        .collect();
+    gaussian(jtj, jty)
+}
+
+/// Gaussian elimination with partial pivoting. `None` if `a` is singular.
+fn gaussian(
+    mut a: Vec<Vec<DecimalScalar>>,
+    mut b: Vec<DecimalScalar>,
+) -> Option<Vec<DecimalScalar>> {
+    let n = b.len();
+
+    for col in 0..n {
+        let pivot = (col..n).max_by(|x, y| a[*x][col].abs().total_cmp(&a[*y][col].abs()))?;
+        if a[pivot][col].abs() <= DecimalScalar::EPSILON || a[pivot][col].is_nan() {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let (top, bottom) = a.split_at_mut(col + 1);
+        let pivot = &top[col];
+        for (row, other) in bottom.iter_mut().enumerate() {
+            let factor = other[col] / pivot[col];
+            for (x, p) in other.iter_mut().zip(pivot).skip(col) {
+                *x -= factor * p;
+            }
+            b[col + 1 + row] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.; n];
+    for row in (0..n).rev() {
+        let sum = (row + 1..n)
+            .map(|c| a[row][c] * x[c])
+            .sum::<DecimalScalar>();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+
+    x.iter().all(|x| x.is_finite()).then_some(x)
+}
+
+/// Replaces a numeric solution by nearby rationals if they solve every equation exactly.
+fn snap(
+    residuals: &[ExpressionElement],
+    vars: &[&str],
+    x: &[DecimalScalar],
+) -> Vec<ExpressionElement> {
+    let decimals = || x.iter().map(|x| Number::Decimal(*x).into()).collect();
+
+    let Some(rationals) = x
+        .iter()
+        .map(|x| {
+            (1..=MAX_SNAP_DENOMINATOR).find_map(|den| {
+                let n = (x * den as DecimalScalar).round();
+                ((n / den as DecimalScalar - x).abs() <= TOLERANCE.sqrt() * (1. + x.abs()))
+                    .then(|| Rational::new(n as IntegerScalar, den))
+                    .flatten()
+            })
+        })
+        .collect::<Option<Vec<_>>>()
+    else {
+        return decimals();
+    };
+
+    let exact = residuals.iter().all(|f| {
+        let value = vars
+            .iter()
+            .zip(&rationals)
+            .fold(f.clone(), |f, (v, r)| f.substitute(v, &num(*r)));
+        ExpressionElement::from(value.evaluate()).as_rational() == Some(Rational::ZERO)
+    });
+
+    if exact {
+        rationals.into_iter().map(num).collect()
+    } else {
+        decimals()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::math::{
+        system::{EquationSystem, Solutions},
+        FromRawExpr, IntoRawExpr, ParsingScope,
+    };
+
+    #[test]
+    fn test_systems() {
+        let scope = ParsingScope::new(None)
+            .with_symbol("x")
+            .with_symbol("y")
+            .with_symbol("z");
+        let solve = |expr, vars: &[&str]| {
+            let assemble = |s: &[_]| {
+                s.iter()
+                    .map(|e: &crate::math::ExpressionElement| e.assemble())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            match EquationSystem::parse_scoped(expr, &scope)
+                .unwrap()
+                .solve(vars)
+                .unwrap()
+            {
+                Solutions::None => vec!["none".to_string()],
+                Solutions::Unique(s) => vec![assemble(&s)],
+                Solutions::Finite(s) => s.iter().map(|s| assemble(s)).collect(),
+                Solutions::Infinite(s) => vec![format!("infinite: {}", assemble(&s))],
+            }
+        };
+
+        let system =
+            EquationSystem::parse_scoped(r#"\begin{cases}x+y&=3\\x-y&=1\end{cases}"#, &scope)
+                .unwrap();
+        assert_eq!(system.equations().len(), 2);
+        assert_eq!(system.assemble(), r#"\begin{cases}x+y=3\\x-y=1\end{cases}"#);
+
+        assert_eq!(solve(r#"x+y=3,x-y=1"#, &["x", "y"]), ["2, 1"]);
+        assert_eq!(
+            solve(r#"x+y+z=6\\2x-y=0\\y+\frac{z}{3}=3"#, &["x", "y", "z"]),
+            ["1, 2, 3"]
+        );
+        assert_eq!(solve(r#"x+y=1,x+y=2"#, &["x", "y"]), ["none"]);
+        assert_eq!(solve(r#"x+y=1,2x+2y=2"#, &["x", "y"]), ["infinite: 1-y, y"]);
+        assert_eq!(
+            solve(r#"x^2+y^2=25,x-y=1"#, &["x", "y"]),
+            ["-3, -4", "4, 3"]
+        );
+        assert_eq!(solve(r#"x^2+y^2=-1,x=y"#, &["x", "y"]), ["none"]);
+        assert_eq!(solve(r#"e^{x}=y,x+y=1"#, &["x", "y"]), ["0, 1"]);
+        assert_eq!(solve(r#"x^2=4"#, &["x"]), ["-2", "2"]);
+        assert_eq!(
+            solve(r#"x y=1,x+y=3"#, &["x", "y"]),
+            [
+                "0.38196601125010515, 2.618033988749895",
+                "2.618033988749895, 0.38196601125010515"
+            ]
+        );
+        assert!(EquationSystem::parse_scoped(r#"x^2+y=1"#, &scope)
+            .unwrap()
+            .solve(&["x", "y"])
+            .is_none());
+        assert!(EquationSystem::parse_scoped(r#"x+y=1,"#, &scope).is_ok());
+        assert!(EquationSystem::parse_scoped(r#","#, &scope).is_err());
+    }
+}
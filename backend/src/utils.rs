@@ -1,4 +1,4 @@
-use crate::math::symbol::BracketState;
+use crate::{math::symbol::BracketState, IntegerScalar};
 
 #[derive(Debug, Default)]
 pub struct BracketStack {
@@ -37,6 +37,14 @@ impl BracketStack {
     }
 }
 
+#[inline]
+pub fn gcd(mut a: IntegerScalar, mut b: IntegerScalar) -> IntegerScalar {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.wrapping_abs()
+}
+
 #[macro_export]
 macro_rules! sub_expr {
     ($exprs: expr, $nth: expr) => {